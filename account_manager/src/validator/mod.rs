@@ -1,5 +1,6 @@
 pub mod create;
 pub mod deposit;
+pub mod slashing_protection;
 
 use crate::common::base_wallet_dir;
 use clap::{App, Arg, ArgMatches};
@@ -20,6 +21,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
         )
         .subcommand(create::cli_app())
         .subcommand(deposit::cli_app())
+        .subcommand(slashing_protection::cli_app())
 }
 
 pub fn cli_run<T: EthSpec>(matches: &ArgMatches, env: Environment<T>) -> Result<(), String> {
@@ -28,6 +30,9 @@ pub fn cli_run<T: EthSpec>(matches: &ArgMatches, env: Environment<T>) -> Result<
     match matches.subcommand() {
         (create::CMD, Some(matches)) => create::cli_run::<T>(matches, env, base_wallet_dir),
         (deposit::CMD, Some(matches)) => deposit::cli_run::<T>(matches, env),
+        (slashing_protection::CMD, Some(matches)) => {
+            slashing_protection::cli_run::<T>(matches, env)
+        }
         (unknown, _) => {
             return Err(format!(
                 "{} does not have a {} command. See --help",
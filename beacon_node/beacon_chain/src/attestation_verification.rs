@@ -18,6 +18,9 @@
 //!      types::Attestation              types::SignedAggregateAndProof
 //!              |                                    |
 //!              ▼                                    ▼
+//!  IndexedUnaggregatedAttestation      IndexedAggregatedAttestation
+//!              |                                    |
+//!              ▼                                    ▼
 //!  VerifiedUnaggregatedAttestation     VerifiedAggregatedAttestation
 //!              |                                    |
 //!              -------------------------------------
@@ -25,6 +28,10 @@
 //!                                ▼
 //!                  impl SignatureVerifiedAttestation
 //! ```
+//!
+//! The "indexed" types have passed every check except for signature verification. This allows the
+//! signatures of many attestations to be verified in a single batch (see
+//! `batch_verify_unaggregated_attestations` and `batch_verify_aggregated_attestations`).
 
 use crate::{
     beacon_chain::{
@@ -34,9 +41,10 @@ use crate::{
     metrics,
     observed_attestations::ObserveOutcome,
     observed_attesters::Error as ObservedAttestersError,
+    validator_pubkey_cache::ValidatorPubkeyCache,
     BeaconChain, BeaconChainError, BeaconChainTypes,
 };
-use bls::verify_signature_sets;
+use bls::{verify_signature_sets, SignatureSet};
 use slog::debug;
 use slot_clock::SlotClock;
 use state_processing::{
@@ -51,8 +59,8 @@ use state_processing::{
 use std::borrow::Cow;
 use tree_hash::TreeHash;
 use types::{
-    Attestation, BeaconCommittee, CommitteeIndex, Epoch, EthSpec, Hash256, IndexedAttestation,
-    RelativeEpoch, SelectionProof, SignedAggregateAndProof, Slot, SubnetId,
+    Attestation, BeaconCommittee, CommitteeIndex, Epoch, EthSpec, Fork, Hash256,
+    IndexedAttestation, RelativeEpoch, SelectionProof, SignedAggregateAndProof, Slot, SubnetId,
 };
 
 /// Returned when an attestation was not successfully verified. It might not have been verified for
@@ -140,6 +148,21 @@ impl From<BeaconChainError> for Error {
     }
 }
 
+/// Wraps a `SignedAggregateAndProof` that has passed every gossip check except for signature
+/// verification.
+pub struct IndexedAggregatedAttestation<T: BeaconChainTypes> {
+    signed_aggregate: SignedAggregateAndProof<T::EthSpec>,
+    indexed_attestation: IndexedAttestation<T::EthSpec>,
+    attestation_root: Hash256,
+}
+
+/// Wraps an `Attestation` that has passed every gossip check except for signature verification.
+pub struct IndexedUnaggregatedAttestation<T: BeaconChainTypes> {
+    attestation: Attestation<T::EthSpec>,
+    indexed_attestation: IndexedAttestation<T::EthSpec>,
+    validator_index: u64,
+}
+
 /// Wraps a `SignedAggregateAndProof` that has been verified for propagation on the gossip network.
 pub struct VerifiedAggregatedAttestation<T: BeaconChainTypes> {
     signed_aggregate: SignedAggregateAndProof<T::EthSpec>,
@@ -181,9 +204,9 @@ impl<T: BeaconChainTypes> SignatureVerifiedAttestation<T> for VerifiedUnaggregat
     }
 }
 
-impl<T: BeaconChainTypes> VerifiedAggregatedAttestation<T> {
-    /// Returns `Ok(Self)` if the `signed_aggregate` is valid to be (re)published on the gossip
-    /// network.
+impl<T: BeaconChainTypes> IndexedAggregatedAttestation<T> {
+    /// Returns `Ok(Self)` if the `signed_aggregate` passes every gossip check which does not
+    /// require verifying its signatures.
     pub fn verify(
         signed_aggregate: SignedAggregateAndProof<T::EthSpec>,
         chain: &BeaconChain<T>,
@@ -263,11 +286,49 @@ impl<T: BeaconChainTypes> VerifiedAggregatedAttestation<T> {
                     .map_err(|e| BeaconChainError::from(e).into())
             })?;
 
+        Ok(IndexedAggregatedAttestation {
+            signed_aggregate,
+            indexed_attestation,
+            attestation_root,
+        })
+    }
+}
+
+impl<T: BeaconChainTypes> VerifiedAggregatedAttestation<T> {
+    /// Returns `Ok(Self)` if the `signed_aggregate` is valid to be (re)published on the gossip
+    /// network.
+    pub fn verify(
+        signed_aggregate: SignedAggregateAndProof<T::EthSpec>,
+        chain: &BeaconChain<T>,
+    ) -> Result<Self, Error> {
+        let indexed = IndexedAggregatedAttestation::verify(signed_aggregate, chain)?;
+
         // Ensure that all signatures are valid.
-        if !verify_signed_aggregate_signatures(chain, &signed_aggregate, &indexed_attestation)? {
+        if !verify_signed_aggregate_signatures(
+            chain,
+            &indexed.signed_aggregate,
+            &indexed.indexed_attestation,
+        )? {
             return Err(Error::InvalidSignature);
         }
 
+        Self::from_signature_verified_components(indexed, chain)
+    }
+
+    /// Completes the verification of an `IndexedAggregatedAttestation` whose signatures are known
+    /// to be valid, observing it so that it will not be processed again.
+    fn from_signature_verified_components(
+        indexed: IndexedAggregatedAttestation<T>,
+        chain: &BeaconChain<T>,
+    ) -> Result<Self, Error> {
+        let IndexedAggregatedAttestation {
+            signed_aggregate,
+            indexed_attestation,
+            attestation_root,
+        } = indexed;
+        let attestation = &signed_aggregate.message.aggregate;
+        let aggregator_index = signed_aggregate.message.aggregator_index;
+
         // Observe the valid attestation so we do not re-process it.
         //
         // It's important to double check that the attestation is not already known, otherwise two
@@ -312,9 +373,9 @@ impl<T: BeaconChainTypes> VerifiedAggregatedAttestation<T> {
     }
 }
 
-impl<T: BeaconChainTypes> VerifiedUnaggregatedAttestation<T> {
-    /// Returns `Ok(Self)` if the `attestation` is valid to be (re)published on the gossip
-    /// network.
+impl<T: BeaconChainTypes> IndexedUnaggregatedAttestation<T> {
+    /// Returns `Ok(Self)` if the `attestation` passes every gossip check which does not require
+    /// verifying its signature.
     ///
     /// `subnet_id` is the subnet from which we received this attestation. This function will
     /// verify that it was received on the correct subnet.
@@ -378,8 +439,44 @@ impl<T: BeaconChainTypes> VerifiedUnaggregatedAttestation<T> {
             });
         }
 
+        Ok(Self {
+            attestation,
+            indexed_attestation,
+            validator_index,
+        })
+    }
+}
+
+impl<T: BeaconChainTypes> VerifiedUnaggregatedAttestation<T> {
+    /// Returns `Ok(Self)` if the `attestation` is valid to be (re)published on the gossip
+    /// network.
+    ///
+    /// `subnet_id` is the subnet from which we received this attestation. This function will
+    /// verify that it was received on the correct subnet.
+    pub fn verify(
+        attestation: Attestation<T::EthSpec>,
+        subnet_id: SubnetId,
+        chain: &BeaconChain<T>,
+    ) -> Result<Self, Error> {
+        let indexed = IndexedUnaggregatedAttestation::verify(attestation, subnet_id, chain)?;
+
         // The aggregate signature of the attestation is valid.
-        verify_attestation_signature(chain, &indexed_attestation)?;
+        verify_attestation_signature(chain, &indexed.indexed_attestation)?;
+
+        Self::from_signature_verified_components(indexed, chain)
+    }
+
+    /// Completes the verification of an `IndexedUnaggregatedAttestation` whose signature is known
+    /// to be valid, observing the attester so that no other attestation from them is accepted.
+    fn from_signature_verified_components(
+        indexed: IndexedUnaggregatedAttestation<T>,
+        chain: &BeaconChain<T>,
+    ) -> Result<Self, Error> {
+        let IndexedUnaggregatedAttestation {
+            attestation,
+            indexed_attestation,
+            validator_index,
+        } = indexed;
 
         // Now that the attestation has been fully verified, store that we have received a valid
         // attestation from this validator.
@@ -487,6 +584,72 @@ pub fn verify_propagation_slot_range<T: BeaconChainTypes>(
     Ok(())
 }
 
+/// Returns the fork of the canonical head, which is used to compute signing domains.
+fn head_fork<T: BeaconChainTypes>(chain: &BeaconChain<T>) -> Result<Fork, Error> {
+    chain
+        .canonical_head
+        .try_read_for(HEAD_LOCK_TIMEOUT)
+        .ok_or_else(|| BeaconChainError::CanonicalHeadLockTimeout)
+        .map(|head| head.beacon_state.fork.clone())
+        .map_err(Into::into)
+}
+
+/// Returns the signature set for the signature of the `indexed_attestation`.
+fn attestation_signature_set<T: BeaconChainTypes>(
+    chain: &BeaconChain<T>,
+    pubkey_cache: &ValidatorPubkeyCache,
+    fork: &Fork,
+    indexed_attestation: &IndexedAttestation<T::EthSpec>,
+) -> Result<SignatureSet, Error> {
+    indexed_attestation_signature_set_from_pubkeys(
+        |validator_index| pubkey_cache.get(validator_index).map(Cow::Borrowed),
+        &indexed_attestation.signature,
+        &indexed_attestation,
+        fork,
+        chain.genesis_validators_root,
+        &chain.spec,
+    )
+    .map_err(|e| BeaconChainError::SignatureSetError(e).into())
+}
+
+/// Returns the signature sets for the three signatures of a `SignedAggregateAndProof`:
+///
+/// - `signed_aggregate.signature`
+/// - `signed_aggregate.message.selection_proof`
+/// - `signed_aggregate.message.aggregate.signature`
+fn signed_aggregate_signature_sets<T: BeaconChainTypes>(
+    chain: &BeaconChain<T>,
+    pubkey_cache: &ValidatorPubkeyCache,
+    fork: &Fork,
+    signed_aggregate: &SignedAggregateAndProof<T::EthSpec>,
+    indexed_attestation: &IndexedAttestation<T::EthSpec>,
+) -> Result<Vec<SignatureSet>, Error> {
+    let aggregator_index = signed_aggregate.message.aggregator_index;
+    if aggregator_index >= pubkey_cache.len() as u64 {
+        return Err(Error::AggregatorPubkeyUnknown(aggregator_index));
+    }
+
+    Ok(vec![
+        signed_aggregate_selection_proof_signature_set(
+            |validator_index| pubkey_cache.get(validator_index).map(Cow::Borrowed),
+            &signed_aggregate,
+            fork,
+            chain.genesis_validators_root,
+            &chain.spec,
+        )
+        .map_err(BeaconChainError::SignatureSetError)?,
+        signed_aggregate_signature_set(
+            |validator_index| pubkey_cache.get(validator_index).map(Cow::Borrowed),
+            &signed_aggregate,
+            fork,
+            chain.genesis_validators_root,
+            &chain.spec,
+        )
+        .map_err(BeaconChainError::SignatureSetError)?,
+        attestation_signature_set(chain, pubkey_cache, fork, indexed_attestation)?,
+    ])
+}
+
 /// Verifies that the signature of the `indexed_attestation` is valid.
 pub fn verify_attestation_signature<T: BeaconChainTypes>(
     chain: &BeaconChain<T>,
@@ -500,21 +663,10 @@ pub fn verify_attestation_signature<T: BeaconChainTypes>(
         .try_read_for(VALIDATOR_PUBKEY_CACHE_LOCK_TIMEOUT)
         .ok_or_else(|| BeaconChainError::ValidatorPubkeyCacheLockTimeout)?;
 
-    let fork = chain
-        .canonical_head
-        .try_read_for(HEAD_LOCK_TIMEOUT)
-        .ok_or_else(|| BeaconChainError::CanonicalHeadLockTimeout)
-        .map(|head| head.beacon_state.fork.clone())?;
+    let fork = head_fork(chain)?;
 
-    let signature_set = indexed_attestation_signature_set_from_pubkeys(
-        |validator_index| pubkey_cache.get(validator_index).map(Cow::Borrowed),
-        &indexed_attestation.signature,
-        &indexed_attestation,
-        &fork,
-        chain.genesis_validators_root,
-        &chain.spec,
-    )
-    .map_err(BeaconChainError::SignatureSetError)?;
+    let signature_set =
+        attestation_signature_set(chain, &pubkey_cache, &fork, indexed_attestation)?;
 
     metrics::stop_timer(signature_setup_timer);
 
@@ -550,46 +702,160 @@ pub fn verify_signed_aggregate_signatures<T: BeaconChainTypes>(
         .try_read_for(VALIDATOR_PUBKEY_CACHE_LOCK_TIMEOUT)
         .ok_or_else(|| BeaconChainError::ValidatorPubkeyCacheLockTimeout)?;
 
-    let aggregator_index = signed_aggregate.message.aggregator_index;
-    if aggregator_index >= pubkey_cache.len() as u64 {
-        return Err(Error::AggregatorPubkeyUnknown(aggregator_index));
+    let fork = head_fork(chain)?;
+
+    let signature_sets = signed_aggregate_signature_sets(
+        chain,
+        &pubkey_cache,
+        &fork,
+        signed_aggregate,
+        indexed_attestation,
+    )?;
+
+    Ok(verify_signature_sets(signature_sets))
+}
+
+/// Returns `true` if all the signature sets produced by `signature_sets` are valid, verifying them
+/// in a single batch.
+///
+/// Returns `false` if any signature is invalid or if any signature set cannot be produced, in
+/// which case the caller should fall back to verifying each item individually so that only the
+/// faulty items are rejected.
+fn batch_verify_signature_sets<T, I, F>(
+    chain: &BeaconChain<T>,
+    items: I,
+    signature_sets: F,
+) -> Result<bool, Error>
+where
+    T: BeaconChainTypes,
+    I: Iterator,
+    F: Fn(&ValidatorPubkeyCache, &Fork, I::Item) -> Result<Vec<SignatureSet>, Error>,
+{
+    let signature_setup_timer =
+        metrics::start_timer(&metrics::ATTESTATION_PROCESSING_BATCH_SIGNATURE_SETUP_TIMES);
+
+    let pubkey_cache = chain
+        .validator_pubkey_cache
+        .try_read_for(VALIDATOR_PUBKEY_CACHE_LOCK_TIMEOUT)
+        .ok_or_else(|| BeaconChainError::ValidatorPubkeyCacheLockTimeout)?;
+
+    let fork = head_fork(chain)?;
+
+    let mut sets = vec![];
+    for item in items {
+        match signature_sets(&pubkey_cache, &fork, item) {
+            Ok(item_sets) => sets.extend(item_sets),
+            Err(_) => return Ok(false),
+        }
     }
 
-    let fork = chain
-        .canonical_head
-        .try_read_for(HEAD_LOCK_TIMEOUT)
-        .ok_or_else(|| BeaconChainError::CanonicalHeadLockTimeout)
-        .map(|head| head.beacon_state.fork.clone())?;
+    metrics::stop_timer(signature_setup_timer);
 
-    let signature_sets = vec![
-        signed_aggregate_selection_proof_signature_set(
-            |validator_index| pubkey_cache.get(validator_index).map(Cow::Borrowed),
-            &signed_aggregate,
-            &fork,
-            chain.genesis_validators_root,
-            &chain.spec,
-        )
-        .map_err(BeaconChainError::SignatureSetError)?,
-        signed_aggregate_signature_set(
-            |validator_index| pubkey_cache.get(validator_index).map(Cow::Borrowed),
-            &signed_aggregate,
-            &fork,
-            chain.genesis_validators_root,
-            &chain.spec,
-        )
-        .map_err(BeaconChainError::SignatureSetError)?,
-        indexed_attestation_signature_set_from_pubkeys(
-            |validator_index| pubkey_cache.get(validator_index).map(Cow::Borrowed),
-            &indexed_attestation.signature,
-            &indexed_attestation,
-            &fork,
-            chain.genesis_validators_root,
-            &chain.spec,
-        )
-        .map_err(BeaconChainError::SignatureSetError)?,
-    ];
+    if sets.is_empty() {
+        return Ok(true);
+    }
 
-    Ok(verify_signature_sets(signature_sets))
+    let _signature_verification_timer =
+        metrics::start_timer(&metrics::ATTESTATION_PROCESSING_BATCH_SIGNATURE_TIMES);
+
+    Ok(verify_signature_sets(sets))
+}
+
+/// Verifies a batch of unaggregated attestations for propagation on the gossip network, returning
+/// a result for each attestation in the order they were given.
+///
+/// The signatures of all attestations which pass the other checks are verified together. If the
+/// batch is invalid, each signature is verified individually.
+pub fn batch_verify_unaggregated_attestations<T, I>(
+    attestations: I,
+    chain: &BeaconChain<T>,
+) -> Result<Vec<Result<VerifiedUnaggregatedAttestation<T>, Error>>, Error>
+where
+    T: BeaconChainTypes,
+    I: Iterator<Item = (Attestation<T::EthSpec>, SubnetId)>,
+{
+    let indexed_results = attestations
+        .map(|(attestation, subnet_id)| {
+            IndexedUnaggregatedAttestation::verify(attestation, subnet_id, chain)
+        })
+        .collect::<Vec<_>>();
+
+    let batch_is_valid = batch_verify_signature_sets(
+        chain,
+        indexed_results.iter().flatten(),
+        |pubkey_cache, fork, indexed: &IndexedUnaggregatedAttestation<T>| {
+            attestation_signature_set(chain, pubkey_cache, fork, &indexed.indexed_attestation)
+                .map(|set| vec![set])
+        },
+    )?;
+
+    if !batch_is_valid {
+        metrics::inc_counter(&metrics::ATTESTATION_PROCESSING_BATCH_SIGNATURE_FAILURES);
+    }
+
+    Ok(indexed_results
+        .into_iter()
+        .map(|result| {
+            let indexed = result?;
+            if !batch_is_valid {
+                verify_attestation_signature(chain, &indexed.indexed_attestation)?;
+            }
+            VerifiedUnaggregatedAttestation::from_signature_verified_components(indexed, chain)
+        })
+        .collect())
+}
+
+/// Verifies a batch of `SignedAggregateAndProof` for propagation on the gossip network, returning
+/// a result for each aggregate in the order they were given.
+///
+/// The signatures of all aggregates which pass the other checks are verified together. If the
+/// batch is invalid, the signatures of each aggregate are verified individually.
+pub fn batch_verify_aggregated_attestations<T, I>(
+    signed_aggregates: I,
+    chain: &BeaconChain<T>,
+) -> Result<Vec<Result<VerifiedAggregatedAttestation<T>, Error>>, Error>
+where
+    T: BeaconChainTypes,
+    I: Iterator<Item = SignedAggregateAndProof<T::EthSpec>>,
+{
+    let indexed_results = signed_aggregates
+        .map(|signed_aggregate| IndexedAggregatedAttestation::verify(signed_aggregate, chain))
+        .collect::<Vec<_>>();
+
+    let batch_is_valid = batch_verify_signature_sets(
+        chain,
+        indexed_results.iter().flatten(),
+        |pubkey_cache, fork, indexed: &IndexedAggregatedAttestation<T>| {
+            signed_aggregate_signature_sets(
+                chain,
+                pubkey_cache,
+                fork,
+                &indexed.signed_aggregate,
+                &indexed.indexed_attestation,
+            )
+        },
+    )?;
+
+    if !batch_is_valid {
+        metrics::inc_counter(&metrics::ATTESTATION_PROCESSING_BATCH_SIGNATURE_FAILURES);
+    }
+
+    Ok(indexed_results
+        .into_iter()
+        .map(|result| {
+            let indexed = result?;
+            if !batch_is_valid
+                && !verify_signed_aggregate_signatures(
+                    chain,
+                    &indexed.signed_aggregate,
+                    &indexed.indexed_attestation,
+                )?
+            {
+                return Err(Error::InvalidSignature);
+            }
+            VerifiedAggregatedAttestation::from_signature_verified_components(indexed, chain)
+        })
+        .collect())
 }
 
 /// Assists in readability.
@@ -1,4 +1,5 @@
 use crate::attestation_verification::{
+    batch_verify_aggregated_attestations, batch_verify_unaggregated_attestations,
     Error as AttestationError, SignatureVerifiedAttestation, VerifiedAggregatedAttestation,
     VerifiedUnaggregatedAttestation,
 };
@@ -8,7 +9,7 @@ use crate::block_verification::{
 };
 use crate::errors::{BeaconChainError as Error, BlockProductionError};
 use crate::eth1_chain::{Eth1Chain, Eth1ChainBackend};
-use crate::events::{EventHandler, EventKind};
+use crate::events::{EventHandler, EventKind, EventTopic};
 use crate::head_tracker::HeadTracker;
 use crate::metrics;
 use crate::migrate::Migrate;
@@ -505,6 +506,19 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         })
     }
 
+    /// Returns `checkpoint`, or the checkpoint of the database anchor if `checkpoint` predates it.
+    ///
+    /// Following a checkpoint sync, states may reference finalized checkpoints whose blocks were
+    /// never imported. The anchor is the latest finalized checkpoint that is known locally.
+    pub fn finalized_checkpoint_or_anchor(&self, checkpoint: Checkpoint) -> Checkpoint {
+        match self.store.get_anchor_info() {
+            Some(anchor) if checkpoint.epoch < anchor.checkpoint::<T::EthSpec>().epoch => {
+                anchor.checkpoint::<T::EthSpec>()
+            }
+            _ => checkpoint,
+        }
+    }
+
     /// Returns the current heads of the `BeaconChain`. For the canonical head, see `Self::head`.
     ///
     /// Returns `(block_root, block_slot)`.
@@ -752,6 +766,19 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         self.naive_aggregation_pool.get(data).map_err(Into::into)
     }
 
+    /// Returns an aggregated `Attestation`, if any, at `slot` with an `attestation.data` that has
+    /// the given `tree_hash_root`.
+    ///
+    /// The attestation will be obtained from `self.naive_aggregation_pool`.
+    pub fn get_aggregated_attestation_by_slot_and_root(
+        &self,
+        slot: Slot,
+        attestation_data_root: &Hash256,
+    ) -> Option<Attestation<T::EthSpec>> {
+        self.naive_aggregation_pool
+            .get_by_slot_and_root(slot, attestation_data_root)
+    }
+
     /// Produce an unaggregated `Attestation` that is valid for the given `slot` and `index`.
     ///
     /// The produced `Attestation` will not be valid until it has been signed by exactly one
@@ -904,6 +931,59 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         })
     }
 
+    /// Accepts a batch of unaggregated `Attestation`s from the network and attempts to verify
+    /// them, returning a result for each attestation in the order they were given.
+    ///
+    /// The signatures of the attestations are verified together, falling back to verifying them
+    /// individually if the batch is invalid.
+    pub fn batch_verify_unaggregated_attestations_for_gossip(
+        &self,
+        attestations: Vec<(Attestation<T::EthSpec>, SubnetId)>,
+    ) -> Result<Vec<Result<VerifiedUnaggregatedAttestation<T>, AttestationError>>, AttestationError>
+    {
+        metrics::inc_counter_by(
+            &metrics::UNAGGREGATED_ATTESTATION_PROCESSING_REQUESTS,
+            attestations.len() as i64,
+        );
+        let _timer = metrics::start_timer(
+            &metrics::UNAGGREGATED_ATTESTATION_GOSSIP_BATCH_VERIFICATION_TIMES,
+        );
+
+        let results = batch_verify_unaggregated_attestations(attestations.into_iter(), self)?;
+        metrics::inc_counter_by(
+            &metrics::UNAGGREGATED_ATTESTATION_PROCESSING_SUCCESSES,
+            results.iter().flatten().count() as i64,
+        );
+
+        Ok(results)
+    }
+
+    /// Accepts a batch of `SignedAggregateAndProof`s from the network and attempts to verify them,
+    /// returning a result for each aggregate in the order they were given.
+    ///
+    /// The signatures of the aggregates are verified together, falling back to verifying them
+    /// individually if the batch is invalid.
+    pub fn batch_verify_aggregated_attestations_for_gossip(
+        &self,
+        signed_aggregates: Vec<SignedAggregateAndProof<T::EthSpec>>,
+    ) -> Result<Vec<Result<VerifiedAggregatedAttestation<T>, AttestationError>>, AttestationError>
+    {
+        metrics::inc_counter_by(
+            &metrics::AGGREGATED_ATTESTATION_PROCESSING_REQUESTS,
+            signed_aggregates.len() as i64,
+        );
+        let _timer =
+            metrics::start_timer(&metrics::AGGREGATED_ATTESTATION_GOSSIP_BATCH_VERIFICATION_TIMES);
+
+        let results = batch_verify_aggregated_attestations(signed_aggregates.into_iter(), self)?;
+        metrics::inc_counter_by(
+            &metrics::AGGREGATED_ATTESTATION_PROCESSING_SUCCESSES,
+            results.iter().flatten().count() as i64,
+        );
+
+        Ok(results)
+    }
+
     /// Accepts some attestation-type object and attempts to verify it in the context of fork
     /// choice. If it is valid it is applied to `self.fork_choice`.
     ///
@@ -927,7 +1007,8 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
     /// aggregation pool".
     ///
     /// The naive aggregation pool is used by local validators to produce
-    /// `SignedAggregateAndProof`.
+    /// `SignedAggregateAndProof`. This is the final step of importing an unaggregated attestation,
+    /// so a `BeaconAttestationImported` event is registered once it succeeds.
     ///
     /// If the attestation is too old (low slot) to be included in the pool it is simply dropped
     /// and no error is returned.
@@ -970,12 +1051,22 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             }
         };
 
+        if self.event_handler.has_subscribers(EventTopic::Attestation) {
+            let _ = self
+                .event_handler
+                .register(EventKind::BeaconAttestationImported {
+                    attestation: Box::new(attestation.clone()),
+                });
+        }
+
         Ok(unaggregated_attestation)
     }
 
     /// Accepts a `VerifiedAggregatedAttestation` and attempts to apply it to `self.op_pool`.
     ///
-    /// The op pool is used by local block producers to pack blocks with operations.
+    /// The op pool is used by local block producers to pack blocks with operations. This is the
+    /// final step of importing an aggregate, so a `BeaconAttestationImported` event is registered
+    /// once it succeeds.
     pub fn add_to_block_inclusion_pool(
         &self,
         signed_aggregate: VerifiedAggregatedAttestation<T>,
@@ -1004,6 +1095,14 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
                 .map_err(Error::from)?;
         }
 
+        if self.event_handler.has_subscribers(EventTopic::Attestation) {
+            let _ = self
+                .event_handler
+                .register(EventKind::BeaconAttestationImported {
+                    attestation: Box::new(signed_aggregate.attestation().clone()),
+                });
+        }
+
         Ok(signed_aggregate)
     }
 
@@ -1710,7 +1809,9 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
         let beacon_block_root = self.fork_choice.write().get_head(self.slot()?)?;
 
         let current_head = self.head_info()?;
-        let old_finalized_root = current_head.finalized_checkpoint.root;
+        let old_finalized_checkpoint =
+            self.finalized_checkpoint_or_anchor(current_head.finalized_checkpoint);
+        let old_finalized_root = old_finalized_checkpoint.root;
 
         if beacon_block_root == current_head.block_root {
             return Ok(());
@@ -1790,9 +1891,11 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             );
         };
 
-        let old_finalized_epoch = current_head.finalized_checkpoint.epoch;
-        let new_finalized_epoch = new_head.beacon_state.finalized_checkpoint.epoch;
-        let finalized_root = new_head.beacon_state.finalized_checkpoint.root;
+        let new_finalized_checkpoint =
+            self.finalized_checkpoint_or_anchor(new_head.beacon_state.finalized_checkpoint);
+        let old_finalized_epoch = old_finalized_checkpoint.epoch;
+        let new_finalized_epoch = new_finalized_checkpoint.epoch;
+        let finalized_root = new_finalized_checkpoint.root;
 
         // It is an error to try to update to a head with a lesser finalized epoch.
         if new_finalized_epoch < old_finalized_epoch {
@@ -1802,16 +1905,28 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             });
         }
 
-        if current_head.slot.epoch(T::EthSpec::slots_per_epoch())
+        let is_epoch_transition = current_head.slot.epoch(T::EthSpec::slots_per_epoch())
             < new_head
                 .beacon_state
                 .slot
-                .epoch(T::EthSpec::slots_per_epoch())
-            || is_reorg
-        {
+                .epoch(T::EthSpec::slots_per_epoch());
+
+        if is_epoch_transition || is_reorg {
             self.persist_head_and_fork_choice()?;
         }
 
+        // Measure the re-org whilst the previous head is still available. Failing to obtain the
+        // lock only results in the re-org event being omitted.
+        let reorg_depth_opt = if is_reorg {
+            self.canonical_head
+                .try_read_for(HEAD_LOCK_TIMEOUT)
+                .map(|old_head| reorg_depth(&old_head, &new_head))
+        } else {
+            None
+        };
+        let new_head_slot = new_head.beacon_block.slot();
+        let new_head_state_root = new_head.beacon_state_root;
+
         let update_head_timer = metrics::start_timer(&metrics::UPDATE_HEAD_TIMES);
 
         // Update the snapshot that stores the head of the chain at the time it received the
@@ -1845,10 +1960,25 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             )?;
         }
 
+        if let Some(depth) = reorg_depth_opt {
+            let _ = self.event_handler.register(EventKind::BeaconChainReorg {
+                slot: new_head_slot,
+                depth,
+                old_head_block_root: current_head.block_root,
+                new_head_block_root: beacon_block_root,
+                old_head_state_root: current_head.state_root,
+                new_head_state_root,
+                epoch: new_head_slot.epoch(T::EthSpec::slots_per_epoch()),
+            });
+        }
+
         let _ = self.event_handler.register(EventKind::BeaconHeadChanged {
             reorg: is_reorg,
-            previous_head_beacon_block_root: current_head.block_root,
+            current_head_slot: new_head_slot,
             current_head_beacon_block_root: beacon_block_root,
+            current_head_state_root: new_head_state_root,
+            previous_head_beacon_block_root: current_head.block_root,
+            epoch_transition: is_epoch_transition,
         });
 
         Ok(())
@@ -1927,6 +2057,7 @@ impl<T: BeaconChainTypes> BeaconChain<T> {
             let _ = self.event_handler.register(EventKind::BeaconFinalization {
                 epoch: new_finalized_epoch,
                 root: finalized_block_root,
+                state_root: finalized_block.state_root,
             });
 
             Ok(())
@@ -2149,3 +2280,33 @@ impl ChainSegmentResult {
         }
     }
 }
+
+/// Returns the number of slots between `old_head` and the most recent slot at which it shares a
+/// block with `new_head`.
+///
+/// If the chains do not meet within `SLOTS_PER_HISTORICAL_ROOT` slots, the distance to the oldest
+/// slot searched is returned.
+fn reorg_depth<E: EthSpec>(old_head: &BeaconSnapshot<E>, new_head: &BeaconSnapshot<E>) -> u64 {
+    let root_at = |snapshot: &BeaconSnapshot<E>, slot: Slot| {
+        if slot == snapshot.beacon_block.slot() {
+            Some(snapshot.beacon_block_root)
+        } else {
+            snapshot.beacon_state.get_block_root(slot).ok().copied()
+        }
+    };
+
+    let old_slot = old_head.beacon_block.slot();
+    let start_slot = std::cmp::min(old_slot, new_head.beacon_block.slot());
+    let lowest_slot = start_slot.saturating_sub(E::slots_per_historical_root() as u64);
+
+    let common_slot = (lowest_slot.as_u64()..=start_slot.as_u64())
+        .rev()
+        .map(Slot::new)
+        .find(|slot| {
+            let old_root = root_at(old_head, *slot);
+            old_root.is_some() && old_root == root_at(new_head, *slot)
+        })
+        .unwrap_or(lowest_slot);
+
+    (old_slot - common_slot).as_u64()
+}
@@ -25,7 +25,7 @@ use std::marker::PhantomData;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
-use store::{HotColdDB, ItemStore};
+use store::{AnchorInfo, HotColdDB, ItemStore};
 use types::{
     BeaconBlock, BeaconState, ChainSpec, EthSpec, Hash256, Signature, SignedBeaconBlock, Slot,
 };
@@ -287,7 +287,17 @@ where
                 .unwrap_or_else(OperationPool::new),
         );
 
-        let finalized_block_root = head_state.finalized_checkpoint.root;
+        // Following a checkpoint sync the head state may reference a finalized block prior to
+        // the database anchor, which is not stored. Use the anchor block instead.
+        let finalized_block_root = match store.get_anchor_info() {
+            Some(anchor)
+                if head_state.finalized_checkpoint.epoch
+                    < anchor.checkpoint::<TEthSpec>().epoch =>
+            {
+                anchor.anchor_block_root
+            }
+            _ => head_state.finalized_checkpoint.root,
+        };
         let finalized_block = store
             .get_item::<SignedBeaconBlock<TEthSpec>>(&finalized_block_root)
             .map_err(|e| format!("DB error when reading finalized block: {:?}", e))?
@@ -368,6 +378,121 @@ where
         Ok(self.empty_op_pool())
     }
 
+    /// Starts a new chain from a trusted finalized ("weak subjectivity") state and block.
+    ///
+    /// The state must be the post-state of the block, and both must lie on the first slot of an
+    /// epoch. The `genesis_state` is used to determine the genesis block root and to check that
+    /// the anchor belongs to the expected chain. Blocks and states prior to the anchor are not
+    /// available.
+    pub fn weak_subjectivity_state(
+        mut self,
+        mut weak_subj_state: BeaconState<TEthSpec>,
+        weak_subj_block: SignedBeaconBlock<TEthSpec>,
+        mut genesis_state: BeaconState<TEthSpec>,
+    ) -> Result<Self, String> {
+        let store = self
+            .store
+            .clone()
+            .ok_or_else(|| "weak_subjectivity_state requires a store")?;
+        let log = self
+            .log
+            .as_ref()
+            .ok_or_else(|| "weak_subjectivity_state requires a log".to_string())?;
+
+        let weak_subj_slot = weak_subj_state.slot;
+        let weak_subj_block_root = weak_subj_block.canonical_root();
+        let weak_subj_state_root = weak_subj_block.state_root();
+
+        if weak_subj_slot % TEthSpec::slots_per_epoch() != 0 {
+            return Err(format!(
+                "Checkpoint state at slot {} is not on an epoch boundary",
+                weak_subj_slot
+            ));
+        }
+
+        if weak_subj_block.slot() != weak_subj_slot {
+            return Err(format!(
+                "Checkpoint block slot {} does not match checkpoint state slot {}",
+                weak_subj_block.slot(),
+                weak_subj_slot
+            ));
+        }
+
+        let computed_state_root = weak_subj_state
+            .update_tree_hash_cache()
+            .map_err(|e| format!("Error hashing checkpoint state: {:?}", e))?;
+
+        if computed_state_root != weak_subj_state_root {
+            return Err(format!(
+                "Checkpoint state root {:?} does not match the checkpoint block ({:?})",
+                computed_state_root, weak_subj_state_root
+            ));
+        }
+
+        if weak_subj_state.genesis_validators_root != genesis_state.genesis_validators_root {
+            return Err(format!(
+                "Checkpoint state is from a different chain (genesis_validators_root {:?}, \
+                 expected {:?})",
+                weak_subj_state.genesis_validators_root, genesis_state.genesis_validators_root
+            ));
+        }
+
+        info!(
+            log,
+            "Starting beacon chain";
+            "method" => "checkpoint",
+            "slot" => weak_subj_slot,
+            "block_root" => format!("{:?}", weak_subj_block_root),
+        );
+
+        let genesis_block = genesis_block(&mut genesis_state, &self.spec)?;
+        let genesis_block_root = genesis_block.canonical_root();
+
+        weak_subj_state
+            .build_all_caches(&self.spec)
+            .map_err(|e| format!("Failed to build checkpoint state caches: {:?}", e))?;
+
+        // Move the split point to the anchor before storing any states, so that the anchor
+        // state is stored in the hot database.
+        store
+            .init_anchor_info(AnchorInfo {
+                anchor_slot: weak_subj_slot,
+                anchor_block_root: weak_subj_block_root,
+                anchor_state_root: weak_subj_state_root,
+            })
+            .map_err(|e| format!("Failed to initialize database anchor: {:?}", e))?;
+        store
+            .put_state(&weak_subj_state_root, &weak_subj_state)
+            .map_err(|e| format!("Failed to store checkpoint state: {:?}", e))?;
+        store
+            .put_item(&weak_subj_block_root, &weak_subj_block)
+            .map_err(|e| format!("Failed to store checkpoint block: {:?}", e))?;
+
+        // Store the genesis block so that it can be served by root and under the `ZERO_HASH`
+        // alias. The genesis state is not stored, it is not possible to replay blocks from it.
+        store
+            .put_item(&genesis_block_root, &genesis_block)
+            .map_err(|e| format!("Failed to store genesis block: {:?}", e))?;
+        store
+            .put_item(&Hash256::zero(), &genesis_block)
+            .map_err(|e| {
+                format!(
+                    "Failed to store genesis block under 0x00..00 alias: {:?}",
+                    e
+                )
+            })?;
+
+        self.genesis_block_root = Some(genesis_block_root);
+        self.finalized_snapshot = Some(BeaconSnapshot {
+            beacon_block_root: weak_subj_block_root,
+            beacon_block: weak_subj_block,
+            beacon_state_root: weak_subj_state_root,
+            beacon_state: weak_subj_state,
+        });
+
+        Ok(self.empty_op_pool())
+    }
+
     /// Sets the `BeaconChain` eth1 backend.
     pub fn eth1_backend(mut self, backend: Option<TEth1Backend>) -> Self {
         self.eth1_chain = backend.map(Eth1Chain::new);
@@ -1,68 +1,319 @@
+use crate::metrics;
 use bus::Bus;
+use futures::channel::mpsc;
 use parking_lot::Mutex;
 use serde_derive::{Deserialize, Serialize};
-use slog::{error, Logger};
+use serde_json::json;
+use slog::{debug, error, Logger};
+use std::fmt;
 use std::marker::PhantomData;
+use std::str::FromStr;
 use std::sync::Arc;
-use types::{Attestation, Epoch, EthSpec, Hash256, SignedBeaconBlock, SignedBeaconBlockHash};
+use types::{Attestation, Epoch, EthSpec, Hash256, SignedBeaconBlock, SignedBeaconBlockHash, Slot};
 pub use websocket_server::WebSocketSender;
 
+/// The number of events which may be queued for each server-sent event subscriber before it is
+/// disconnected.
+pub const EVENT_QUEUE_CAPACITY: usize = 1_024;
+
 pub trait EventHandler<T: EthSpec>: Sized + Send + Sync {
     fn register(&self, kind: EventKind<T>) -> Result<(), String>;
+
+    /// Returns `false` if events of `topic` would be discarded by `register`, so that callers can
+    /// avoid building expensive events (e.g., attestations) which nobody will receive.
+    fn has_subscribers(&self, _topic: EventTopic) -> bool {
+        true
+    }
 }
 
 pub struct NullEventHandler<T: EthSpec>(PhantomData<T>);
 
 impl<T: EthSpec> EventHandler<T> for WebSocketSender<T> {
     fn register(&self, kind: EventKind<T>) -> Result<(), String> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
         self.send_string(
             serde_json::to_string(&kind)
                 .map_err(|e| format!("Unable to serialize event: {:?}", e))?,
         )
     }
+
+    fn has_subscribers(&self, _topic: EventTopic) -> bool {
+        self.is_enabled()
+    }
+}
+
+/// The topics which may be subscribed to on the server-sent event stream.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EventTopic {
+    Head,
+    Block,
+    Attestation,
+    FinalizedCheckpoint,
+    ChainReorg,
+}
+
+impl EventTopic {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventTopic::Head => "head",
+            EventTopic::Block => "block",
+            EventTopic::Attestation => "attestation",
+            EventTopic::FinalizedCheckpoint => "finalized_checkpoint",
+            EventTopic::ChainReorg => "chain_reorg",
+        }
+    }
+}
+
+impl FromStr for EventTopic {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "head" => Ok(EventTopic::Head),
+            "block" => Ok(EventTopic::Block),
+            "attestation" => Ok(EventTopic::Attestation),
+            "finalized_checkpoint" => Ok(EventTopic::FinalizedCheckpoint),
+            "chain_reorg" => Ok(EventTopic::ChainReorg),
+            other => Err(format!("Unknown event topic: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for EventTopic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// An event for the server-sent event stream, with its data already serialized to JSON.
+///
+/// Serialization happens once, when the event is registered, rather than once per subscriber. It
+/// is skipped entirely if nobody is subscribed to the event's topic.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SseEvent {
+    pub topic: EventTopic,
+    pub data: String,
+}
+
+impl SseEvent {
+    /// Returns the server-sent event for `kind`, if it corresponds to some `EventTopic`.
+    pub fn from_event_kind<T: EthSpec>(kind: &EventKind<T>) -> Option<Self> {
+        let (topic, data) = match kind {
+            EventKind::BeaconHeadChanged {
+                current_head_slot,
+                current_head_beacon_block_root,
+                current_head_state_root,
+                epoch_transition,
+                ..
+            } => (
+                EventTopic::Head,
+                json!({
+                    "slot": current_head_slot,
+                    "block": current_head_beacon_block_root,
+                    "state": current_head_state_root,
+                    "epoch_transition": epoch_transition,
+                }),
+            ),
+            EventKind::BeaconChainReorg {
+                slot,
+                depth,
+                old_head_block_root,
+                new_head_block_root,
+                old_head_state_root,
+                new_head_state_root,
+                epoch,
+            } => (
+                EventTopic::ChainReorg,
+                json!({
+                    "slot": slot,
+                    "depth": depth,
+                    "old_head_block": old_head_block_root,
+                    "new_head_block": new_head_block_root,
+                    "old_head_state": old_head_state_root,
+                    "new_head_state": new_head_state_root,
+                    "epoch": epoch,
+                }),
+            ),
+            EventKind::BeaconFinalization {
+                epoch,
+                root,
+                state_root,
+            } => (
+                EventTopic::FinalizedCheckpoint,
+                json!({
+                    "block": root,
+                    "state": state_root,
+                    "epoch": epoch,
+                }),
+            ),
+            EventKind::BeaconBlockImported { block_root, block } => (
+                EventTopic::Block,
+                json!({
+                    "slot": block.slot(),
+                    "block": block_root,
+                }),
+            ),
+            EventKind::BeaconAttestationImported { attestation } => {
+                (EventTopic::Attestation, json!(attestation))
+            }
+            EventKind::BeaconBlockRejected { .. } | EventKind::BeaconAttestationRejected { .. } => {
+                return None
+            }
+        };
+
+        Some(Self {
+            topic,
+            data: data.to_string(),
+        })
+    }
+}
+
+/// A client of the server-sent event stream.
+struct Subscriber {
+    topics: Vec<EventTopic>,
+    sender: mpsc::Sender<SseEvent>,
+}
+
+/// The clients subscribed to the server-sent event stream.
+///
+/// Each subscriber has its own queue of `EVENT_QUEUE_CAPACITY` events. A subscriber which falls
+/// that far behind is disconnected, so that it cannot cause events to be dropped for anyone else.
+#[derive(Clone, Default)]
+pub struct EventSubscribers {
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
+}
+
+impl EventSubscribers {
+    /// Returns a stream of the events of `topics`.
+    ///
+    /// The stream ends if the subscriber is disconnected for falling too far behind. Dropping the
+    /// stream unsubscribes.
+    pub fn subscribe(&self, topics: Vec<EventTopic>) -> mpsc::Receiver<SseEvent> {
+        let (sender, receiver) = mpsc::channel(EVENT_QUEUE_CAPACITY);
+        self.subscribers.lock().push(Subscriber { topics, sender });
+        receiver
+    }
+
+    /// Returns the number of subscribers which have not yet dropped their stream.
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers
+            .lock()
+            .iter()
+            .filter(|subscriber| !subscriber.sender.is_closed())
+            .count()
+    }
+
+    /// Returns `true` if any subscriber is interested in `topic`.
+    pub fn has_subscribers(&self, topic: EventTopic) -> bool {
+        self.subscribers
+            .lock()
+            .iter()
+            .any(|subscriber| !subscriber.sender.is_closed() && subscriber.topics.contains(&topic))
+    }
+
+    /// Sends `event` to every subscriber of its topic without blocking.
+    ///
+    /// Subscribers which have dropped their stream or whose queue is full are removed.
+    fn broadcast(&self, event: &SseEvent, log: &Logger) {
+        let mut subscribers = self.subscribers.lock();
+        subscribers.retain(|subscriber| !subscriber.sender.is_closed());
+
+        for subscriber in subscribers.iter_mut() {
+            if !subscriber.topics.contains(&event.topic) {
+                continue;
+            }
+
+            if let Err(e) = subscriber.sender.try_send(event.clone()) {
+                if e.is_full() {
+                    metrics::inc_counter(&metrics::SSE_SUBSCRIBERS_DISCONNECTED);
+                    debug!(
+                        log,
+                        "Disconnecting lagging event subscriber";
+                        "dropped_topic" => event.topic.as_str(),
+                    );
+                }
+                // Closing the channel ends the subscriber's stream once it has drained its queue.
+                subscriber.sender.close_channel();
+            }
+        }
+    }
+}
+
+/// The queues which `ServerSentEvents` broadcasts to. HTTP clients subscribe by adding a reader
+/// to one of these queues.
+#[derive(Clone)]
+pub struct EventQueues {
+    /// The roots of new head blocks, used by `/beacon/fork/stream`.
+    pub head_changed: Arc<Mutex<Bus<SignedBeaconBlockHash>>>,
+    /// Every event which has an `EventTopic`, used by `/eth/v1/events`.
+    pub events: EventSubscribers,
 }
 
 pub struct ServerSentEvents<T: EthSpec> {
     // Bus<> is itself Sync + Send.  We use Mutex<> here only because of the surrounding code does
     // not enforce mutability statically (i.e. relies on interior mutability).
-    head_changed_queue: Arc<Mutex<Bus<SignedBeaconBlockHash>>>,
+    queues: EventQueues,
     log: Logger,
     _phantom: PhantomData<T>,
 }
 
 impl<T: EthSpec> ServerSentEvents<T> {
-    pub fn new(log: Logger) -> (Self, Arc<Mutex<Bus<SignedBeaconBlockHash>>>) {
-        let bus = Bus::new(T::slots_per_epoch() as usize);
-        let mutex = Mutex::new(bus);
-        let arc = Arc::new(mutex);
+    pub fn new(log: Logger) -> (Self, EventQueues) {
+        let queues = EventQueues {
+            head_changed: Arc::new(Mutex::new(Bus::new(T::slots_per_epoch() as usize))),
+            events: EventSubscribers::default(),
+        };
         let this = Self {
-            head_changed_queue: arc.clone(),
-            log: log,
+            queues: queues.clone(),
+            log,
             _phantom: PhantomData,
         };
-        (this, arc)
+        (this, queues)
     }
 }
 
 impl<T: EthSpec> EventHandler<T> for ServerSentEvents<T> {
+    /// Broadcasts `kind` to all subscribers without blocking.
+    ///
+    /// A server-sent event subscriber which has fallen `EVENT_QUEUE_CAPACITY` events behind is
+    /// disconnected rather than stalling the caller (e.g., `BeaconChain::fork_choice`).
     fn register(&self, kind: EventKind<T>) -> Result<(), String> {
-        match kind {
-            EventKind::BeaconHeadChanged {
-                current_head_beacon_block_root,
-                ..
-            } => {
-                let mut guard = self.head_changed_queue.lock();
-                if let Err(_) = guard.try_broadcast(current_head_beacon_block_root.into()) {
-                    error!(
-                        self.log,
-                        "Head change streaming queue full";
-                        "dropped_change" => format!("{}", current_head_beacon_block_root),
-                    );
-                }
-                Ok(())
+        if let EventKind::BeaconHeadChanged {
+            current_head_beacon_block_root,
+            ..
+        } = kind
+        {
+            let mut guard = self.queues.head_changed.lock();
+            if guard
+                .try_broadcast(current_head_beacon_block_root.into())
+                .is_err()
+            {
+                error!(
+                    self.log,
+                    "Head change streaming queue full";
+                    "dropped_change" => format!("{}", current_head_beacon_block_root),
+                );
             }
-            _ => Ok(()),
         }
+
+        let has_subscribers = kind
+            .topic()
+            .map_or(false, |topic| self.queues.events.has_subscribers(topic));
+        if has_subscribers {
+            if let Some(event) = SseEvent::from_event_kind(&kind) {
+                self.queues.events.broadcast(&event, &self.log);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn has_subscribers(&self, topic: EventTopic) -> bool {
+        // `/beacon/fork/stream` readers are not tracked, so head events are always wanted
+        topic == EventTopic::Head || self.queues.events.has_subscribers(topic)
     }
 }
 
@@ -78,28 +329,57 @@ impl<E: EthSpec> TeeEventHandler<E> {
     pub fn new(
         log: Logger,
         websockets_handler: WebSocketSender<E>,
-    ) -> Result<(Self, Arc<Mutex<Bus<SignedBeaconBlockHash>>>), String> {
-        let (sse_handler, bus) = ServerSentEvents::new(log);
+    ) -> Result<(Self, EventQueues), String> {
+        let (sse_handler, queues) = ServerSentEvents::new(log);
         let result = Self {
             websockets_handler: websockets_handler,
             sse_handler: sse_handler,
         };
-        Ok((result, bus))
+        Ok((result, queues))
     }
 }
 
 impl<E: EthSpec> EventHandler<E> for TeeEventHandler<E> {
+    /// Attestation events are only sent to the SSE handler, the legacy websocket API does not
+    /// include them.
     fn register(&self, kind: EventKind<E>) -> Result<(), String> {
-        self.websockets_handler.register(kind.clone())?;
+        if kind.topic() != Some(EventTopic::Attestation) {
+            self.websockets_handler.register(kind.clone())?;
+        }
         self.sse_handler.register(kind)?;
         Ok(())
     }
+
+    fn has_subscribers(&self, topic: EventTopic) -> bool {
+        (topic != EventTopic::Attestation && self.websockets_handler.has_subscribers(topic))
+            || self.sse_handler.has_subscribers(topic)
+    }
+}
+
+impl<T: EthSpec> EventKind<T> {
+    /// Returns the server-sent event topic of this event, if it has one.
+    pub fn topic(&self) -> Option<EventTopic> {
+        match self {
+            EventKind::BeaconHeadChanged { .. } => Some(EventTopic::Head),
+            EventKind::BeaconChainReorg { .. } => Some(EventTopic::ChainReorg),
+            EventKind::BeaconFinalization { .. } => Some(EventTopic::FinalizedCheckpoint),
+            EventKind::BeaconBlockImported { .. } => Some(EventTopic::Block),
+            EventKind::BeaconAttestationImported { .. } => Some(EventTopic::Attestation),
+            EventKind::BeaconBlockRejected { .. } | EventKind::BeaconAttestationRejected { .. } => {
+                None
+            }
+        }
+    }
 }
 
 impl<T: EthSpec> EventHandler<T> for NullEventHandler<T> {
     fn register(&self, _kind: EventKind<T>) -> Result<(), String> {
         Ok(())
     }
+
+    fn has_subscribers(&self, _topic: EventTopic) -> bool {
+        false
+    }
 }
 
 impl<T: EthSpec> Default for NullEventHandler<T> {
@@ -118,12 +398,30 @@ impl<T: EthSpec> Default for NullEventHandler<T> {
 pub enum EventKind<T: EthSpec> {
     BeaconHeadChanged {
         reorg: bool,
+        current_head_slot: Slot,
         current_head_beacon_block_root: Hash256,
+        current_head_state_root: Hash256,
         previous_head_beacon_block_root: Hash256,
+        /// True if the new head is in a later epoch than the previous head.
+        epoch_transition: bool,
+    },
+    /// The head has switched to a block which does not descend from the previous head.
+    BeaconChainReorg {
+        /// The slot of the new head.
+        slot: Slot,
+        /// The number of slots between the previous head and the most recent block it shares
+        /// with the new head.
+        depth: u64,
+        old_head_block_root: Hash256,
+        new_head_block_root: Hash256,
+        old_head_state_root: Hash256,
+        new_head_state_root: Hash256,
+        epoch: Epoch,
     },
     BeaconFinalization {
         epoch: Epoch,
         root: Hash256,
+        state_root: Hash256,
     },
     BeaconBlockImported {
         block_root: Hash256,
@@ -91,6 +91,10 @@ lazy_static! {
         "beacon_unaggregated_attestation_gossip_verification_seconds",
         "Full runtime of aggregated attestation gossip verification"
     );
+    pub static ref UNAGGREGATED_ATTESTATION_GOSSIP_BATCH_VERIFICATION_TIMES: Result<Histogram> = try_create_histogram(
+        "beacon_unaggregated_attestation_gossip_batch_verification_seconds",
+        "Full runtime of unaggregated attestation gossip verification for a batch of attestations"
+    );
 
     /*
      * Aggregated Attestation Verification
@@ -107,6 +111,10 @@ lazy_static! {
         "beacon_aggregated_attestation_gossip_verification_seconds",
         "Full runtime of aggregated attestation gossip verification"
     );
+    pub static ref AGGREGATED_ATTESTATION_GOSSIP_BATCH_VERIFICATION_TIMES: Result<Histogram> = try_create_histogram(
+        "beacon_aggregated_attestation_gossip_batch_verification_seconds",
+        "Full runtime of aggregated attestation gossip verification for a batch of attestations"
+    );
 
     /*
      * General Attestation Processing
@@ -171,6 +179,18 @@ lazy_static! {
         "beacon_attestation_processing_signature_seconds",
         "Time spent on the signature verification of attestation processing"
     );
+    pub static ref ATTESTATION_PROCESSING_BATCH_SIGNATURE_SETUP_TIMES: Result<Histogram> = try_create_histogram(
+        "beacon_attestation_processing_batch_signature_setup_seconds",
+        "Time spent on setting up for the signature verification of a batch of attestations"
+    );
+    pub static ref ATTESTATION_PROCESSING_BATCH_SIGNATURE_TIMES: Result<Histogram> = try_create_histogram(
+        "beacon_attestation_processing_batch_signature_seconds",
+        "Time spent on the signature verification of a batch of attestations"
+    );
+    pub static ref ATTESTATION_PROCESSING_BATCH_SIGNATURE_FAILURES: Result<IntCounter> = try_create_int_counter(
+        "beacon_attestation_processing_batch_signature_failures_total",
+        "Count of attestation batches with an invalid signature, which were verified individually"
+    );
 
     /*
      * Shuffling cache
@@ -328,6 +348,14 @@ lazy_static! {
         "beacon_attn_observation_epoch_aggregators",
         "Count of aggregators that have been seen by the beacon chain in the previous epoch"
     );
+
+    /*
+     * Server-Sent Events
+     */
+    pub static ref SSE_SUBSCRIBERS_DISCONNECTED: Result<IntCounter> = try_create_int_counter(
+        "beacon_sse_subscribers_disconnected_total",
+        "Count of server-sent event subscribers disconnected for falling too far behind"
+    );
 }
 
 /// Scrape the `beacon_chain` for metrics that are not constantly updated (e.g., the present slot,
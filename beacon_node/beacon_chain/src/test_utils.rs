@@ -6,7 +6,7 @@ pub use crate::persisted_beacon_chain::PersistedBeaconChain;
 use crate::{
     builder::{BeaconChainBuilder, Witness},
     eth1_chain::CachingEth1Backend,
-    events::NullEventHandler,
+    events::{EventHandler, EventQueues, NullEventHandler, ServerSentEvents},
     BeaconChain, BeaconChainTypes, StateSkipConfig,
 };
 use genesis::interop_genesis_state;
@@ -18,7 +18,7 @@ use std::borrow::Cow;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
-use store::{config::StoreConfig, HotColdDB, ItemStore, LevelDB, MemoryStore};
+use store::{config::StoreConfig, DiskStore, HotColdDB, ItemStore, MemoryStore};
 use tempfile::{tempdir, TempDir};
 use tree_hash::TreeHash;
 use types::{
@@ -34,19 +34,27 @@ pub const HARNESS_GENESIS_TIME: u64 = 1_567_552_690;
 // This parameter is required by a builder but not used because we use the `TestingSlotClock`.
 pub const HARNESS_SLOT_TIME: Duration = Duration::from_secs(1);
 
-pub type BaseHarnessType<TStoreMigrator, TEthSpec, THotStore, TColdStore> = Witness<
+pub type BaseHarnessType<
+    TStoreMigrator,
+    TEthSpec,
+    THotStore,
+    TColdStore,
+    TEventHandler = NullEventHandler<TEthSpec>,
+> = Witness<
     TStoreMigrator,
     TestingSlotClock,
     CachingEth1Backend<TEthSpec>,
     TEthSpec,
-    NullEventHandler<TEthSpec>,
+    TEventHandler,
     THotStore,
     TColdStore,
 >;
 
 pub type HarnessType<E> = BaseHarnessType<NullMigrator, E, MemoryStore<E>, MemoryStore<E>>;
+pub type EventsHarnessType<E> =
+    BaseHarnessType<NullMigrator, E, MemoryStore<E>, MemoryStore<E>, ServerSentEvents<E>>;
 pub type DiskHarnessType<E> =
-    BaseHarnessType<BlockingMigrator<E, LevelDB<E>, LevelDB<E>>, E, LevelDB<E>, LevelDB<E>>;
+    BaseHarnessType<BlockingMigrator<E, DiskStore<E>, DiskStore<E>>, E, DiskStore<E>, DiskStore<E>>;
 
 /// Indicates how the `BeaconChainHarness` should produce blocks.
 #[derive(Clone, Copy, Debug)]
@@ -137,11 +145,59 @@ impl<E: EthSpec> BeaconChainHarness<HarnessType<E>> {
     }
 }
 
+impl<E: EthSpec> BeaconChainHarness<EventsHarnessType<E>> {
+    /// Instantiate a new harness with `validator_count` initial validators, which registers its
+    /// events with a `ServerSentEvents` handler.
+    ///
+    /// Returns the queues which the events may be read from.
+    pub fn new_with_events(
+        eth_spec_instance: E,
+        keypairs: Vec<Keypair>,
+        config: StoreConfig,
+    ) -> (Self, EventQueues) {
+        let data_dir = tempdir().expect("should create temporary data_dir");
+        let mut spec = E::default_spec();
+
+        // See `BeaconChainHarness::new` for why all validators are aggregators.
+        spec.target_aggregators_per_committee = 1 << 32;
+
+        let log = NullLoggerBuilder.build().expect("logger should build");
+        let (event_handler, queues) = ServerSentEvents::new(log.clone());
+        let store = HotColdDB::open_ephemeral(config, spec.clone(), log.clone()).unwrap();
+        let chain = BeaconChainBuilder::new(eth_spec_instance)
+            .logger(log)
+            .custom_spec(spec.clone())
+            .store(Arc::new(store))
+            .store_migrator(NullMigrator)
+            .data_dir(data_dir.path().to_path_buf())
+            .genesis_state(
+                interop_genesis_state::<E>(&keypairs, HARNESS_GENESIS_TIME, &spec)
+                    .expect("should generate interop state"),
+            )
+            .expect("should build state using recent genesis")
+            .dummy_eth1_backend()
+            .expect("should build dummy backend")
+            .event_handler(event_handler)
+            .testing_slot_clock(HARNESS_SLOT_TIME)
+            .expect("should configure testing slot clock")
+            .build()
+            .expect("should build");
+
+        let harness = Self {
+            spec: chain.spec.clone(),
+            chain,
+            keypairs,
+            data_dir,
+        };
+        (harness, queues)
+    }
+}
+
 impl<E: EthSpec> BeaconChainHarness<DiskHarnessType<E>> {
     /// Instantiate a new harness with `validator_count` initial validators.
     pub fn new_with_disk_store(
         eth_spec_instance: E,
-        store: Arc<HotColdDB<E, LevelDB<E>, LevelDB<E>>>,
+        store: Arc<HotColdDB<E, DiskStore<E>, DiskStore<E>>>,
         keypairs: Vec<Keypair>,
     ) -> Self {
         let data_dir = tempdir().expect("should create temporary data_dir");
@@ -179,7 +235,7 @@ impl<E: EthSpec> BeaconChainHarness<DiskHarnessType<E>> {
     /// Instantiate a new harness with `validator_count` initial validators.
     pub fn resume_from_disk_store(
         eth_spec_instance: E,
-        store: Arc<HotColdDB<E, LevelDB<E>, LevelDB<E>>>,
+        store: Arc<HotColdDB<E, DiskStore<E>, DiskStore<E>>>,
         keypairs: Vec<Keypair>,
         data_dir: TempDir,
     ) -> Self {
@@ -215,12 +271,13 @@ impl<E: EthSpec> BeaconChainHarness<DiskHarnessType<E>> {
     }
 }
 
-impl<M, E, Hot, Cold> BeaconChainHarness<BaseHarnessType<M, E, Hot, Cold>>
+impl<M, E, Hot, Cold, H> BeaconChainHarness<BaseHarnessType<M, E, Hot, Cold, H>>
 where
     M: Migrate<E, Hot, Cold>,
     E: EthSpec,
     Hot: ItemStore<E>,
     Cold: ItemStore<E>,
+    H: EventHandler<E> + 'static,
 {
     /// Advance the slot of the `BeaconChain`.
     ///
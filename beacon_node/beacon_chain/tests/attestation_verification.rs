@@ -767,6 +767,138 @@ fn unaggregated_gossip_verification() {
     );
 }
 
+/// Ensures that a batch containing an attestation with an invalid signature only rejects that
+/// attestation.
+#[test]
+fn unaggregated_batch_verification_isolates_invalid_signatures() {
+    let harness = get_harness(VALIDATOR_COUNT);
+    let chain = &harness.chain;
+
+    harness.extend_chain(
+        MainnetEthSpec::slots_per_epoch() as usize * 3 - 1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    harness.advance_slot();
+
+    let head = chain.head().expect("should get head");
+    let current_slot = chain.slot().expect("should get slot");
+
+    // The attestations are made by the committees of the state's slot.
+    let mut state = head.beacon_state.clone();
+    while state.slot < current_slot {
+        per_slot_processing(&mut state, None, &harness.spec).expect("should process slot");
+    }
+
+    let mut attestations = harness
+        .get_unaggregated_attestations(
+            &AttestationStrategy::AllValidators,
+            &state,
+            head.beacon_block_root,
+            current_slot,
+        )
+        .into_iter()
+        .flatten()
+        .take(3)
+        .collect::<Vec<_>>();
+    assert_eq!(attestations.len(), 3, "should have three attestations");
+
+    let mut agg_sig = AggregateSignature::new();
+    agg_sig.add(&Signature::new(&[42, 42], &KEYPAIRS[0].sk));
+    attestations[1].0.signature = agg_sig;
+
+    let results = chain
+        .batch_verify_unaggregated_attestations_for_gossip(attestations)
+        .expect("should verify batch");
+
+    assert!(results[0].is_ok(), "first attestation should be valid");
+    assert!(
+        matches!(results[1], Err(AttnError::InvalidSignature)),
+        "second attestation should have an invalid signature"
+    );
+    assert!(results[2].is_ok(), "third attestation should be valid");
+}
+
+/// Ensures that a batch containing an aggregate with an invalid signature only rejects that
+/// aggregate.
+#[test]
+fn aggregated_batch_verification_isolates_invalid_signatures() {
+    // Every validator is an aggregator, so that a committee has several aggregators.
+    let harness = BeaconChainHarness::new(
+        MainnetEthSpec,
+        KEYPAIRS[..].to_vec(),
+        StoreConfig::default(),
+    );
+    harness.advance_slot();
+    let chain = &harness.chain;
+
+    harness.extend_chain(
+        MainnetEthSpec::slots_per_epoch() as usize * 3 - 1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+    harness.advance_slot();
+
+    let head = chain.head().expect("should get head");
+    let current_slot = chain.slot().expect("should get slot");
+
+    // The attestations are made by the committees of the state's slot.
+    let mut state = head.beacon_state.clone();
+    while state.slot < current_slot {
+        per_slot_processing(&mut state, None, &harness.spec).expect("should process slot");
+    }
+
+    let attestations = harness
+        .get_unaggregated_attestations(
+            &AttestationStrategy::AllValidators,
+            &state,
+            head.beacon_block_root,
+            current_slot,
+        )
+        .into_iter()
+        .next()
+        .expect("should have a committee");
+    let committee = state
+        .get_beacon_committee(current_slot, attestations[0].0.data.index)
+        .expect("should get committee")
+        .committee
+        .to_vec();
+
+    // Each aggregator includes one more attestation than the last, so that every aggregate is
+    // distinct.
+    let mut aggregate = attestations[0].0.clone();
+    let mut signed_aggregates = vec![];
+    for (i, (attestation, _)) in attestations.iter().enumerate().take(3) {
+        if i > 0 {
+            aggregate.aggregate(attestation);
+        }
+        let aggregator_index = committee[i];
+        signed_aggregates.push(SignedAggregateAndProof::from_aggregate(
+            aggregator_index as u64,
+            aggregate.clone(),
+            None,
+            &generate_deterministic_keypair(aggregator_index).sk,
+            &state.fork,
+            chain.genesis_validators_root,
+            &chain.spec,
+        ));
+    }
+    assert_eq!(signed_aggregates.len(), 3, "should have three aggregates");
+
+    signed_aggregates[1].signature = Signature::new(&[42, 42], &KEYPAIRS[0].sk);
+
+    let results = chain
+        .batch_verify_aggregated_attestations_for_gossip(signed_aggregates)
+        .expect("should verify batch");
+
+    assert!(results[0].is_ok(), "first aggregate should be valid");
+    assert!(
+        matches!(results[1], Err(AttnError::InvalidSignature)),
+        "second aggregate should have an invalid signature"
+    );
+    assert!(results[2].is_ok(), "third aggregate should be valid");
+}
+
 /// Ensures that an attestation that skips epochs can still be processed.
 ///
 /// This also checks that we can do a state lookup if we don't get a hit from the shuffling cache.
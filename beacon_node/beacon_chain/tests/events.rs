@@ -0,0 +1,232 @@
+#![cfg(not(debug_assertions))]
+
+#[macro_use]
+extern crate lazy_static;
+
+use beacon_chain::{
+    events::{
+        EventHandler, EventKind, EventQueues, EventTopic, ServerSentEvents, SseEvent,
+        EVENT_QUEUE_CAPACITY,
+    },
+    test_utils::{AttestationStrategy, BeaconChainHarness, BlockStrategy, EventsHarnessType},
+};
+use futures::channel::mpsc::Receiver;
+use serde_json::Value;
+use sloggers::{null::NullLoggerBuilder, Build};
+use store::config::StoreConfig;
+use types::{Epoch, EthSpec, Hash256, Keypair, MinimalEthSpec, Slot};
+
+pub const VALIDATOR_COUNT: usize = 24;
+
+lazy_static! {
+    /// A cached set of keys.
+    static ref KEYPAIRS: Vec<Keypair> = types::test_utils::generate_deterministic_keypairs(VALIDATOR_COUNT);
+}
+
+type E = MinimalEthSpec;
+
+fn get_harness() -> (BeaconChainHarness<EventsHarnessType<E>>, EventQueues) {
+    let (harness, queues) = BeaconChainHarness::new_with_events(
+        MinimalEthSpec,
+        KEYPAIRS.to_vec(),
+        StoreConfig::default(),
+    );
+
+    harness.advance_slot();
+
+    (harness, queues)
+}
+
+/// Returns the events which are waiting in `receiver`, with their data parsed as JSON.
+fn drain(receiver: &mut Receiver<SseEvent>) -> Vec<(EventTopic, Value)> {
+    let mut events = vec![];
+    while let Ok(Some(event)) = receiver.try_next() {
+        let data = serde_json::from_str(&event.data).expect("event data should be JSON");
+        events.push((event.topic, data));
+    }
+    events
+}
+
+fn root(value: &Value) -> Hash256 {
+    serde_json::from_value(value.clone()).expect("should be a root")
+}
+
+fn slot(value: &Value) -> Slot {
+    serde_json::from_value(value.clone()).expect("should be a slot")
+}
+
+#[test]
+fn head_block_and_finalized_events() {
+    let (harness, queues) = get_harness();
+    let mut receiver = queues.events.subscribe(vec![
+        EventTopic::Head,
+        EventTopic::Block,
+        EventTopic::FinalizedCheckpoint,
+    ]);
+
+    let num_blocks = E::slots_per_epoch() * 5;
+    harness.extend_chain(
+        num_blocks as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let events = drain(&mut receiver);
+    let head = harness.chain.head().expect("should get head");
+    let finalized_checkpoint = head.beacon_state.finalized_checkpoint;
+    assert!(
+        finalized_checkpoint.epoch > 0,
+        "the chain should have finalized"
+    );
+
+    let blocks = events
+        .iter()
+        .filter(|(topic, _)| *topic == EventTopic::Block)
+        .map(|(_, data)| (slot(&data["slot"]), root(&data["block"])))
+        .collect::<Vec<_>>();
+    assert_eq!(blocks.len() as u64, num_blocks, "one event per block");
+    for (slot, block_root) in &blocks {
+        assert_eq!(
+            harness
+                .chain
+                .root_at_slot(*slot)
+                .expect("should get block root"),
+            Some(*block_root),
+            "block event at slot {}",
+            slot
+        );
+    }
+
+    let heads = events
+        .iter()
+        .filter(|(topic, _)| *topic == EventTopic::Head)
+        .map(|(_, data)| data)
+        .collect::<Vec<_>>();
+    assert_eq!(heads.len() as u64, num_blocks, "one head event per block");
+    let last_head = heads.last().expect("should have a head event");
+    assert_eq!(root(&last_head["block"]), head.beacon_block_root);
+    assert_eq!(root(&last_head["state"]), head.beacon_state_root);
+    assert_eq!(slot(&last_head["slot"]), head.beacon_block.slot());
+    assert_eq!(
+        heads
+            .iter()
+            .filter(|data| data["epoch_transition"] == Value::Bool(true))
+            .count() as u64,
+        num_blocks / E::slots_per_epoch(),
+        "one epoch transition per epoch"
+    );
+
+    let last_finalized = events
+        .iter()
+        .filter(|(topic, _)| *topic == EventTopic::FinalizedCheckpoint)
+        .map(|(_, data)| data)
+        .last()
+        .expect("should have a finalized event");
+    assert_eq!(root(&last_finalized["block"]), finalized_checkpoint.root);
+    assert_eq!(
+        serde_json::from_value::<u64>(last_finalized["epoch"].clone()).unwrap(),
+        finalized_checkpoint.epoch.as_u64()
+    );
+
+    assert!(
+        events
+            .iter()
+            .all(|(topic, _)| *topic != EventTopic::ChainReorg),
+        "there should not be a reorg"
+    );
+}
+
+#[test]
+fn unsubscribed_topics_are_not_delivered() {
+    let (harness, queues) = get_harness();
+    let mut receiver = queues.events.subscribe(vec![EventTopic::Attestation]);
+
+    harness.extend_chain(
+        1,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    let events = drain(&mut receiver);
+    assert!(!events.is_empty(), "should receive attestation events");
+    assert!(events
+        .iter()
+        .all(|(topic, _)| *topic == EventTopic::Attestation));
+}
+
+#[test]
+fn reorg_event() {
+    let (harness, queues) = get_harness();
+    let mut receiver = queues.events.subscribe(vec![EventTopic::ChainReorg]);
+
+    // Build two blocks which no validator attests to.
+    let old_head = harness.extend_chain(
+        2,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::SomeValidators(vec![]),
+    );
+    let old_head_slot = Slot::new(2);
+
+    // Build a competing fork upon genesis which every validator attests to.
+    let new_head = harness.extend_chain(
+        2,
+        BlockStrategy::ForkCanonicalChainAt {
+            previous_slot: Slot::new(0),
+            first_slot: old_head_slot + 1,
+        },
+        AttestationStrategy::AllValidators,
+    );
+    assert_eq!(
+        harness
+            .chain
+            .head()
+            .expect("should get head")
+            .beacon_block_root,
+        new_head,
+        "the fork should become the head"
+    );
+
+    let events = drain(&mut receiver);
+    assert_eq!(events.len(), 1, "there should be exactly one reorg");
+    let (_, reorg) = &events[0];
+
+    // The chains only share the genesis block.
+    assert_eq!(reorg["depth"], Value::from(old_head_slot.as_u64()));
+    assert_eq!(root(&reorg["old_head_block"]), old_head);
+    assert!(slot(&reorg["slot"]) > old_head_slot);
+}
+
+#[test]
+fn lagging_subscriber_is_disconnected() {
+    let log = NullLoggerBuilder.build().expect("logger should build");
+    let (handler, queues) = ServerSentEvents::<E>::new(log);
+    let mut lagging = queues
+        .events
+        .subscribe(vec![EventTopic::FinalizedCheckpoint]);
+    let mut active = queues
+        .events
+        .subscribe(vec![EventTopic::FinalizedCheckpoint]);
+
+    let num_events = EVENT_QUEUE_CAPACITY * 2;
+    let mut received = 0;
+    for epoch in 0..num_events {
+        handler
+            .register(EventKind::BeaconFinalization {
+                epoch: Epoch::new(epoch as u64),
+                root: Hash256::zero(),
+                state_root: Hash256::zero(),
+            })
+            .expect("should register event");
+        received += drain(&mut active).len();
+    }
+    assert_eq!(
+        received, num_events,
+        "the active subscriber should receive every event"
+    );
+
+    // The lagging subscriber receives its queued events, then its stream ends.
+    let lagged = drain(&mut lagging).len();
+    assert!(lagged >= EVENT_QUEUE_CAPACITY && lagged < num_events);
+    assert_eq!(lagging.try_next().ok(), Some(None), "stream should end");
+    assert_eq!(queues.events.subscriber_count(), 1);
+}
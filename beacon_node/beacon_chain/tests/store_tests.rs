@@ -4,19 +4,25 @@
 extern crate lazy_static;
 
 use beacon_chain::attestation_verification::Error as AttnError;
+use beacon_chain::builder::BeaconChainBuilder;
+use beacon_chain::migrate::{BlockingMigrator, Migrate};
 use beacon_chain::test_utils::{
-    AttestationStrategy, BeaconChainHarness, BlockStrategy, DiskHarnessType,
+    AttestationStrategy, BeaconChainHarness, BlockStrategy, DiskHarnessType, HARNESS_GENESIS_TIME,
+    HARNESS_SLOT_TIME,
 };
 use beacon_chain::BeaconSnapshot;
 use beacon_chain::StateSkipConfig;
+use genesis::interop_genesis_state;
 use rand::Rng;
 use sloggers::{null::NullLoggerBuilder, Build};
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::sync::Arc;
 use store::{
+    hot_cold_store::HotColdDBError,
     iter::{BlockRootsIterator, StateRootsIterator},
-    HotColdDB, LevelDB, StoreConfig,
+    AnchorInfo, DatabaseBackend, DiskStore, HotColdDB, SchemaVersion, StoreConfig,
+    CURRENT_SCHEMA_VERSION,
 };
 use tempfile::{tempdir, TempDir};
 use tree_hash::TreeHash;
@@ -35,11 +41,27 @@ lazy_static! {
 type E = MinimalEthSpec;
 type TestHarness = BeaconChainHarness<DiskHarnessType<E>>;
 
-fn get_store(db_path: &TempDir) -> Arc<HotColdDB<E, LevelDB<E>, LevelDB<E>>> {
+type Store = HotColdDB<E, DiskStore<E>, DiskStore<E>>;
+
+/// The default store config, using the backend named by `STORE_TESTS_BACKEND` if it is set.
+fn store_config() -> StoreConfig {
+    let mut config = StoreConfig::default();
+    if let Ok(backend) = std::env::var("STORE_TESTS_BACKEND") {
+        config.backend = backend
+            .parse()
+            .expect("STORE_TESTS_BACKEND should be valid");
+    }
+    config
+}
+
+fn get_store(db_path: &TempDir) -> Arc<Store> {
+    get_store_with_config(db_path, store_config())
+}
+
+fn get_store_with_config(db_path: &TempDir, config: StoreConfig) -> Arc<Store> {
     let spec = MinimalEthSpec::default_spec();
     let hot_path = db_path.path().join("hot_db");
     let cold_path = db_path.path().join("cold_db");
-    let config = StoreConfig::default();
     let log = NullLoggerBuilder.build().expect("logger should build");
     Arc::new(
         HotColdDB::open(&hot_path, &cold_path, config, spec, log)
@@ -47,10 +69,7 @@ fn get_store(db_path: &TempDir) -> Arc<HotColdDB<E, LevelDB<E>, LevelDB<E>>> {
     )
 }
 
-fn get_harness(
-    store: Arc<HotColdDB<E, LevelDB<E>, LevelDB<E>>>,
-    validator_count: usize,
-) -> TestHarness {
+fn get_harness(store: Arc<Store>, validator_count: usize) -> TestHarness {
     let harness = BeaconChainHarness::new_with_disk_store(
         MinimalEthSpec,
         store,
@@ -1284,6 +1303,269 @@ fn prunes_skipped_slots_states() {
     }
 }
 
+#[test]
+fn prune_historic_states() {
+    let num_blocks_produced = E::slots_per_epoch() * 5;
+    let db_path = tempdir().unwrap();
+    let mut config = store_config();
+    config.slots_per_restore_point = 2 * E::slots_per_epoch();
+    config.prune_historic_states = true;
+    config.historic_state_retention_epochs = 1;
+    let store = get_store_with_config(&db_path, config);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        num_blocks_produced as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    check_finalization(&harness, num_blocks_produced);
+    check_split_slot(&harness, store.clone());
+
+    // Finalized at epoch 3, retaining the states of epoch 2 onwards.
+    let state_lower_limit = Slot::new(2 * E::slots_per_epoch());
+    assert_eq!(
+        store.get_pruning_info().map(|info| info.state_lower_limit),
+        Some(state_lower_limit)
+    );
+
+    for slot in 0..state_lower_limit.as_u64() {
+        match store.load_cold_state_by_slot(Slot::new(slot)) {
+            Err(store::Error::HotColdDBError(HotColdDBError::StatePruned { .. })) => (),
+            other => panic!("slot {} should be pruned, got {:?}", slot, other.err()),
+        }
+    }
+
+    for slot in state_lower_limit.as_u64()..store.get_split_slot().as_u64() {
+        let state = store
+            .load_cold_state_by_slot(Slot::new(slot))
+            .expect("retained state should load");
+        assert_eq!(state.slot, slot);
+    }
+
+    // The blocks of pruned slots are kept.
+    check_iterators(&harness);
+}
+
+#[test]
+fn historic_state_cache() {
+    let num_blocks_produced = E::slots_per_epoch() * 5;
+    let db_path = tempdir().unwrap();
+    let mut config = store_config();
+    config.slots_per_restore_point = 2 * E::slots_per_epoch();
+    config.historic_state_cache_size = 2;
+    let store = get_store_with_config(&db_path, config);
+    let harness = get_harness(store.clone(), LOW_VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        num_blocks_produced as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    check_finalization(&harness, num_blocks_produced);
+    check_split_slot(&harness, store.clone());
+
+    let split_slot = store.get_split_slot().as_u64();
+    let state_roots = harness
+        .chain
+        .rev_iter_state_roots()
+        .expect("should get iter")
+        .map(Result::unwrap)
+        .map(|(state_root, slot)| (slot, state_root))
+        .collect::<HashMap<_, _>>();
+
+    // Ascending slots are replayed from the previous cached state, while descending slots fall
+    // back to the restore points. Both must reconstruct the canonical states.
+    let ascending = (0..split_slot).collect::<Vec<_>>();
+    let descending = (0..split_slot).rev().collect::<Vec<_>>();
+    for slot in ascending.iter().chain(&descending).chain(&ascending) {
+        let slot = Slot::new(*slot);
+        let state = store
+            .load_cold_state_by_slot(slot)
+            .expect("cold state should load");
+        assert_eq!(state.slot, slot);
+        assert_eq!(
+            state.canonical_root(),
+            state_roots[&slot],
+            "state at slot {} should be canonical",
+            slot
+        );
+    }
+}
+
+#[test]
+fn schema_version_written_on_open() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+
+    assert_eq!(
+        store.load_schema_version().unwrap(),
+        Some(CURRENT_SCHEMA_VERSION)
+    );
+
+    // Re-opening a database at the current version leaves it unchanged.
+    drop(store);
+    let store = get_store(&db_path);
+    assert_eq!(
+        store.load_schema_version().unwrap(),
+        Some(CURRENT_SCHEMA_VERSION)
+    );
+}
+
+#[test]
+fn newer_schema_version_refused() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let newer_version = SchemaVersion(CURRENT_SCHEMA_VERSION.as_u64() + 1);
+    store.store_schema_version(newer_version).unwrap();
+    drop(store);
+
+    let spec = MinimalEthSpec::default_spec();
+    let hot_path = db_path.path().join("hot_db");
+    let cold_path = db_path.path().join("cold_db");
+    let config = store_config();
+    let log = NullLoggerBuilder.build().expect("logger should build");
+
+    match Store::open(&hot_path, &cold_path, config, spec, log) {
+        Err(store::Error::HotColdDBError(HotColdDBError::UnsupportedSchemaVersion {
+            schema_version,
+            current_version,
+        })) => {
+            assert_eq!(schema_version, newer_version);
+            assert_eq!(current_version, CURRENT_SCHEMA_VERSION);
+        }
+        other => panic!("expected unsupported schema version, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn other_backend_refused() {
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let existing = store.get_config().backend;
+    drop(store);
+
+    let mut config = store_config();
+    config.backend = match existing {
+        DatabaseBackend::LevelDB => DatabaseBackend::Lmdb,
+        DatabaseBackend::Lmdb => DatabaseBackend::LevelDB,
+    };
+    let configured = config.backend;
+
+    let spec = MinimalEthSpec::default_spec();
+    let hot_path = db_path.path().join("hot_db");
+    let cold_path = db_path.path().join("cold_db");
+    let log = NullLoggerBuilder.build().expect("logger should build");
+
+    match Store::open(&hot_path, &cold_path, config, spec, log) {
+        Err(store::Error::DatabaseBackendMismatch(mismatch)) => {
+            assert_eq!(mismatch.configured, configured);
+            assert_eq!(mismatch.existing, existing);
+        }
+        other => panic!("expected backend mismatch, got {:?}", other.err()),
+    }
+}
+
+#[test]
+fn checkpoint_sync_from_finalized_state() {
+    let num_blocks_produced = E::slots_per_epoch() * 5;
+    let db_path = tempdir().unwrap();
+    let store = get_store(&db_path);
+    let harness = get_harness(store, LOW_VALIDATOR_COUNT);
+
+    harness.extend_chain(
+        num_blocks_produced as usize,
+        BlockStrategy::OnCanonicalHead,
+        AttestationStrategy::AllValidators,
+    );
+
+    // Take the finalized block and its post-state as the checkpoint.
+    let finalized = harness
+        .chain
+        .head()
+        .expect("should get head")
+        .beacon_state
+        .finalized_checkpoint;
+    let wss_block = harness
+        .chain
+        .get_block(&finalized.root)
+        .unwrap()
+        .expect("should get finalized block");
+    let wss_state = harness
+        .chain
+        .get_state(&wss_block.state_root(), Some(wss_block.slot()))
+        .unwrap()
+        .expect("should get finalized state");
+    let wss_slot = wss_block.slot();
+    assert_eq!(wss_slot, finalized.epoch.start_slot(E::slots_per_epoch()));
+
+    let spec = MinimalEthSpec::default_spec();
+    let genesis_state = interop_genesis_state::<E>(
+        &KEYPAIRS[0..LOW_VALIDATOR_COUNT],
+        HARNESS_GENESIS_TIME,
+        &spec,
+    )
+    .expect("should generate interop state");
+
+    // Start a new chain with an empty database from the checkpoint.
+    let wss_db_path = tempdir().unwrap();
+    let wss_store = get_store(&wss_db_path);
+    let log = NullLoggerBuilder.build().expect("logger should build");
+    let data_dir = tempdir().unwrap();
+    let chain = BeaconChainBuilder::new(MinimalEthSpec)
+        .logger(log.clone())
+        .custom_spec(spec)
+        .store(wss_store.clone())
+        .store_migrator(BlockingMigrator::new(wss_store.clone(), log))
+        .data_dir(data_dir.path().to_path_buf())
+        .weak_subjectivity_state(wss_state, wss_block.clone(), genesis_state)
+        .expect("should start from the checkpoint")
+        .dummy_eth1_backend()
+        .expect("should build dummy backend")
+        .null_event_handler()
+        .testing_slot_clock(HARNESS_SLOT_TIME)
+        .expect("should configure testing slot clock")
+        .build()
+        .expect("should build");
+
+    assert_eq!(
+        wss_store.get_anchor_info(),
+        Some(AnchorInfo {
+            anchor_slot: wss_slot,
+            anchor_block_root: finalized.root,
+            anchor_state_root: wss_block.state_root(),
+        })
+    );
+    assert_eq!(wss_store.get_split_slot(), wss_slot);
+    assert_eq!(chain.head_info().unwrap().block_root, finalized.root);
+
+    // Import the block following the checkpoint on top of it.
+    let next_slot = wss_slot + 1;
+    let next_block_root = harness
+        .chain
+        .root_at_slot(next_slot)
+        .unwrap()
+        .expect("should have a block after the checkpoint");
+    let next_block = harness
+        .chain
+        .get_block(&next_block_root)
+        .unwrap()
+        .expect("should get block after the checkpoint");
+    assert_eq!(next_block.slot(), next_slot);
+
+    chain.slot_clock.set_slot(next_slot.as_u64());
+    assert_eq!(
+        chain
+            .process_block(next_block)
+            .expect("should import block after the checkpoint"),
+        next_block_root
+    );
+    chain.fork_choice().expect("should run fork choice");
+    assert_eq!(chain.head_info().unwrap().block_root, next_block_root);
+}
+
 /// Check that the head state's slot matches `expected_slot`.
 fn check_slot(harness: &TestHarness, expected_slot: u64) {
     let state = &harness.chain.head().expect("should get head").beacon_state;
@@ -1313,7 +1595,7 @@ fn check_finalization(harness: &TestHarness, expected_slot: u64) {
 }
 
 /// Check that the HotColdDB's split_slot is equal to the start slot of the last finalized epoch.
-fn check_split_slot(harness: &TestHarness, store: Arc<HotColdDB<E, LevelDB<E>, LevelDB<E>>>) {
+fn check_split_slot(harness: &TestHarness, store: Arc<Store>) {
     let split_slot = store.get_split_slot();
     assert_eq!(
         harness
@@ -1,24 +1,23 @@
 use crate::config::{ClientGenesis, Config as ClientConfig};
 use crate::notifier::spawn_notifier;
 use crate::Client;
-use beacon_chain::events::TeeEventHandler;
+use beacon_chain::events::{EventQueues, TeeEventHandler};
 use beacon_chain::{
     builder::{BeaconChainBuilder, Witness},
     eth1_chain::{CachingEth1Backend, Eth1Chain},
     migrate::{BackgroundMigrator, Migrate},
     slot_clock::{SlotClock, SystemTimeSlotClock},
-    store::{HotColdDB, ItemStore, LevelDB, StoreConfig},
+    store::{DiskStore, HotColdDB, ItemStore, StoreConfig},
     BeaconChain, BeaconChainTypes, Eth1ChainBackend, EventHandler,
 };
-use bus::Bus;
 use environment::RuntimeContext;
 use eth1::{Config as Eth1Config, Service as Eth1Service};
 use eth2_config::Eth2Config;
 use eth2_libp2p::NetworkGlobals;
-use genesis::interop_genesis_state;
+use genesis::{interop_genesis_state, Eth1GenesisService};
 use network::{NetworkConfig, NetworkMessage, NetworkService};
-use parking_lot::Mutex;
-use slog::info;
+use remote_beacon_node::RemoteBeaconNode;
+use slog::{info, Logger};
 use ssz::Decode;
 use std::net::SocketAddr;
 use std::path::Path;
@@ -27,14 +26,23 @@ use std::time::Duration;
 use timer::spawn_timer;
 use tokio::sync::mpsc::UnboundedSender;
 use types::{
-    test_utils::generate_deterministic_keypairs, BeaconState, ChainSpec, EthSpec,
-    SignedBeaconBlockHash,
+    test_utils::generate_deterministic_keypairs, Address, BeaconState, ChainSpec, Epoch, EthSpec,
+    SignedBeaconBlock,
 };
 use websocket_server::{Config as WebSocketConfig, WebSocketSender};
 
 /// Interval between polling the eth1 node for genesis information.
 pub const ETH1_GENESIS_UPDATE_INTERVAL_MILLIS: u64 = 7_000;
 
+/// Timeout for requests made to a remote beacon node during checkpoint sync.
+///
+/// States can be several megabytes, so this is considerably longer than the default.
+pub const CHECKPOINT_SYNC_HTTP_TIMEOUT_SECS: u64 = 180;
+
+/// The number of epochs prior to the remote finalized epoch that will be searched for an epoch
+/// boundary block during checkpoint sync.
+pub const CHECKPOINT_SYNC_MAX_SKIPPED_EPOCHS: u64 = 8;
+
 /// Builds a `Client` instance.
 ///
 /// ## Notes
@@ -134,6 +142,7 @@ where
         let eth_spec_instance = self.eth_spec_instance.clone();
         let data_dir = config.data_dir.clone();
         let disabled_forks = config.disabled_forks.clone();
+        let eth1_config = config.eth1.clone();
 
         let store =
             store.ok_or_else(|| "beacon_chain_start_method requires a store".to_string())?;
@@ -153,6 +162,25 @@ where
             .custom_spec(spec.clone())
             .disabled_forks(disabled_forks);
 
+        let chain_exists = builder
+            .store_contains_beacon_chain()
+            .unwrap_or_else(|_| false);
+
+        // If the client is expected to resume but there's no beacon chain in the database, use the
+        // `DepositContract` method. This scenario is quite common when the client is shutdown
+        // before finding genesis via eth1.
+        //
+        // Alternatively, if there's a beacon chain in the database then always resume using it.
+        let client_genesis = if client_genesis == ClientGenesis::FromStore && !chain_exists {
+            info!(context.log(), "Defaulting to deposit contract genesis");
+
+            ClientGenesis::DepositContract
+        } else if chain_exists {
+            ClientGenesis::FromStore
+        } else {
+            client_genesis
+        };
+
         let (beacon_chain_builder, eth1_service_option) = match client_genesis {
             ClientGenesis::Interop {
                 validator_count,
@@ -175,6 +203,65 @@ where
 
                 builder.genesis_state(genesis_state).map(|v| (v, None))?
             }
+            ClientGenesis::WeakSubjSszBytes {
+                genesis_state_bytes,
+                anchor_state_bytes,
+                anchor_block_bytes,
+            } => {
+                info!(context.log(), "Starting checkpoint sync");
+
+                let genesis_state = BeaconState::from_ssz_bytes(&genesis_state_bytes)
+                    .map_err(|e| format!("Unable to parse genesis state SSZ: {:?}", e))?;
+                let anchor_state = BeaconState::from_ssz_bytes(&anchor_state_bytes)
+                    .map_err(|e| format!("Unable to parse weak subj state SSZ: {:?}", e))?;
+                let anchor_block = SignedBeaconBlock::from_ssz_bytes(&anchor_block_bytes)
+                    .map_err(|e| format!("Unable to parse weak subj block SSZ: {:?}", e))?;
+
+                builder
+                    .weak_subjectivity_state(anchor_state, anchor_block, genesis_state)
+                    .map(|v| (v, None))?
+            }
+            ClientGenesis::CheckpointSyncUrl {
+                genesis_state_bytes,
+                url,
+            } => {
+                info!(
+                    context.log(),
+                    "Starting checkpoint sync";
+                    "remote_url" => &url,
+                );
+
+                let genesis_state = BeaconState::from_ssz_bytes(&genesis_state_bytes)
+                    .map_err(|e| format!("Unable to parse genesis state SSZ: {:?}", e))?;
+                let (anchor_state, anchor_block) = download_checkpoint(url, context.log()).await?;
+
+                builder
+                    .weak_subjectivity_state(anchor_state, anchor_block, genesis_state)
+                    .map(|v| (v, None))?
+            }
+            ClientGenesis::DepositContract => {
+                info!(
+                    context.log(),
+                    "Waiting for eth2 genesis from eth1";
+                    "eth1_endpoints" => format!("{:?}", eth1_config.endpoints),
+                    "contract_deploy_block" => eth1_config.deposit_contract_deploy_block,
+                    "deposit_contract" => &eth1_config.deposit_contract_address
+                );
+
+                let genesis_service =
+                    Eth1GenesisService::new(eth1_config, context.log().clone(), spec.clone());
+
+                let genesis_state = genesis_service
+                    .wait_for_genesis_state(
+                        Duration::from_millis(ETH1_GENESIS_UPDATE_INTERVAL_MILLIS),
+                        spec.clone(),
+                    )
+                    .await?;
+
+                builder
+                    .genesis_state(genesis_state)
+                    .map(|v| (v, Some(genesis_service.into_core_service())))?
+            }
             ClientGenesis::FromStore => builder.resume_from_db().map(|v| (v, None))?,
         };
 
@@ -233,7 +320,7 @@ where
         mut self,
         client_config: &ClientConfig,
         eth2_config: &Eth2Config,
-        events: Arc<Mutex<Bus<SignedBeaconBlockHash>>>,
+        events: EventQueues,
     ) -> Result<Self, String> {
         let beacon_chain = self
             .beacon_chain
@@ -258,6 +345,13 @@ where
             network_chan: network_send,
         };
 
+        let deposit_contract_address = client_config
+            .eth1
+            .deposit_contract_address
+            .trim_start_matches("0x")
+            .parse::<Address>()
+            .map_err(|e| format!("Invalid deposit contract address: {:?}", e))?;
+
         let listening_addr = rest_api::start_server(
             context.executor,
             &client_config.rest_api,
@@ -270,9 +364,10 @@ where
                 .create_freezer_db_path()
                 .map_err(|_| "unable to read freezer DB dir")?,
             eth2_config.clone(),
+            deposit_contract_address,
             events,
         )
-        .map_err(|e| format!("Failed to start HTTP API: {:?}", e))?;
+        .map_err(|e| format!("Failed to start HTTP API: {}", e))?;
 
         self.http_listen_addr = Some(listening_addr);
 
@@ -451,7 +546,7 @@ where
     pub fn tee_event_handler(
         mut self,
         config: WebSocketConfig,
-    ) -> Result<(Self, Arc<Mutex<Bus<SignedBeaconBlockHash>>>), String> {
+    ) -> Result<(Self, EventQueues), String> {
         let context = self
             .runtime_context
             .as_ref()
@@ -468,9 +563,9 @@ where
         };
 
         self.websocket_listen_addr = listening_addr;
-        let (tee_event_handler, bus) = TeeEventHandler::new(log, sender)?;
+        let (tee_event_handler, events) = TeeEventHandler::new(log, sender)?;
         self.event_handler = Some(tee_event_handler);
-        Ok((self, bus))
+        Ok((self, events))
     }
 }
 
@@ -482,13 +577,13 @@ impl<TStoreMigrator, TSlotClock, TEth1Backend, TEthSpec, TEventHandler>
             TEth1Backend,
             TEthSpec,
             TEventHandler,
-            LevelDB<TEthSpec>,
-            LevelDB<TEthSpec>,
+            DiskStore<TEthSpec>,
+            DiskStore<TEthSpec>,
         >,
     >
 where
     TSlotClock: SlotClock + 'static,
-    TStoreMigrator: Migrate<TEthSpec, LevelDB<TEthSpec>, LevelDB<TEthSpec>> + 'static,
+    TStoreMigrator: Migrate<TEthSpec, DiskStore<TEthSpec>, DiskStore<TEthSpec>> + 'static,
     TEth1Backend: Eth1ChainBackend<TEthSpec> + 'static,
     TEthSpec: EthSpec + 'static,
     TEventHandler: EventHandler<TEthSpec> + 'static,
@@ -713,3 +808,73 @@ where
         Ok(self)
     }
 }
+
+/// Downloads the most recent finalized state and block from the beacon node at `url`.
+///
+/// The block and state must lie on an epoch boundary. If the finalized epoch started with a skip
+/// slot then earlier epochs are tried, up to `CHECKPOINT_SYNC_MAX_SKIPPED_EPOCHS`.
+async fn download_checkpoint<E: EthSpec>(
+    url: String,
+    log: &Logger,
+) -> Result<(BeaconState<E>, SignedBeaconBlock<E>), String> {
+    let remote = RemoteBeaconNode::<E>::new_with_timeout(
+        url,
+        Duration::from_secs(CHECKPOINT_SYNC_HTTP_TIMEOUT_SECS),
+    )?;
+
+    let head = remote
+        .http
+        .beacon()
+        .get_head()
+        .await
+        .map_err(|e| format!("Unable to get remote head: {:?}", e))?;
+
+    let finalized_epoch = head.finalized_slot.epoch(E::slots_per_epoch());
+
+    for i in 0..=CHECKPOINT_SYNC_MAX_SKIPPED_EPOCHS {
+        if finalized_epoch < Epoch::new(i) {
+            break;
+        }
+        let epoch = finalized_epoch - i;
+        let boundary_slot = epoch.start_slot(E::slots_per_epoch());
+
+        let (block, block_root) = remote
+            .http
+            .beacon()
+            .get_block_by_slot(boundary_slot)
+            .await
+            .map_err(|e| format!("Unable to get remote block at {}: {:?}", boundary_slot, e))?;
+
+        if block.slot() != boundary_slot {
+            info!(
+                log,
+                "Skipping checkpoint epoch";
+                "reason" => "no block at epoch boundary",
+                "epoch" => epoch,
+            );
+            continue;
+        }
+
+        let (state, _) = remote
+            .http
+            .beacon()
+            .get_state_by_root(block.state_root())
+            .await
+            .map_err(|e| format!("Unable to get remote state at {}: {:?}", boundary_slot, e))?;
+
+        info!(
+            log,
+            "Downloaded checkpoint state";
+            "epoch" => epoch,
+            "block_root" => format!("{:?}", block_root),
+            "state_root" => format!("{:?}", block.state_root()),
+        );
+
+        return Ok((state, block));
+    }
+
+    Err(format!(
+        "Unable to find an epoch boundary block within {} epochs of the remote finalized epoch {}",
+        CHECKPOINT_SYNC_MAX_SKIPPED_EPOCHS, finalized_epoch
+    ))
+}
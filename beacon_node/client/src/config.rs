@@ -21,19 +21,32 @@ pub enum ClientGenesis {
     },
     /// Reads the genesis state and other persisted data from the `Store`.
     FromStore,
+    /// Connects to an eth1 node and waits until it can create the genesis state from the deposit
+    /// contract.
+    DepositContract,
     /// Loads the genesis state from SSZ-encoded `BeaconState` bytes.
     ///
     /// We include the bytes instead of the `BeaconState<E>` because the `EthSpec` type
     /// parameter would be very annoying.
     SszBytes { genesis_state_bytes: Vec<u8> },
+    /// Starts from a trusted finalized state and block (a "weak subjectivity" checkpoint), rather
+    /// than from genesis. All three values are SSZ-encoded.
+    WeakSubjSszBytes {
+        genesis_state_bytes: Vec<u8>,
+        anchor_state_bytes: Vec<u8>,
+        anchor_block_bytes: Vec<u8>,
+    },
+    /// Starts from the latest finalized state and block served by the beacon node HTTP API at
+    /// `url`.
+    CheckpointSyncUrl {
+        genesis_state_bytes: Vec<u8>,
+        url: String,
+    },
 }
 
 impl Default for ClientGenesis {
     fn default() -> Self {
-        Self::Interop {
-            validator_count: 0,
-            genesis_time: 0,
-        }
+        Self::FromStore
     }
 }
 
@@ -54,6 +67,11 @@ pub struct Config {
     /// The `genesis` field is not serialized or deserialized by `serde` to ensure it is defined
     /// via the CLI at runtime, instead of from a configuration file saved to disk.
     pub genesis: ClientGenesis,
+    /// If true, connects to one or more eth1 nodes and uses the caching eth1 backend for block
+    /// production.
+    pub sync_eth1_chain: bool,
+    /// If true, uses an eth1 backend that produces deterministic junk `Eth1Data` and no deposits.
+    pub dummy_eth1_backend: bool,
     pub store: store::StoreConfig,
     pub network: network::NetworkConfig,
     pub rest_api: rest_api::Config,
@@ -70,6 +88,8 @@ impl Default for Config {
             testnet_dir: None,
             log_file: PathBuf::from(""),
             genesis: <_>::default(),
+            sync_eth1_chain: false,
+            dummy_eth1_backend: false,
             store: <_>::default(),
             network: NetworkConfig::default(),
             rest_api: <_>::default(),
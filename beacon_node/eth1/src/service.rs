@@ -9,7 +9,7 @@ use crate::{
 use futures::{future::TryFutureExt, stream, stream::TryStreamExt, StreamExt};
 use parking_lot::{RwLock, RwLockReadGuard};
 use serde::{Deserialize, Serialize};
-use slog::{debug, error, info, trace, Logger};
+use slog::{debug, error, info, trace, warn, Logger};
 use std::ops::{Range, RangeInclusive};
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -36,8 +36,8 @@ pub enum Error {
     },
     /// Failed to download a block from the eth1 node.
     BlockDownloadFailed(String),
-    /// Failed to get the current block number from the eth1 node.
-    GetBlockNumberFailed(String),
+    /// None of the configured eth1 endpoints responded, or no endpoints were configured.
+    NoAvailableEndpoint(Vec<(String, String)>),
     /// Failed to read the deposit contract root from the eth1 node.
     GetDepositRootFailed(String),
     /// Failed to read the deposit contract deposit count from the eth1 node.
@@ -72,8 +72,10 @@ pub struct DepositCacheUpdateOutcome {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
-    /// An Eth1 node (e.g., Geth) running a HTTP JSON-RPC endpoint.
-    pub endpoint: String,
+    /// One or more Eth1 nodes (e.g., Geth) running a HTTP JSON-RPC endpoint.
+    ///
+    /// Endpoints are tried in order, the first endpoint that responds is used for each update.
+    pub endpoints: Vec<String>,
     /// The address the `BlockCache` and `DepositCache` should assume is the canonical deposit contract.
     pub deposit_contract_address: String,
     /// Defines the first block that the `DepositCache` will start searching for deposit logs.
@@ -103,7 +105,7 @@ pub struct Config {
 impl Default for Config {
     fn default() -> Self {
         Self {
-            endpoint: "http://localhost:8545".into(),
+            endpoints: vec!["http://localhost:8545".into()],
             deposit_contract_address: "0x0000000000000000000000000000000000000000".into(),
             deposit_contract_deploy_block: 1,
             lowest_cached_block_number: 1,
@@ -271,6 +273,38 @@ impl Service {
         self.inner.config.write().lowest_cached_block_number = block_number;
     }
 
+    /// Returns the first configured endpoint that responds to an `eth_blockNumber` request,
+    /// along with the block number it reported.
+    ///
+    /// Endpoints are tried in the order they were configured. A warning is logged for each
+    /// endpoint that fails to respond.
+    async fn first_available_endpoint(&self) -> Result<(String, u64), Error> {
+        let endpoints = self.config().endpoints.clone();
+        let mut errors = Vec::with_capacity(endpoints.len());
+
+        for endpoint in endpoints {
+            match get_block_number(
+                &endpoint,
+                Duration::from_millis(BLOCK_NUMBER_TIMEOUT_MILLIS),
+            )
+            .await
+            {
+                Ok(block_number) => return Ok((endpoint, block_number)),
+                Err(e) => {
+                    warn!(
+                        self.log,
+                        "Eth1 endpoint unavailable";
+                        "endpoint" => &endpoint,
+                        "error" => &e,
+                    );
+                    errors.push((endpoint, e));
+                }
+            }
+        }
+
+        Err(Error::NoAvailableEndpoint(errors))
+    }
+
     /// Update the deposit and block cache, returning an error if either fail.
     ///
     /// ## Returns
@@ -373,7 +407,6 @@ impl Service {
     ///
     /// Emits logs for debugging and errors.
     pub async fn update_deposit_cache(&self) -> Result<DepositCacheUpdateOutcome, Error> {
-        let endpoint = self.config().endpoint.clone();
         let follow_distance = self.config().follow_distance;
         let deposit_contract_address = self.config().deposit_contract_address.clone();
 
@@ -390,7 +423,9 @@ impl Service {
             .map(|n| n + 1)
             .unwrap_or_else(|| self.config().deposit_contract_deploy_block);
 
-        let range = get_new_block_numbers(&endpoint, next_required_block, follow_distance).await?;
+        let (endpoint, remote_highest_block) = self.first_available_endpoint().await?;
+        let range =
+            get_new_block_numbers(next_required_block, remote_highest_block, follow_distance)?;
 
         let block_number_chunks = if let Some(range) = range {
             range
@@ -522,10 +557,11 @@ impl Service {
             .map(|n| n + 1)
             .unwrap_or_else(|| self.config().lowest_cached_block_number);
 
-        let endpoint = self.config().endpoint.clone();
         let follow_distance = self.config().follow_distance;
 
-        let range = get_new_block_numbers(&endpoint, next_required_block, follow_distance).await?;
+        let (endpoint, remote_highest_block) = self.first_available_endpoint().await?;
+        let range =
+            get_new_block_numbers(next_required_block, remote_highest_block, follow_distance)?;
         // Map the range of required blocks into a Vec.
         //
         // If the required range is larger than the size of the cache, drop the exiting cache
@@ -576,7 +612,8 @@ impl Service {
             |mut block_numbers| async {
                 match block_numbers.next() {
                     Some(block_number) => {
-                        match download_eth1_block(self.inner.clone(), block_number).await {
+                        match download_eth1_block(self.inner.clone(), &endpoint, block_number).await
+                        {
                             Ok(eth1_block) => Ok(Some((eth1_block, block_numbers))),
                             Err(e) => Err(e),
                         }
@@ -659,15 +696,11 @@ impl Service {
 
 /// Determine the range of blocks that need to be downloaded, given the remotes best block and
 /// the locally stored best block.
-async fn get_new_block_numbers<'a>(
-    endpoint: &str,
+fn get_new_block_numbers(
     next_required_block: u64,
+    remote_highest_block: u64,
     follow_distance: u64,
 ) -> Result<Option<RangeInclusive<u64>>, Error> {
-    let remote_highest_block =
-        get_block_number(endpoint, Duration::from_millis(BLOCK_NUMBER_TIMEOUT_MILLIS))
-            .map_err(Error::GetBlockNumberFailed)
-            .await?;
     let remote_follow_block = remote_highest_block.saturating_sub(follow_distance);
 
     if next_required_block <= remote_follow_block {
@@ -693,9 +726,11 @@ async fn get_new_block_numbers<'a>(
 /// `block_number`.
 ///
 /// Performs three async calls to an Eth1 HTTP JSON RPC endpoint.
-async fn download_eth1_block(cache: Arc<Inner>, block_number: u64) -> Result<Eth1Block, Error> {
-    let endpoint = cache.config.read().endpoint.clone();
-
+async fn download_eth1_block(
+    cache: Arc<Inner>,
+    endpoint: &str,
+    block_number: u64,
+) -> Result<Eth1Block, Error> {
     let deposit_root = cache
         .deposit_cache
         .read()
@@ -710,7 +745,7 @@ async fn download_eth1_block(cache: Arc<Inner>, block_number: u64) -> Result<Eth
 
     // Performs a `get_blockByNumber` call to an eth1 node.
     let http_block = get_block(
-        &endpoint,
+        endpoint,
         block_number,
         Duration::from_millis(GET_BLOCK_TIMEOUT_MILLIS),
     )
@@ -116,7 +116,7 @@ mod eth1_cache {
 
             let service = Service::new(
                 Config {
-                    endpoint: eth1.endpoint(),
+                    endpoints: vec![eth1.endpoint()],
                     deposit_contract_address: deposit_contract.address(),
                     lowest_cached_block_number: initial_block_number,
                     follow_distance,
@@ -191,7 +191,7 @@ mod eth1_cache {
 
         let service = Service::new(
             Config {
-                endpoint: eth1.endpoint(),
+                endpoints: vec![eth1.endpoint()],
                 deposit_contract_address: deposit_contract.address(),
                 lowest_cached_block_number: get_block_number(&web3).await,
                 follow_distance: 0,
@@ -240,7 +240,7 @@ mod eth1_cache {
 
         let service = Service::new(
             Config {
-                endpoint: eth1.endpoint(),
+                endpoints: vec![eth1.endpoint()],
                 deposit_contract_address: deposit_contract.address(),
                 lowest_cached_block_number: get_block_number(&web3).await,
                 follow_distance: 0,
@@ -286,7 +286,7 @@ mod eth1_cache {
 
         let service = Service::new(
             Config {
-                endpoint: eth1.endpoint(),
+                endpoints: vec![eth1.endpoint()],
                 deposit_contract_address: deposit_contract.address(),
                 lowest_cached_block_number: get_block_number(&web3).await,
                 follow_distance: 0,
@@ -330,7 +330,7 @@ mod deposit_tree {
 
         let service = Service::new(
             Config {
-                endpoint: eth1.endpoint(),
+                endpoints: vec![eth1.endpoint()],
                 deposit_contract_address: deposit_contract.address(),
                 deposit_contract_deploy_block: start_block,
                 follow_distance: 0,
@@ -405,7 +405,7 @@ mod deposit_tree {
 
         let service = Service::new(
             Config {
-                endpoint: eth1.endpoint(),
+                endpoints: vec![eth1.endpoint()],
                 deposit_contract_address: deposit_contract.address(),
                 deposit_contract_deploy_block: start_block,
                 lowest_cached_block_number: start_block,
@@ -645,7 +645,7 @@ mod fast {
         let now = get_block_number(&web3).await;
         let service = Service::new(
             Config {
-                endpoint: eth1.endpoint(),
+                endpoints: vec![eth1.endpoint()],
                 deposit_contract_address: deposit_contract.address(),
                 deposit_contract_deploy_block: now,
                 lowest_cached_block_number: now,
@@ -717,7 +717,7 @@ mod persist {
 
         let now = get_block_number(&web3).await;
         let config = Config {
-            endpoint: eth1.endpoint(),
+            endpoints: vec![eth1.endpoint()],
             deposit_contract_address: deposit_contract.address(),
             deposit_contract_deploy_block: now,
             lowest_cached_block_number: now,
@@ -778,3 +778,84 @@ mod persist {
         );
     }
 }
+
+mod fallback {
+    use super::*;
+
+    /// An endpoint on which no eth1 node is listening.
+    const OFFLINE_ENDPOINT: &str = "http://127.0.0.1:1";
+
+    #[tokio::test]
+    async fn update_uses_fallback_endpoint() {
+        let log = null_logger();
+
+        let eth1 = GanacheEth1Instance::new()
+            .await
+            .expect("should start eth1 environment");
+        let deposit_contract = &eth1.deposit_contract;
+        let web3 = eth1.web3();
+
+        let now = get_block_number(&web3).await;
+        let service = Service::new(
+            Config {
+                endpoints: vec![OFFLINE_ENDPOINT.into(), eth1.endpoint()],
+                deposit_contract_address: deposit_contract.address(),
+                deposit_contract_deploy_block: now,
+                lowest_cached_block_number: now,
+                follow_distance: 0,
+                block_cache_truncation: None,
+                ..Config::default()
+            },
+            log,
+            MainnetEthSpec::default_spec(),
+        );
+
+        let n = 4;
+        for _ in 0..n {
+            deposit_contract
+                .deposit(random_deposit_data())
+                .await
+                .expect("should perform a deposit");
+        }
+
+        service
+            .update_deposit_cache()
+            .await
+            .expect("should update deposit cache from the fallback endpoint");
+        service
+            .update_block_cache()
+            .await
+            .expect("should update block cache from the fallback endpoint");
+
+        assert!(
+            service.deposit_cache_len() >= n,
+            "should have imported n deposits"
+        );
+        assert!(
+            service.block_cache_len() >= n,
+            "should have imported n eth1 blocks"
+        );
+    }
+
+    #[tokio::test]
+    async fn update_fails_without_available_endpoint() {
+        let log = null_logger();
+
+        let service = Service::new(
+            Config {
+                endpoints: vec![OFFLINE_ENDPOINT.into()],
+                ..Config::default()
+            },
+            log,
+            MainnetEthSpec::default_spec(),
+        );
+
+        match service.update_block_cache().await {
+            Err(eth1::Error::NoAvailableEndpoint(errors)) => {
+                assert_eq!(errors.len(), 1, "should report the failed endpoint");
+                assert_eq!(errors[0].0, OFFLINE_ENDPOINT);
+            }
+            other => panic!("expected NoAvailableEndpoint, got {:?}", other),
+        }
+    }
+}
@@ -1,6 +1,7 @@
+use crate::metrics;
 use crate::peer_manager::{PeerManager, PeerManagerEvent};
 use crate::rpc::*;
-use crate::types::{GossipEncoding, GossipKind, GossipTopic};
+use crate::types::{GossipEncoding, GossipKind, GossipTopic, MessageAcceptance};
 use crate::Eth2Enr;
 use crate::{error, Enr, NetworkConfig, NetworkGlobals, PubsubMessage, TopicHash};
 use futures::prelude::*;
@@ -56,7 +57,10 @@ pub struct Behaviour<TSpec: EthSpec> {
     /// A cache of recently seen gossip messages. This is used to filter out any possible
     /// duplicates that may still be seen over gossipsub.
     // TODO: Remove this
-    seen_gossip_messages: LruCache<MessageId, ()>,
+    ///
+    /// The kind of each message is kept so that the peer which delivered it can be scored once the
+    /// message has been validated. It is `None` if the message could not be decoded.
+    seen_gossip_messages: LruCache<MessageId, Option<GossipKind>>,
     /// A collections of variables accessible outside the network service.
     network_globals: Arc<NetworkGlobals<TSpec>>,
     /// Keeps track of the current EnrForkId for upgrading gossipsub topics.
@@ -263,7 +267,7 @@ impl<TSpec: EthSpec> Behaviour<TSpec> {
         };
 
         Ok(Behaviour {
-            eth2_rpc: RPC::new(log.clone()),
+            eth2_rpc: RPC::new(net_conf.rpc_rate_limits.clone(), log.clone()),
             gossipsub: Gossipsub::new(local_peer_id, net_conf.gs_config.clone()),
             identify,
             peer_manager: PeerManager::new(local_key, net_conf, network_globals.clone(), log)?,
@@ -376,6 +380,29 @@ impl<TSpec: EthSpec> Behaviour<TSpec> {
             .propagate_message(&message_id, propagation_source);
     }
 
+    /// Informs the behaviour of the outcome of validating a gossip message.
+    ///
+    /// Accepted messages are propagated. The peer which delivered the message is scored on the
+    /// outcome.
+    pub fn report_message_validation_result(
+        &mut self,
+        propagation_source: &PeerId,
+        message_id: MessageId,
+        validation_result: MessageAcceptance,
+    ) {
+        if let Some(Some(kind)) = self.seen_gossip_messages.get(&message_id).cloned() {
+            self.peer_manager.gossip_validation_result(
+                propagation_source,
+                &kind,
+                validation_result,
+            );
+        }
+
+        if validation_result == MessageAcceptance::Accept {
+            self.propagate_message(propagation_source, message_id);
+        }
+    }
+
     /* Eth2 RPC behaviour functions */
 
     /// Send a request to a peer over RPC.
@@ -569,35 +596,13 @@ impl<TSpec: EthSpec> Behaviour<TSpec> {
 
     fn on_gossip_event(&mut self, event: GossipsubEvent) {
         match event {
-            GossipsubEvent::Message(propagation_source, id, gs_msg) => {
-                // Note: We are keeping track here of the peer that sent us the message, not the
-                // peer that originally published the message.
-                if self.seen_gossip_messages.put(id.clone(), ()).is_none() {
-                    match PubsubMessage::decode(&gs_msg.topics, &gs_msg.data) {
-                        Err(e) => {
-                            debug!(self.log, "Could not decode gossipsub message"; "error" => format!("{}", e))
-                        }
-                        Ok(msg) => {
-                            // if this message isn't a duplicate, notify the network
-                            self.events.push(BehaviourEvent::PubsubMessage {
-                                id,
-                                source: propagation_source,
-                                topics: gs_msg.topics,
-                                message: msg,
-                            });
-                        }
-                    }
-                } else {
-                    match PubsubMessage::<TSpec>::decode(&gs_msg.topics, &gs_msg.data) {
-                        Err(e) => {
-                            debug!(self.log, "Could not decode gossipsub message"; "error" => format!("{}", e))
-                        }
-                        Ok(msg) => {
-                            debug!(self.log, "A duplicate gossipsub message was received"; "message_source" => format!("{}", gs_msg.source), "propagated_peer" => format!("{}",propagation_source), "message" => format!("{}", msg));
-                        }
-                    }
-                }
-            }
+            GossipsubEvent::Message(propagation_source, id, gs_msg) => self.on_gossip_message(
+                propagation_source,
+                id,
+                gs_msg.source,
+                gs_msg.topics,
+                &gs_msg.data,
+            ),
             GossipsubEvent::Subscribed { peer_id, topic } => {
                 self.events
                     .push(BehaviourEvent::PeerSubscribed(peer_id, topic));
@@ -606,6 +611,75 @@ impl<TSpec: EthSpec> Behaviour<TSpec> {
         }
     }
 
+    /// Handles a gossip message delivered by `propagation_source`, which was originally published
+    /// by `message_source`.
+    fn on_gossip_message(
+        &mut self,
+        propagation_source: PeerId,
+        id: MessageId,
+        message_source: PeerId,
+        topics: Vec<TopicHash>,
+        data: &[u8],
+    ) {
+        // Gossip from peers with a low score is ignored. Gossipsub has already added the message
+        // to its duplicate cache, so a later delivery by another peer is dropped by gossipsub.
+        // The message is reported as ignored, so that it is never propagated.
+        if self.peer_manager.is_graylisted(&propagation_source) {
+            debug!(self.log, "Ignoring gossip from graylisted peer"; "peer_id" => propagation_source.to_string());
+            metrics::inc_counter(&metrics::GOSSIP_GRAYLISTED_MESSAGES);
+            self.seen_gossip_messages.put(id.clone(), None);
+            self.report_message_validation_result(
+                &propagation_source,
+                id,
+                MessageAcceptance::Ignore,
+            );
+            return;
+        }
+
+        // Note: We are keeping track here of the peer that sent us the message, not the
+        // peer that originally published the message.
+        if !self.seen_gossip_messages.contains(&id) {
+            match PubsubMessage::decode(&topics, data) {
+                Err(e) => {
+                    debug!(self.log, "Could not decode gossipsub message"; "error" => format!("{}", e));
+                    // the message is invalid on its topic, penalise the peer
+                    let kind = topics.iter().find_map(|topic| {
+                        GossipTopic::decode(topic.as_str())
+                            .ok()
+                            .map(|topic| topic.kind().clone())
+                    });
+                    if let Some(kind) = &kind {
+                        self.peer_manager.gossip_validation_result(
+                            &propagation_source,
+                            kind,
+                            MessageAcceptance::Reject,
+                        );
+                    }
+                    self.seen_gossip_messages.put(id, None);
+                }
+                Ok(msg) => {
+                    // if this message isn't a duplicate, notify the network
+                    self.seen_gossip_messages.put(id.clone(), Some(msg.kind()));
+                    self.events.push(BehaviourEvent::PubsubMessage {
+                        id,
+                        source: propagation_source,
+                        topics,
+                        message: msg,
+                    });
+                }
+            }
+        } else {
+            match PubsubMessage::<TSpec>::decode(&topics, data) {
+                Err(e) => {
+                    debug!(self.log, "Could not decode gossipsub message"; "error" => format!("{}", e))
+                }
+                Ok(msg) => {
+                    debug!(self.log, "A duplicate gossipsub message was received"; "message_source" => format!("{}", message_source), "propagated_peer" => format!("{}",propagation_source), "message" => format!("{}", msg));
+                }
+            }
+        }
+    }
+
     /// Queues the response to be sent upwards as long at it was requested outside the Behaviour.
     fn propagate_response(&mut self, id: RequestId, peer_id: PeerId, response: Response<TSpec>) {
         if !matches!(id, RequestId::Behaviour) {
@@ -648,8 +722,14 @@ impl<TSpec: EthSpec> Behaviour<TSpec> {
                         self.peer_manager.handle_rpc_error(&peer_id, proto, &error);
                     }
                     HandlerErr::Outbound { id, proto, error } => {
-                        // Inform the peer manager that a request we sent to the peer failed
-                        self.peer_manager.handle_rpc_error(&peer_id, proto, &error);
+                        // Inform the peer manager that a request we sent to the peer failed,
+                        // unless the peer was only enforcing its rate limit on us.
+                        if !matches!(
+                            error,
+                            RPCError::ErrorResponse(RPCResponseErrorCode::RateLimited, _)
+                        ) {
+                            self.peer_manager.handle_rpc_error(&peer_id, proto, &error);
+                        }
                         // inform failures of requests comming outside the behaviour
                         if !matches!(id, RequestId::Behaviour) {
                             self.events
@@ -940,3 +1020,66 @@ pub enum BehaviourEvent<TSpec: EthSpec> {
     /// Inform the network to send a Status to this peer.
     StatusPeer(PeerId),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::peer_manager::tests::{build_config, build_network_globals, null_logger};
+    use types::{BeaconBlock, MinimalEthSpec, Signature};
+
+    type E = MinimalEthSpec;
+
+    fn build_behaviour() -> Behaviour<E> {
+        let local_key = Keypair::generate_secp256k1();
+        let config = build_config();
+        let network_globals = build_network_globals(&local_key, &config, vec![]);
+        Behaviour::new(&local_key, &config, network_globals, &null_logger())
+            .expect("should build behaviour")
+    }
+
+    /// Delivers a distinct block from `peer_id`, returning its message id.
+    fn deliver_block(behaviour: &mut Behaviour<E>, peer_id: &PeerId, slot: u64) -> MessageId {
+        let mut block = BeaconBlock::empty(&E::default_spec());
+        block.slot = slot.into();
+        let message = PubsubMessage::BeaconBlock(Box::new(SignedBeaconBlock {
+            message: block,
+            signature: Signature::empty_signature(),
+        }));
+        let topics = message
+            .topics(GossipEncoding::default(), [0; 4])
+            .into_iter()
+            .map(|topic| {
+                let topic: String = topic.into();
+                TopicHash::from_raw(topic)
+            })
+            .collect();
+        let data = message
+            .encode(GossipEncoding::default())
+            .expect("should encode message");
+        let id = MessageId(slot.to_string());
+
+        behaviour.on_gossip_message(peer_id.clone(), id.clone(), peer_id.clone(), topics, &data);
+        id
+    }
+
+    fn delivered_messages(behaviour: &mut Behaviour<E>) -> usize {
+        behaviour
+            .events
+            .drain(..)
+            .filter(|event| matches!(event, BehaviourEvent::PubsubMessage { .. }))
+            .count()
+    }
+
+    #[tokio::test]
+    async fn rejected_messages_lower_the_score_of_their_source() {
+        let mut behaviour = build_behaviour();
+        let peer_id = PeerId::random();
+
+        let id = deliver_block(&mut behaviour, &peer_id, 1);
+        assert_eq!(delivered_messages(&mut behaviour), 1);
+
+        let score = behaviour.peer_manager.score(&peer_id);
+        behaviour.report_message_validation_result(&peer_id, id, MessageAcceptance::Reject);
+        assert!(behaviour.peer_manager.score(&peer_id) < score);
+    }
+}
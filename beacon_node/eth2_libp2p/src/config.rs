@@ -1,8 +1,10 @@
+use crate::peer_manager::{PeerScoreParams, PeerScoreThresholds};
+use crate::rpc::RateLimiterConfig;
 use crate::types::GossipKind;
 use crate::Enr;
 use discv5::{Discv5Config, Discv5ConfigBuilder};
 use libp2p::gossipsub::{GossipsubConfig, GossipsubConfigBuilder, GossipsubMessage, MessageId};
-use libp2p::Multiaddr;
+use libp2p::{Multiaddr, PeerId};
 use serde_derive::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::path::PathBuf;
@@ -10,6 +12,28 @@ use std::time::Duration;
 
 pub const GOSSIP_MAX_SIZE: usize = 1_048_576;
 
+/// A transport which libp2p may open listening sockets on.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ListenTransport {
+    /// Plain TCP, listening on `libp2p_port`.
+    Tcp,
+    /// WebSockets over TCP, listening on `ws_port`. This allows browser-based light clients to
+    /// connect.
+    WebSocket,
+}
+
+impl std::str::FromStr for ListenTransport {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tcp" => Ok(ListenTransport::Tcp),
+            "ws" => Ok(ListenTransport::WebSocket),
+            _ => Err(format!("Unknown listen transport: {}", s)),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(default)]
 /// Network configuration for lighthouse.
@@ -26,6 +50,13 @@ pub struct Config {
     /// UDP port that discovery listens on.
     pub discovery_port: u16,
 
+    /// The transports that libp2p opens listening sockets on.
+    pub listen_transports: Vec<ListenTransport>,
+
+    /// The TCP port that libp2p listens on for WebSocket connections, if enabled in
+    /// `listen_transports`.
+    pub ws_port: u16,
+
     /// The address to broadcast to peers about which address we are listening on. None indicates
     /// that no discovery address has been set in the CLI args.
     pub enr_address: Option<std::net::IpAddr>,
@@ -36,6 +67,10 @@ pub struct Config {
     /// The tcp port to broadcast to peers in order to reach back for libp2p services.
     pub enr_tcp_port: Option<u16>,
 
+    /// The WebSocket port to broadcast to peers, if WebSockets are enabled in
+    /// `listen_transports`.
+    pub enr_ws_port: Option<u16>,
+
     /// Target number of connected peers.
     pub max_peers: usize,
 
@@ -43,16 +78,37 @@ pub struct Config {
     #[serde(skip)]
     pub gs_config: GossipsubConfig,
 
+    /// The parameters for scoring peers on the gossip messages they deliver.
+    #[serde(skip)]
+    pub gs_score_params: PeerScoreParams,
+
+    /// The scores below which peers are graylisted and disconnected.
+    #[serde(skip)]
+    pub gs_score_thresholds: PeerScoreThresholds,
+
     /// Discv5 configuration parameters.
     #[serde(skip)]
     pub discv5_config: Discv5Config,
 
+    /// The quotas which limit the rate of RPC requests of each peer.
+    #[serde(skip)]
+    pub rpc_rate_limits: RateLimiterConfig,
+
     /// List of nodes to initially connect to.
     pub boot_nodes: Vec<Enr>,
 
     /// List of libp2p nodes to initially connect to.
     pub libp2p_nodes: Vec<Multiaddr>,
 
+    /// List of peers which are never scored down or disconnected to make room for other peers,
+    /// and are redialed whenever they disconnect.
+    ///
+    /// A trusted peer can only be redialed after it has connected once, since its addresses are
+    /// learned from that connection (unless it has an ENR in the DHT). Failed redials are retried
+    /// with an exponential backoff.
+    #[serde(skip)]
+    pub trusted_peers: Vec<PeerId>,
+
     /// Client version
     pub client_version: String,
 
@@ -119,14 +175,21 @@ impl Default for Config {
             listen_address: "0.0.0.0".parse().expect("valid ip address"),
             libp2p_port: 9000,
             discovery_port: 9000,
+            listen_transports: vec![ListenTransport::Tcp],
+            ws_port: 9001,
             enr_address: None,
             enr_udp_port: None,
             enr_tcp_port: None,
+            enr_ws_port: None,
             max_peers: 50,
             gs_config,
+            gs_score_params: PeerScoreParams::default(),
+            gs_score_thresholds: PeerScoreThresholds::default(),
             discv5_config,
+            rpc_rate_limits: RateLimiterConfig::default(),
             boot_nodes: vec![],
             libp2p_nodes: vec![],
+            trusted_peers: vec![],
             client_version: version::version(),
             disable_discovery: false,
             topics,
@@ -5,8 +5,8 @@ pub use discv5::enr::{self, CombinedKey, EnrBuilder};
 use super::enr_ext::CombinedKeyExt;
 use super::ENR_FILENAME;
 use crate::types::{Enr, EnrBitfield};
-use crate::NetworkConfig;
-use libp2p::core::identity::Keypair;
+use crate::{ListenTransport, Multiaddr, NetworkConfig};
+use libp2p::core::{identity::Keypair, multiaddr::Protocol};
 use slog::{debug, warn};
 use ssz::{Decode, Encode};
 use ssz_types::BitVector;
@@ -20,6 +20,8 @@ use types::{EnrForkId, EthSpec};
 pub const ETH2_ENR_KEY: &'static str = "eth2";
 /// The ENR field specifying the subnet bitfield.
 pub const BITFIELD_ENR_KEY: &'static str = "attnets";
+/// The ENR field specifying the TCP port on which libp2p accepts WebSocket connections.
+pub const WS_ENR_KEY: &'static str = "ws";
 
 /// Extension trait for ENR's within Eth2.
 pub trait Eth2Enr {
@@ -27,6 +29,13 @@ pub trait Eth2Enr {
     fn bitfield<TSpec: EthSpec>(&self) -> Result<EnrBitfield<TSpec>, &'static str>;
 
     fn eth2(&self) -> Result<EnrForkId, &'static str>;
+
+    /// The WebSocket port associated with the ENR, if the node accepts WebSocket connections.
+    fn ws_port(&self) -> Option<u16>;
+
+    /// Returns the WebSocket multiaddrs (`/tcp/<ws>/ws`) of the ENR's `ip` and `ip6` addresses,
+    /// if it has a WebSocket port.
+    fn multiaddr_ws(&self) -> Vec<Multiaddr>;
 }
 
 impl Eth2Enr for Enr {
@@ -46,6 +55,31 @@ impl Eth2Enr for Enr {
 
         EnrForkId::from_ssz_bytes(eth2_bytes).map_err(|_| "Could not decode EnrForkId")
     }
+
+    fn ws_port(&self) -> Option<u16> {
+        self.get(WS_ENR_KEY)
+            .and_then(|ws_bytes| u16::from_ssz_bytes(ws_bytes).ok())
+    }
+
+    fn multiaddr_ws(&self) -> Vec<Multiaddr> {
+        let ws_port = match self.ws_port() {
+            Some(ws_port) => ws_port,
+            None => return Vec::new(),
+        };
+
+        let mut multiaddrs = Vec::new();
+        if let Some(ip) = self.ip() {
+            multiaddrs.push(Multiaddr::from(ip));
+        }
+        if let Some(ip6) = self.ip6() {
+            multiaddrs.push(Multiaddr::from(ip6));
+        }
+        for multiaddr in multiaddrs.iter_mut() {
+            multiaddr.push(Protocol::Tcp(ws_port));
+            multiaddr.push(Protocol::Ws("/".into()));
+        }
+        multiaddrs
+    }
 }
 
 /// Loads an ENR from file if it exists and matches the current NodeId and sequence number. If none
@@ -75,6 +109,18 @@ pub fn build_or_load_enr<T: EthSpec>(
                     Ok(disk_enr) => {
                         // if the same node id, then we may need to update our sequence number
                         if local_enr.node_id() == disk_enr.node_id() {
+                            // keep the long-lived subnets of the stored ENR, so that they persist
+                            // across restarts
+                            if let (Ok(_), Some(bitfield_bytes)) =
+                                (disk_enr.bitfield::<T>(), disk_enr.get(BITFIELD_ENR_KEY))
+                            {
+                                local_enr
+                                    .insert(BITFIELD_ENR_KEY, bitfield_bytes.to_vec(), &enr_key)
+                                    .map_err(|e| {
+                                        format!("Could not restore ENR bitfield: {:?}", e)
+                                    })?;
+                            }
+
                             if compare_enr(&local_enr, &disk_enr) {
                                 debug!(log, "ENR loaded from disk"; "file" => format!("{:?}", enr_f));
                                 // the stored ENR has the same configuration, use it
@@ -115,10 +161,12 @@ pub fn build_enr<T: EthSpec>(
     if let Some(udp_port) = config.enr_udp_port {
         builder.udp(udp_port);
     }
-    // we always give it our listening tcp port
+    // give it our listening tcp port, unless we only listen for WebSocket connections
     // TODO: Add uPnP support to map udp and tcp ports
-    let tcp_port = config.enr_tcp_port.unwrap_or_else(|| config.libp2p_port);
-    builder.tcp(tcp_port);
+    if config.listen_transports.contains(&ListenTransport::Tcp) {
+        let tcp_port = config.enr_tcp_port.unwrap_or_else(|| config.libp2p_port);
+        builder.tcp(tcp_port);
+    }
 
     // set the `eth2` field on our ENR
     builder.add_value(ETH2_ENR_KEY.into(), enr_fork_id.as_ssz_bytes());
@@ -128,8 +176,16 @@ pub fn build_enr<T: EthSpec>(
 
     builder.add_value(BITFIELD_ENR_KEY.into(), bitfield.as_ssz_bytes());
 
+    // advertise the WebSocket port if we listen for WebSocket connections
+    if config
+        .listen_transports
+        .contains(&ListenTransport::WebSocket)
+    {
+        let ws_port = config.enr_ws_port.unwrap_or_else(|| config.ws_port);
+        builder.add_value(WS_ENR_KEY.into(), ws_port.as_ssz_bytes());
+    }
+
     builder
-        .tcp(config.libp2p_port)
         .build(enr_key)
         .map_err(|e| format!("Could not build Local ENR: {:?}", e))
 }
@@ -141,12 +197,14 @@ fn compare_enr(local_enr: &Enr, disk_enr: &Enr) -> bool {
     (local_enr.ip().is_none() || local_enr.ip() == disk_enr.ip())
         // tcp ports must match
         && local_enr.tcp() == disk_enr.tcp()
+        // the websocket ports must match, including whether one is advertised at all
+        && local_enr.get(WS_ENR_KEY) == disk_enr.get(WS_ENR_KEY)
         // must match on the same fork
         && local_enr.get(ETH2_ENR_KEY) == disk_enr.get(ETH2_ENR_KEY)
         // take preference over disk udp port if one is not specified
         && (local_enr.udp().is_none() || local_enr.udp() == disk_enr.udp())
-        // we need the BITFIELD_ENR_KEY key to match, otherwise we use a new ENR. This will likely only
-        // be true for non-validating nodes
+        // we need the BITFIELD_ENR_KEY key to match, otherwise we use a new ENR. The bitfield is
+        // restored from the disk ENR, so this only fails if the disk bitfield is invalid
         && local_enr.get(BITFIELD_ENR_KEY) == disk_enr.get(BITFIELD_ENR_KEY)
 }
 
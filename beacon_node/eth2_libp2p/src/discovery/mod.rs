@@ -3,7 +3,7 @@ pub(crate) mod enr;
 pub mod enr_ext;
 
 // Allow external use of the lighthouse ENR builder
-pub use enr::{build_enr, CombinedKey, Eth2Enr};
+pub use enr::{build_enr, CombinedKey, Eth2Enr, BITFIELD_ENR_KEY, WS_ENR_KEY};
 pub use enr_ext::{CombinedKeyExt, EnrExt};
 pub use libp2p::core::identity::Keypair;
 
@@ -125,6 +125,10 @@ pub struct Discovery<TSpec: EthSpec> {
     /// The directory where the ENR is stored.
     enr_dir: String,
 
+    /// Whether the local ENR has changed since it was last saved to disk. Subnet changes to the
+    /// ENR are frequent, so they are only saved when discovery is dropped.
+    enr_unsaved: bool,
+
     /// The handle for the underlying discv5 Server.
     ///
     /// This is behind a Reference counter to allow for futures to be spawned and polled with a
@@ -224,6 +228,7 @@ impl<TSpec: EthSpec> Discovery<TSpec> {
             started: !config.disable_discovery,
             log,
             enr_dir,
+            enr_unsaved: false,
         })
     }
 
@@ -329,6 +334,9 @@ impl<TSpec: EthSpec> Discovery<TSpec> {
 
         // replace the global version
         *self.network_globals.local_enr.write() = self.discv5.local_enr();
+
+        // persist the bitfield on shutdown, so that our long-lived subnets are kept across restarts
+        self.enr_unsaved = true;
         Ok(())
     }
 
@@ -645,6 +653,7 @@ impl<TSpec: EthSpec> Discovery<TSpec> {
                             // to disk.
                             let enr = self.discv5.local_enr();
                             enr::save_enr_to_disk(Path::new(&self.enr_dir), &enr, &self.log);
+                            self.enr_unsaved = false;
                             return Poll::Ready(DiscoveryEvent::SocketUpdated(socket));
                         }
                         _ => {} // Ignore all other discv5 server events
@@ -655,3 +664,11 @@ impl<TSpec: EthSpec> Discovery<TSpec> {
         Poll::Pending
     }
 }
+
+impl<TSpec: EthSpec> Drop for Discovery<TSpec> {
+    fn drop(&mut self) {
+        if self.enr_unsaved {
+            enr::save_enr_to_disk(Path::new(&self.enr_dir), &self.local_enr(), &self.log);
+        }
+    }
+}
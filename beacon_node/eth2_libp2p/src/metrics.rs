@@ -17,6 +17,18 @@ lazy_static! {
         "libp2p_peer_disconnect_event_total",
         "Count of libp2p peer disconnect events"
     );
+    pub static ref PEER_BAN_EVENT_COUNT: Result<IntCounter> = try_create_int_counter(
+        "libp2p_peer_ban_event_total",
+        "Count of peers banned for a low reputation"
+    );
+    pub static ref PEERS_BANNED: Result<IntGauge> = try_create_int_gauge(
+        "libp2p_peer_banned_peers_total",
+        "Count of peers which are currently banned"
+    );
+    pub static ref GOSSIP_GRAYLISTED_MESSAGES: Result<IntCounter> = try_create_int_counter(
+        "gossipsub_graylisted_messages_total",
+        "Count of gossip messages ignored because the delivering peer was graylisted"
+    );
     pub static ref DISCOVERY_QUEUE: Result<IntGauge> = try_create_int_gauge(
         "discovery_queue_size",
         "The number of discovery queries awaiting execution"
@@ -34,6 +46,10 @@ lazy_static! {
         "Unsolicited discovery requests per ip per second",
         &["Addresses"]
     );
+    pub static ref RPC_REQUESTS_RATE_LIMITED: Result<IntCounter> = try_create_int_counter(
+        "libp2p_rpc_requests_rate_limited_total",
+        "Count of inbound RPC requests rejected for exceeding the peer's rate limit"
+    );
 }
 
 pub fn scrape_discovery_metrics() {
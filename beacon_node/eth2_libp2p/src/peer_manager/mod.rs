@@ -3,8 +3,9 @@
 pub use self::peerdb::*;
 use crate::discovery::{Discovery, DiscoveryEvent};
 use crate::rpc::{MetaData, Protocol, RPCError, RPCResponseErrorCode};
+use crate::types::{GossipKind, MessageAcceptance};
 use crate::{error, metrics};
-use crate::{Enr, EnrExt, NetworkConfig, NetworkGlobals, PeerId};
+use crate::{Enr, EnrExt, Eth2Enr, NetworkConfig, NetworkGlobals, PeerId};
 use futures::prelude::*;
 use futures::Stream;
 use hashset_delay::HashSetDelay;
@@ -13,6 +14,7 @@ use libp2p::identify::IdentifyInfo;
 use slog::{crit, debug, error};
 use smallvec::SmallVec;
 use std::{
+    collections::HashMap,
     net::SocketAddr,
     pin::Pin,
     sync::Arc,
@@ -27,12 +29,14 @@ pub mod client;
 mod peer_info;
 mod peer_sync_status;
 mod peerdb;
+mod score;
 
 pub use peer_info::{PeerConnectionStatus::*, PeerInfo};
 pub use peer_sync_status::{PeerSyncStatus, SyncInfo};
-/// The minimum reputation before a peer is disconnected.
+pub use score::{PeerScore, PeerScoreParams, PeerScoreThresholds, TopicScoreParams};
+/// The minimum reputation before a peer is banned.
 // Most likely this needs tweaking.
-const _MIN_REP_BEFORE_BAN: Rep = 10;
+const MIN_REP_BEFORE_BAN: Rep = 10;
 /// The time in seconds between re-status's peers.
 const STATUS_INTERVAL: u64 = 300;
 /// The time in seconds between PING events. We do not send a ping if the other peer as PING'd us within
@@ -43,6 +47,11 @@ const PING_INTERVAL: u64 = 30;
 /// requests. This defines the interval in seconds.  
 const HEARTBEAT_INTERVAL: u64 = 30;
 
+/// The maximum time in seconds between attempts to redial a disconnected trusted peer. The time
+/// between attempts doubles after each failed attempt, starting at one heartbeat, up to this
+/// limit.
+const MAX_TRUSTED_PEER_DIAL_BACKOFF: u64 = 30 * 60;
+
 /// The main struct that handles peer's reputation and connection status.
 pub struct PeerManager<TSpec: EthSpec> {
     /// Storage of network globals to access the `PeerDB`.
@@ -59,6 +68,13 @@ pub struct PeerManager<TSpec: EthSpec> {
     discovery: Discovery<TSpec>,
     /// The heartbeat interval to perform routine maintenance.
     heartbeat: tokio::time::Interval,
+    /// The scores of peers based on the gossip messages they deliver.
+    gossip_scores: PeerScore,
+    /// The scores below which peers are graylisted and disconnected.
+    score_thresholds: PeerScoreThresholds,
+    /// The number of failed attempts to redial each disconnected trusted peer, and the time of
+    /// the next attempt.
+    trusted_peer_dials: HashMap<PeerId, (u32, Instant)>,
     /// The logger associated with the `PeerManager`.
     log: slog::Logger,
 }
@@ -141,6 +157,9 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
             target_peers: config.max_peers, //TODO: Add support for target peers and max peers
             discovery,
             heartbeat,
+            gossip_scores: PeerScore::new(config.gs_score_params.clone()),
+            score_thresholds: config.gs_score_thresholds,
+            trusted_peer_dials: HashMap::new(),
             log: log.clone(),
         })
     }
@@ -179,8 +198,10 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
     }
 
     /// Updates the state of the peer as disconnected.
+    ///
+    /// Trusted peers are redialed on a later heartbeat if we know an address to dial them at, see
+    /// `redial_trusted_peers`.
     pub fn notify_disconnect(&mut self, peer_id: &PeerId) {
-        //self.update_reputations();
         self.network_globals.peers.write().disconnect(peer_id);
 
         // remove the ping and status timer for the peer
@@ -218,15 +239,101 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
 
     /// Reports a peer for some action.
     ///
-    /// If the peer doesn't exist, log a warning and insert defaults.
+    /// If the peer's reputation falls below `MIN_REP_BEFORE_BAN` it is banned and disconnected.
+    /// Trusted peers are never scored down.
     pub fn report_peer(&mut self, peer_id: &PeerId, action: PeerAction) {
-        //TODO: Check these. There are double disconnects for example
-        // self.update_reputations();
-        self.network_globals
-            .peers
-            .write()
-            .add_reputation(peer_id, action.rep_change());
-        // self.update_reputations();
+        let should_ban = {
+            let mut peerdb = self.network_globals.peers.write();
+            peerdb.add_reputation(peer_id, action.rep_change());
+            peerdb.reputation(peer_id) < MIN_REP_BEFORE_BAN
+                && !peerdb.peer_banned(peer_id)
+                && !peerdb.is_trusted(peer_id)
+        };
+
+        if should_ban {
+            self.ban_peer(peer_id, "low reputation");
+        }
+    }
+
+    /* Gossip scoring */
+
+    /// Updates the gossip score of the peer which delivered a gossip message on `kind`, given the
+    /// outcome of validating the message.
+    pub fn gossip_validation_result(
+        &mut self,
+        peer_id: &PeerId,
+        kind: &GossipKind,
+        validation_result: MessageAcceptance,
+    ) {
+        match validation_result {
+            MessageAcceptance::Accept => self.gossip_scores.first_message_delivered(peer_id, kind),
+            MessageAcceptance::Reject => {
+                self.gossip_scores.invalid_message_delivered(peer_id, kind)
+            }
+            MessageAcceptance::Ignore => {}
+        }
+    }
+
+    /// Returns the combined score of a peer: its gossip score plus its reputation relative to the
+    /// default reputation.
+    pub fn score(&self, peer_id: &PeerId) -> f64 {
+        let reputation = self.network_globals.peers.read().reputation(peer_id);
+        self.gossip_scores.score(peer_id)
+            + self.gossip_scores.params().app_specific_weight
+                * (f64::from(reputation) - f64::from(DEFAULT_REPUTATION))
+    }
+
+    /// Returns the records of the peers which should be remembered across restarts: those the
+    /// `PeerDB` remembers and any untrusted peer with a non-zero gossip score.
+    pub fn peer_records(&self) -> Vec<PeerRecord> {
+        let peerdb = self.network_globals.peers.read();
+        let mut records = peerdb
+            .peer_records()
+            .into_iter()
+            .map(|record| (record.peer_id.clone(), record))
+            .collect::<HashMap<_, _>>();
+
+        for (peer_id, gossip_score) in self.gossip_scores.scores() {
+            if peerdb.is_trusted(&peer_id) {
+                continue;
+            }
+            let reputation = peerdb.reputation(&peer_id);
+            records
+                .entry(peer_id.clone())
+                .or_insert_with(|| PeerRecord {
+                    peer_id,
+                    reputation,
+                    ban_expiry: None,
+                    gossip_score: 0.0,
+                })
+                .gossip_score = gossip_score;
+        }
+
+        records.into_iter().map(|(_, record)| record).collect()
+    }
+
+    /// Restores the reputations, gossip scores and bans of peers from records which were
+    /// persisted by a previous run. Trusted peers are left untouched.
+    pub fn restore_peer_records(&mut self, records: Vec<PeerRecord>) {
+        let gossip_scores = records
+            .iter()
+            .filter(|record| record.gossip_score != 0.0)
+            .map(|record| (record.peer_id.clone(), record.gossip_score))
+            .collect::<Vec<_>>();
+
+        let mut peerdb = self.network_globals.peers.write();
+        peerdb.restore_peer_records(records);
+        for (peer_id, gossip_score) in gossip_scores {
+            if !peerdb.is_trusted(&peer_id) {
+                self.gossip_scores.restore_score(peer_id, gossip_score);
+            }
+        }
+    }
+
+    /// Returns true if gossip from this peer should be ignored due to its low score.
+    pub fn is_graylisted(&self, peer_id: &PeerId) -> bool {
+        self.score(peer_id) < self.score_thresholds.graylist_threshold
+            && !self.network_globals.peers.read().is_trusted(peer_id)
     }
 
     /// Updates `PeerInfo` with `identify` information.
@@ -265,6 +372,16 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
                 RPCResponseErrorCode::Unknown => PeerAction::HighToleranceError,
                 RPCResponseErrorCode::ServerError => PeerAction::MidToleranceError,
                 RPCResponseErrorCode::InvalidRequest => PeerAction::LowToleranceError,
+                // The peer exceeded our rate limit. Well-behaved peers stay within it, so repeated
+                // offences lead to a ban.
+                RPCResponseErrorCode::RateLimited => match protocol {
+                    Protocol::BlocksByRange => PeerAction::MidToleranceError,
+                    Protocol::BlocksByRoot => PeerAction::MidToleranceError,
+                    Protocol::Ping => PeerAction::MidToleranceError,
+                    Protocol::Goodbye => PeerAction::LowToleranceError,
+                    Protocol::MetaData => PeerAction::LowToleranceError,
+                    Protocol::Status => PeerAction::LowToleranceError,
+                },
             },
             RPCError::SSZDecodeError(_) => PeerAction::Fatal,
             RPCError::UnsupportedProtocol => {
@@ -396,7 +513,16 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
                     .is_none()
             });
 
+            // Peers which only accept WebSocket connections are dialed on their WebSocket port
+            if out_list.is_empty() {
+                out_list = enr.multiaddr_ws();
+            }
+
             out_list
+        } else if let Some(info) = self.network_globals.peers.read().peer_info(peer_id) {
+            // Peers without an ENR, such as trusted peers, can be redialed at the addresses they
+            // last told us they were listening on.
+            info.listening_addresses.clone()
         } else {
             // PeerId is not known
             Vec::new()
@@ -458,16 +584,15 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
     /// This informs if the peer was accepted in to the db or not.
     // TODO: Drop peers if over max_peer limit
     fn connect_peer(&mut self, peer_id: &PeerId, connection: ConnectingType) -> bool {
-        // TODO: remove after timed updates
-        //self.update_reputations();
-
         {
             let mut peerdb = self.network_globals.peers.write();
-            if peerdb.connection_status(peer_id).map(|c| c.is_banned()) == Some(true) {
-                // don't connect if the peer is banned
-                // TODO: Handle this case. If peer is banned this shouldn't be reached. It will put
-                // our connection/disconnection out of sync with libp2p
-                // return false;
+            if peerdb.peer_banned(peer_id) {
+                // don't connect if the peer is banned. The peer remains banned when the connection
+                // is closed.
+                debug!(self.log, "Disconnecting banned peer"; "peer_id" => peer_id.to_string());
+                self.events
+                    .push(PeerManagerEvent::DisconnectPeer(peer_id.clone()));
+                return false;
             }
 
             match connection {
@@ -477,6 +602,9 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
             }
         }
 
+        // a trusted peer which is connected again no longer needs to be redialed
+        self.trusted_peer_dials.remove(peer_id);
+
         // start a ping and status timer for the peer
         self.ping_peers.insert(peer_id.clone());
         self.status_peers.insert(peer_id.clone());
@@ -491,100 +619,65 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
         true
     }
 
+    /// Bans a peer and requests that it is disconnected.
+    ///
+    /// The peer's gossip score is forgotten, so that it starts afresh once its ban expires.
+    fn ban_peer(&mut self, peer_id: &PeerId, reason: &str) {
+        debug!(self.log, "Banning peer"; "peer_id" => peer_id.to_string(), "reason" => reason,
+            "reputation" => self.network_globals.peers.read().reputation(peer_id),
+            "score" => self.score(peer_id));
+        self.network_globals.peers.write().ban(peer_id);
+        self.gossip_scores.remove_peer(peer_id);
+        self.ping_peers.remove(peer_id);
+        self.status_peers.remove(peer_id);
+        self.events
+            .push(PeerManagerEvent::DisconnectPeer(peer_id.clone()));
+        metrics::inc_counter(&metrics::PEER_BAN_EVENT_COUNT);
+    }
+
     /// Notifies the peer manager that this peer is being dialed.
     pub fn _dialing_peer(&mut self, peer_id: &PeerId) {
         self.network_globals.peers.write().dialing_peer(peer_id);
     }
 
-    /// Updates the reputation of known peers according to their connection
-    /// status and the time that has passed.
-    ///
-    /// **Disconnected peers** get a 1rep hit every hour they stay disconnected.
-    /// **Banned peers** get a 1rep gain for every hour to slowly allow them back again.
+    /// Dials the trusted peers which are disconnected, since they should always be connected.
     ///
-    /// A banned(disconnected) peer that gets its rep above(below) MIN_REP_BEFORE_BAN is
-    /// now considered a disconnected(banned) peer.
-    // TODO: Implement when reputation is added.
-    fn _update_reputations(&mut self) {
-        /*
-        // avoid locking the peerdb too often
-        // TODO: call this on a timer
+    /// Peers without an address to dial them at are skipped until discovery or a previous
+    /// connection provides one. After each attempt the peer is not dialed again until its backoff
+    /// has elapsed, which doubles with every attempt that does not lead to a connection.
+    fn redial_trusted_peers(&mut self) {
+        let disconnected_peers = {
+            let peerdb = self.network_globals.peers.read();
+            peerdb
+                .trusted_peers()
+                .filter(|peer_id| !peerdb.is_connected_or_dialing(peer_id))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
 
         let now = Instant::now();
-
-        // Check for peers that get banned, unbanned and that should be disconnected
-        let mut ban_queue = Vec::new();
-        let mut unban_queue = Vec::new();
-
-        /* Check how long have peers been in this state and update their reputations if needed */
-        let mut pdb = self.network_globals.peers.write();
-
-        for (id, info) in pdb._peers_mut() {
-            // Update reputations
-            match info.connection_status {
-                Connected { .. } => {
-                    // Connected peers gain reputation by sending useful messages
-                }
-                Disconnected { since } | Banned { since } => {
-                    // For disconnected peers, lower their reputation by 1 for every hour they
-                    // stay disconnected. This helps us slowly forget disconnected peers.
-                    // In the same way, slowly allow banned peers back again.
-                    let dc_hours = now
-                        .checked_duration_since(since)
-                        .unwrap_or_else(|| Duration::from_secs(0))
-                        .as_secs()
-                        / 3600;
-                    let last_dc_hours = self
-                        ._last_updated
-                        .checked_duration_since(since)
-                        .unwrap_or_else(|| Duration::from_secs(0))
-                        .as_secs()
-                        / 3600;
-                    if dc_hours > last_dc_hours {
-                        // this should be 1 most of the time
-                        let rep_dif = (dc_hours - last_dc_hours)
-                            .try_into()
-                            .unwrap_or(Rep::max_value());
-
-                        info.reputation = if info.connection_status.is_banned() {
-                            info.reputation.saturating_add(rep_dif)
-                        } else {
-                            info.reputation.saturating_sub(rep_dif)
-                        };
-                    }
-                }
-                Dialing { since } => {
-                    // A peer shouldn't be dialing for more than 2 minutes
-                    if since.elapsed().as_secs() > 120 {
-                        warn!(self.log,"Peer has been dialing for too long"; "peer_id" => id.to_string());
-                        // TODO: decide how to handle this
-                    }
-                }
-                Unknown => {} //TODO: Handle this case
-            }
-            // Check if the peer gets banned or unbanned and if it should be disconnected
-            if info.reputation < _MIN_REP_BEFORE_BAN && !info.connection_status.is_banned() {
-                // This peer gets banned. Check if we should request disconnection
-                ban_queue.push(id.clone());
-            } else if info.reputation >= _MIN_REP_BEFORE_BAN && info.connection_status.is_banned() {
-                // This peer gets unbanned
-                unban_queue.push(id.clone());
+        for peer_id in disconnected_peers {
+            let (failed_dials, next_dial) = self
+                .trusted_peer_dials
+                .get(&peer_id)
+                .cloned()
+                .unwrap_or((0, now));
+            if next_dial > now || self.addresses_of_peer(&peer_id).is_empty() {
+                continue;
             }
-        }
-
-        for id in ban_queue {
-            pdb.ban(&id);
-
-            self.events
-                .push(PeerManagerEvent::DisconnectPeer(id.clone()));
-        }
 
-        for id in unban_queue {
-            pdb.disconnect(&id);
+            let backoff = HEARTBEAT_INTERVAL
+                .saturating_mul(1 << failed_dials.min(16))
+                .min(MAX_TRUSTED_PEER_DIAL_BACKOFF);
+            self.trusted_peer_dials.insert(
+                peer_id.clone(),
+                (failed_dials + 1, now + Duration::from_secs(backoff)),
+            );
+
+            debug!(self.log, "Dialing trusted peer"; "peer_id" => peer_id.to_string(),
+                "failed_dials" => failed_dials);
+            self.events.push(PeerManagerEvent::Dial(peer_id));
         }
-
-        self._last_updated = Instant::now();
-        */
     }
 
     /// The Peer manager's heartbeat maintains the peer count and maintains peer reputations.
@@ -602,10 +695,56 @@ impl<TSpec: EthSpec> PeerManager<TSpec> {
             self.discovery.discover_peers();
         }
 
-        // TODO: If we have too many peers, remove peers that are not required for subnet
-        // validation.
+        // Decay the gossip scores and ban any connected peers whose score is too low.
+        self.gossip_scores.refresh();
+        let untrusted_peers = self
+            .network_globals
+            .peers
+            .read()
+            .connected_peers()
+            .filter(|(_, info)| !info.is_trusted)
+            .map(|(peer_id, _)| peer_id.clone())
+            .collect::<Vec<_>>();
+        let low_scoring_peers = untrusted_peers
+            .into_iter()
+            .filter(|peer_id| self.score(peer_id) < self.score_thresholds.disconnect_threshold)
+            .collect::<Vec<_>>();
+        for peer_id in low_scoring_peers {
+            self.ban_peer(&peer_id, "low score");
+        }
+
+        self.redial_trusted_peers();
 
-        // TODO: Perform peer reputation maintenance here
+        let mut peerdb = self.network_globals.peers.write();
+
+        for peer_id in peerdb.unban_expired() {
+            debug!(self.log, "Peer ban has expired"; "peer_id" => peer_id.to_string());
+        }
+
+        // If we have too many peers, disconnect those with the lowest reputation which are neither
+        // trusted nor required for a subnet.
+        let connected_peers = peerdb.connected_peer_ids().count();
+        if connected_peers > self.target_peers {
+            let now = Instant::now();
+            let mut excess_peers = peerdb
+                .connected_peers()
+                .filter(|(_, info)| {
+                    !info.is_trusted && info.min_ttl.map_or(true, |min_ttl| min_ttl < now)
+                })
+                .map(|(peer_id, info)| (peer_id.clone(), info.reputation))
+                .collect::<Vec<_>>();
+            excess_peers.sort_by_key(|(_, reputation)| *reputation);
+
+            for (peer_id, _) in excess_peers
+                .into_iter()
+                .take(connected_peers - self.target_peers)
+            {
+                debug!(self.log, "Disconnecting excess peer"; "peer_id" => peer_id.to_string());
+                self.events.push(PeerManagerEvent::DisconnectPeer(peer_id));
+            }
+        }
+
+        metrics::set_gauge(&metrics::PEERS_BANNED, peerdb.banned_peers().count() as i64);
     }
 }
 
@@ -673,3 +812,211 @@ enum ConnectingType {
     /// We have successfully dialed a peer.
     OutgoingConnected,
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::discovery::{build_enr, CombinedKey, CombinedKeyExt};
+    use std::net::UdpSocket;
+    use tempdir::TempDir;
+    use types::{EnrForkId, MinimalEthSpec};
+
+    type E = MinimalEthSpec;
+
+    pub(crate) fn null_logger() -> slog::Logger {
+        slog::Logger::root(slog::Discard, slog::o!())
+    }
+
+    /// Returns a config whose discovery service listens on an unused port.
+    pub(crate) fn build_config() -> NetworkConfig {
+        let port = UdpSocket::bind("127.0.0.1:0")
+            .and_then(|socket| socket.local_addr())
+            .expect("should find an unused port")
+            .port();
+
+        let mut config = NetworkConfig::default();
+        config.libp2p_port = port;
+        config.discovery_port = port;
+        config.enr_address = Some("127.0.0.1".parse().unwrap());
+        config.enr_udp_port = Some(port);
+        config.enr_tcp_port = Some(port);
+        config.network_dir = TempDir::new("peer_manager_test")
+            .expect("should create temp dir")
+            .into_path();
+        config
+    }
+
+    pub(crate) fn build_network_globals(
+        local_key: &Keypair,
+        config: &NetworkConfig,
+        trusted_peers: Vec<PeerId>,
+    ) -> Arc<NetworkGlobals<E>> {
+        let enr_key = CombinedKey::from_libp2p(local_key).expect("should convert key");
+        let enr = build_enr::<E>(&enr_key, config, EnrForkId::default()).expect("should build enr");
+        Arc::new(NetworkGlobals::new(
+            enr,
+            config.libp2p_port,
+            config.discovery_port,
+            trusted_peers,
+            &null_logger(),
+        ))
+    }
+
+    fn build_peer_manager_with_trusted_peers(trusted_peers: Vec<PeerId>) -> PeerManager<E> {
+        let local_key = Keypair::generate_secp256k1();
+        let config = build_config();
+        let network_globals = build_network_globals(&local_key, &config, trusted_peers);
+        PeerManager::new(&local_key, &config, network_globals, &null_logger())
+            .expect("should build peer manager")
+    }
+
+    fn build_peer_manager() -> PeerManager<E> {
+        build_peer_manager_with_trusted_peers(vec![])
+    }
+
+    fn reject_blocks(peer_manager: &mut PeerManager<E>, peer_id: &PeerId, count: usize) {
+        for _ in 0..count {
+            peer_manager.gossip_validation_result(
+                peer_id,
+                &GossipKind::BeaconBlock,
+                MessageAcceptance::Reject,
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn gossip_validation_results_change_the_score() {
+        let mut peer_manager = build_peer_manager();
+        let peer_id = PeerId::random();
+        assert_eq!(peer_manager.score(&peer_id), 0.0);
+
+        peer_manager.gossip_validation_result(
+            &peer_id,
+            &GossipKind::BeaconBlock,
+            MessageAcceptance::Ignore,
+        );
+        assert_eq!(peer_manager.score(&peer_id), 0.0);
+
+        peer_manager.gossip_validation_result(
+            &peer_id,
+            &GossipKind::BeaconBlock,
+            MessageAcceptance::Accept,
+        );
+        let score = peer_manager.score(&peer_id);
+        assert!(score > 0.0);
+
+        reject_blocks(&mut peer_manager, &peer_id, 1);
+        assert!(peer_manager.score(&peer_id) < score);
+        assert!(!peer_manager.is_graylisted(&peer_id));
+    }
+
+    #[tokio::test]
+    async fn heartbeat_bans_peers_below_the_disconnect_threshold() {
+        let mut peer_manager = build_peer_manager();
+        let bad_peer = PeerId::random();
+        let graylisted_peer = PeerId::random();
+        {
+            let mut peerdb = peer_manager.network_globals.peers.write();
+            peerdb.connect_ingoing(&bad_peer);
+            peerdb.connect_ingoing(&graylisted_peer);
+        }
+
+        // With the default parameters, four invalid blocks put a peer between the graylist and
+        // disconnect thresholds, and five put it below both.
+        reject_blocks(&mut peer_manager, &graylisted_peer, 4);
+        reject_blocks(&mut peer_manager, &bad_peer, 5);
+        assert!(peer_manager.is_graylisted(&graylisted_peer));
+        assert!(peer_manager.is_graylisted(&bad_peer));
+
+        peer_manager.events.clear();
+        peer_manager.heartbeat();
+
+        let peerdb = peer_manager.network_globals.peers.read();
+        assert!(peerdb.peer_banned(&bad_peer));
+        assert!(!peerdb.peer_banned(&graylisted_peer));
+        let disconnected = |peer: &PeerId| {
+            peer_manager.events.iter().any(|event| match event {
+                PeerManagerEvent::DisconnectPeer(peer_id) => peer_id == peer,
+                _ => false,
+            })
+        };
+        assert!(disconnected(&bad_peer));
+        assert!(!disconnected(&graylisted_peer));
+
+        // A banned peer's score starts afresh once its ban expires.
+        assert_eq!(peer_manager.gossip_scores.score(&bad_peer), 0.0);
+    }
+
+    #[tokio::test]
+    async fn trusted_peers_are_redialed_with_a_backoff() {
+        let reachable_peer = PeerId::random();
+        let unreachable_peer = PeerId::random();
+        let mut peer_manager = build_peer_manager_with_trusted_peers(vec![
+            reachable_peer.clone(),
+            unreachable_peer.clone(),
+        ]);
+        peer_manager
+            .network_globals
+            .peers
+            .write()
+            .peer_info_mut(&reachable_peer)
+            .expect("trusted peer should be known")
+            .listening_addresses = vec!["/ip4/127.0.0.1/tcp/9000".parse().unwrap()];
+
+        let dialed = |peer_manager: &PeerManager<E>, peer: &PeerId| {
+            peer_manager.events.iter().any(|event| match event {
+                PeerManagerEvent::Dial(peer_id) => peer_id == peer,
+                _ => false,
+            })
+        };
+
+        // Only the peer with an address is dialed.
+        peer_manager.events.clear();
+        peer_manager.heartbeat();
+        assert!(dialed(&peer_manager, &reachable_peer));
+        assert!(!dialed(&peer_manager, &unreachable_peer));
+
+        // The dial failed, so the peer is not dialed again until its backoff has elapsed.
+        peer_manager.events.clear();
+        peer_manager.heartbeat();
+        assert!(!dialed(&peer_manager, &reachable_peer));
+
+        peer_manager
+            .trusted_peer_dials
+            .get_mut(&reachable_peer)
+            .expect("should track the dial")
+            .1 = Instant::now();
+        peer_manager.heartbeat();
+        assert!(dialed(&peer_manager, &reachable_peer));
+        assert_eq!(peer_manager.trusted_peer_dials[&reachable_peer].0, 2);
+
+        // Connecting resets the backoff.
+        peer_manager.connect_ingoing(&reachable_peer);
+        assert!(!peer_manager
+            .trusted_peer_dials
+            .contains_key(&reachable_peer));
+    }
+
+    #[tokio::test]
+    async fn peer_records_include_gossip_scores() {
+        let mut peer_manager = build_peer_manager();
+        let peer_id = PeerId::random();
+        reject_blocks(&mut peer_manager, &peer_id, 2);
+        let score = peer_manager.score(&peer_id);
+
+        let records = peer_manager.peer_records();
+        assert_eq!(
+            records,
+            vec![PeerRecord {
+                peer_id: peer_id.clone(),
+                reputation: DEFAULT_REPUTATION,
+                ban_expiry: None,
+                gossip_score: score,
+            }]
+        );
+
+        let mut restored = build_peer_manager();
+        restored.restore_peer_records(records);
+        assert_eq!(restored.score(&peer_id), score);
+    }
+}
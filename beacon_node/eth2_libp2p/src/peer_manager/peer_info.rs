@@ -35,6 +35,8 @@ pub struct PeerInfo<T: EthSpec> {
     /// necessary.
     #[serde(skip)]
     pub min_ttl: Option<Instant>,
+    /// Trusted peers are never scored down, banned or disconnected to make room for other peers.
+    pub is_trusted: bool,
 }
 
 impl<TSpec: EthSpec> Default for PeerInfo<TSpec> {
@@ -48,11 +50,21 @@ impl<TSpec: EthSpec> Default for PeerInfo<TSpec> {
             sync_status: PeerSyncStatus::Unknown,
             meta_data: None,
             min_ttl: None,
+            is_trusted: false,
         }
     }
 }
 
 impl<T: EthSpec> PeerInfo<T> {
+    /// Creates the info of a peer given in the `--trusted-peers` list.
+    pub fn trusted() -> Self {
+        PeerInfo {
+            reputation: Rep::max_value(),
+            is_trusted: true,
+            ..Default::default()
+        }
+    }
+
     /// Returns if the peer is subscribed to a given `SubnetId`
     pub fn on_subnet(&self, subnet_id: SubnetId) -> bool {
         if let Some(meta_data) = &self.meta_data {
@@ -99,6 +111,8 @@ pub enum PeerConnectionStatus {
     Banned {
         /// moment when the peer was banned.
         since: Instant,
+        /// moment when the ban expires.
+        until: Instant,
     },
     /// We are currently dialing this peer.
     Dialing {
@@ -124,9 +138,13 @@ impl Serialize for PeerConnectionStatus {
                 s.serialize_field("since", &since.elapsed().as_secs())?;
                 s.end()
             }
-            Banned { since } => {
-                let mut s = serializer.serialize_struct_variant("", 2, "Banned", 1)?;
+            Banned { since, until } => {
+                let mut s = serializer.serialize_struct_variant("", 2, "Banned", 2)?;
                 s.serialize_field("since", &since.elapsed().as_secs())?;
+                s.serialize_field(
+                    "expires_in",
+                    &until.saturating_duration_since(Instant::now()).as_secs(),
+                )?;
                 s.end()
             }
             Dialing { since } => {
@@ -207,13 +225,22 @@ impl PeerConnectionStatus {
         };
     }
 
-    /// Modifies the status to Banned
-    pub fn ban(&mut self) {
+    /// Modifies the status to Banned until `until`.
+    pub fn ban(&mut self, until: Instant) {
         *self = Banned {
             since: Instant::now(),
+            until,
         };
     }
 
+    /// Returns true if the status is a ban which has expired by `now`.
+    pub fn ban_expired(&self, now: Instant) -> bool {
+        match self {
+            Banned { until, .. } => *until <= now,
+            _ => false,
+        }
+    }
+
     pub fn connections(&self) -> (u8, u8) {
         match self {
             Connected { n_in, n_out } => (*n_in, *n_out),
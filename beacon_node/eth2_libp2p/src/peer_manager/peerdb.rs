@@ -4,7 +4,7 @@ use crate::rpc::methods::MetaData;
 use crate::PeerId;
 use slog::{crit, debug, trace, warn};
 use std::collections::{hash_map::Entry, HashMap};
-use std::time::Instant;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use types::{EthSpec, SubnetId};
 
 /// A peer's reputation (perceived potential usefulness)
@@ -22,6 +22,22 @@ const MAX_DC_PEERS: usize = 30;
 /// The default starting reputation for an unknown peer.
 pub const DEFAULT_REPUTATION: Rep = 50;
 
+/// The time for which a peer is banned.
+pub const BAN_DURATION: Duration = Duration::from_secs(60 * 60);
+
+/// The reputation, gossip score and ban of a peer, in a form which can be persisted across
+/// restarts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerRecord {
+    pub peer_id: PeerId,
+    pub reputation: Rep,
+    /// The time at which the peer's ban expires, in seconds since the unix epoch. `None` if the
+    /// peer is not banned.
+    pub ban_expiry: Option<u64>,
+    /// The peer's gossip score, which is kept by the `PeerManager` rather than the `PeerDB`.
+    pub gossip_score: f64,
+}
+
 /// Storage of known peers, their reputation and information
 pub struct PeerDB<TSpec: EthSpec> {
     /// The collection of known connected peers, their status and reputation
@@ -54,11 +70,15 @@ impl RepChange {
 }
 
 impl<TSpec: EthSpec> PeerDB<TSpec> {
-    pub fn new(log: &slog::Logger) -> Self {
+    pub fn new(trusted_peers: Vec<PeerId>, log: &slog::Logger) -> Self {
+        let peers = trusted_peers
+            .into_iter()
+            .map(|peer_id| (peer_id, PeerInfo::trusted()))
+            .collect();
         Self {
             log: log.clone(),
             n_dc: 0,
-            peers: HashMap::new(),
+            peers,
         }
     }
 
@@ -114,6 +134,21 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
         }
     }
 
+    /// Returns true if the peer is in the `--trusted-peers` list.
+    pub fn is_trusted(&self, peer_id: &PeerId) -> bool {
+        self.peers
+            .get(peer_id)
+            .map_or(false, |info| info.is_trusted)
+    }
+
+    /// Gives the ids of all trusted peers.
+    pub fn trusted_peers(&self) -> impl Iterator<Item = &PeerId> {
+        self.peers
+            .iter()
+            .filter(|(_, info)| info.is_trusted)
+            .map(|(peer_id, _)| peer_id)
+    }
+
     /// Gives the ids of all known connected peers.
     pub fn connected_peers(&self) -> impl Iterator<Item = (&PeerId, &PeerInfo<TSpec>)> {
         self.peers
@@ -331,7 +366,7 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
         }
     }
 
-    /// Sets a peer as banned
+    /// Sets a peer as banned for `BAN_DURATION`. Trusted peers are never banned.
     pub fn ban(&mut self, peer_id: &PeerId) {
         let log_ref = &self.log;
         let info = self.peers.entry(peer_id.clone()).or_insert_with(|| {
@@ -339,10 +374,114 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
                     "peer_id" => peer_id.to_string());
             PeerInfo::default()
         });
+        if info.is_trusted {
+            debug!(log_ref, "Not banning trusted peer"; "peer_id" => peer_id.to_string());
+            return;
+        }
         if info.connection_status.is_disconnected() {
             self.n_dc = self.n_dc.saturating_sub(1);
         }
-        info.connection_status.ban();
+        info.connection_status.ban(Instant::now() + BAN_DURATION);
+    }
+
+    /// Lifts the bans which have expired, returning the ids of the unbanned peers.
+    ///
+    /// An unbanned peer is considered disconnected and starts again from the default reputation,
+    /// since it has served its ban.
+    pub fn unban_expired(&mut self) -> Vec<PeerId> {
+        let now = Instant::now();
+        let mut unbanned = Vec::new();
+
+        for (peer_id, info) in self.peers.iter_mut() {
+            if info.connection_status.ban_expired(now) {
+                info.connection_status.disconnect();
+                info.reputation = DEFAULT_REPUTATION;
+                self.n_dc += 1;
+                unbanned.push(peer_id.clone());
+            }
+        }
+
+        self.shrink_to_fit();
+        unbanned
+    }
+
+    /// Returns the records of the peers which should be remembered across restarts: all banned
+    /// peers and any untrusted peer whose reputation differs from the default. The gossip scores
+    /// of the records are zero, they are filled in by the `PeerManager`.
+    pub fn peer_records(&self) -> Vec<PeerRecord> {
+        let now = Instant::now();
+        let unix_now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_else(|_| Duration::from_secs(0));
+
+        self.peers
+            .iter()
+            .filter_map(|(peer_id, info)| {
+                let ban_expiry = match info.connection_status {
+                    PeerConnectionStatus::Banned { until, .. } => {
+                        Some((unix_now + until.saturating_duration_since(now)).as_secs())
+                    }
+                    _ => None,
+                };
+
+                if info.is_trusted
+                    || (ban_expiry.is_none() && info.reputation == DEFAULT_REPUTATION)
+                {
+                    return None;
+                }
+
+                Some(PeerRecord {
+                    peer_id: peer_id.clone(),
+                    reputation: info.reputation,
+                    ban_expiry,
+                    gossip_score: 0.0,
+                })
+            })
+            .collect()
+    }
+
+    /// Restores peers from records which were persisted by a previous run.
+    ///
+    /// Bans which have expired whilst we were offline are dropped, along with the reputation of
+    /// the peer. Peers which are already known (e.g., peers we are dialing) keep their connection
+    /// status, but take on the recorded reputation and ban. Trusted peers are left untouched.
+    pub fn restore_peer_records(&mut self, records: Vec<PeerRecord>) {
+        let now = Instant::now();
+        let unix_now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_else(|_| Duration::from_secs(0))
+            .as_secs();
+
+        for record in records {
+            let ban_until = match record.ban_expiry {
+                Some(expiry) if expiry > unix_now => {
+                    Some(now + Duration::from_secs(expiry - unix_now))
+                }
+                Some(_) => continue,
+                None => None,
+            };
+
+            let info = match self.peers.entry(record.peer_id) {
+                Entry::Vacant(entry) => {
+                    let mut info = PeerInfo::default();
+                    info.connection_status.disconnect();
+                    self.n_dc += 1;
+                    entry.insert(info)
+                }
+                Entry::Occupied(entry) if !entry.get().is_trusted => entry.into_mut(),
+                Entry::Occupied(_) => continue,
+            };
+
+            info.reputation = record.reputation;
+            if let Some(until) = ban_until {
+                if info.connection_status.is_disconnected() {
+                    self.n_dc = self.n_dc.saturating_sub(1);
+                }
+                info.connection_status.ban(until);
+            }
+        }
+
+        self.shrink_to_fit();
     }
 
     /// Add the meta data of a peer.
@@ -375,6 +514,8 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
 
     /// Adds to a peer's reputation by `change`. If the reputation exceeds Rep's
     /// upper (lower) bounds, it stays at the maximum (minimum) value.
+    ///
+    /// The reputation of trusted peers is never decreased.
     pub(super) fn add_reputation(&mut self, peer_id: &PeerId, change: RepChange) {
         let log_ref = &self.log;
         let info = match self.peers.entry(peer_id.clone()) {
@@ -386,6 +527,10 @@ impl<TSpec: EthSpec> PeerDB<TSpec> {
             Entry::Occupied(e) => e.into_mut(),
         };
 
+        if info.is_trusted && !change.is_good {
+            return;
+        }
+
         info.reputation = if change.is_good {
             info.reputation.saturating_add(change.diff)
         } else {
@@ -415,7 +560,7 @@ mod tests {
 
     fn get_db() -> PeerDB<M> {
         let log = build_log(slog::Level::Debug, false);
-        PeerDB::new(&log)
+        PeerDB::new(vec![], &log)
     }
 
     #[test]
@@ -578,4 +723,115 @@ mod tests {
         pdb.disconnect(&random_peer);
         assert_eq!(pdb.n_dc, pdb.disconnected_peers().count());
     }
+
+    #[test]
+    fn test_trusted_peers_are_not_penalised() {
+        let trusted_peer = PeerId::random();
+        let log = build_log(slog::Level::Debug, false);
+        let mut pdb: PeerDB<M> = PeerDB::new(vec![trusted_peer.clone()], &log);
+
+        pdb.connect_ingoing(&trusted_peer);
+        pdb.add_reputation(&trusted_peer, RepChange::worst());
+        assert_eq!(pdb.reputation(&trusted_peer), Rep::max_value());
+
+        pdb.ban(&trusted_peer);
+        assert!(!pdb.peer_banned(&trusted_peer));
+        assert!(pdb.is_trusted(&trusted_peer));
+    }
+
+    #[test]
+    fn test_expired_bans_are_lifted() {
+        let mut pdb = get_db();
+        let random_peer = PeerId::random();
+
+        pdb.connect_ingoing(&random_peer);
+        pdb.set_reputation(&random_peer, 0);
+        pdb.ban(&random_peer);
+        assert!(pdb.unban_expired().is_empty());
+        assert!(pdb.peer_banned(&random_peer));
+
+        let now = Instant::now();
+        pdb.peer_info_mut(&random_peer)
+            .unwrap()
+            .connection_status
+            .ban(now);
+        assert_eq!(pdb.unban_expired(), vec![random_peer.clone()]);
+        assert!(!pdb.peer_banned(&random_peer));
+        assert_eq!(pdb.reputation(&random_peer), DEFAULT_REPUTATION);
+        assert_eq!(pdb.n_dc, pdb.disconnected_peers().count());
+    }
+
+    #[test]
+    fn test_peer_records_round_trip() {
+        let mut pdb = get_db();
+
+        let banned_peer = PeerId::random();
+        let bad_peer = PeerId::random();
+        let default_peer = PeerId::random();
+        for peer in &[&banned_peer, &bad_peer, &default_peer] {
+            pdb.connect_ingoing(peer);
+        }
+        pdb.set_reputation(&banned_peer, 0);
+        pdb.ban(&banned_peer);
+        pdb.set_reputation(&bad_peer, 20);
+
+        let records = pdb.peer_records();
+        assert_eq!(records.len(), 2);
+
+        let mut restored = get_db();
+        restored.restore_peer_records(records);
+        assert!(restored.peer_banned(&banned_peer));
+        assert_eq!(restored.reputation(&banned_peer), 0);
+        assert_eq!(restored.reputation(&bad_peer), 20);
+        assert!(restored.peer_info(&default_peer).is_none());
+        assert_eq!(restored.n_dc, restored.disconnected_peers().count());
+
+        // Expired bans are forgotten.
+        let expired = PeerRecord {
+            peer_id: PeerId::random(),
+            reputation: 0,
+            ban_expiry: Some(0),
+            gossip_score: 0.0,
+        };
+        restored.restore_peer_records(vec![expired.clone()]);
+        assert!(restored.peer_info(&expired.peer_id).is_none());
+    }
+
+    #[test]
+    fn test_peer_records_are_merged_into_known_peers() {
+        let log = build_log(slog::Level::Debug, false);
+        let trusted_peer = PeerId::random();
+        let mut pdb: PeerDB<M> = PeerDB::new(vec![trusted_peer.clone()], &log);
+
+        let dialing_peer = PeerId::random();
+        let disconnected_peer = PeerId::random();
+        pdb.dialing_peer(&dialing_peer);
+        pdb.connect_ingoing(&disconnected_peer);
+        pdb.disconnect(&disconnected_peer);
+
+        let ban_expiry = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 3_600;
+        let records = [&trusted_peer, &dialing_peer, &disconnected_peer]
+            .iter()
+            .map(|peer_id| PeerRecord {
+                peer_id: (*peer_id).clone(),
+                reputation: 0,
+                ban_expiry: Some(ban_expiry),
+                gossip_score: 0.0,
+            })
+            .collect();
+        pdb.restore_peer_records(records);
+
+        assert!(pdb.peer_banned(&dialing_peer));
+        assert!(pdb.peer_banned(&disconnected_peer));
+        assert_eq!(pdb.reputation(&dialing_peer), 0);
+        assert_eq!(pdb.n_dc, pdb.disconnected_peers().count());
+
+        // Trusted peers are never banned.
+        assert!(!pdb.peer_banned(&trusted_peer));
+        assert_eq!(pdb.reputation(&trusted_peer), Rep::max_value());
+    }
 }
@@ -0,0 +1,337 @@
+//! Scores peers on the gossip messages they deliver, in the style of gossipsub v1.1 peer scoring.
+//!
+//! Each gossip topic has its own parameters. For every peer and topic we track:
+//!
+//! - The number of messages the peer was first to deliver to us which we found to be valid.
+//!   These raise the score, up to a cap.
+//! - The number of messages the peer delivered which we found to be invalid. These lower the
+//!   score by the square of the count, so that persistent misbehaviour is punished quickly.
+//!
+//! The counters decay each time the scores are refreshed, so that peers are gradually forgiven
+//! and cannot bank a high score. Scores persisted by a previous run are restored as a single value
+//! per peer, which decays in the same way. The combination of this score with the peer's
+//! reputation is computed by the `PeerManager`.
+
+use crate::types::GossipKind;
+use crate::PeerId;
+use std::collections::{HashMap, HashSet};
+
+/// The score parameters of a single gossip topic.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TopicScoreParams {
+    /// The weight of the topic's score in the peer's score.
+    pub topic_weight: f64,
+    /// The weight of each valid message the peer was first to deliver.
+    pub first_message_deliveries_weight: f64,
+    /// The factor by which the first message deliveries decay on each refresh.
+    pub first_message_deliveries_decay: f64,
+    /// The maximum number of first message deliveries which count towards the score.
+    pub first_message_deliveries_cap: f64,
+    /// The weight of the square of the invalid message deliveries. This should be negative.
+    pub invalid_message_deliveries_weight: f64,
+    /// The factor by which the invalid message deliveries decay on each refresh.
+    pub invalid_message_deliveries_decay: f64,
+}
+
+/// The parameters for scoring peers, with separate parameters for each `GossipKind`.
+///
+/// Each attestation subnet is a separate topic, but all subnets share the same parameters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeerScoreParams {
+    pub beacon_block: TopicScoreParams,
+    pub aggregate_and_proof: TopicScoreParams,
+    pub attestation_subnet: TopicScoreParams,
+    pub voluntary_exit: TopicScoreParams,
+    pub proposer_slashing: TopicScoreParams,
+    pub attester_slashing: TopicScoreParams,
+    /// The maximum contribution of all topics to a peer's score. It only limits positive scores.
+    pub topic_score_cap: f64,
+    /// The weight of the peer's reputation (relative to the default reputation) in its score.
+    pub app_specific_weight: f64,
+    /// The factor by which scores restored from a previous run decay on each refresh.
+    pub restored_score_decay: f64,
+    /// Counters which decay below this value are reset to zero.
+    pub decay_to_zero: f64,
+}
+
+impl Default for PeerScoreParams {
+    /// Parameters which assume the scores are refreshed on each peer manager heartbeat (every
+    /// 30 seconds).
+    fn default() -> Self {
+        let topic =
+            |topic_weight, first_message_deliveries_weight, first_message_deliveries_cap| {
+                TopicScoreParams {
+                    topic_weight,
+                    first_message_deliveries_weight,
+                    first_message_deliveries_decay: 0.9,
+                    first_message_deliveries_cap,
+                    invalid_message_deliveries_weight: -10.0,
+                    invalid_message_deliveries_decay: 0.97,
+                }
+            };
+
+        Self {
+            beacon_block: topic(0.5, 1.0, 20.0),
+            aggregate_and_proof: topic(0.5, 0.1, 100.0),
+            attestation_subnet: topic(0.05, 0.1, 100.0),
+            voluntary_exit: topic(0.05, 1.0, 5.0),
+            proposer_slashing: topic(0.05, 1.0, 5.0),
+            attester_slashing: topic(0.05, 1.0, 5.0),
+            topic_score_cap: 20.0,
+            app_specific_weight: 1.0,
+            restored_score_decay: 0.97,
+            decay_to_zero: 0.01,
+        }
+    }
+}
+
+impl PeerScoreParams {
+    pub fn topic_params(&self, kind: &GossipKind) -> &TopicScoreParams {
+        match kind {
+            GossipKind::BeaconBlock => &self.beacon_block,
+            GossipKind::BeaconAggregateAndProof => &self.aggregate_and_proof,
+            GossipKind::Attestation(_) => &self.attestation_subnet,
+            GossipKind::VoluntaryExit => &self.voluntary_exit,
+            GossipKind::ProposerSlashing => &self.proposer_slashing,
+            GossipKind::AttesterSlashing => &self.attester_slashing,
+        }
+    }
+}
+
+/// The scores below which we act against a peer. Trusted peers are exempt.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PeerScoreThresholds {
+    /// Gossip from peers below this score is ignored.
+    pub graylist_threshold: f64,
+    /// Peers below this score are banned and disconnected.
+    pub disconnect_threshold: f64,
+}
+
+impl Default for PeerScoreThresholds {
+    fn default() -> Self {
+        Self {
+            graylist_threshold: -50.0,
+            disconnect_threshold: -80.0,
+        }
+    }
+}
+
+/// The counters of a single peer for a single topic.
+#[derive(Debug, Clone, Copy, Default)]
+struct TopicStats {
+    first_message_deliveries: f64,
+    invalid_message_deliveries: f64,
+}
+
+impl TopicStats {
+    fn score(&self, params: &TopicScoreParams) -> f64 {
+        let first_message_deliveries = self
+            .first_message_deliveries
+            .min(params.first_message_deliveries_cap);
+        params.topic_weight
+            * (first_message_deliveries * params.first_message_deliveries_weight
+                + self.invalid_message_deliveries.powi(2)
+                    * params.invalid_message_deliveries_weight)
+    }
+}
+
+/// The gossip scores of all peers which have recently delivered gossip to us.
+pub struct PeerScore {
+    params: PeerScoreParams,
+    peers: HashMap<PeerId, HashMap<GossipKind, TopicStats>>,
+    /// The scores of peers as they were when a previous run persisted them.
+    restored_scores: HashMap<PeerId, f64>,
+}
+
+impl PeerScore {
+    pub fn new(params: PeerScoreParams) -> Self {
+        Self {
+            params,
+            peers: HashMap::new(),
+            restored_scores: HashMap::new(),
+        }
+    }
+
+    pub fn params(&self) -> &PeerScoreParams {
+        &self.params
+    }
+
+    /// Returns the gossip score of `peer_id`, which is zero for unknown peers.
+    pub fn score(&self, peer_id: &PeerId) -> f64 {
+        let topics_score: f64 = self.peers.get(peer_id).map_or(0.0, |topics| {
+            topics
+                .iter()
+                .map(|(kind, stats)| stats.score(self.params.topic_params(kind)))
+                .sum()
+        });
+        let restored_score = self.restored_scores.get(peer_id).cloned().unwrap_or(0.0);
+
+        (topics_score + restored_score).min(self.params.topic_score_cap)
+    }
+
+    /// Returns the peers with a non-zero score, along with their scores.
+    pub fn scores(&self) -> Vec<(PeerId, f64)> {
+        self.peers
+            .keys()
+            .chain(self.restored_scores.keys())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .map(|peer_id| (peer_id.clone(), self.score(peer_id)))
+            .filter(|(_, score)| *score != 0.0)
+            .collect()
+    }
+
+    /// Restores the score of `peer_id` which was persisted by a previous run.
+    pub fn restore_score(&mut self, peer_id: PeerId, score: f64) {
+        self.restored_scores.insert(peer_id, score);
+    }
+
+    /// Records a valid message on `kind` which `peer_id` was the first to deliver.
+    pub fn first_message_delivered(&mut self, peer_id: &PeerId, kind: &GossipKind) {
+        let cap = self.params.topic_params(kind).first_message_deliveries_cap;
+        let stats = self.stats_mut(peer_id, kind);
+        stats.first_message_deliveries = (stats.first_message_deliveries + 1.0).min(cap);
+    }
+
+    /// Records an invalid message on `kind` delivered by `peer_id`.
+    pub fn invalid_message_delivered(&mut self, peer_id: &PeerId, kind: &GossipKind) {
+        self.stats_mut(peer_id, kind).invalid_message_deliveries += 1.0;
+    }
+
+    /// Forgets the scores of `peer_id`.
+    pub fn remove_peer(&mut self, peer_id: &PeerId) {
+        self.peers.remove(peer_id);
+        self.restored_scores.remove(peer_id);
+    }
+
+    /// Decays all counters, forgetting any which have decayed to zero.
+    pub fn refresh(&mut self) {
+        let params = &self.params;
+        let decay = |value: f64, factor: f64| {
+            let decayed = value * factor;
+            if decayed < params.decay_to_zero {
+                0.0
+            } else {
+                decayed
+            }
+        };
+
+        self.peers.retain(|_, topics| {
+            topics.retain(|kind, stats| {
+                let topic_params = params.topic_params(kind);
+                stats.first_message_deliveries = decay(
+                    stats.first_message_deliveries,
+                    topic_params.first_message_deliveries_decay,
+                );
+                stats.invalid_message_deliveries = decay(
+                    stats.invalid_message_deliveries,
+                    topic_params.invalid_message_deliveries_decay,
+                );
+                stats.first_message_deliveries > 0.0 || stats.invalid_message_deliveries > 0.0
+            });
+            !topics.is_empty()
+        });
+
+        self.restored_scores.retain(|_, score| {
+            *score *= params.restored_score_decay;
+            score.abs() >= params.decay_to_zero
+        });
+    }
+
+    fn stats_mut(&mut self, peer_id: &PeerId, kind: &GossipKind) -> &mut TopicStats {
+        self.peers
+            .entry(peer_id.clone())
+            .or_default()
+            .entry(kind.clone())
+            .or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::SubnetId;
+
+    fn assert_score(score: &PeerScore, peer_id: &PeerId, expected: f64) {
+        let actual = score.score(peer_id);
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "score {} should be {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn invalid_messages_outweigh_valid_messages() {
+        let mut score = PeerScore::new(PeerScoreParams::default());
+        let peer_id = PeerId::random();
+        let kind = GossipKind::BeaconBlock;
+
+        for _ in 0..100 {
+            score.first_message_delivered(&peer_id, &kind);
+        }
+        // The first message deliveries are capped.
+        assert_score(&score, &peer_id, 10.0);
+
+        for _ in 0..3 {
+            score.invalid_message_delivered(&peer_id, &kind);
+        }
+        assert_score(&score, &peer_id, 10.0 - 45.0);
+    }
+
+    #[test]
+    fn positive_scores_are_capped() {
+        let mut score = PeerScore::new(PeerScoreParams::default());
+        let peer_id = PeerId::random();
+
+        for subnet in 0..64 {
+            for _ in 0..100 {
+                score.first_message_delivered(
+                    &peer_id,
+                    &GossipKind::Attestation(SubnetId::new(subnet)),
+                );
+            }
+        }
+        score.first_message_delivered(&peer_id, &GossipKind::BeaconBlock);
+
+        assert_score(&score, &peer_id, score.params().topic_score_cap);
+    }
+
+    #[test]
+    fn counters_decay_to_zero() {
+        let mut score = PeerScore::new(PeerScoreParams::default());
+        let peer_id = PeerId::random();
+
+        score.invalid_message_delivered(&peer_id, &GossipKind::VoluntaryExit);
+        score.refresh();
+        assert!(score.score(&peer_id) < 0.0);
+        assert!(score.score(&peer_id) > -0.5);
+
+        for _ in 0..200 {
+            score.refresh();
+        }
+        assert_score(&score, &peer_id, 0.0);
+        assert!(score.peers.is_empty());
+    }
+
+    #[test]
+    fn restored_scores_decay_to_zero() {
+        let mut score = PeerScore::new(PeerScoreParams::default());
+        let peer_id = PeerId::random();
+
+        score.restore_score(peer_id.clone(), -60.0);
+        score.invalid_message_delivered(&peer_id, &GossipKind::BeaconBlock);
+        assert_score(&score, &peer_id, -65.0);
+        assert_eq!(score.scores(), vec![(peer_id.clone(), -65.0)]);
+
+        score.refresh();
+        assert!(score.score(&peer_id) > -65.0);
+
+        for _ in 0..300 {
+            score.refresh();
+        }
+        assert_score(&score, &peer_id, 0.0);
+        assert!(score.scores().is_empty());
+    }
+}
@@ -386,6 +386,7 @@ where
         match response {
             RPCCodedResponse::InvalidRequest(ref reason)
             | RPCCodedResponse::ServerError(ref reason)
+            | RPCCodedResponse::RateLimited(ref reason)
             | RPCCodedResponse::Unknown(ref reason) => {
                 let code = &response
                     .error_code()
@@ -1062,6 +1063,7 @@ where
                             RPCCodedResponse::Success(resp) => Ok(RPCReceived::Response(id, resp)),
                             RPCCodedResponse::InvalidRequest(ref r)
                             | RPCCodedResponse::ServerError(ref r)
+                            | RPCCodedResponse::RateLimited(ref r)
                             | RPCCodedResponse::Unknown(ref r) => {
                                 let code = response.error_code().expect(
                                     "Response indicating and error should map to an error code",
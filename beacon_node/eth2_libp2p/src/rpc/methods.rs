@@ -239,6 +239,9 @@ pub enum RPCCodedResponse<T: EthSpec> {
     /// The response indicates a server error.
     ServerError(ErrorType),
 
+    /// The request exceeded the rate limit of the server.
+    RateLimited(ErrorType),
+
     /// There was an unknown response.
     Unknown(ErrorType),
 
@@ -251,6 +254,7 @@ pub enum RPCCodedResponse<T: EthSpec> {
 pub enum RPCResponseErrorCode {
     InvalidRequest,
     ServerError,
+    RateLimited,
     Unknown,
 }
 
@@ -261,6 +265,7 @@ impl<T: EthSpec> RPCCodedResponse<T> {
             RPCCodedResponse::Success(_) => Some(0),
             RPCCodedResponse::InvalidRequest(_) => Some(1),
             RPCCodedResponse::ServerError(_) => Some(2),
+            RPCCodedResponse::RateLimited(_) => Some(139),
             RPCCodedResponse::Unknown(_) => Some(255),
             RPCCodedResponse::StreamTermination(_) => None,
         }
@@ -279,6 +284,7 @@ impl<T: EthSpec> RPCCodedResponse<T> {
         match response_code {
             1 => RPCCodedResponse::InvalidRequest(err.into()),
             2 => RPCCodedResponse::ServerError(err.into()),
+            139 => RPCCodedResponse::RateLimited(err.into()),
             _ => RPCCodedResponse::Unknown(err.into()),
         }
     }
@@ -288,6 +294,7 @@ impl<T: EthSpec> RPCCodedResponse<T> {
         match response_code {
             RPCResponseErrorCode::InvalidRequest => RPCCodedResponse::InvalidRequest(err.into()),
             RPCResponseErrorCode::ServerError => RPCCodedResponse::ServerError(err.into()),
+            RPCResponseErrorCode::RateLimited => RPCCodedResponse::RateLimited(err.into()),
             RPCResponseErrorCode::Unknown => RPCCodedResponse::Unknown(err.into()),
         }
     }
@@ -304,6 +311,7 @@ impl<T: EthSpec> RPCCodedResponse<T> {
             },
             RPCCodedResponse::InvalidRequest(_) => true,
             RPCCodedResponse::ServerError(_) => true,
+            RPCCodedResponse::RateLimited(_) => true,
             RPCCodedResponse::Unknown(_) => true,
             // Stream terminations are part of responses that have chunks
             RPCCodedResponse::StreamTermination(_) => true,
@@ -325,6 +333,7 @@ impl<T: EthSpec> RPCCodedResponse<T> {
             RPCCodedResponse::StreamTermination(_) => None,
             RPCCodedResponse::InvalidRequest(_) => Some(RPCResponseErrorCode::InvalidRequest),
             RPCCodedResponse::ServerError(_) => Some(RPCResponseErrorCode::ServerError),
+            RPCCodedResponse::RateLimited(_) => Some(RPCResponseErrorCode::RateLimited),
             RPCCodedResponse::Unknown(_) => Some(RPCResponseErrorCode::Unknown),
         }
     }
@@ -335,6 +344,7 @@ impl std::fmt::Display for RPCResponseErrorCode {
         let repr = match self {
             RPCResponseErrorCode::InvalidRequest => "The request was invalid",
             RPCResponseErrorCode::ServerError => "Server error occurred",
+            RPCResponseErrorCode::RateLimited => "Rate limited",
             RPCResponseErrorCode::Unknown => "Unknown error occurred",
         };
         f.write_str(repr)
@@ -369,6 +379,7 @@ impl<T: EthSpec> std::fmt::Display for RPCCodedResponse<T> {
             RPCCodedResponse::Success(res) => write!(f, "{}", res),
             RPCCodedResponse::InvalidRequest(err) => write!(f, "Invalid Request: {:?}", err),
             RPCCodedResponse::ServerError(err) => write!(f, "Server Error: {:?}", err),
+            RPCCodedResponse::RateLimited(err) => write!(f, "Rate Limited: {:?}", err),
             RPCCodedResponse::Unknown(err) => write!(f, "Unknown Error: {:?}", err),
             RPCCodedResponse::StreamTermination(_) => write!(f, "Stream Termination"),
         }
@@ -4,6 +4,7 @@
 //! direct peer-to-peer communication primarily for sending/receiving chain information for
 //! syncing.
 
+use crate::metrics;
 use handler::RPCHandler;
 use libp2p::core::{connection::ConnectionId, ConnectedPoint};
 use libp2p::swarm::{
@@ -11,10 +12,13 @@ use libp2p::swarm::{
     PollParameters, SubstreamProtocol,
 };
 use libp2p::{Multiaddr, PeerId};
-use slog::{debug, o};
+use rate_limiter::{RPCRateLimiter, RateLimitedErr};
+use slog::{crit, debug, o};
+use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::task::{Context, Poll};
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tokio::time::delay_queue::{DelayQueue, Key};
 use types::EthSpec;
 
 pub(crate) use handler::HandlerErr;
@@ -27,11 +31,17 @@ pub use methods::{
     RPCResponseErrorCode, RequestId, ResponseTermination, StatusMessage, MAX_REQUEST_BLOCKS,
 };
 pub use protocol::{Protocol, RPCError};
+pub use rate_limiter::{ProtocolQuota, Quota, RateLimiterConfig};
 
 pub(crate) mod codec;
 mod handler;
 pub mod methods;
 mod protocol;
+mod rate_limiter;
+
+/// The interval at which the buckets of peers which have stopped making requests are removed from
+/// the rate limiters.
+const RATE_LIMITER_PRUNE_INTERVAL: Duration = Duration::from_secs(30);
 
 /// RPC events sent from Lighthouse.
 #[derive(Debug, Clone)]
@@ -91,15 +101,37 @@ pub struct RPCMessage<TSpec: EthSpec> {
 pub struct RPC<TSpec: EthSpec> {
     /// Queue of events to be processed.
     events: Vec<NetworkBehaviourAction<RPCSend<TSpec>, RPCMessage<TSpec>>>,
+    /// Limits the rate of the requests made to us by each peer.
+    inbound_limiter: RPCRateLimiter,
+    /// Limits the rate of our own requests to each peer, so that we stay within the quotas which
+    /// peers enforce on us.
+    self_limiter: RPCRateLimiter,
+    /// Our requests which were over the `self_limiter` quotas, waiting to be sent.
+    ///
+    /// Each request is stored with a unique number, which identifies it in `delayed_request_keys`.
+    delayed_requests: DelayQueue<(PeerId, u64, RequestId, RPCRequest<TSpec>)>,
+    /// The keys of each peer's entries in `delayed_requests`, so that they can be dropped when the
+    /// peer disconnects.
+    delayed_request_keys: HashMap<PeerId, HashMap<u64, Key>>,
+    /// The number given to the next delayed request.
+    next_delayed_request: u64,
+    /// The last time the buckets of the rate limiters were pruned.
+    last_prune: Instant,
     /// Slog logger for RPC behaviour.
     log: slog::Logger,
 }
 
 impl<TSpec: EthSpec> RPC<TSpec> {
-    pub fn new(log: slog::Logger) -> Self {
+    pub fn new(rate_limiter_config: RateLimiterConfig, log: slog::Logger) -> Self {
         let log = log.new(o!("service" => "libp2p_rpc"));
         RPC {
             events: Vec::new(),
+            inbound_limiter: RPCRateLimiter::new(rate_limiter_config.clone()),
+            self_limiter: RPCRateLimiter::new(rate_limiter_config),
+            delayed_requests: DelayQueue::new(),
+            delayed_request_keys: HashMap::new(),
+            next_delayed_request: 0,
+            last_prune: Instant::now(),
             log,
         }
     }
@@ -122,18 +154,46 @@ impl<TSpec: EthSpec> RPC<TSpec> {
 
     /// Submits an RPC request.
     ///
-    /// The peer must be connected for this to succeed.
+    /// Requests which exceed the quota the peer is expected to enforce are delayed until they are
+    /// within it. The peer must be connected for this to succeed.
     pub fn send_request(
         &mut self,
         peer_id: PeerId,
         request_id: RequestId,
         event: RPCRequest<TSpec>,
     ) {
-        self.events.push(NetworkBehaviourAction::NotifyHandler {
-            peer_id,
-            handler: NotifyHandler::Any,
-            event: RPCSend::Request(request_id, event),
-        });
+        match self.self_limiter.allows(&peer_id, &event) {
+            Ok(()) => self.events.push(NetworkBehaviourAction::NotifyHandler {
+                peer_id,
+                handler: NotifyHandler::Any,
+                event: RPCSend::Request(request_id, event),
+            }),
+            Err(RateLimitedErr::TooSoon(wait_time)) => {
+                debug!(self.log, "Delaying request to stay within the peer's rate limit";
+                    "peer_id" => peer_id.to_string(), "request" => event.to_string(),
+                    "wait_time_ms" => wait_time.as_millis());
+                let number = self.next_delayed_request;
+                self.next_delayed_request += 1;
+                let key = self
+                    .delayed_requests
+                    .insert((peer_id.clone(), number, request_id, event), wait_time);
+                self.delayed_request_keys
+                    .entry(peer_id)
+                    .or_default()
+                    .insert(number, key);
+            }
+            Err(RateLimitedErr::TooLarge) => {
+                // Our quotas allow every request we make, so this is a bug. Send the request
+                // anyway and let the peer decide.
+                crit!(self.log, "Request exceeds the rate limit quota";
+                    "peer_id" => peer_id.to_string(), "request" => event.to_string());
+                self.events.push(NetworkBehaviourAction::NotifyHandler {
+                    peer_id,
+                    handler: NotifyHandler::Any,
+                    event: RPCSend::Request(request_id, event),
+                });
+            }
+        }
     }
 }
 
@@ -163,15 +223,36 @@ where
     fn inject_connected(&mut self, peer_id: &PeerId) {
         // find the peer's meta-data
         debug!(self.log, "Requesting new peer's metadata"; "peer_id" => format!("{}",peer_id));
-        let rpc_event = RPCSend::Request(RequestId::Behaviour, RPCRequest::MetaData(PhantomData));
-        self.events.push(NetworkBehaviourAction::NotifyHandler {
-            peer_id: peer_id.clone(),
-            handler: NotifyHandler::Any,
-            event: rpc_event,
-        });
+        self.send_request(
+            peer_id.clone(),
+            RequestId::Behaviour,
+            RPCRequest::MetaData(PhantomData),
+        );
     }
 
-    fn inject_disconnected(&mut self, _peer_id: &PeerId) {}
+    fn inject_disconnected(&mut self, peer_id: &PeerId) {
+        // Fail the requests which were waiting for the peer's rate limit, as they can no longer be
+        // sent. The connection they are reported on no longer exists, so its id is meaningless.
+        let keys = self
+            .delayed_request_keys
+            .remove(peer_id)
+            .unwrap_or_default();
+        for key in keys.values() {
+            let (peer_id, _, id, request) = self.delayed_requests.remove(key).into_inner();
+            debug!(self.log, "Dropping delayed request to disconnected peer";
+                "peer_id" => peer_id.to_string(), "request" => request.to_string());
+            self.events
+                .push(NetworkBehaviourAction::GenerateEvent(RPCMessage {
+                    peer_id,
+                    conn_id: ConnectionId::new(0),
+                    event: Err(HandlerErr::Outbound {
+                        id,
+                        proto: request.protocol(),
+                        error: RPCError::HandlerRejected,
+                    }),
+                }));
+        }
+    }
 
     fn inject_connection_established(
         &mut self,
@@ -195,6 +276,28 @@ where
         conn_id: ConnectionId,
         event: <Self::ProtocolsHandler as ProtocolsHandler>::OutEvent,
     ) {
+        match event {
+            // Goodbye has no response stream to answer with an error, so it is never limited.
+            Ok(RPCReceived::Request(ref id, ref req)) if req.protocol() != Protocol::Goodbye => {
+                if let Err(e) = self.inbound_limiter.allows(&peer_id, req) {
+                    // Answer the request with an error instead of sending it to the user. The
+                    // handler reports the error response, so that the peer manager can score the
+                    // peer.
+                    debug!(self.log, "Request exceeds the peer's rate limit";
+                        "peer_id" => peer_id.to_string(), "request" => req.to_string(),
+                        "error" => format!("{:?}", e));
+                    metrics::inc_counter(&metrics::RPC_REQUESTS_RATE_LIMITED);
+                    self.send_response(
+                        peer_id,
+                        (conn_id, *id),
+                        RPCCodedResponse::RateLimited("Rate limited".into()),
+                    );
+                    return;
+                }
+            }
+            _ => {}
+        }
+
         // send the event to the user
         self.events
             .push(NetworkBehaviourAction::GenerateEvent(RPCMessage {
@@ -206,7 +309,7 @@ where
 
     fn poll(
         &mut self,
-        _cx: &mut Context,
+        cx: &mut Context,
         _: &mut impl PollParameters,
     ) -> Poll<
         NetworkBehaviourAction<
@@ -214,9 +317,90 @@ where
             Self::OutEvent,
         >,
     > {
+        if self.last_prune.elapsed() >= RATE_LIMITER_PRUNE_INTERVAL {
+            self.inbound_limiter.prune();
+            self.self_limiter.prune();
+            self.last_prune = Instant::now();
+        }
+
+        // Send the delayed requests which are due, delaying them again if the quota has been
+        // consumed in the meantime.
+        while let Poll::Ready(Some(Ok(expired))) = self.delayed_requests.poll_expired(cx) {
+            let (peer_id, number, request_id, request) = expired.into_inner();
+            if let Some(keys) = self.delayed_request_keys.get_mut(&peer_id) {
+                keys.remove(&number);
+                if keys.is_empty() {
+                    self.delayed_request_keys.remove(&peer_id);
+                }
+            }
+            self.send_request(peer_id, request_id, request);
+        }
+
         if !self.events.is_empty() {
             return Poll::Ready(self.events.remove(0));
         }
         Poll::Pending
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use methods::Ping;
+    use types::MinimalEthSpec;
+
+    fn rpc() -> RPC<MinimalEthSpec> {
+        RPC::new(
+            RateLimiterConfig::default(),
+            slog::Logger::root(slog::Discard, o!()),
+        )
+    }
+
+    #[tokio::test]
+    async fn disconnect_fails_delayed_requests() {
+        let mut rpc = rpc();
+        let peer_id = PeerId::random();
+        let other_peer_id = PeerId::random();
+
+        // The default quota allows two pings, so the third of each peer's pings is delayed.
+        for id in 0..3 {
+            rpc.send_request(
+                peer_id.clone(),
+                RequestId::Sync(id),
+                RPCRequest::Ping(Ping { data: 0 }),
+            );
+            rpc.send_request(
+                other_peer_id.clone(),
+                RequestId::Sync(id),
+                RPCRequest::Ping(Ping { data: 0 }),
+            );
+        }
+        assert_eq!(rpc.events.len(), 4);
+        assert_eq!(rpc.delayed_request_keys.len(), 2);
+        rpc.events.clear();
+
+        rpc.inject_disconnected(&peer_id);
+
+        // Only the disconnected peer's request is failed.
+        assert_eq!(rpc.delayed_request_keys.len(), 1);
+        assert!(rpc.delayed_request_keys.contains_key(&other_peer_id));
+        assert_eq!(rpc.events.len(), 1);
+        match rpc.events.remove(0) {
+            NetworkBehaviourAction::GenerateEvent(RPCMessage {
+                peer_id: failed_peer_id,
+                event:
+                    Err(HandlerErr::Outbound {
+                        id: RequestId::Sync(2),
+                        proto: Protocol::Ping,
+                        error: RPCError::HandlerRejected,
+                    }),
+                ..
+            }) => assert_eq!(failed_peer_id, peer_id),
+            _ => panic!("expected the delayed request to fail"),
+        }
+
+        // Disconnecting again has no effect.
+        rpc.inject_disconnected(&peer_id);
+        assert!(rpc.events.is_empty());
+    }
+}
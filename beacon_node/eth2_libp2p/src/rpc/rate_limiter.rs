@@ -0,0 +1,343 @@
+//! Token bucket rate limiting of RPC requests, per peer and per protocol.
+//!
+//! Each protocol has a quota on the number of requests a peer may make. `BlocksByRange` and
+//! `BlocksByRoot` additionally have a quota on the number of blocks a peer may request, since the
+//! cost of serving them is proportional to the number of blocks read from the database.
+
+use super::protocol::{Protocol, RPCRequest};
+use libp2p::PeerId;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use types::EthSpec;
+
+/// The rate at which a token bucket is filled, expressed as the number of tokens it may hold and
+/// the time taken to refill an empty bucket.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quota {
+    /// The maximum number of tokens, i.e., the largest burst which is allowed.
+    pub max_tokens: u64,
+    /// The time taken for an empty bucket to become full.
+    pub replenish_all_every: Duration,
+}
+
+impl Quota {
+    pub const fn n_every(max_tokens: u64, seconds: u64) -> Self {
+        Self {
+            max_tokens,
+            replenish_all_every: Duration::from_secs(seconds),
+        }
+    }
+
+    /// The number of tokens added to the bucket per second.
+    fn tokens_per_second(&self) -> f64 {
+        self.max_tokens as f64 / self.replenish_all_every.as_secs_f64()
+    }
+}
+
+/// The quotas which apply to requests for a single protocol.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProtocolQuota {
+    /// The quota on the number of requests.
+    pub requests: Quota,
+    /// The quota on the number of blocks requested, for protocols which return blocks.
+    pub blocks: Option<Quota>,
+}
+
+/// The quotas for every RPC protocol.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimiterConfig {
+    pub ping: ProtocolQuota,
+    pub meta_data: ProtocolQuota,
+    pub status: ProtocolQuota,
+    pub goodbye: ProtocolQuota,
+    pub blocks_by_range: ProtocolQuota,
+    pub blocks_by_root: ProtocolQuota,
+}
+
+impl Default for RateLimiterConfig {
+    fn default() -> Self {
+        let requests_only = |requests| ProtocolQuota {
+            requests,
+            blocks: None,
+        };
+
+        Self {
+            ping: requests_only(Quota::n_every(2, 10)),
+            meta_data: requests_only(Quota::n_every(2, 5)),
+            status: requests_only(Quota::n_every(5, 15)),
+            goodbye: requests_only(Quota::n_every(1, 10)),
+            // Requests for more blocks than a quota's `max_tokens` cost the whole bucket.
+            blocks_by_range: ProtocolQuota {
+                requests: Quota::n_every(16, 10),
+                blocks: Some(Quota::n_every(1_024, 10)),
+            },
+            blocks_by_root: ProtocolQuota {
+                requests: Quota::n_every(16, 10),
+                blocks: Some(Quota::n_every(128, 10)),
+            },
+        }
+    }
+}
+
+impl RateLimiterConfig {
+    fn quota(&self, protocol: Protocol) -> &ProtocolQuota {
+        match protocol {
+            Protocol::Ping => &self.ping,
+            Protocol::MetaData => &self.meta_data,
+            Protocol::Status => &self.status,
+            Protocol::Goodbye => &self.goodbye,
+            Protocol::BlocksByRange => &self.blocks_by_range,
+            Protocol::BlocksByRoot => &self.blocks_by_root,
+        }
+    }
+}
+
+/// The reason a request was not allowed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RateLimitedErr {
+    /// The request can never be allowed, since the quota permits no requests.
+    TooLarge,
+    /// The request would be allowed after waiting for the given duration.
+    TooSoon(Duration),
+}
+
+/// A bucket of tokens which is refilled at a constant rate, up to the `max_tokens` of its quota.
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    last_update: Instant,
+}
+
+impl Bucket {
+    fn full(quota: &Quota, now: Instant) -> Self {
+        Self {
+            tokens: quota.max_tokens as f64,
+            last_update: now,
+        }
+    }
+
+    fn refill(&mut self, quota: &Quota, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.last_update)
+            .as_secs_f64();
+        self.tokens =
+            (self.tokens + elapsed * quota.tokens_per_second()).min(quota.max_tokens as f64);
+        self.last_update = now;
+    }
+
+    fn is_full(&self, quota: &Quota) -> bool {
+        self.tokens >= quota.max_tokens as f64
+    }
+
+    /// Returns the time until `tokens` tokens are available, which is zero if they are available
+    /// now.
+    fn wait_for(&self, quota: &Quota, tokens: u64) -> Duration {
+        let missing = tokens as f64 - self.tokens;
+        if missing > 0.0 {
+            Duration::from_secs_f64(missing / quota.tokens_per_second())
+        } else {
+            Duration::from_secs(0)
+        }
+    }
+}
+
+/// The buckets of a single peer for a single protocol.
+struct PeerBuckets {
+    requests: Bucket,
+    blocks: Option<Bucket>,
+}
+
+/// Limits the rate of RPC requests of each peer, with separate quotas for each protocol.
+pub struct RPCRateLimiter {
+    config: RateLimiterConfig,
+    buckets: HashMap<(PeerId, Protocol), PeerBuckets>,
+}
+
+impl RPCRateLimiter {
+    pub fn new(config: RateLimiterConfig) -> Self {
+        Self {
+            config,
+            buckets: HashMap::new(),
+        }
+    }
+
+    /// Consumes the tokens required by `request` from the buckets of `peer_id`, if they are all
+    /// available. No tokens are consumed if the request is not allowed.
+    pub fn allows<T: EthSpec>(
+        &mut self,
+        peer_id: &PeerId,
+        request: &RPCRequest<T>,
+    ) -> Result<(), RateLimitedErr> {
+        self.allows_at(peer_id, request, Instant::now())
+    }
+
+    fn allows_at<T: EthSpec>(
+        &mut self,
+        peer_id: &PeerId,
+        request: &RPCRequest<T>,
+        now: Instant,
+    ) -> Result<(), RateLimitedErr> {
+        let protocol = request.protocol();
+        let quota = self.config.quota(protocol);
+        // A request for more blocks than the bucket holds costs the whole bucket, since the spec
+        // permits requests of up to `MAX_REQUEST_BLOCKS` regardless of the configured quota.
+        let blocks = quota.blocks.map_or(0, |blocks_quota| {
+            std::cmp::min(request.expected_responses() as u64, blocks_quota.max_tokens)
+        });
+
+        if quota.requests.max_tokens < 1 {
+            return Err(RateLimitedErr::TooLarge);
+        }
+
+        let buckets = self
+            .buckets
+            .entry((peer_id.clone(), protocol))
+            .or_insert_with(|| PeerBuckets {
+                requests: Bucket::full(&quota.requests, now),
+                blocks: quota
+                    .blocks
+                    .map(|blocks_quota| Bucket::full(&blocks_quota, now)),
+            });
+
+        buckets.requests.refill(&quota.requests, now);
+        let mut wait = buckets.requests.wait_for(&quota.requests, 1);
+
+        if let (Some(bucket), Some(blocks_quota)) = (&mut buckets.blocks, &quota.blocks) {
+            bucket.refill(blocks_quota, now);
+            wait = std::cmp::max(wait, bucket.wait_for(blocks_quota, blocks));
+        }
+
+        if wait > Duration::from_secs(0) {
+            return Err(RateLimitedErr::TooSoon(wait));
+        }
+
+        buckets.requests.tokens -= 1.0;
+        if let Some(bucket) = &mut buckets.blocks {
+            bucket.tokens -= blocks as f64;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the buckets which have refilled completely, since they are equivalent to the
+    /// buckets of a peer which has made no requests.
+    pub fn prune(&mut self) {
+        let now = Instant::now();
+        let config = &self.config;
+
+        self.buckets.retain(|(_, protocol), buckets| {
+            let quota = config.quota(*protocol);
+            buckets.requests.refill(&quota.requests, now);
+            let blocks_full = match (&mut buckets.blocks, &quota.blocks) {
+                (Some(bucket), Some(blocks_quota)) => {
+                    bucket.refill(blocks_quota, now);
+                    bucket.is_full(blocks_quota)
+                }
+                _ => true,
+            };
+
+            !(buckets.requests.is_full(&quota.requests) && blocks_full)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::methods::{BlocksByRangeRequest, Ping};
+    use types::MinimalEthSpec;
+
+    type Request = RPCRequest<MinimalEthSpec>;
+
+    fn blocks_by_range(count: u64) -> Request {
+        RPCRequest::BlocksByRange(BlocksByRangeRequest {
+            start_slot: 0,
+            count,
+            step: 1,
+        })
+    }
+
+    #[test]
+    fn request_quota_refills_over_time() {
+        let mut limiter = RPCRateLimiter::new(RateLimiterConfig::default());
+        let peer_id = PeerId::random();
+        let ping = Request::Ping(Ping { data: 0 });
+        let now = Instant::now();
+
+        assert_eq!(limiter.allows_at(&peer_id, &ping, now), Ok(()));
+        assert_eq!(limiter.allows_at(&peer_id, &ping, now), Ok(()));
+        assert_eq!(
+            limiter.allows_at(&peer_id, &ping, now),
+            Err(RateLimitedErr::TooSoon(Duration::from_secs(5)))
+        );
+
+        // Other peers have their own buckets.
+        assert_eq!(limiter.allows_at(&PeerId::random(), &ping, now), Ok(()));
+
+        let later = now + Duration::from_secs(5);
+        assert_eq!(limiter.allows_at(&peer_id, &ping, later), Ok(()));
+    }
+
+    #[test]
+    fn block_quota_limits_requests() {
+        let mut limiter = RPCRateLimiter::new(RateLimiterConfig::default());
+        let peer_id = PeerId::random();
+        let now = Instant::now();
+
+        assert_eq!(
+            limiter.allows_at(&peer_id, &blocks_by_range(1_000), now),
+            Ok(())
+        );
+        assert!(limiter
+            .allows_at(&peer_id, &blocks_by_range(64), now)
+            .is_err());
+
+        // A rejected request consumes no tokens.
+        assert_eq!(
+            limiter.allows_at(&peer_id, &blocks_by_range(24), now),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn oversized_block_requests_cost_the_whole_bucket() {
+        let config = RateLimiterConfig {
+            blocks_by_range: ProtocolQuota {
+                requests: Quota::n_every(16, 10),
+                blocks: Some(Quota::n_every(64, 10)),
+            },
+            ..RateLimiterConfig::default()
+        };
+        let mut limiter = RPCRateLimiter::new(config);
+        let peer_id = PeerId::random();
+        let now = Instant::now();
+
+        assert_eq!(
+            limiter.allows_at(&peer_id, &blocks_by_range(1_024), now),
+            Ok(())
+        );
+        assert_eq!(
+            limiter.allows_at(&peer_id, &blocks_by_range(1_024), now),
+            Err(RateLimitedErr::TooSoon(Duration::from_secs(10)))
+        );
+    }
+
+    #[test]
+    fn prune_removes_full_buckets() {
+        let mut limiter = RPCRateLimiter::new(RateLimiterConfig::default());
+        let peer_id = PeerId::random();
+
+        limiter
+            .allows(&peer_id, &blocks_by_range(1))
+            .expect("should allow request");
+        limiter.buckets.values_mut().for_each(|buckets| {
+            buckets.requests.last_update -= Duration::from_secs(10);
+            if let Some(bucket) = &mut buckets.blocks {
+                bucket.last_update -= Duration::from_secs(10);
+            }
+        });
+        limiter.prune();
+
+        assert!(limiter.buckets.is_empty());
+    }
+}
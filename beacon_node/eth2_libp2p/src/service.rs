@@ -3,8 +3,8 @@ use crate::discovery::enr;
 use crate::multiaddr::Protocol;
 use crate::rpc::{RPCResponseErrorCode, RequestId};
 use crate::types::{error, GossipKind};
-use crate::EnrExt;
-use crate::{NetworkConfig, NetworkGlobals};
+use crate::{EnrExt, Eth2Enr};
+use crate::{ListenTransport, NetworkConfig, NetworkGlobals};
 use futures::prelude::*;
 use libp2p::core::{
     identity::Keypair,
@@ -106,6 +106,7 @@ impl<TSpec: EthSpec> Service<TSpec> {
             enr.clone(),
             config.libp2p_port,
             config.discovery_port,
+            config.trusted_peers.clone(),
             &log,
         ));
 
@@ -137,29 +138,29 @@ impl<TSpec: EthSpec> Service<TSpec> {
                 .build()
         };
 
-        // listen on the specified address
-        let listen_multiaddr = {
-            let mut m = Multiaddr::from(config.listen_address);
-            m.push(Protocol::Tcp(config.libp2p_port));
-            m
-        };
-
-        match Swarm::listen_on(&mut swarm, listen_multiaddr.clone()) {
-            Ok(_) => {
-                let mut log_address = listen_multiaddr;
-                log_address.push(Protocol::P2p(local_peer_id.clone().into()));
-                info!(log, "Listening established"; "address" => format!("{}", log_address));
-            }
-            Err(err) => {
-                crit!(
-                    log,
-                    "Unable to listen on libp2p address";
-                    "error" => format!("{:?}", err),
-                    "listen_multiaddr" => format!("{}", listen_multiaddr),
-                );
-                return Err("Libp2p was unable to listen on the given listen address.".into());
-            }
-        };
+        // listen on the specified address for each enabled transport
+        if config.listen_transports.is_empty() {
+            return Err("At least one libp2p listen transport must be enabled".into());
+        }
+        for transport in &config.listen_transports {
+            let listen_multiaddr = listen_multiaddr(config, *transport);
+            match Swarm::listen_on(&mut swarm, listen_multiaddr.clone()) {
+                Ok(_) => {
+                    let mut log_address = listen_multiaddr;
+                    log_address.push(Protocol::P2p(local_peer_id.clone().into()));
+                    info!(log, "Listening established"; "address" => format!("{}", log_address));
+                }
+                Err(err) => {
+                    crit!(
+                        log,
+                        "Unable to listen on libp2p address";
+                        "error" => format!("{:?}", err),
+                        "listen_multiaddr" => format!("{}", listen_multiaddr),
+                    );
+                    return Err("Libp2p was unable to listen on the given listen address.".into());
+                }
+            };
+        }
 
         // helper closure for dialing peers
         let mut dial_addr = |multiaddr: &Multiaddr| {
@@ -182,7 +183,12 @@ impl<TSpec: EthSpec> Service<TSpec> {
         boot_nodes.dedup();
 
         for bootnode_enr in boot_nodes {
-            for multiaddr in &bootnode_enr.multiaddr() {
+            // boot nodes which only accept WebSocket connections are dialed on their WebSocket port
+            let mut multiaddrs = bootnode_enr.multiaddr();
+            if bootnode_enr.multiaddr_tcp().is_empty() {
+                multiaddrs.extend(bootnode_enr.multiaddr_ws());
+            }
+            for multiaddr in &multiaddrs {
                 // ignore udp multiaddr if it exists
                 let components = multiaddr.iter().collect::<Vec<_>>();
                 if let Protocol::Udp(_) = components[1] {
@@ -270,17 +276,23 @@ impl<TSpec: EthSpec> Service<TSpec> {
                             // has been established and update the db
                             if num_established.get() == 1 {
                                 // update the peerdb
-                                match endpoint {
+                                let accepted = match endpoint {
                                     ConnectedPoint::Listener { .. } => {
-                                        self.swarm.peer_manager().connect_ingoing(&peer_id);
+                                        self.swarm.peer_manager().connect_ingoing(&peer_id)
+                                    }
+                                    ConnectedPoint::Dialer { .. } => {
+                                        self.network_globals
+                                            .peers
+                                            .write()
+                                            .connect_outgoing(&peer_id);
+                                        true
                                     }
-                                    ConnectedPoint::Dialer { .. } => self
-                                        .network_globals
-                                        .peers
-                                        .write()
-                                        .connect_outgoing(&peer_id),
+                                };
+                                // banned peers are disconnected by the peer manager and are not
+                                // reported to the network layer
+                                if accepted {
+                                    return Libp2pEvent::PeerConnected { peer_id, endpoint };
                                 }
-                                return Libp2pEvent::PeerConnected { peer_id, endpoint };
                             }
                         }
                         SwarmEvent::ConnectionClosed {
@@ -369,6 +381,19 @@ impl<TSpec: EthSpec> Service<TSpec> {
     }
 }
 
+/// Returns the multiaddr that libp2p listens on for the given transport.
+fn listen_multiaddr(config: &NetworkConfig, transport: ListenTransport) -> Multiaddr {
+    let mut multiaddr = Multiaddr::from(config.listen_address);
+    match transport {
+        ListenTransport::Tcp => multiaddr.push(Protocol::Tcp(config.libp2p_port)),
+        ListenTransport::WebSocket => {
+            multiaddr.push(Protocol::Tcp(config.ws_port));
+            multiaddr.push(Protocol::Ws("/".into()));
+        }
+    }
+    multiaddr
+}
+
 /// The implementation supports TCP/IP, WebSockets over TCP/IP, noise as the encryption layer, and
 /// yamux or mplex as the multiplexing layer.
 
@@ -377,7 +402,7 @@ fn build_transport(
 ) -> Result<Boxed<(PeerId, StreamMuxerBox), Error>, Error> {
     let transport = libp2p::tcp::TokioTcpConfig::new().nodelay(true);
     let transport = libp2p::dns::DnsConfig::new(transport)?;
-    #[cfg(feature = "libp2p-websocket")]
+    // WebSockets are always supported for dialing, regardless of the listen transports
     let transport = {
         let trans_clone = transport.clone();
         transport.or_transport(libp2p::websocket::WsConfig::new(trans_clone))
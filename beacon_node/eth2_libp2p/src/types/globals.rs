@@ -1,7 +1,7 @@
 //! A collection of variables that are accessible outside of the network thread itself.
 use crate::peer_manager::PeerDB;
 use crate::rpc::methods::MetaData;
-use crate::types::SyncState;
+use crate::types::{BlockLookupInfo, SubnetSubscriptions, SyncState};
 use crate::Client;
 use crate::EnrExt;
 use crate::{Enr, Eth2Enr, GossipTopic, Multiaddr, PeerId};
@@ -29,10 +29,20 @@ pub struct NetworkGlobals<TSpec: EthSpec> {
     pub gossipsub_subscriptions: RwLock<HashSet<GossipTopic>>,
     /// The current sync status of the node.
     pub sync_state: RwLock<SyncState>,
+    /// The searches for unknown blocks currently being performed by sync.
+    pub block_lookups: RwLock<Vec<BlockLookupInfo>>,
+    /// The attestation subnets the node is subscribed to, or plans to subscribe to.
+    pub subnet_subscriptions: RwLock<SubnetSubscriptions>,
 }
 
 impl<TSpec: EthSpec> NetworkGlobals<TSpec> {
-    pub fn new(enr: Enr, tcp_port: u16, udp_port: u16, log: &slog::Logger) -> Self {
+    pub fn new(
+        enr: Enr,
+        tcp_port: u16,
+        udp_port: u16,
+        trusted_peers: Vec<PeerId>,
+        log: &slog::Logger,
+    ) -> Self {
         // set up the local meta data of the node
         let meta_data = RwLock::new(MetaData {
             seq_number: 0,
@@ -48,9 +58,11 @@ impl<TSpec: EthSpec> NetworkGlobals<TSpec> {
             listen_multiaddrs: RwLock::new(Vec::new()),
             listen_port_tcp: AtomicU16::new(tcp_port),
             listen_port_udp: AtomicU16::new(udp_port),
-            peers: RwLock::new(PeerDB::new(log)),
+            peers: RwLock::new(PeerDB::new(trusted_peers, log)),
             gossipsub_subscriptions: RwLock::new(HashSet::new()),
             sync_state: RwLock::new(SyncState::Stalled),
+            block_lookups: RwLock::new(Vec::new()),
+            subnet_subscriptions: RwLock::new(SubnetSubscriptions::default()),
         }
     }
 
@@ -100,6 +112,16 @@ impl<TSpec: EthSpec> NetworkGlobals<TSpec> {
         self.sync_state.read().clone()
     }
 
+    /// Returns the searches for unknown blocks currently being performed by sync.
+    pub fn block_lookups(&self) -> Vec<BlockLookupInfo> {
+        self.block_lookups.read().clone()
+    }
+
+    /// Returns the attestation subnets the node is subscribed to, or plans to subscribe to.
+    pub fn subnet_subscriptions(&self) -> SubnetSubscriptions {
+        self.subnet_subscriptions.read().clone()
+    }
+
     /// Returns a `Client` type if one is known for the `PeerId`.
     pub fn client(&self, peer_id: &PeerId) -> Client {
         self.peers
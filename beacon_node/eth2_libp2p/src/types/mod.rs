@@ -1,6 +1,7 @@
 pub mod error;
 mod globals;
 mod pubsub;
+mod subnet;
 mod sync_state;
 mod topics;
 
@@ -12,6 +13,7 @@ pub type EnrBitfield<T: EthSpec> = BitVector<T::SubnetBitfieldLength>;
 pub type Enr = discv5::enr::Enr<discv5::enr::CombinedKey>;
 
 pub use globals::NetworkGlobals;
-pub use pubsub::PubsubMessage;
-pub use sync_state::SyncState;
+pub use pubsub::{MessageAcceptance, PubsubMessage};
+pub use subnet::{ExactSubnet, SubnetSubscriptions};
+pub use sync_state::{BlockLookupInfo, BlockLookupKind, SyncState};
 pub use topics::{GossipEncoding, GossipKind, GossipTopic};
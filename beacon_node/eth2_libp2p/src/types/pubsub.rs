@@ -12,6 +12,18 @@ use types::{
     SignedBeaconBlock, SignedVoluntaryExit,
 };
 
+/// The outcome of validating a gossip message, which decides whether it is propagated and how it
+/// affects the score of the peer which delivered it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MessageAcceptance {
+    /// The message is valid and is propagated to other peers.
+    Accept,
+    /// The message is invalid and the peer which delivered it is penalised.
+    Reject,
+    /// The message is not propagated, but the peer which delivered it is not at fault.
+    Ignore,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum PubsubMessage<T: EthSpec> {
     /// Gossipsub message providing notification of a new block.
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use types::{Slot, SubnetId};
+
+/// A particular subnet at a given slot.
+#[derive(PartialEq, Eq, Hash, Clone, Debug, Serialize, Deserialize)]
+pub struct ExactSubnet {
+    /// The `SubnetId` associated with this subnet.
+    pub subnet_id: SubnetId,
+    /// The `Slot` associated with this subnet.
+    pub slot: Slot,
+}
+
+/// The attestation subnets the node is subscribed to, or plans to subscribe to.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SubnetSubscriptions {
+    /// The long-lived random subnets, which are advertised in the local ENR.
+    pub long_lived: Vec<SubnetId>,
+    /// The subnets required by aggregating validators, along with the slot of each duty.
+    pub duties: Vec<ExactSubnet>,
+}
@@ -22,6 +22,31 @@ pub enum SyncState {
     Stalled,
 }
 
+/// The kind of search being performed for an unknown block.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum BlockLookupKind {
+    /// A block referenced by a peer (e.g. in an attestation) is being downloaded.
+    Single,
+    /// The unknown ancestors of a block are being downloaded.
+    Parent,
+}
+
+/// The state of an ongoing search for an unknown block, for display to users.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BlockLookupInfo {
+    /// The root of the block which started the lookup.
+    pub block_root: Hash256,
+    pub kind: BlockLookupKind,
+    /// The number of blocks in the chain downloaded so far.
+    pub downloaded_blocks: usize,
+    /// The number of peers which claim to have the block.
+    pub peers: usize,
+    /// The number of failed attempts to download the next block.
+    pub failed_attempts: usize,
+    /// Whether a request is in flight, as opposed to waiting for a retry.
+    pub request_pending: bool,
+}
+
 impl PartialEq for SyncState {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
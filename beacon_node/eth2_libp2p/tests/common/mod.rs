@@ -91,13 +91,25 @@ pub fn build_config(port: u16, mut boot_nodes: Vec<Enr>) -> NetworkConfig {
 pub fn build_libp2p_instance(boot_nodes: Vec<Enr>, log: slog::Logger) -> Libp2pInstance {
     let port = unused_port("tcp").unwrap();
     let config = build_config(port, boot_nodes);
+    build_libp2p_instance_from_config(&config, log)
+}
+
+pub fn build_libp2p_instance_from_config(
+    config: &NetworkConfig,
+    log: slog::Logger,
+) -> Libp2pInstance {
     // launch libp2p service
 
     let (signal, exit) = exit_future::signal();
-    let executor =
-        environment::TaskExecutor::new(tokio::runtime::Handle::current(), exit, log.clone());
+    let (shutdown_tx, _) = futures::channel::mpsc::channel(1);
+    let executor = environment::TaskExecutor::new(
+        tokio::runtime::Handle::current(),
+        exit,
+        log.clone(),
+        shutdown_tx,
+    );
     Libp2pInstance(
-        LibP2PService::new(executor, &config, EnrForkId::default(), &log)
+        LibP2PService::new(executor, config, EnrForkId::default(), &log)
             .expect("should build libp2p instance")
             .1,
         signal,
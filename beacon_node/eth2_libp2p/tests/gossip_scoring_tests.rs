@@ -0,0 +1,109 @@
+#![cfg(test)]
+use eth2_libp2p::types::GossipKind;
+use eth2_libp2p::{BehaviourEvent, Libp2pEvent, MessageAcceptance, PubsubMessage};
+use slog::{debug, Level};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::time::delay_for;
+use types::{BeaconBlock, EthSpec, MinimalEthSpec, Signature, SignedBeaconBlock};
+
+mod common;
+
+type E = MinimalEthSpec;
+
+/// Returns a distinct block for each `slot`, so that each has its own gossipsub message id.
+fn block_message(slot: u64) -> PubsubMessage<E> {
+    let mut block = BeaconBlock::empty(&E::default_spec());
+    block.slot = slot.into();
+    PubsubMessage::BeaconBlock(Box::new(SignedBeaconBlock {
+        message: block,
+        signature: Signature::empty_signature(),
+    }))
+}
+
+#[tokio::test]
+// Tests that blocks gossiped by a peer are no longer delivered once the receiver has rejected
+// enough of them to graylist the peer.
+async fn test_gossip_from_graylisted_peer_is_ignored() {
+    // set up the logging. The level and enabled logging or not
+    let log_level = Level::Debug;
+    let enable_logging = false;
+
+    let log = common::build_log(log_level, enable_logging);
+
+    // get sender/receiver
+    let (mut sender, mut receiver) = common::build_node_pair(&log).await;
+    sender.swarm.subscribe_kind(GossipKind::BeaconBlock);
+    receiver.swarm.subscribe_kind(GossipKind::BeaconBlock);
+
+    // the receiver asks for the next block once it has validated the previous one
+    let (next_block_send, mut next_block_recv) = mpsc::unbounded_channel::<()>();
+
+    // build the sender future
+    let sender_future = async {
+        let mut slot = 0;
+        loop {
+            tokio::select! {
+                event = sender.next_event() => {
+                    if let Libp2pEvent::Behaviour(BehaviourEvent::PeerSubscribed(..)) = event {
+                        if slot == 0 {
+                            slot += 1;
+                            debug!(log, "Sender publishing"; "slot" => slot);
+                            sender.swarm.publish(vec![block_message(slot)]);
+                        }
+                    }
+                }
+                Some(()) = next_block_recv.recv() => {
+                    slot += 1;
+                    debug!(log, "Sender publishing"; "slot" => slot);
+                    sender.swarm.publish(vec![block_message(slot)]);
+                }
+            }
+        }
+    };
+
+    // build the receiver future
+    let receiver_future = async {
+        // reject blocks until the sender is graylisted
+        loop {
+            if let Libp2pEvent::Behaviour(BehaviourEvent::PubsubMessage { id, source, .. }) =
+                receiver.next_event().await
+            {
+                receiver.swarm.report_message_validation_result(
+                    &source,
+                    id,
+                    MessageAcceptance::Reject,
+                );
+                // ask for another block, which should be ignored once the sender is graylisted
+                next_block_send.send(()).unwrap();
+                if receiver.swarm.peer_manager().is_graylisted(&source) {
+                    debug!(log, "Sender graylisted");
+                    break;
+                }
+            }
+        }
+
+        // no further blocks are delivered
+        let no_delivery = async {
+            loop {
+                if let Libp2pEvent::Behaviour(BehaviourEvent::PubsubMessage { .. }) =
+                    receiver.next_event().await
+                {
+                    panic!("Gossip from a graylisted peer was delivered");
+                }
+            }
+        };
+        tokio::select! {
+            _ = no_delivery => {}
+            _ = delay_for(Duration::from_secs(2)) => {}
+        }
+    };
+
+    tokio::select! {
+        _ = sender_future => {}
+        _ = receiver_future => {}
+        _ = delay_for(Duration::from_secs(30)) => {
+            panic!("Future timed out");
+        }
+    }
+}
@@ -0,0 +1,155 @@
+#![cfg(test)]
+use eth2_libp2p::discovery::{build_enr, CombinedKey, Keypair};
+use eth2_libp2p::multiaddr::Protocol;
+use eth2_libp2p::{
+    CombinedKeyExt, ConnectedPoint, EnrExt, Eth2Enr, Libp2pEvent, ListenTransport, Multiaddr,
+};
+use slog::{debug, o, Level};
+use std::time::Duration;
+use tokio::time::delay_for;
+use types::{EnrForkId, MinimalEthSpec};
+
+mod common;
+
+/// Builds a node which listens for both TCP and WebSocket connections.
+fn build_ws_instance(log: slog::Logger) -> (common::Libp2pInstance, u16) {
+    let port = common::unused_port("tcp").unwrap();
+    let ws_port = common::unused_port("tcp").unwrap();
+    let mut config = common::build_config(port, vec![]);
+    config.listen_transports = vec![ListenTransport::Tcp, ListenTransport::WebSocket];
+    config.ws_port = ws_port;
+    (
+        common::build_libp2p_instance_from_config(&config, log),
+        ws_port,
+    )
+}
+
+#[tokio::test]
+// Tests that two nodes can connect over the WebSocket transport
+async fn test_websocket_connection() {
+    // set up the logging. The level and enabled logging or not
+    let log_level = Level::Debug;
+    let enable_logging = false;
+
+    let log = common::build_log(log_level, enable_logging);
+
+    let (mut sender, _) = build_ws_instance(log.new(o!("who" => "sender")));
+    let (mut receiver, receiver_ws_port) = build_ws_instance(log.new(o!("who" => "receiver")));
+
+    // the receiver advertises its WebSocket port
+    let receiver_enr = common::get_enr(&receiver);
+    assert_eq!(receiver_enr.ws_port(), Some(receiver_ws_port));
+    let receiver_peer_id = receiver.local_peer_id.clone();
+
+    let receiver_multiaddr = {
+        let mut m = Multiaddr::from(receiver_enr.ip().expect("ENR has an ip"));
+        m.push(Protocol::Tcp(receiver_ws_port));
+        m.push(Protocol::Ws("/".into()));
+        m
+    };
+
+    // wait for the receiver to listen on both transports
+    let mut listen_addrs = 0;
+    while listen_addrs < 2 {
+        tokio::select! {
+            event = receiver.next_event() => {
+                if let Libp2pEvent::NewListenAddr(_) = event {
+                    listen_addrs += 1;
+                }
+            }
+            _ = delay_for(Duration::from_secs(5)) => panic!("Receiver failed to listen"),
+        }
+    }
+
+    libp2p::Swarm::dial_addr(&mut sender.swarm, receiver_multiaddr.clone())
+        .expect("should dial the receiver");
+    debug!(log, "Sender dialed receiver"; "address" => format!("{}", receiver_multiaddr));
+
+    // the sender should connect to the receiver over the WebSocket address it dialed
+    let sender_future = async {
+        loop {
+            if let Libp2pEvent::PeerConnected { peer_id, endpoint } = sender.next_event().await {
+                assert_eq!(peer_id, receiver_peer_id);
+                match endpoint {
+                    ConnectedPoint::Dialer { address } => {
+                        assert_eq!(address, receiver_multiaddr)
+                    }
+                    ConnectedPoint::Listener { .. } => panic!("Sender should be the dialer"),
+                }
+                return;
+            }
+        }
+    };
+
+    // the receiver should accept the connection
+    let receiver_future = async {
+        loop {
+            if let Libp2pEvent::PeerConnected { .. } = receiver.next_event().await {
+                return;
+            }
+        }
+    };
+
+    tokio::select! {
+        _ = futures::future::join(sender_future, receiver_future) => {}
+        _ = delay_for(Duration::from_secs(30)) => {
+            panic!("Future timed out");
+        }
+    }
+}
+
+#[tokio::test]
+// Tests that nodes do not advertise a WebSocket port unless they listen for WebSockets
+async fn test_no_websocket_port_by_default() {
+    let log = common::build_log(Level::Debug, false);
+
+    let node = common::build_libp2p_instance(vec![], log);
+
+    assert_eq!(common::get_enr(&node).ws_port(), None);
+}
+
+/// Returns a config for a node which only listens for WebSocket connections, and its `ws_port`.
+fn build_ws_only_config() -> (eth2_libp2p::NetworkConfig, u16) {
+    let port = common::unused_port("tcp").unwrap();
+    let ws_port = common::unused_port("tcp").unwrap();
+    let mut config = common::build_config(port, vec![]);
+    config.listen_transports = vec![ListenTransport::WebSocket];
+    config.ws_port = ws_port;
+    (config, ws_port)
+}
+
+#[test]
+// Tests that a node which only listens for WebSockets advertises no TCP port
+fn test_websocket_only_enr() {
+    let (config, ws_port) = build_ws_only_config();
+    let enr_key = CombinedKey::from_libp2p(&Keypair::generate_secp256k1()).unwrap();
+    let enr = build_enr::<MinimalEthSpec>(&enr_key, &config, EnrForkId::default()).unwrap();
+
+    assert_eq!(enr.tcp(), None);
+    assert!(enr.multiaddr_tcp().is_empty());
+    assert_eq!(enr.ws_port(), Some(ws_port));
+
+    let mut ws_multiaddr = Multiaddr::from(enr.ip().expect("ENR has an ip"));
+    ws_multiaddr.push(Protocol::Tcp(ws_port));
+    ws_multiaddr.push(Protocol::Ws("/".into()));
+    assert_eq!(enr.multiaddr_ws(), vec![ws_multiaddr]);
+}
+
+#[tokio::test]
+// Tests that a discovered peer which only listens for WebSockets is dialed on its WebSocket port
+async fn test_websocket_only_peer_is_dialed_on_its_ws_port() {
+    let log = common::build_log(Level::Debug, false);
+
+    let mut node = common::build_libp2p_instance(vec![], log);
+
+    let (config, _) = build_ws_only_config();
+    let enr_key = CombinedKey::from_libp2p(&Keypair::generate_secp256k1()).unwrap();
+    let peer_enr = build_enr::<MinimalEthSpec>(&enr_key, &config, EnrForkId::default()).unwrap();
+    let peer_id = peer_enr.peer_id();
+    node.swarm.add_enr(peer_enr.clone());
+
+    assert_eq!(
+        node.swarm.peer_manager().addresses_of_peer(&peer_id),
+        peer_enr.multiaddr_ws()
+    );
+}
@@ -1,10 +1,18 @@
 //! This service keeps track of which shard subnet the beacon node should be subscribed to at any
 //! given time. It schedules subscriptions to shard subnets, requests peer discoveries and
 //! determines whether attestations should be aggregated and/or passed to the beacon node.
+//!
+//! Subscriptions are planned from the duties the validator client submits via
+//! `/validator/subscribe`. Peers are searched for one epoch before the slot of a duty, so that we
+//! have peers on the subnet by the time we need it. The long-lived random subnets are advertised
+//! in our ENR, which is persisted to disk, and are restored when the node restarts.
 
 use crate::metrics;
 use beacon_chain::{BeaconChain, BeaconChainTypes};
-use eth2_libp2p::{types::GossipKind, NetworkGlobals};
+use eth2_libp2p::{
+    types::{GossipKind, SubnetSubscriptions},
+    Eth2Enr, NetworkGlobals,
+};
 use futures::prelude::*;
 use hashset_delay::HashSetDelay;
 use rand::seq::SliceRandom;
@@ -16,17 +24,19 @@ use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::{Duration, Instant};
-use types::{Attestation, EthSpec, Slot, SubnetId};
+use types::{Attestation, EthSpec, SubnetId};
+
+pub use eth2_libp2p::types::ExactSubnet;
 
 mod tests;
 
 /// The minimum number of slots ahead that we attempt to discover peers for a subscription. If the
 /// slot is less than this number, skip the peer discovery process.
 const MIN_PEER_DISCOVERY_SLOT_LOOK_AHEAD: u64 = 1;
-/// The number of slots ahead that we attempt to discover peers for a subscription. If the slot to
-/// attest to is greater than this, we queue a discovery request for this many slots prior to
-/// subscribing.
-const TARGET_PEER_DISCOVERY_SLOT_LOOK_AHEAD: u64 = 6;
+/// The number of epochs ahead that we attempt to discover peers for a subscription. If the slot to
+/// attest to is further ahead than this, we queue a discovery request for this many epochs prior
+/// to subscribing.
+const TARGET_PEER_DISCOVERY_EPOCH_LOOK_AHEAD: u64 = 1;
 /// The time (in slots) before a last seen validator is considered absent and we unsubscribe from the random
 /// gossip topics that we subscribed to due to the validator connection.
 const LAST_SEEN_VALIDATOR_TIMEOUT: u32 = 150;
@@ -91,15 +101,6 @@ impl PartialEq for AttServiceMessage {
     }
 }
 
-/// A particular subnet at a given slot.
-#[derive(PartialEq, Eq, Hash, Clone, Debug)]
-pub struct ExactSubnet {
-    /// The `SubnetId` associated with this subnet.
-    pub subnet_id: SubnetId,
-    /// The `Slot` associated with this subnet.
-    pub slot: Slot,
-}
-
 pub struct AttestationService<T: BeaconChainTypes> {
     /// Queued events to return to the driving service.
     events: VecDeque<AttServiceMessage>,
@@ -164,7 +165,7 @@ impl<T: BeaconChainTypes> AttestationService<T> {
             .checked_mul(DEFAULT_EXPIRATION_TIMEOUT)
             .expect("DEFAULT_EXPIRATION_TIMEOUT must not be ridiculoustly large");
 
-        AttestationService {
+        let mut attestation_service = AttestationService {
             events: VecDeque::with_capacity(10),
             network_globals,
             beacon_chain,
@@ -176,7 +177,11 @@ impl<T: BeaconChainTypes> AttestationService<T> {
             known_validators: HashSetDelay::new(last_seen_val_timeout),
             waker: None,
             log,
-        }
+        };
+
+        attestation_service.restore_random_subnets();
+        attestation_service.update_subnet_subscriptions();
+        attestation_service
     }
 
     /// Processes a list of validator subscriptions.
@@ -261,6 +266,8 @@ impl<T: BeaconChainTypes> AttestationService<T> {
             }
         }
 
+        self.update_subnet_subscriptions();
+
         // pre-emptively wake the thread to check for new events
         if let Some(waker) = &self.waker {
             waker.wake_by_ref();
@@ -284,6 +291,54 @@ impl<T: BeaconChainTypes> AttestationService<T> {
 
     /* Internal private functions */
 
+    /// Re-subscribes to the long-lived random subnets advertised in our ENR, which is restored
+    /// from disk when the node restarts.
+    ///
+    /// The subnets are dropped on their next expiry if no validators require them.
+    fn restore_random_subnets(&mut self) {
+        let bitfield = match self.network_globals.local_enr().bitfield::<T::EthSpec>() {
+            Ok(bitfield) => bitfield,
+            Err(e) => {
+                warn!(self.log, "Could not read the local ENR bitfield"; "error" => e);
+                return;
+            }
+        };
+
+        let restored_subnets = (0..bitfield.len())
+            .filter(|id| bitfield.get(*id).unwrap_or(false))
+            .map(|id| SubnetId::new(id as u64))
+            .collect::<Vec<_>>();
+
+        for subnet_id in restored_subnets {
+            debug!(self.log, "Restoring long-lived random subnet"; "subnet_id" => *subnet_id);
+            self.random_subnets.insert(subnet_id);
+            self.send_or_update_discovery_event(subnet_id, None);
+            self.events
+                .push_back(AttServiceMessage::Subscribe(subnet_id));
+        }
+    }
+
+    /// Publishes our current and planned subnet subscriptions to the `NetworkGlobals`.
+    fn update_subnet_subscriptions(&self) {
+        let mut long_lived = self.random_subnets.keys().cloned().collect::<Vec<_>>();
+        long_lived.sort_by_key(|subnet_id| **subnet_id);
+        let mut duties = self
+            .aggregate_validators_on_subnet
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        duties.sort_by_key(|exact_subnet| (exact_subnet.slot, *exact_subnet.subnet_id));
+
+        metrics::set_gauge(
+            &metrics::SUBNET_LONG_LIVED_SUBSCRIPTIONS,
+            long_lived.len() as i64,
+        );
+        metrics::set_gauge(&metrics::SUBNET_DUTY_SUBSCRIPTIONS, duties.len() as i64);
+
+        *self.network_globals.subnet_subscriptions.write() =
+            SubnetSubscriptions { long_lived, duties };
+    }
+
     /// Checks if there are currently queued discovery requests and the time required to make the
     /// request.
     ///
@@ -296,6 +351,8 @@ impl<T: BeaconChainTypes> AttestationService<T> {
             .now()
             .ok_or_else(|| "Could not get the current slot")?;
         let slot_duration = self.beacon_chain.slot_clock.slot_duration();
+        let target_look_ahead_slots =
+            TARGET_PEER_DISCOVERY_EPOCH_LOOK_AHEAD * T::EthSpec::slots_per_epoch();
 
         // if there is enough time to perform a discovery lookup
         if exact_subnet.slot >= current_slot.saturating_add(MIN_PEER_DISCOVERY_SLOT_LOOK_AHEAD) {
@@ -306,9 +363,9 @@ impl<T: BeaconChainTypes> AttestationService<T> {
             }
 
             // if the slot is more than epoch away, add an event to start looking for peers
-            if exact_subnet.slot
-                < current_slot.saturating_add(TARGET_PEER_DISCOVERY_SLOT_LOOK_AHEAD)
-            {
+            if exact_subnet.slot < current_slot.saturating_add(target_look_ahead_slots) {
+                metrics::inc_counter(&metrics::SUBNET_DISCOVERY_REQUESTS_IMMEDIATE);
+
                 // add one slot to ensure we keep the peer for the subscription slot
                 let min_ttl = self
                     .beacon_chain
@@ -319,7 +376,8 @@ impl<T: BeaconChainTypes> AttestationService<T> {
                 self.send_or_update_discovery_event(exact_subnet.subnet_id, min_ttl);
             } else {
                 // Queue the discovery event to be executed for
-                // TARGET_PEER_DISCOVERY_SLOT_LOOK_AHEAD
+                // TARGET_PEER_DISCOVERY_EPOCH_LOOK_AHEAD
+                metrics::inc_counter(&metrics::SUBNET_DISCOVERY_REQUESTS_SCHEDULED);
 
                 let duration_to_discover = {
                     let duration_to_next_slot = self
@@ -333,7 +391,7 @@ impl<T: BeaconChainTypes> AttestationService<T> {
                         .slot
                         .saturating_sub(current_slot)
                         .saturating_sub(1u64)
-                        .saturating_sub(TARGET_PEER_DISCOVERY_SLOT_LOOK_AHEAD);
+                        .saturating_sub(target_look_ahead_slots);
 
                     duration_to_next_slot + slot_duration * (slots_until_discover.as_u64() as u32)
                 };
@@ -346,6 +404,7 @@ impl<T: BeaconChainTypes> AttestationService<T> {
             // maintain peers for a least this duration.
             // We may want to check the global PeerInfo to see estimated timeouts for each
             // peer before they can be removed.
+            metrics::inc_counter(&metrics::SUBNET_DISCOVERY_REQUESTS_SKIPPED);
             return Err("Not enough time for a discovery search");
         }
         Ok(())
@@ -463,6 +522,7 @@ impl<T: BeaconChainTypes> AttestationService<T> {
         }
 
         // We are not currently subscribed and have no waiting subscription, create one
+        metrics::inc_counter(&metrics::SUBNET_SUBSCRIPTIONS_SCHEDULED);
         self.subscriptions
             .insert_at(exact_subnet.clone(), duration_to_subscribe);
 
@@ -640,7 +700,24 @@ impl<T: BeaconChainTypes> AttestationService<T> {
     /// This function selects a new subnet to join, or extends the expiry if there are no more
     /// available subnets to choose from.
     fn handle_random_subnet_expiry(&mut self, subnet_id: SubnetId) {
-        let subnet_count = self.beacon_chain.spec.attestation_subnet_count;
+        let spec = &self.beacon_chain.spec;
+        let subnet_count = spec.attestation_subnet_count;
+        let required_subnets = std::cmp::min(
+            self.known_validators.len() as u64 * spec.random_subnets_per_validator,
+            subnet_count,
+        );
+
+        // The expired subnet has been removed. If we still have the random subnets our validators
+        // require, (e.g. subnets restored from the ENR with no validators connected), drop it.
+        if self.random_subnets.len() as u64 >= required_subnets {
+            debug!(self.log, "Dropping unneeded random subnet"; "subnet_id" => *subnet_id);
+            self.events
+                .push_back(AttServiceMessage::Unsubscribe(subnet_id));
+            self.events
+                .push_back(AttServiceMessage::EnrRemove(subnet_id));
+            return;
+        }
+
         if self.random_subnets.len() == (subnet_count - 1) as usize {
             // We are at capacity, simply increase the timeout of the current subnet
             self.random_subnets.insert(subnet_id);
@@ -758,9 +835,16 @@ impl<T: BeaconChainTypes> Stream for AttestationService<T> {
             Poll::Ready(None) | Poll::Pending => {}
         }
 
+        // the published subnet subscriptions only change when a random subnet, known validator or
+        // aggregator duty expires
+        let mut subscriptions_changed = false;
+
         // process any random subnet expiries
         match self.random_subnets.poll_next_unpin(cx) {
-            Poll::Ready(Some(Ok(subnet))) => self.handle_random_subnet_expiry(subnet),
+            Poll::Ready(Some(Ok(subnet))) => {
+                self.handle_random_subnet_expiry(subnet);
+                subscriptions_changed = true;
+            }
             Poll::Ready(Some(Err(e))) => {
                 error!(self.log, "Failed to check for random subnet cycles"; "error"=> format!("{}", e));
             }
@@ -771,15 +855,24 @@ impl<T: BeaconChainTypes> Stream for AttestationService<T> {
         match self.known_validators.poll_next_unpin(cx) {
             Poll::Ready(Some(Ok(_validator_index))) => {
                 let _ = self.handle_known_validator_expiry();
+                subscriptions_changed = true;
             }
             Poll::Ready(Some(Err(e))) => {
                 error!(self.log, "Failed to check for random subnet cycles"; "error"=> format!("{}", e));
             }
             Poll::Ready(None) | Poll::Pending => {}
         }
-        // poll to remove entries on expiration, no need to act on expiration events
-        if let Poll::Ready(Some(Err(e))) = self.aggregate_validators_on_subnet.poll_next_unpin(cx) {
-            error!(self.log, "Failed to check for aggregate validator on subnet expirations"; "error"=> format!("{}", e));
+        // poll to remove entries on expiration, only the published subscriptions need updating
+        match self.aggregate_validators_on_subnet.poll_next_unpin(cx) {
+            Poll::Ready(Some(Ok(_exact_subnet))) => subscriptions_changed = true,
+            Poll::Ready(Some(Err(e))) => {
+                error!(self.log, "Failed to check for aggregate validator on subnet expirations"; "error"=> format!("{}", e));
+            }
+            Poll::Ready(None) | Poll::Pending => {}
+        }
+
+        if subscriptions_changed {
+            self.update_subnet_subscriptions();
         }
 
         // process any generated events
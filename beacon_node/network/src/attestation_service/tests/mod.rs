@@ -7,7 +7,7 @@ mod tests {
         events::NullEventHandler,
         migrate::NullMigrator,
     };
-    use eth2_libp2p::discovery::{build_enr, Keypair};
+    use eth2_libp2p::discovery::{build_enr, Keypair, BITFIELD_ENR_KEY};
     use eth2_libp2p::{discovery::CombinedKey, CombinedKeyExt, NetworkConfig, NetworkGlobals};
     use futures::Stream;
     use genesis::{generate_deterministic_keypairs, interop_genesis_state};
@@ -16,11 +16,12 @@ mod tests {
     use slog::Logger;
     use sloggers::{null::NullLoggerBuilder, Build};
     use slot_clock::{SlotClock, SystemTimeSlotClock};
+    use ssz::Encode;
     use std::time::{Duration, SystemTime};
     use store::config::StoreConfig;
     use store::{HotColdDB, MemoryStore};
     use tempfile::tempdir;
-    use types::{CommitteeIndex, EnrForkId, EthSpec, MinimalEthSpec};
+    use types::{CommitteeIndex, EnrForkId, EthSpec, MinimalEthSpec, Slot};
 
     const SLOT_DURATION_MILLIS: u64 = 200;
 
@@ -100,7 +101,8 @@ mod tests {
         let enr_key = CombinedKey::from_libp2p(&Keypair::generate_secp256k1()).unwrap();
         let enr = build_enr::<MinimalEthSpec>(&enr_key, &config, EnrForkId::default()).unwrap();
 
-        let network_globals: NetworkGlobals<MinimalEthSpec> = NetworkGlobals::new(enr, 0, 0, &log);
+        let network_globals: NetworkGlobals<MinimalEthSpec> =
+            NetworkGlobals::new(enr, 0, 0, vec![], &log);
         AttestationService::new(beacon_chain, Arc::new(network_globals), &log)
     }
 
@@ -424,7 +426,7 @@ mod tests {
         let validator_index = 1;
         let committee_index = 1;
         let subscription_slot = 7;
-        let no_events_expected = 3;
+        let no_events_expected = 4;
         let committee_count = 1;
 
         // create the attestation service and subscriptions
@@ -447,8 +449,23 @@ mod tests {
             .validator_subscriptions(subscriptions)
             .unwrap();
 
-        // ten slots ahead is before our target peer discover time, so expect no messages
-        let expected: Vec<AttServiceMessage> = vec![];
+        let min_ttl = Instant::now().checked_add(
+            attestation_service
+                .beacon_chain
+                .slot_clock
+                .duration_to_slot(current_slot + Slot::new(subscription_slot) + Slot::new(1))
+                .unwrap(),
+        );
+
+        // seven slots ahead is within the epoch of look-ahead, so discover peers immediately
+        let subnet_id = SubnetId::compute_subnet::<MinimalEthSpec>(
+            current_slot + Slot::new(subscription_slot),
+            committee_index,
+            committee_count,
+            &attestation_service.beacon_chain.spec,
+        )
+        .unwrap();
+        let expected = vec![AttServiceMessage::DiscoverPeers { subnet_id, min_ttl }];
 
         let events = get_events(attestation_service, no_events_expected, 1).await;
 
@@ -515,7 +532,7 @@ mod tests {
         )
         .unwrap();
 
-        // expect discover peers because we will enter TARGET_PEER_DISCOVERY_SLOT_LOOK_AHEAD range
+        // expect discover peers because we will enter TARGET_PEER_DISCOVERY_EPOCH_LOOK_AHEAD range
         let expected: Vec<AttServiceMessage> =
             vec![AttServiceMessage::DiscoverPeers { subnet_id, min_ttl }];
 
@@ -639,4 +656,46 @@ mod tests {
         assert_eq!(enr_add_count, 64);
         assert_eq!(unexpected_msg_count, 0);
     }
+
+    #[tokio::test]
+    async fn restore_random_subnets_from_enr() {
+        let log = get_logger();
+
+        // build an ENR advertising long-lived subnets, as if loaded from disk
+        let config = NetworkConfig::default();
+        let enr_key = CombinedKey::from_libp2p(&Keypair::generate_secp256k1()).unwrap();
+        let mut enr = build_enr::<MinimalEthSpec>(&enr_key, &config, EnrForkId::default()).unwrap();
+        let mut bitfield = enr.bitfield::<MinimalEthSpec>().unwrap();
+        bitfield.set(1, true).unwrap();
+        bitfield.set(5, true).unwrap();
+        enr.insert(BITFIELD_ENR_KEY, bitfield.as_ssz_bytes(), &enr_key)
+            .unwrap();
+
+        let network_globals: Arc<NetworkGlobals<MinimalEthSpec>> =
+            Arc::new(NetworkGlobals::new(enr, 0, 0, vec![], &log));
+        let attestation_service =
+            AttestationService::new(CHAIN.chain.clone(), network_globals.clone(), &log);
+
+        assert_eq!(
+            network_globals.subnet_subscriptions().long_lived,
+            vec![SubnetId::new(1), SubnetId::new(5)]
+        );
+
+        // the restored subnets are already in the ENR, so only discover peers and subscribe
+        let expected = vec![
+            AttServiceMessage::DiscoverPeers {
+                subnet_id: SubnetId::new(1),
+                min_ttl: None,
+            },
+            AttServiceMessage::Subscribe(SubnetId::new(1)),
+            AttServiceMessage::DiscoverPeers {
+                subnet_id: SubnetId::new(5),
+                min_ttl: None,
+            },
+            AttServiceMessage::Subscribe(SubnetId::new(5)),
+        ];
+
+        let events = get_events(attestation_service, expected.len(), 1).await;
+        assert_eq!(expected, events);
+    }
 }
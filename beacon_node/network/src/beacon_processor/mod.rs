@@ -0,0 +1,513 @@
+//! Provides the `BeaconProcessor`, which verifies and imports gossip blocks and attestations on a
+//! pool of workers, rather than on the router task.
+//!
+//! The `BeaconProcessor` consists of:
+//!
+//! - A "manager" task, which receives work from the router and either gives it to an idle worker
+//!   or holds it in a queue.
+//! - Up to `max_workers` "worker" tasks which run on blocking threads and perform the
+//!   time-intensive work on the `BeaconChain`.
+//!
+//! ## Prioritisation
+//!
+//! Each type of work has its own bounded queue. Whenever a worker is free, it is given work from
+//! the first non-empty queue in the following order:
+//!
+//! 1. Gossip blocks.
+//! 2. Gossip aggregates, in batches of up to `MAX_ATTESTATION_BATCH_SIZE`.
+//! 3. Gossip unaggregated attestations, in batches of up to `MAX_ATTESTATION_BATCH_SIZE`.
+//!
+//! The signatures in a batch are verified together, which is considerably faster than verifying
+//! them one at a time. When a queue is full, the oldest item is dropped to make room for the new
+//! one, since older gossip messages are the least likely to still be useful.
+//!
+//! Only one worker processes gossip blocks at a time. Blocks are imported in the order they were
+//! received, so a child which arrives shortly after its parent is not verified before the parent
+//! has been imported (which would needlessly send the child to the sync manager as an orphan).
+
+use crate::metrics;
+use crate::service::NetworkMessage;
+use crate::sync::SyncMessage;
+use beacon_chain::{BeaconChain, BeaconChainTypes};
+use environment::TaskExecutor;
+use eth2_libp2p::{MessageId, PeerId};
+use futures::prelude::*;
+use slog::{crit, debug, trace, Logger};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use types::{Attestation, EthSpec, SignedAggregateAndProof, SignedBeaconBlock, SubnetId};
+use worker::Worker;
+
+mod worker;
+
+/// The maximum size of the channel for work events to the `BeaconProcessor`.
+///
+/// Setting this too low will cause consensus messages to be dropped.
+pub const MAX_WORK_EVENT_QUEUE_LEN: usize = 16_384;
+
+/// The maximum size of the channel for idle events to the `BeaconProcessor`.
+///
+/// Setting this too low will prevent new workers from being spawned. It *should* only need to be
+/// the number of workers, but it is set higher to be safe.
+const MAX_IDLE_QUEUE_LEN: usize = 16_384;
+
+/// The maximum number of queued gossip blocks.
+const MAX_GOSSIP_BLOCK_QUEUE_LEN: usize = 1_024;
+
+/// The maximum number of queued gossip aggregates.
+const MAX_AGGREGATED_ATTESTATION_QUEUE_LEN: usize = 1_024;
+
+/// The maximum number of queued gossip unaggregated attestations.
+const MAX_UNAGGREGATED_ATTESTATION_QUEUE_LEN: usize = 16_384;
+
+/// The maximum number of attestations or aggregates given to a worker at once.
+const MAX_ATTESTATION_BATCH_SIZE: usize = 64;
+
+/// The name of the manager task.
+const MANAGER_TASK_NAME: &str = "beacon_processor_manager";
+
+/// The name of the worker tasks.
+const WORKER_TASK_NAME: &str = "beacon_processor_worker";
+
+/// A block received on gossip.
+pub struct GossipBlock<E: EthSpec> {
+    pub message_id: MessageId,
+    pub peer_id: PeerId,
+    pub block: Box<SignedBeaconBlock<E>>,
+}
+
+/// An aggregate received on gossip.
+pub struct GossipAggregate<E: EthSpec> {
+    pub message_id: MessageId,
+    pub peer_id: PeerId,
+    pub aggregate: SignedAggregateAndProof<E>,
+}
+
+/// An unaggregated attestation received on gossip.
+pub struct GossipAttestation<E: EthSpec> {
+    pub message_id: MessageId,
+    pub peer_id: PeerId,
+    pub attestation: Attestation<E>,
+    pub subnet_id: SubnetId,
+}
+
+/// Work sent to the `BeaconProcessor` by the router.
+pub enum WorkEvent<E: EthSpec> {
+    GossipBlock(GossipBlock<E>),
+    GossipAggregate(GossipAggregate<E>),
+    GossipAttestation(GossipAttestation<E>),
+}
+
+/// Work given to a single worker.
+enum Work<E: EthSpec> {
+    GossipBlock(GossipBlock<E>),
+    GossipAggregateBatch(Vec<GossipAggregate<E>>),
+    GossipAttestationBatch(Vec<GossipAttestation<E>>),
+}
+
+impl<E: EthSpec> Work<E> {
+    fn is_block(&self) -> bool {
+        matches!(self, Work::GossipBlock(_))
+    }
+}
+
+/// Sent by a worker when it has finished its work.
+struct WorkerIdle {
+    /// `true` if the worker was processing a block.
+    was_block_worker: bool,
+}
+
+/// Events received by the manager task.
+enum InboundEvent<E: EthSpec> {
+    /// A worker has finished its work.
+    WorkerIdle(WorkerIdle),
+    /// New work from the router.
+    WorkEvent(WorkEvent<E>),
+}
+
+/// A FIFO queue which drops its oldest item when an item is pushed whilst it is full.
+struct BoundedQueue<T> {
+    queue: VecDeque<T>,
+    max_length: usize,
+}
+
+impl<T> BoundedQueue<T> {
+    fn new(max_length: usize) -> Self {
+        Self {
+            queue: VecDeque::new(),
+            max_length,
+        }
+    }
+
+    /// Add `item` to the back of the queue, returning `true` if the oldest item was dropped to
+    /// make room for it.
+    fn push(&mut self, item: T) -> bool {
+        let dropped = if self.queue.len() >= self.max_length {
+            self.queue.pop_front();
+            true
+        } else {
+            false
+        };
+        self.queue.push_back(item);
+        dropped
+    }
+
+    fn pop(&mut self) -> Option<T> {
+        self.queue.pop_front()
+    }
+
+    /// Remove up to `max_items` items from the front of the queue.
+    fn pop_batch(&mut self, max_items: usize) -> Vec<T> {
+        let batch_size = std::cmp::min(self.queue.len(), max_items);
+        self.queue.drain(..batch_size).collect()
+    }
+
+    fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
+
+/// The queues of work waiting for a worker.
+struct WorkQueues<E: EthSpec> {
+    blocks: BoundedQueue<GossipBlock<E>>,
+    aggregates: BoundedQueue<GossipAggregate<E>>,
+    attestations: BoundedQueue<GossipAttestation<E>>,
+}
+
+impl<E: EthSpec> WorkQueues<E> {
+    fn new() -> Self {
+        Self {
+            blocks: BoundedQueue::new(MAX_GOSSIP_BLOCK_QUEUE_LEN),
+            aggregates: BoundedQueue::new(MAX_AGGREGATED_ATTESTATION_QUEUE_LEN),
+            attestations: BoundedQueue::new(MAX_UNAGGREGATED_ATTESTATION_QUEUE_LEN),
+        }
+    }
+
+    /// Adds `event` to its queue, dropping the oldest item of that queue if it is full.
+    fn push(&mut self, event: WorkEvent<E>) {
+        match event {
+            WorkEvent::GossipBlock(block) => {
+                if self.blocks.push(block) {
+                    metrics::inc_counter(&metrics::BEACON_PROCESSOR_GOSSIP_BLOCKS_DROPPED);
+                }
+            }
+            WorkEvent::GossipAggregate(aggregate) => {
+                if self.aggregates.push(aggregate) {
+                    metrics::inc_counter(&metrics::BEACON_PROCESSOR_AGGREGATES_DROPPED);
+                }
+            }
+            WorkEvent::GossipAttestation(attestation) => {
+                if self.attestations.push(attestation) {
+                    metrics::inc_counter(&metrics::BEACON_PROCESSOR_ATTESTATIONS_DROPPED);
+                }
+            }
+        }
+    }
+
+    /// Removes the highest priority work from the queues.
+    ///
+    /// Blocks are skipped whilst `block_worker_active` is `true`, so that they are processed one
+    /// at a time.
+    fn pop(&mut self, block_worker_active: bool) -> Option<Work<E>> {
+        if !block_worker_active && !self.blocks.is_empty() {
+            self.blocks.pop().map(Work::GossipBlock)
+        } else if !self.aggregates.is_empty() {
+            Some(Work::GossipAggregateBatch(
+                self.aggregates.pop_batch(MAX_ATTESTATION_BATCH_SIZE),
+            ))
+        } else if !self.attestations.is_empty() {
+            Some(Work::GossipAttestationBatch(
+                self.attestations.pop_batch(MAX_ATTESTATION_BATCH_SIZE),
+            ))
+        } else {
+            None
+        }
+    }
+
+    fn set_metrics(&self) {
+        metrics::set_gauge(
+            &metrics::BEACON_PROCESSOR_GOSSIP_BLOCK_QUEUE_LEN,
+            self.blocks.len() as i64,
+        );
+        metrics::set_gauge(
+            &metrics::BEACON_PROCESSOR_AGGREGATE_QUEUE_LEN,
+            self.aggregates.len() as i64,
+        );
+        metrics::set_gauge(
+            &metrics::BEACON_PROCESSOR_ATTESTATION_QUEUE_LEN,
+            self.attestations.len() as i64,
+        );
+    }
+}
+
+/// Sends an idle event to the manager when dropped, so that the manager learns that a worker has
+/// finished even if the worker panics.
+struct SendOnDrop {
+    idle_tx: mpsc::Sender<WorkerIdle>,
+    was_block_worker: bool,
+    log: Logger,
+}
+
+impl Drop for SendOnDrop {
+    fn drop(&mut self) {
+        let idle = WorkerIdle {
+            was_block_worker: self.was_block_worker,
+        };
+        if let Err(e) = self.idle_tx.try_send(idle) {
+            crit!(
+                self.log,
+                "Unable to free beacon processor worker";
+                "error" => format!("{}", e)
+            );
+        }
+    }
+}
+
+/// Verifies and imports gossip blocks and attestations on a pool of workers.
+///
+/// See the module-level documentation for more information.
+pub struct BeaconProcessor<T: BeaconChainTypes> {
+    pub beacon_chain: Arc<BeaconChain<T>>,
+    pub network_tx: mpsc::UnboundedSender<NetworkMessage<T::EthSpec>>,
+    pub sync_tx: mpsc::UnboundedSender<SyncMessage<T::EthSpec>>,
+    pub executor: TaskExecutor,
+    pub max_workers: usize,
+    pub current_workers: usize,
+    /// `true` whilst a worker is processing a block.
+    pub block_worker_active: bool,
+    pub log: Logger,
+}
+
+impl<T: BeaconChainTypes> BeaconProcessor<T> {
+    /// Spawns the manager task, which receives work on `event_rx` and runs until `event_rx` is
+    /// closed.
+    pub fn spawn_manager(mut self, event_rx: mpsc::Receiver<WorkEvent<T::EthSpec>>) {
+        let (idle_tx, idle_rx) = mpsc::channel::<WorkerIdle>(MAX_IDLE_QUEUE_LEN);
+
+        let mut queues = WorkQueues::new();
+
+        let executor = self.executor.clone();
+        let manager_future = async move {
+            let mut inbound_events = stream::select(
+                event_rx.map(InboundEvent::WorkEvent),
+                idle_rx.map(InboundEvent::WorkerIdle),
+            );
+
+            while let Some(event) = inbound_events.next().await {
+                match event {
+                    InboundEvent::WorkerIdle(idle) => {
+                        self.current_workers = self.current_workers.saturating_sub(1);
+                        if idle.was_block_worker {
+                            self.block_worker_active = false;
+                        }
+                    }
+                    InboundEvent::WorkEvent(event) => queues.push(event),
+                }
+
+                // Give work to as many idle workers as possible, in order of priority.
+                while self.current_workers < self.max_workers {
+                    match queues.pop(self.block_worker_active) {
+                        Some(work) => self.spawn_worker(idle_tx.clone(), work),
+                        None => break,
+                    }
+                }
+
+                metrics::set_gauge(
+                    &metrics::BEACON_PROCESSOR_WORKERS_ACTIVE,
+                    self.current_workers as i64,
+                );
+                queues.set_metrics();
+            }
+
+            debug!(self.log, "Beacon processor stopped");
+        };
+
+        executor.spawn(manager_future, MANAGER_TASK_NAME);
+    }
+
+    /// Performs `work` on a new worker, which sends an event on `idle_tx` once it has finished.
+    fn spawn_worker(&mut self, idle_tx: mpsc::Sender<WorkerIdle>, work: Work<T::EthSpec>) {
+        self.current_workers += 1;
+        if work.is_block() {
+            self.block_worker_active = true;
+        }
+
+        let worker = Worker {
+            chain: self.beacon_chain.clone(),
+            network_tx: self.network_tx.clone(),
+            sync_tx: self.sync_tx.clone(),
+            log: self.log.clone(),
+        };
+        let send_idle_on_drop = SendOnDrop {
+            idle_tx,
+            was_block_worker: work.is_block(),
+            log: self.log.clone(),
+        };
+        let log = self.log.clone();
+
+        self.executor.spawn_blocking(
+            move || {
+                let _send_idle_on_drop = send_idle_on_drop;
+
+                match work {
+                    Work::GossipBlock(block) => worker.process_gossip_block(block),
+                    Work::GossipAggregateBatch(aggregates) => {
+                        trace!(log, "Processing aggregate batch"; "size" => aggregates.len());
+                        worker.process_gossip_aggregates(aggregates)
+                    }
+                    Work::GossipAttestationBatch(attestations) => {
+                        trace!(log, "Processing attestation batch"; "size" => attestations.len());
+                        worker.process_gossip_attestations(attestations)
+                    }
+                }
+            },
+            WORKER_TASK_NAME,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::{test_utils::test_random_instance, MinimalEthSpec};
+
+    type E = MinimalEthSpec;
+
+    fn message_id(id: usize) -> MessageId {
+        MessageId(id.to_string())
+    }
+
+    fn block(id: usize) -> WorkEvent<E> {
+        WorkEvent::GossipBlock(GossipBlock {
+            message_id: message_id(id),
+            peer_id: PeerId::random(),
+            block: Box::new(test_random_instance()),
+        })
+    }
+
+    fn aggregate(id: usize) -> WorkEvent<E> {
+        WorkEvent::GossipAggregate(GossipAggregate {
+            message_id: message_id(id),
+            peer_id: PeerId::random(),
+            aggregate: test_random_instance(),
+        })
+    }
+
+    fn attestation(id: usize) -> WorkEvent<E> {
+        WorkEvent::GossipAttestation(GossipAttestation {
+            message_id: message_id(id),
+            peer_id: PeerId::random(),
+            attestation: test_random_instance(),
+            subnet_id: SubnetId::new(0),
+        })
+    }
+
+    /// Returns the type of `work` and the message ids of its items.
+    fn describe(work: Option<Work<E>>) -> (&'static str, Vec<MessageId>) {
+        match work.expect("should have work") {
+            Work::GossipBlock(block) => ("block", vec![block.message_id]),
+            Work::GossipAggregateBatch(aggregates) => (
+                "aggregates",
+                aggregates.into_iter().map(|a| a.message_id).collect(),
+            ),
+            Work::GossipAttestationBatch(attestations) => (
+                "attestations",
+                attestations.into_iter().map(|a| a.message_id).collect(),
+            ),
+        }
+    }
+
+    #[test]
+    fn work_is_given_out_in_priority_order() {
+        let mut queues = WorkQueues::new();
+        queues.push(attestation(0));
+        queues.push(aggregate(1));
+        queues.push(block(2));
+        queues.push(block(3));
+
+        assert_eq!(describe(queues.pop(false)), ("block", vec![message_id(2)]));
+
+        // The next block waits for the block worker to finish.
+        assert_eq!(
+            describe(queues.pop(true)),
+            ("aggregates", vec![message_id(1)])
+        );
+        assert_eq!(
+            describe(queues.pop(true)),
+            ("attestations", vec![message_id(0)])
+        );
+        assert!(queues.pop(true).is_none());
+
+        assert_eq!(describe(queues.pop(false)), ("block", vec![message_id(3)]));
+        assert!(queues.pop(false).is_none());
+    }
+
+    #[test]
+    fn attestations_are_given_out_in_batches() {
+        let mut queues = WorkQueues::new();
+        for id in 0..=MAX_ATTESTATION_BATCH_SIZE {
+            queues.push(aggregate(id));
+            queues.push(attestation(id));
+        }
+
+        let (_, first_batch) = describe(queues.pop(false));
+        assert_eq!(
+            first_batch,
+            (0..MAX_ATTESTATION_BATCH_SIZE)
+                .map(message_id)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            describe(queues.pop(false)),
+            ("aggregates", vec![message_id(MAX_ATTESTATION_BATCH_SIZE)])
+        );
+        assert_eq!(
+            describe(queues.pop(false)).1.len(),
+            MAX_ATTESTATION_BATCH_SIZE
+        );
+        assert_eq!(
+            describe(queues.pop(false)),
+            ("attestations", vec![message_id(MAX_ATTESTATION_BATCH_SIZE)])
+        );
+    }
+
+    #[test]
+    fn full_queues_drop_their_oldest_work() {
+        let mut queues = WorkQueues::new();
+        for id in 0..=MAX_GOSSIP_BLOCK_QUEUE_LEN {
+            queues.push(block(id));
+        }
+        for id in 0..=MAX_AGGREGATED_ATTESTATION_QUEUE_LEN {
+            queues.push(aggregate(id));
+        }
+
+        assert_eq!(queues.blocks.len(), MAX_GOSSIP_BLOCK_QUEUE_LEN);
+        assert_eq!(describe(queues.pop(false)), ("block", vec![message_id(1)]));
+
+        assert_eq!(
+            queues.aggregates.len(),
+            MAX_AGGREGATED_ATTESTATION_QUEUE_LEN
+        );
+        let (_, batch) = describe(queues.pop(true));
+        assert_eq!(batch[0], message_id(1));
+    }
+
+    #[test]
+    fn bounded_queue_drops_oldest_items() {
+        let mut queue = BoundedQueue::new(3);
+
+        assert!(!queue.push(1));
+        assert!(!queue.push(2));
+        assert!(!queue.push(3));
+        assert!(queue.push(4), "should drop an item when full");
+
+        assert_eq!(queue.pop_batch(2), vec![2, 3]);
+        assert_eq!(queue.pop(), Some(4));
+        assert!(queue.is_empty());
+    }
+}
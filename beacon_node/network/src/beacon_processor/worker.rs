@@ -0,0 +1,616 @@
+use super::{GossipAggregate, GossipAttestation, GossipBlock};
+use crate::service::NetworkMessage;
+use crate::sync::SyncMessage;
+use beacon_chain::{
+    attestation_verification::{
+        Error as AttnError, SignatureVerifiedAttestation, VerifiedAggregatedAttestation,
+        VerifiedUnaggregatedAttestation,
+    },
+    BeaconChain, BeaconChainError, BeaconChainTypes, BlockError, BlockProcessingOutcome,
+    ForkChoiceError,
+};
+use eth2_libp2p::{MessageAcceptance, MessageId, PeerId};
+use slog::{debug, error, info, trace, warn};
+use ssz::Encode;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use types::Hash256;
+
+/// Verifies and imports the gossip messages given to it by the `BeaconProcessor`.
+///
+/// A `Worker` is created for each piece of work and runs on a blocking thread, so it may freely
+/// perform time-intensive work on the `BeaconChain`.
+pub struct Worker<T: BeaconChainTypes> {
+    pub chain: Arc<BeaconChain<T>>,
+    pub network_tx: mpsc::UnboundedSender<NetworkMessage<T::EthSpec>>,
+    pub sync_tx: mpsc::UnboundedSender<SyncMessage<T::EthSpec>>,
+    pub log: slog::Logger,
+}
+
+impl<T: BeaconChainTypes> Worker<T> {
+    /// Verify a gossip block, propagate it if it is valid and then import it.
+    ///
+    /// Blocks with an unknown parent are sent to the sync manager, which looks up their parents.
+    pub fn process_gossip_block(self, gossip_block: GossipBlock<T::EthSpec>) {
+        let GossipBlock {
+            message_id,
+            peer_id,
+            block,
+        } = gossip_block;
+
+        let verified_block = match self.chain.verify_block_for_gossip(*block.clone()) {
+            Ok(verified_block) => verified_block,
+            Err(BlockError::ParentUnknown(_)) => {
+                // if we don't know the parent, start a parent lookup
+                self.propagate_validation_result(
+                    message_id,
+                    peer_id.clone(),
+                    MessageAcceptance::Ignore,
+                );
+                self.send_to_sync(SyncMessage::UnknownBlock(peer_id, block));
+                return;
+            }
+            Err(e) => {
+                warn!(self.log, "Could not verify block for gossip";
+                    "error" => format!("{:?}", e));
+                let validation_result = match e {
+                    // These errors do not imply that the peer is at fault, e.g., they may be
+                    // caused by a difference between our clock and the peer's or by a block we
+                    // have already seen.
+                    BlockError::FutureSlot { .. }
+                    | BlockError::WouldRevertFinalizedSlot { .. }
+                    | BlockError::GenesisBlock
+                    | BlockError::BlockIsAlreadyKnown
+                    | BlockError::RepeatProposal { .. }
+                    | BlockError::BlockSlotLimitReached
+                    | BlockError::BeaconChainError(_) => MessageAcceptance::Ignore,
+                    // The peer has published an invalid block.
+                    _ => MessageAcceptance::Reject,
+                };
+                self.propagate_validation_result(message_id, peer_id, validation_result);
+                return;
+            }
+        };
+
+        info!(self.log, "New block received";
+            "slot" => verified_block.block.slot(),
+            "hash" => verified_block.block_root.to_string());
+        self.propagate_validation_result(message_id, peer_id.clone(), MessageAcceptance::Accept);
+
+        match BlockProcessingOutcome::shim(self.chain.process_block(verified_block)) {
+            Ok(outcome) => match outcome {
+                BlockProcessingOutcome::Processed { .. } => {
+                    trace!(self.log, "Gossipsub block processed";
+                            "peer_id" => format!("{:?}",peer_id));
+
+                    match self.chain.fork_choice() {
+                        Ok(()) => trace!(
+                            self.log,
+                            "Fork choice success";
+                            "location" => "block gossip"
+                        ),
+                        Err(e) => error!(
+                            self.log,
+                            "Fork choice failed";
+                            "error" => format!("{:?}", e),
+                            "location" => "block gossip"
+                        ),
+                    }
+                }
+                BlockProcessingOutcome::ParentUnknown { .. } => {
+                    // Inform the sync manager to find parents for this block
+                    // This should not occur. It should be checked by `verify_block_for_gossip`
+                    error!(self.log, "Block with unknown parent attempted to be processed";
+                            "peer_id" => format!("{:?}",peer_id));
+                    self.send_to_sync(SyncMessage::UnknownBlock(peer_id, block));
+                }
+                other => {
+                    warn!(
+                        self.log,
+                        "Invalid gossip beacon block";
+                        "outcome" => format!("{:?}", other),
+                        "block root" => format!("{}", block.canonical_root()),
+                        "block slot" => block.slot()
+                    );
+                    trace!(
+                        self.log,
+                        "Invalid gossip beacon block ssz";
+                        "ssz" => format!("0x{}", hex::encode(block.as_ssz_bytes())),
+                    );
+                }
+            },
+            Err(_) => {
+                // error is logged during the processing therefore no error is logged here
+                trace!(
+                    self.log,
+                    "Erroneous gossip beacon block ssz";
+                    "ssz" => format!("0x{}", hex::encode(block.as_ssz_bytes())),
+                );
+            }
+        }
+    }
+
+    /// Verify a batch of gossip aggregates, then propagate and import the valid ones.
+    ///
+    /// The signatures of a batch are verified together, so a single aggregate is verified on its
+    /// own to avoid the overhead of batching.
+    pub fn process_gossip_aggregates(self, aggregates: Vec<GossipAggregate<T::EthSpec>>) {
+        let (metadata, aggregates): (Vec<_>, Vec<_>) = aggregates
+            .into_iter()
+            .map(|gossip| {
+                let beacon_block_root = gossip.aggregate.message.aggregate.data.beacon_block_root;
+                (
+                    (gossip.message_id, gossip.peer_id, beacon_block_root),
+                    gossip.aggregate,
+                )
+            })
+            .unzip();
+
+        let results = if aggregates.len() == 1 {
+            aggregates
+                .into_iter()
+                .map(|aggregate| {
+                    self.chain
+                        .verify_aggregated_attestation_for_gossip(aggregate)
+                })
+                .collect()
+        } else {
+            match self
+                .chain
+                .batch_verify_aggregated_attestations_for_gossip(aggregates)
+            {
+                Ok(results) => results,
+                Err(e) => {
+                    error!(
+                        self.log,
+                        "Unable to verify aggregate batch";
+                        "error" => format!("{:?}", e),
+                        "batch_size" => metadata.len(),
+                    );
+                    return;
+                }
+            }
+        };
+
+        for ((message_id, peer_id, beacon_block_root), result) in metadata.into_iter().zip(results)
+        {
+            match result {
+                Ok(verified_attestation) => {
+                    self.propagate_validation_result(
+                        message_id,
+                        peer_id.clone(),
+                        MessageAcceptance::Accept,
+                    );
+                    self.import_aggregated_attestation(peer_id, verified_attestation);
+                }
+                Err(e) => self.handle_attestation_verification_failure(
+                    message_id,
+                    peer_id,
+                    beacon_block_root,
+                    "aggregated",
+                    e,
+                ),
+            }
+        }
+    }
+
+    /// Verify a batch of gossip attestations, then propagate and import the valid ones.
+    ///
+    /// The signatures of a batch are verified together, so a single attestation is verified on
+    /// its own to avoid the overhead of batching.
+    pub fn process_gossip_attestations(self, attestations: Vec<GossipAttestation<T::EthSpec>>) {
+        let (metadata, attestations): (Vec<_>, Vec<_>) = attestations
+            .into_iter()
+            .map(|gossip| {
+                let beacon_block_root = gossip.attestation.data.beacon_block_root;
+                (
+                    (gossip.message_id, gossip.peer_id, beacon_block_root),
+                    (gossip.attestation, gossip.subnet_id),
+                )
+            })
+            .unzip();
+
+        let results = if attestations.len() == 1 {
+            attestations
+                .into_iter()
+                .map(|(attestation, subnet_id)| {
+                    self.chain
+                        .verify_unaggregated_attestation_for_gossip(attestation, subnet_id)
+                })
+                .collect()
+        } else {
+            match self
+                .chain
+                .batch_verify_unaggregated_attestations_for_gossip(attestations)
+            {
+                Ok(results) => results,
+                Err(e) => {
+                    error!(
+                        self.log,
+                        "Unable to verify attestation batch";
+                        "error" => format!("{:?}", e),
+                        "batch_size" => metadata.len(),
+                    );
+                    return;
+                }
+            }
+        };
+
+        for ((message_id, peer_id, beacon_block_root), result) in metadata.into_iter().zip(results)
+        {
+            match result {
+                Ok(verified_attestation) => {
+                    self.propagate_validation_result(
+                        message_id,
+                        peer_id.clone(),
+                        MessageAcceptance::Accept,
+                    );
+                    self.import_unaggregated_attestation(peer_id, verified_attestation);
+                }
+                Err(e) => self.handle_attestation_verification_failure(
+                    message_id,
+                    peer_id,
+                    beacon_block_root,
+                    "unaggregated",
+                    e,
+                ),
+            }
+        }
+    }
+
+    fn send_to_sync(&self, message: SyncMessage<T::EthSpec>) {
+        self.sync_tx.send(message).unwrap_or_else(|_| {
+            warn!(
+                self.log,
+                "Could not send message to the sync service";
+            )
+        });
+    }
+
+    /// Informs the network service of the outcome of validating a gossip message. Accepted
+    /// messages are forwarded to other peers.
+    fn propagate_validation_result(
+        &self,
+        message_id: MessageId,
+        propagation_source: PeerId,
+        validation_result: MessageAcceptance,
+    ) {
+        self.network_tx
+            .send(NetworkMessage::ValidationResult {
+                propagation_source,
+                message_id,
+                validation_result,
+            })
+            .unwrap_or_else(|_| {
+                warn!(
+                    self.log,
+                    "Could not send validation result to the network service"
+                )
+            });
+    }
+
+    /// Handle an error whilst verifying an `Attestation` or `SignedAggregateAndProof` from the
+    /// network.
+    ///
+    /// The outcome is reported to the network service, which scores the peer accordingly.
+    fn handle_attestation_verification_failure(
+        &self,
+        message_id: MessageId,
+        peer_id: PeerId,
+        beacon_block_root: Hash256,
+        attestation_type: &str,
+        error: AttnError,
+    ) {
+        debug!(
+            self.log,
+            "Invalid attestation from network";
+            "block" => format!("{}", beacon_block_root),
+            "peer_id" => format!("{:?}", peer_id),
+            "type" => format!("{:?}", attestation_type),
+        );
+
+        let validation_result = match error {
+            AttnError::FutureEpoch { .. }
+            | AttnError::PastEpoch { .. }
+            | AttnError::FutureSlot { .. }
+            | AttnError::PastSlot { .. } => {
+                /*
+                 * These errors can be triggered by a mismatch between our slot and the peer.
+                 *
+                 *
+                 * The peer has published an invalid consensus message, _only_ if we trust our own clock.
+                 */
+                MessageAcceptance::Ignore
+            }
+            AttnError::InvalidSelectionProof { .. } | AttnError::InvalidSignature => {
+                /*
+                 * These errors are caused by invalid signatures.
+                 *
+                 * The peer has published an invalid consensus message.
+                 */
+                MessageAcceptance::Reject
+            }
+            AttnError::EmptyAggregationBitfield => {
+                /*
+                 * The aggregate had no signatures and is therefore worthless.
+                 *
+                 * Whilst we don't gossip this attestation, this act is **not** a clear
+                 * violation of the spec nor indication of fault.
+                 *
+                 * This may change soon. Reference:
+                 *
+                 * https://github.com/ethereum/eth2.0-specs/pull/1732
+                 */
+                MessageAcceptance::Ignore
+            }
+            AttnError::AggregatorPubkeyUnknown(_) => {
+                /*
+                 * The aggregator index was higher than any known validator index. This is
+                 * possible in two cases:
+                 *
+                 * 1. The attestation is malformed
+                 * 2. The attestation attests to a beacon_block_root that we do not know.
+                 *
+                 * It should be impossible to reach (2) without triggering
+                 * `AttnError::UnknownHeadBlock`, so we can safely assume the peer is
+                 * faulty.
+                 *
+                 * The peer has published an invalid consensus message.
+                 */
+                MessageAcceptance::Reject
+            }
+            AttnError::AggregatorNotInCommittee { .. } => {
+                /*
+                 * The aggregator index was higher than any known validator index. This is
+                 * possible in two cases:
+                 *
+                 * 1. The attestation is malformed
+                 * 2. The attestation attests to a beacon_block_root that we do not know.
+                 *
+                 * It should be impossible to reach (2) without triggering
+                 * `AttnError::UnknownHeadBlock`, so we can safely assume the peer is
+                 * faulty.
+                 *
+                 * The peer has published an invalid consensus message.
+                 */
+                MessageAcceptance::Reject
+            }
+            AttnError::AttestationAlreadyKnown { .. } => {
+                /*
+                 * The aggregate attestation has already been observed on the network or in
+                 * a block.
+                 *
+                 * The peer is not necessarily faulty.
+                 */
+                MessageAcceptance::Ignore
+            }
+            AttnError::AggregatorAlreadyKnown(_) => {
+                /*
+                 * There has already been an aggregate attestation seen from this
+                 * aggregator index.
+                 *
+                 * The peer is not necessarily faulty.
+                 */
+                MessageAcceptance::Ignore
+            }
+            AttnError::PriorAttestationKnown { .. } => {
+                /*
+                 * We have already seen an attestation from this validator for this epoch.
+                 *
+                 * The peer is not necessarily faulty.
+                 */
+                MessageAcceptance::Ignore
+            }
+            AttnError::ValidatorIndexTooHigh(_) => {
+                /*
+                 * The aggregator index (or similar field) was higher than the maximum
+                 * possible number of validators.
+                 *
+                 * The peer has published an invalid consensus message.
+                 */
+                MessageAcceptance::Reject
+            }
+            AttnError::UnknownHeadBlock { beacon_block_root } => {
+                // Note: its a little bit unclear as to whether or not this block is unknown or
+                // just old. See:
+                //
+                // https://github.com/sigp/lighthouse/issues/1039
+
+                // TODO: Maintain this attestation and re-process once sync completes
+                debug!(
+                    self.log,
+                    "Attestation for unknown block";
+                    "peer_id" => format!("{:?}", peer_id),
+                    "block" => format!("{}", beacon_block_root)
+                );
+                // we don't know the block, get the sync manager to handle the block lookup
+                self.send_to_sync(SyncMessage::UnknownBlockHash(
+                    peer_id.clone(),
+                    beacon_block_root,
+                ));
+                MessageAcceptance::Ignore
+            }
+            AttnError::UnknownTargetRoot(_) => {
+                /*
+                 * The block indicated by the target root is not known to us.
+                 *
+                 * We should always get `AttnError::UnknwonHeadBlock` before we get this
+                 * error, so this means we can get this error if:
+                 *
+                 * 1. The target root does not represent a valid block.
+                 * 2. We do not have the target root in our DB.
+                 *
+                 * For (2), we should only be processing attestations when we should have
+                 * all the available information. Note: if we do a weak-subjectivity sync
+                 * it's possible that this situation could occur, but I think it's
+                 * unlikely. For now, we will declare this to be an invalid message>
+                 *
+                 * The peer has published an invalid consensus message.
+                 */
+                MessageAcceptance::Reject
+            }
+            AttnError::BadTargetEpoch => {
+                /*
+                 * The aggregator index (or similar field) was higher than the maximum
+                 * possible number of validators.
+                 *
+                 * The peer has published an invalid consensus message.
+                 */
+                MessageAcceptance::Reject
+            }
+            AttnError::NoCommitteeForSlotAndIndex { .. } => {
+                /*
+                 * It is not possible to attest this the given committee in the given slot.
+                 *
+                 * The peer has published an invalid consensus message.
+                 */
+                MessageAcceptance::Reject
+            }
+            AttnError::NotExactlyOneAggregationBitSet(_) => {
+                /*
+                 * The unaggregated attestation doesn't have only one signature.
+                 *
+                 * The peer has published an invalid consensus message.
+                 */
+                MessageAcceptance::Reject
+            }
+            AttnError::AttestsToFutureBlock { .. } => {
+                /*
+                 * The beacon_block_root is from a higher slot than the attestation.
+                 *
+                 * The peer has published an invalid consensus message.
+                 */
+                MessageAcceptance::Reject
+            }
+
+            AttnError::InvalidSubnetId { received, expected } => {
+                /*
+                 * The attestation was received on an incorrect subnet id.
+                 */
+                debug!(
+                    self.log,
+                    "Received attestation on incorrect subnet";
+                    "expected" => format!("{:?}", expected),
+                    "received" => format!("{:?}", received),
+                );
+                MessageAcceptance::Reject
+            }
+            AttnError::Invalid(_) => {
+                /*
+                 * The attestation failed the state_processing verification.
+                 *
+                 * The peer has published an invalid consensus message.
+                 */
+                MessageAcceptance::Reject
+            }
+            AttnError::BeaconChainError(e) => {
+                /*
+                 * Lighthouse hit an unexpected error whilst processing the attestation. It
+                 * should be impossible to trigger a `BeaconChainError` from the network,
+                 * so we have a bug.
+                 *
+                 * It's not clear if the message is invalid/malicious.
+                 */
+                error!(
+                    self.log,
+                    "Unable to validate aggregate";
+                    "peer_id" => format!("{:?}", peer_id),
+                    "error" => format!("{:?}", e),
+                );
+                MessageAcceptance::Ignore
+            }
+        };
+
+        self.propagate_validation_result(message_id, peer_id, validation_result);
+    }
+
+    fn import_aggregated_attestation(
+        &self,
+        peer_id: PeerId,
+        verified_attestation: VerifiedAggregatedAttestation<T>,
+    ) {
+        // This is provided to the error handling function to assist with debugging.
+        let beacon_block_root = verified_attestation.attestation().data.beacon_block_root;
+
+        self.apply_attestation_to_fork_choice(
+            peer_id.clone(),
+            beacon_block_root,
+            &verified_attestation,
+        );
+
+        if let Err(e) = self.chain.add_to_block_inclusion_pool(verified_attestation) {
+            debug!(
+                self.log,
+                "Attestation invalid for op pool";
+                "reason" => format!("{:?}", e),
+                "peer" => format!("{:?}", peer_id),
+                "beacon_block_root" => format!("{:?}", beacon_block_root)
+            )
+        }
+    }
+
+    fn import_unaggregated_attestation(
+        &self,
+        peer_id: PeerId,
+        verified_attestation: VerifiedUnaggregatedAttestation<T>,
+    ) {
+        // This is provided to the error handling function to assist with debugging.
+        let beacon_block_root = verified_attestation.attestation().data.beacon_block_root;
+
+        self.apply_attestation_to_fork_choice(
+            peer_id.clone(),
+            beacon_block_root,
+            &verified_attestation,
+        );
+
+        if let Err(e) = self
+            .chain
+            .add_to_naive_aggregation_pool(verified_attestation)
+        {
+            debug!(
+                self.log,
+                "Attestation invalid for agg pool";
+                "reason" => format!("{:?}", e),
+                "peer" => format!("{:?}", peer_id),
+                "beacon_block_root" => format!("{:?}", beacon_block_root)
+            )
+        }
+    }
+
+    /// Apply the attestation to fork choice, suppressing errors.
+    ///
+    /// We suppress the errors when adding an attestation to fork choice since the spec
+    /// permits gossiping attestations that are invalid to be applied to fork choice.
+    ///
+    /// An attestation that is invalid for fork choice can still be included in a block.
+    ///
+    /// Reference:
+    /// https://github.com/ethereum/eth2.0-specs/issues/1408#issuecomment-617599260
+    fn apply_attestation_to_fork_choice<'a>(
+        &self,
+        peer_id: PeerId,
+        beacon_block_root: Hash256,
+        attestation: &'a impl SignatureVerifiedAttestation<T>,
+    ) {
+        if let Err(e) = self.chain.apply_attestation_to_fork_choice(attestation) {
+            match e {
+                BeaconChainError::ForkChoiceError(ForkChoiceError::InvalidAttestation(e)) => {
+                    debug!(
+                        self.log,
+                        "Attestation invalid for fork choice";
+                        "reason" => format!("{:?}", e),
+                        "peer" => format!("{:?}", peer_id),
+                        "beacon_block_root" => format!("{:?}", beacon_block_root)
+                    )
+                }
+                e => error!(
+                    self.log,
+                    "Error applying attestation to fork choice";
+                    "reason" => format!("{:?}", e),
+                    "peer" => format!("{:?}", peer_id),
+                    "beacon_block_root" => format!("{:?}", beacon_block_root)
+                ),
+            }
+        }
+    }
+}
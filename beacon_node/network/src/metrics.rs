@@ -48,4 +48,64 @@ lazy_static! {
         "network_subnet_subscriptions_aggregator_total",
         "Count of validator subscription requests where the subscriber is an aggregator."
     );
+    pub static ref SUBNET_SUBSCRIPTIONS_SCHEDULED: Result<IntCounter> = try_create_int_counter(
+        "network_subnet_subscriptions_scheduled_total",
+        "Count of subnet subscriptions scheduled for aggregator duties."
+    );
+    pub static ref SUBNET_DISCOVERY_REQUESTS_IMMEDIATE: Result<IntCounter> = try_create_int_counter(
+        "network_subnet_discovery_requests_immediate_total",
+        "Count of subnet peer discoveries requested immediately, as the duty is within the look-ahead."
+    );
+    pub static ref SUBNET_DISCOVERY_REQUESTS_SCHEDULED: Result<IntCounter> = try_create_int_counter(
+        "network_subnet_discovery_requests_scheduled_total",
+        "Count of subnet peer discoveries scheduled for the start of the duty's look-ahead."
+    );
+    pub static ref SUBNET_DISCOVERY_REQUESTS_SKIPPED: Result<IntCounter> = try_create_int_counter(
+        "network_subnet_discovery_requests_skipped_total",
+        "Count of subnet peer discoveries skipped as there was not enough time before the duty."
+    );
+    pub static ref SUBNET_LONG_LIVED_SUBSCRIPTIONS: Result<IntGauge> = try_create_int_gauge(
+        "network_subnet_long_lived_subscriptions",
+        "Current number of long-lived random subnets."
+    );
+    pub static ref SUBNET_DUTY_SUBSCRIPTIONS: Result<IntGauge> = try_create_int_gauge(
+        "network_subnet_duty_subscriptions",
+        "Current number of subnets and slots required by aggregator duties."
+    );
+
+    /*
+     * Beacon processor
+     */
+    pub static ref BEACON_PROCESSOR_WORK_EVENTS_DROPPED: Result<IntCounter> = try_create_int_counter(
+        "beacon_processor_work_events_dropped_total",
+        "Count of work events dropped because the beacon processor channel was full"
+    );
+    pub static ref BEACON_PROCESSOR_WORKERS_ACTIVE: Result<IntGauge> = try_create_int_gauge(
+        "beacon_processor_workers_active_total",
+        "Count of active workers in the beacon processor"
+    );
+    pub static ref BEACON_PROCESSOR_GOSSIP_BLOCK_QUEUE_LEN: Result<IntGauge> = try_create_int_gauge(
+        "beacon_processor_gossip_block_queue_total",
+        "Count of gossip blocks waiting to be processed"
+    );
+    pub static ref BEACON_PROCESSOR_GOSSIP_BLOCKS_DROPPED: Result<IntCounter> = try_create_int_counter(
+        "beacon_processor_gossip_blocks_dropped_total",
+        "Count of gossip blocks dropped because the queue was full"
+    );
+    pub static ref BEACON_PROCESSOR_AGGREGATE_QUEUE_LEN: Result<IntGauge> = try_create_int_gauge(
+        "beacon_processor_aggregated_attestation_queue_total",
+        "Count of gossip aggregates waiting to be processed"
+    );
+    pub static ref BEACON_PROCESSOR_AGGREGATES_DROPPED: Result<IntCounter> = try_create_int_counter(
+        "beacon_processor_aggregated_attestations_dropped_total",
+        "Count of gossip aggregates dropped because the queue was full"
+    );
+    pub static ref BEACON_PROCESSOR_ATTESTATION_QUEUE_LEN: Result<IntGauge> = try_create_int_gauge(
+        "beacon_processor_unaggregated_attestation_queue_total",
+        "Count of gossip unaggregated attestations waiting to be processed"
+    );
+    pub static ref BEACON_PROCESSOR_ATTESTATIONS_DROPPED: Result<IntCounter> = try_create_int_counter(
+        "beacon_processor_unaggregated_attestations_dropped_total",
+        "Count of gossip unaggregated attestations dropped because the queue was full"
+    );
 }
@@ -1,4 +1,4 @@
-use eth2_libp2p::Enr;
+use eth2_libp2p::{Enr, PeerId, PeerRecord};
 use rlp;
 use std::sync::Arc;
 use store::{DBColumn, Error as StoreError, HotColdDB, ItemStore, StoreItem};
@@ -7,6 +7,10 @@ use types::{EthSpec, Hash256};
 /// 32-byte key for accessing the `DhtEnrs`.
 pub const DHT_DB_KEY: &str = "PERSISTEDDHTPERSISTEDDHTPERSISTE";
 
+/// 32-byte key for accessing the persisted peer reputations, gossip scores and bans, which are
+/// stored in the `DhtEnrs` column alongside the DHT.
+pub const PEERS_DB_KEY: &str = "PERSISTEDPEERSPERSISTEDPEERSPERS";
+
 pub fn load_dht<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
     store: Arc<HotColdDB<E, Hot, Cold>>,
 ) -> Vec<Enr> {
@@ -31,6 +35,30 @@ pub fn persist_dht<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
     Ok(())
 }
 
+/// Load the reputations, gossip scores and bans of peers from a previous run.
+pub fn load_peers<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
+    store: Arc<HotColdDB<E, Hot, Cold>>,
+) -> Vec<PeerRecord> {
+    let key = Hash256::from_slice(&PEERS_DB_KEY.as_bytes());
+    match store.get_item(&key) {
+        Ok(Some(p)) => {
+            let p: PersistedPeers = p;
+            p.peers
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Attempt to persist the reputations, gossip scores and bans of peers to `store`.
+pub fn persist_peers<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
+    store: Arc<HotColdDB<E, Hot, Cold>>,
+    peers: Vec<PeerRecord>,
+) -> Result<(), store::Error> {
+    let key = Hash256::from_slice(&PEERS_DB_KEY.as_bytes());
+    store.put_item(&key, &PersistedPeers { peers })?;
+    Ok(())
+}
+
 /// Wrapper around DHT for persistence to disk.
 pub struct PersistedDht {
     pub enrs: Vec<Enr>,
@@ -54,6 +82,59 @@ impl StoreItem for PersistedDht {
     }
 }
 
+/// Wrapper around the reputations, gossip scores and bans of peers for persistence to disk.
+///
+/// Each peer is encoded as an RLP list of its id, reputation, ban expiry and the bits of its gossip
+/// score, where a ban expiry of zero means the peer is not banned.
+pub struct PersistedPeers {
+    pub peers: Vec<PeerRecord>,
+}
+
+impl StoreItem for PersistedPeers {
+    fn db_column() -> DBColumn {
+        DBColumn::DhtEnrs
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        let mut stream = rlp::RlpStream::new_list(self.peers.len());
+        for peer in &self.peers {
+            stream.begin_list(4);
+            stream.append(&peer.peer_id.as_bytes().to_vec());
+            stream.append(&peer.reputation);
+            stream.append(&peer.ban_expiry.unwrap_or(0));
+            stream.append(&peer.gossip_score.to_bits());
+        }
+        stream.out()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, StoreError> {
+        let rlp_error = |e: rlp::DecoderError| StoreError::RlpError(format!("{}", e));
+
+        let peers = rlp::Rlp::new(bytes)
+            .iter()
+            .map(|item| {
+                let peer_id_bytes: Vec<u8> = item.val_at(0).map_err(rlp_error)?;
+                let peer_id = PeerId::from_bytes(peer_id_bytes)
+                    .map_err(|_| StoreError::RlpError("Invalid peer id".to_string()))?;
+                let ban_expiry: u64 = item.val_at(2).map_err(rlp_error)?;
+                let gossip_score_bits: u64 = item.val_at(3).map_err(rlp_error)?;
+                Ok(PeerRecord {
+                    peer_id,
+                    reputation: item.val_at(1).map_err(rlp_error)?,
+                    ban_expiry: if ban_expiry == 0 {
+                        None
+                    } else {
+                        Some(ban_expiry)
+                    },
+                    gossip_score: f64::from_bits(gossip_score_bits),
+                })
+            })
+            .collect::<Result<_, StoreError>>()?;
+
+        Ok(PersistedPeers { peers })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -79,4 +160,31 @@ mod tests {
         let dht: PersistedDht = store.get_item(&key).unwrap().unwrap();
         assert_eq!(dht.enrs, enrs);
     }
+
+    #[test]
+    fn test_persisted_peers() {
+        let log = NullLoggerBuilder.build().unwrap();
+        let store: HotColdDB<
+            MinimalEthSpec,
+            MemoryStore<MinimalEthSpec>,
+            MemoryStore<MinimalEthSpec>,
+        > = HotColdDB::open_ephemeral(StoreConfig::default(), ChainSpec::minimal(), log).unwrap();
+        let peers = vec![
+            PeerRecord {
+                peer_id: PeerId::random(),
+                reputation: 0,
+                ban_expiry: Some(1_600_000_000),
+                gossip_score: 0.0,
+            },
+            PeerRecord {
+                peer_id: PeerId::random(),
+                reputation: 20,
+                ban_expiry: None,
+                gossip_score: -42.5,
+            },
+        ];
+        let store = Arc::new(store);
+        persist_peers(store.clone(), peers.clone()).unwrap();
+        assert_eq!(load_peers(store), peers);
+    }
 }
@@ -8,14 +8,15 @@ pub mod processor;
 
 use crate::error;
 use crate::service::NetworkMessage;
-use beacon_chain::{BeaconChain, BeaconChainTypes, BlockError};
+use beacon_chain::{BeaconChain, BeaconChainTypes};
 use eth2_libp2p::{
     rpc::{RPCError, RequestId},
-    MessageId, NetworkGlobals, PeerId, PeerRequestId, PubsubMessage, Request, Response,
+    MessageAcceptance, MessageId, NetworkGlobals, PeerId, PeerRequestId, PubsubMessage, Request,
+    Response,
 };
 use futures::prelude::*;
 use processor::Processor;
-use slog::{debug, info, o, trace, warn};
+use slog::{debug, o, trace, warn};
 use std::sync::Arc;
 use tokio::sync::mpsc;
 use types::EthSpec;
@@ -216,53 +217,33 @@ impl<T: BeaconChainTypes> Router<T> {
         gossip_message: PubsubMessage<T::EthSpec>,
     ) {
         match gossip_message {
-            // Attestations should never reach the router.
-            PubsubMessage::AggregateAndProofAttestation(aggregate_and_proof) => {
-                if let Some(gossip_verified) = self
-                    .processor
-                    .verify_aggregated_attestation_for_gossip(peer_id.clone(), *aggregate_and_proof)
-                {
-                    self.propagate_message(id, peer_id.clone());
-                    self.processor
-                        .import_aggregated_attestation(peer_id, gossip_verified);
-                }
-            }
+            // Blocks and attestations are verified and propagated by the beacon processor.
+            PubsubMessage::AggregateAndProofAttestation(aggregate_and_proof) => self
+                .processor
+                .on_aggregated_attestation_gossip(id, peer_id, *aggregate_and_proof),
             PubsubMessage::Attestation(subnet_attestation) => {
-                if let Some(gossip_verified) =
-                    self.processor.verify_unaggregated_attestation_for_gossip(
-                        peer_id.clone(),
-                        subnet_attestation.1.clone(),
-                        subnet_attestation.0,
-                    )
-                {
-                    self.propagate_message(id, peer_id.clone());
-                    self.processor
-                        .import_unaggregated_attestation(peer_id, gossip_verified);
-                }
-            }
-            PubsubMessage::BeaconBlock(block) => {
-                match self.processor.should_forward_block(&peer_id, block) {
-                    Ok(verified_block) => {
-                        info!(self.log, "New block received"; "slot" => verified_block.block.slot(), "hash" => verified_block.block_root.to_string());
-                        self.propagate_message(id, peer_id.clone());
-                        self.processor.on_block_gossip(peer_id, verified_block);
-                    }
-                    Err(BlockError::ParentUnknown { .. }) => {} // performing a parent lookup
-                    Err(e) => {
-                        // performing a parent lookup
-                        warn!(self.log, "Could not verify block for gossip";
-                            "error" => format!("{:?}", e));
-                    }
-                }
+                let (subnet_id, attestation) = *subnet_attestation;
+                self.processor.on_unaggregated_attestation_gossip(
+                    id,
+                    peer_id,
+                    attestation,
+                    subnet_id,
+                )
             }
+            PubsubMessage::BeaconBlock(block) => self.processor.on_block_gossip(id, peer_id, block),
             PubsubMessage::VoluntaryExit(exit) => {
                 debug!(self.log, "Received a voluntary exit"; "peer_id" => format!("{}", peer_id));
-                if let Some(verified_exit) = self
+                match self
                     .processor
                     .verify_voluntary_exit_for_gossip(&peer_id, *exit)
                 {
-                    self.propagate_message(id, peer_id.clone());
-                    self.processor.import_verified_voluntary_exit(verified_exit);
+                    Ok(verified_exit) => {
+                        self.propagate_validation_result(id, peer_id, MessageAcceptance::Accept);
+                        self.processor.import_verified_voluntary_exit(verified_exit);
+                    }
+                    Err(validation_result) => {
+                        self.propagate_validation_result(id, peer_id, validation_result)
+                    }
                 }
             }
             PubsubMessage::ProposerSlashing(proposer_slashing) => {
@@ -271,13 +252,18 @@ impl<T: BeaconChainTypes> Router<T> {
                     "Received a proposer slashing";
                     "peer_id" => format!("{}", peer_id)
                 );
-                if let Some(verified_proposer_slashing) = self
+                match self
                     .processor
                     .verify_proposer_slashing_for_gossip(&peer_id, *proposer_slashing)
                 {
-                    self.propagate_message(id, peer_id.clone());
-                    self.processor
-                        .import_verified_proposer_slashing(verified_proposer_slashing);
+                    Ok(verified_proposer_slashing) => {
+                        self.propagate_validation_result(id, peer_id, MessageAcceptance::Accept);
+                        self.processor
+                            .import_verified_proposer_slashing(verified_proposer_slashing);
+                    }
+                    Err(validation_result) => {
+                        self.propagate_validation_result(id, peer_id, validation_result)
+                    }
                 }
             }
             PubsubMessage::AttesterSlashing(attester_slashing) => {
@@ -286,29 +272,41 @@ impl<T: BeaconChainTypes> Router<T> {
                     "Received a attester slashing";
                     "peer_id" => format!("{}", peer_id)
                 );
-                if let Some(verified_attester_slashing) = self
+                match self
                     .processor
                     .verify_attester_slashing_for_gossip(&peer_id, *attester_slashing)
                 {
-                    self.propagate_message(id, peer_id.clone());
-                    self.processor
-                        .import_verified_attester_slashing(verified_attester_slashing);
+                    Ok(verified_attester_slashing) => {
+                        self.propagate_validation_result(id, peer_id, MessageAcceptance::Accept);
+                        self.processor
+                            .import_verified_attester_slashing(verified_attester_slashing);
+                    }
+                    Err(validation_result) => {
+                        self.propagate_validation_result(id, peer_id, validation_result)
+                    }
                 }
             }
         }
     }
 
-    /// Informs the network service that the message should be forwarded to other peers.
-    fn propagate_message(&mut self, message_id: MessageId, propagation_source: PeerId) {
+    /// Informs the network service of the outcome of validating a gossip message. Accepted
+    /// messages are forwarded to other peers.
+    fn propagate_validation_result(
+        &mut self,
+        message_id: MessageId,
+        propagation_source: PeerId,
+        validation_result: MessageAcceptance,
+    ) {
         self.network_send
-            .send(NetworkMessage::Propagate {
+            .send(NetworkMessage::ValidationResult {
                 propagation_source,
                 message_id,
+                validation_result,
             })
             .unwrap_or_else(|_| {
                 warn!(
                     self.log,
-                    "Could not send propagation request to the network service"
+                    "Could not send validation result to the network service"
                 )
             });
     }
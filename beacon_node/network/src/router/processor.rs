@@ -1,19 +1,19 @@
+use crate::beacon_processor::{
+    BeaconProcessor, GossipAggregate, GossipAttestation, GossipBlock, WorkEvent,
+    MAX_WORK_EVENT_QUEUE_LEN,
+};
+use crate::metrics;
 use crate::service::NetworkMessage;
 use crate::sync::{PeerSyncInfo, SyncMessage};
 use beacon_chain::{
-    attestation_verification::{
-        Error as AttnError, SignatureVerifiedAttestation, VerifiedAggregatedAttestation,
-        VerifiedUnaggregatedAttestation,
-    },
-    observed_operations::ObservationOutcome,
-    BeaconChain, BeaconChainError, BeaconChainTypes, BlockError, BlockProcessingOutcome,
-    ForkChoiceError, GossipVerifiedBlock,
+    observed_operations::ObservationOutcome, BeaconChain, BeaconChainError, BeaconChainTypes,
 };
 use eth2_libp2p::rpc::*;
-use eth2_libp2p::{NetworkGlobals, PeerId, PeerRequestId, Request, Response};
+use eth2_libp2p::{
+    MessageAcceptance, MessageId, NetworkGlobals, PeerId, PeerRequestId, Request, Response,
+};
 use itertools::process_results;
 use slog::{debug, error, o, trace, warn};
-use ssz::Encode;
 use state_processing::SigVerifiedOp;
 use std::sync::Arc;
 use tokio::sync::mpsc;
@@ -35,6 +35,9 @@ pub struct Processor<T: BeaconChainTypes> {
     chain: Arc<BeaconChain<T>>,
     /// A channel to the syncing thread.
     sync_send: mpsc::UnboundedSender<SyncMessage<T::EthSpec>>,
+    /// A channel to the `BeaconProcessor`, which verifies and imports gossip blocks and
+    /// attestations on a pool of workers.
+    beacon_processor_send: mpsc::Sender<WorkEvent<T::EthSpec>>,
     /// A network context to return and handle RPC requests.
     network: HandlerNetworkContext<T::EthSpec>,
     /// The `RPCHandler` logger.
@@ -54,16 +57,31 @@ impl<T: BeaconChainTypes> Processor<T> {
 
         // spawn the sync thread
         let sync_send = crate::sync::manager::spawn(
-            executor,
+            executor.clone(),
             beacon_chain.clone(),
             network_globals,
             network_send.clone(),
             sync_logger,
         );
 
+        let (beacon_processor_send, beacon_processor_receive) =
+            mpsc::channel(MAX_WORK_EVENT_QUEUE_LEN);
+        BeaconProcessor {
+            beacon_chain: beacon_chain.clone(),
+            network_tx: network_send.clone(),
+            sync_tx: sync_send.clone(),
+            executor,
+            max_workers: num_cpus::get(),
+            current_workers: 0,
+            block_worker_active: false,
+            log: log.new(o!("service" => "beacon_processor")),
+        }
+        .spawn_manager(beacon_processor_receive);
+
         Processor {
             chain: beacon_chain,
             sync_send,
+            beacon_processor_send,
             network: HandlerNetworkContext::new(network_send, log.clone()),
             log: log.clone(),
         }
@@ -499,449 +517,75 @@ impl<T: BeaconChainTypes> Processor<T> {
         }
     }
 
-    /// Template function to be called on a block to determine if the block should be propagated
-    /// across the network.
-    pub fn should_forward_block(
-        &mut self,
-        peer_id: &PeerId,
-        block: Box<SignedBeaconBlock<T::EthSpec>>,
-    ) -> Result<GossipVerifiedBlock<T>, BlockError> {
-        let result = self.chain.verify_block_for_gossip(*block.clone());
-
-        if let Err(BlockError::ParentUnknown(_)) = result {
-            // if we don't know the parent, start a parent lookup
-            // TODO: Modify the return to avoid the block clone.
-            self.send_to_sync(SyncMessage::UnknownBlock(peer_id.clone(), block));
+    /// Sends `work` to the `BeaconProcessor`, dropping it if the `BeaconProcessor` is overloaded.
+    fn send_beacon_processor_work(&mut self, work: WorkEvent<T::EthSpec>) {
+        if let Err(e) = self.beacon_processor_send.try_send(work) {
+            metrics::inc_counter(&metrics::BEACON_PROCESSOR_WORK_EVENTS_DROPPED);
+            debug!(
+                self.log,
+                "Dropping gossip message for the beacon processor";
+                "error" => format!("{}", e)
+            );
         }
-        result
     }
 
-    /// Process a gossip message declaring a new block.
-    ///
-    /// Attempts to apply to block to the beacon chain. May queue the block for later processing.
-    ///
-    /// Returns a `bool` which, if `true`, indicates we should forward the block to our peers.
+    /// Queue a gossip block for verification and import by the `BeaconProcessor`.
     pub fn on_block_gossip(
         &mut self,
+        message_id: MessageId,
         peer_id: PeerId,
-        verified_block: GossipVerifiedBlock<T>,
-    ) -> bool {
-        let block = Box::new(verified_block.block.clone());
-        match BlockProcessingOutcome::shim(self.chain.process_block(verified_block)) {
-            Ok(outcome) => match outcome {
-                BlockProcessingOutcome::Processed { .. } => {
-                    trace!(self.log, "Gossipsub block processed";
-                            "peer_id" => format!("{:?}",peer_id));
-
-                    // TODO: It would be better if we can run this _after_ we publish the block to
-                    // reduce block propagation latency.
-                    //
-                    // The `MessageHandler` would be the place to put this, however it doesn't seem
-                    // to have a reference to the `BeaconChain`. I will leave this for future
-                    // works.
-                    match self.chain.fork_choice() {
-                        Ok(()) => trace!(
-                            self.log,
-                            "Fork choice success";
-                            "location" => "block gossip"
-                        ),
-                        Err(e) => error!(
-                            self.log,
-                            "Fork choice failed";
-                            "error" => format!("{:?}", e),
-                            "location" => "block gossip"
-                        ),
-                    }
-                }
-                BlockProcessingOutcome::ParentUnknown { .. } => {
-                    // Inform the sync manager to find parents for this block
-                    // This should not occur. It should be checked by `should_forward_block`
-                    error!(self.log, "Block with unknown parent attempted to be processed";
-                            "peer_id" => format!("{:?}",peer_id));
-                    self.send_to_sync(SyncMessage::UnknownBlock(peer_id, block));
-                }
-                other => {
-                    warn!(
-                        self.log,
-                        "Invalid gossip beacon block";
-                        "outcome" => format!("{:?}", other),
-                        "block root" => format!("{}", block.canonical_root()),
-                        "block slot" => block.slot()
-                    );
-                    trace!(
-                        self.log,
-                        "Invalid gossip beacon block ssz";
-                        "ssz" => format!("0x{}", hex::encode(block.as_ssz_bytes())),
-                    );
-                }
-            },
-            Err(_) => {
-                // error is logged during the processing therefore no error is logged here
-                trace!(
-                    self.log,
-                    "Erroneous gossip beacon block ssz";
-                    "ssz" => format!("0x{}", hex::encode(block.as_ssz_bytes())),
-                );
-            }
-        }
-        // TODO: Update with correct block gossip checking
-        true
-    }
-
-    /// Handle an error whilst verifying an `Attestation` or `SignedAggregateAndProof` from the
-    /// network.
-    pub fn handle_attestation_verification_failure(
-        &mut self,
-        peer_id: PeerId,
-        beacon_block_root: Hash256,
-        attestation_type: &str,
-        error: AttnError,
+        block: Box<SignedBeaconBlock<T::EthSpec>>,
     ) {
-        debug!(
-            self.log,
-            "Invalid attestation from network";
-            "block" => format!("{}", beacon_block_root),
-            "peer_id" => format!("{:?}", peer_id),
-            "type" => format!("{:?}", attestation_type),
-        );
-
-        match error {
-            AttnError::FutureEpoch { .. }
-            | AttnError::PastEpoch { .. }
-            | AttnError::FutureSlot { .. }
-            | AttnError::PastSlot { .. } => {
-                /*
-                 * These errors can be triggered by a mismatch between our slot and the peer.
-                 *
-                 *
-                 * The peer has published an invalid consensus message, _only_ if we trust our own clock.
-                 */
-            }
-            AttnError::InvalidSelectionProof { .. } | AttnError::InvalidSignature => {
-                /*
-                 * These errors are caused by invalid signatures.
-                 *
-                 * The peer has published an invalid consensus message.
-                 */
-            }
-            AttnError::EmptyAggregationBitfield => {
-                /*
-                 * The aggregate had no signatures and is therefore worthless.
-                 *
-                 * Whilst we don't gossip this attestation, this act is **not** a clear
-                 * violation of the spec nor indication of fault.
-                 *
-                 * This may change soon. Reference:
-                 *
-                 * https://github.com/ethereum/eth2.0-specs/pull/1732
-                 */
-            }
-            AttnError::AggregatorPubkeyUnknown(_) => {
-                /*
-                 * The aggregator index was higher than any known validator index. This is
-                 * possible in two cases:
-                 *
-                 * 1. The attestation is malformed
-                 * 2. The attestation attests to a beacon_block_root that we do not know.
-                 *
-                 * It should be impossible to reach (2) without triggering
-                 * `AttnError::UnknownHeadBlock`, so we can safely assume the peer is
-                 * faulty.
-                 *
-                 * The peer has published an invalid consensus message.
-                 */
-            }
-            AttnError::AggregatorNotInCommittee { .. } => {
-                /*
-                 * The aggregator index was higher than any known validator index. This is
-                 * possible in two cases:
-                 *
-                 * 1. The attestation is malformed
-                 * 2. The attestation attests to a beacon_block_root that we do not know.
-                 *
-                 * It should be impossible to reach (2) without triggering
-                 * `AttnError::UnknownHeadBlock`, so we can safely assume the peer is
-                 * faulty.
-                 *
-                 * The peer has published an invalid consensus message.
-                 */
-            }
-            AttnError::AttestationAlreadyKnown { .. } => {
-                /*
-                 * The aggregate attestation has already been observed on the network or in
-                 * a block.
-                 *
-                 * The peer is not necessarily faulty.
-                 */
-            }
-            AttnError::AggregatorAlreadyKnown(_) => {
-                /*
-                 * There has already been an aggregate attestation seen from this
-                 * aggregator index.
-                 *
-                 * The peer is not necessarily faulty.
-                 */
-            }
-            AttnError::PriorAttestationKnown { .. } => {
-                /*
-                 * We have already seen an attestation from this validator for this epoch.
-                 *
-                 * The peer is not necessarily faulty.
-                 */
-            }
-            AttnError::ValidatorIndexTooHigh(_) => {
-                /*
-                 * The aggregator index (or similar field) was higher than the maximum
-                 * possible number of validators.
-                 *
-                 * The peer has published an invalid consensus message.
-                 */
-            }
-            AttnError::UnknownHeadBlock { beacon_block_root } => {
-                // Note: its a little bit unclear as to whether or not this block is unknown or
-                // just old. See:
-                //
-                // https://github.com/sigp/lighthouse/issues/1039
-
-                // TODO: Maintain this attestation and re-process once sync completes
-                debug!(
-                    self.log,
-                    "Attestation for unknown block";
-                    "peer_id" => format!("{:?}", peer_id),
-                    "block" => format!("{}", beacon_block_root)
-                );
-                // we don't know the block, get the sync manager to handle the block lookup
-                self.send_to_sync(SyncMessage::UnknownBlockHash(peer_id, beacon_block_root));
-            }
-            AttnError::UnknownTargetRoot(_) => {
-                /*
-                 * The block indicated by the target root is not known to us.
-                 *
-                 * We should always get `AttnError::UnknwonHeadBlock` before we get this
-                 * error, so this means we can get this error if:
-                 *
-                 * 1. The target root does not represent a valid block.
-                 * 2. We do not have the target root in our DB.
-                 *
-                 * For (2), we should only be processing attestations when we should have
-                 * all the available information. Note: if we do a weak-subjectivity sync
-                 * it's possible that this situation could occur, but I think it's
-                 * unlikely. For now, we will declare this to be an invalid message>
-                 *
-                 * The peer has published an invalid consensus message.
-                 */
-            }
-            AttnError::BadTargetEpoch => {
-                /*
-                 * The aggregator index (or similar field) was higher than the maximum
-                 * possible number of validators.
-                 *
-                 * The peer has published an invalid consensus message.
-                 */
-            }
-            AttnError::NoCommitteeForSlotAndIndex { .. } => {
-                /*
-                 * It is not possible to attest this the given committee in the given slot.
-                 *
-                 * The peer has published an invalid consensus message.
-                 */
-            }
-            AttnError::NotExactlyOneAggregationBitSet(_) => {
-                /*
-                 * The unaggregated attestation doesn't have only one signature.
-                 *
-                 * The peer has published an invalid consensus message.
-                 */
-            }
-            AttnError::AttestsToFutureBlock { .. } => {
-                /*
-                 * The beacon_block_root is from a higher slot than the attestation.
-                 *
-                 * The peer has published an invalid consensus message.
-                 */
-            }
-
-            AttnError::InvalidSubnetId { received, expected } => {
-                /*
-                 * The attestation was received on an incorrect subnet id.
-                 */
-                debug!(
-                    self.log,
-                    "Received attestation on incorrect subnet";
-                    "expected" => format!("{:?}", expected),
-                    "received" => format!("{:?}", received),
-                )
-            }
-            AttnError::Invalid(_) => {
-                /*
-                 * The attestation failed the state_processing verification.
-                 *
-                 * The peer has published an invalid consensus message.
-                 */
-            }
-            AttnError::BeaconChainError(e) => {
-                /*
-                 * Lighthouse hit an unexpected error whilst processing the attestation. It
-                 * should be impossible to trigger a `BeaconChainError` from the network,
-                 * so we have a bug.
-                 *
-                 * It's not clear if the message is invalid/malicious.
-                 */
-                error!(
-                    self.log,
-                    "Unable to validate aggregate";
-                    "peer_id" => format!("{:?}", peer_id),
-                    "error" => format!("{:?}", e),
-                );
-            }
-        }
-    }
-
-    pub fn verify_aggregated_attestation_for_gossip(
-        &mut self,
-        peer_id: PeerId,
-        aggregate_and_proof: SignedAggregateAndProof<T::EthSpec>,
-    ) -> Option<VerifiedAggregatedAttestation<T>> {
-        // This is provided to the error handling function to assist with debugging.
-        let beacon_block_root = aggregate_and_proof.message.aggregate.data.beacon_block_root;
-
-        self.chain
-            .verify_aggregated_attestation_for_gossip(aggregate_and_proof)
-            .map_err(|e| {
-                self.handle_attestation_verification_failure(
-                    peer_id,
-                    beacon_block_root,
-                    "aggregated",
-                    e,
-                )
-            })
-            .ok()
+        self.send_beacon_processor_work(WorkEvent::GossipBlock(GossipBlock {
+            message_id,
+            peer_id,
+            block,
+        }))
     }
 
-    pub fn import_aggregated_attestation(
+    /// Queue a gossip aggregate for verification and import by the `BeaconProcessor`.
+    pub fn on_aggregated_attestation_gossip(
         &mut self,
+        message_id: MessageId,
         peer_id: PeerId,
-        verified_attestation: VerifiedAggregatedAttestation<T>,
+        aggregate: SignedAggregateAndProof<T::EthSpec>,
     ) {
-        // This is provided to the error handling function to assist with debugging.
-        let beacon_block_root = verified_attestation.attestation().data.beacon_block_root;
-
-        self.apply_attestation_to_fork_choice(
-            peer_id.clone(),
-            beacon_block_root,
-            &verified_attestation,
-        );
-
-        if let Err(e) = self.chain.add_to_block_inclusion_pool(verified_attestation) {
-            debug!(
-                self.log,
-                "Attestation invalid for op pool";
-                "reason" => format!("{:?}", e),
-                "peer" => format!("{:?}", peer_id),
-                "beacon_block_root" => format!("{:?}", beacon_block_root)
-            )
-        }
+        self.send_beacon_processor_work(WorkEvent::GossipAggregate(GossipAggregate {
+            message_id,
+            peer_id,
+            aggregate,
+        }))
     }
 
-    pub fn verify_unaggregated_attestation_for_gossip(
+    /// Queue a gossip attestation for verification and import by the `BeaconProcessor`.
+    pub fn on_unaggregated_attestation_gossip(
         &mut self,
+        message_id: MessageId,
         peer_id: PeerId,
-        unaggregated_attestation: Attestation<T::EthSpec>,
+        attestation: Attestation<T::EthSpec>,
         subnet_id: SubnetId,
-    ) -> Option<VerifiedUnaggregatedAttestation<T>> {
-        // This is provided to the error handling function to assist with debugging.
-        let beacon_block_root = unaggregated_attestation.data.beacon_block_root;
-
-        self.chain
-            .verify_unaggregated_attestation_for_gossip(unaggregated_attestation, subnet_id)
-            .map_err(|e| {
-                self.handle_attestation_verification_failure(
-                    peer_id,
-                    beacon_block_root,
-                    "unaggregated",
-                    e,
-                )
-            })
-            .ok()
-    }
-
-    pub fn import_unaggregated_attestation(
-        &mut self,
-        peer_id: PeerId,
-        verified_attestation: VerifiedUnaggregatedAttestation<T>,
-    ) {
-        // This is provided to the error handling function to assist with debugging.
-        let beacon_block_root = verified_attestation.attestation().data.beacon_block_root;
-
-        self.apply_attestation_to_fork_choice(
-            peer_id.clone(),
-            beacon_block_root,
-            &verified_attestation,
-        );
-
-        if let Err(e) = self
-            .chain
-            .add_to_naive_aggregation_pool(verified_attestation)
-        {
-            debug!(
-                self.log,
-                "Attestation invalid for agg pool";
-                "reason" => format!("{:?}", e),
-                "peer" => format!("{:?}", peer_id),
-                "beacon_block_root" => format!("{:?}", beacon_block_root)
-            )
-        }
-    }
-
-    /// Apply the attestation to fork choice, suppressing errors.
-    ///
-    /// We suppress the errors when adding an attestation to fork choice since the spec
-    /// permits gossiping attestations that are invalid to be applied to fork choice.
-    ///
-    /// An attestation that is invalid for fork choice can still be included in a block.
-    ///
-    /// Reference:
-    /// https://github.com/ethereum/eth2.0-specs/issues/1408#issuecomment-617599260
-    fn apply_attestation_to_fork_choice<'a>(
-        &self,
-        peer_id: PeerId,
-        beacon_block_root: Hash256,
-        attestation: &'a impl SignatureVerifiedAttestation<T>,
     ) {
-        if let Err(e) = self.chain.apply_attestation_to_fork_choice(attestation) {
-            match e {
-                BeaconChainError::ForkChoiceError(ForkChoiceError::InvalidAttestation(e)) => {
-                    debug!(
-                        self.log,
-                        "Attestation invalid for fork choice";
-                        "reason" => format!("{:?}", e),
-                        "peer" => format!("{:?}", peer_id),
-                        "beacon_block_root" => format!("{:?}", beacon_block_root)
-                    )
-                }
-                e => error!(
-                    self.log,
-                    "Error applying attestation to fork choice";
-                    "reason" => format!("{:?}", e),
-                    "peer" => format!("{:?}", peer_id),
-                    "beacon_block_root" => format!("{:?}", beacon_block_root)
-                ),
-            }
-        }
+        self.send_beacon_processor_work(WorkEvent::GossipAttestation(GossipAttestation {
+            message_id,
+            peer_id,
+            attestation,
+            subnet_id,
+        }))
     }
 
     /// Verify a voluntary exit before gossiping or processing it.
     ///
-    /// Errors are logged at debug level.
+    /// Errors are logged at debug level. On failure, returns the validation result which should be
+    /// reported for the message.
     pub fn verify_voluntary_exit_for_gossip(
         &self,
         peer_id: &PeerId,
         voluntary_exit: SignedVoluntaryExit,
-    ) -> Option<SigVerifiedOp<SignedVoluntaryExit>> {
+    ) -> Result<SigVerifiedOp<SignedVoluntaryExit>, MessageAcceptance> {
         let validator_index = voluntary_exit.message.validator_index;
 
         match self.chain.verify_voluntary_exit_for_gossip(voluntary_exit) {
-            Ok(ObservationOutcome::New(sig_verified_exit)) => Some(sig_verified_exit),
+            Ok(ObservationOutcome::New(sig_verified_exit)) => Ok(sig_verified_exit),
             Ok(ObservationOutcome::AlreadyKnown) => {
                 debug!(
                     self.log,
@@ -949,17 +593,17 @@ impl<T: BeaconChainTypes> Processor<T> {
                     "validator_index" => validator_index,
                     "peer" => format!("{:?}", peer_id)
                 );
-                None
+                Err(MessageAcceptance::Ignore)
             }
             Err(e) => {
                 debug!(
                     self.log,
-                    "Dropping invalid exit";
+                    "Dropping exit";
                     "validator_index" => validator_index,
                     "peer" => format!("{:?}", peer_id),
                     "error" => format!("{:?}", e)
                 );
-                None
+                Err(operation_verification_failure(&e))
             }
         }
     }
@@ -975,19 +619,20 @@ impl<T: BeaconChainTypes> Processor<T> {
 
     /// Verify a proposer slashing before gossiping or processing it.
     ///
-    /// Errors are logged at debug level.
+    /// Errors are logged at debug level. On failure, returns the validation result which should be
+    /// reported for the message.
     pub fn verify_proposer_slashing_for_gossip(
         &self,
         peer_id: &PeerId,
         proposer_slashing: ProposerSlashing,
-    ) -> Option<SigVerifiedOp<ProposerSlashing>> {
+    ) -> Result<SigVerifiedOp<ProposerSlashing>, MessageAcceptance> {
         let validator_index = proposer_slashing.signed_header_1.message.proposer_index;
 
         match self
             .chain
             .verify_proposer_slashing_for_gossip(proposer_slashing)
         {
-            Ok(ObservationOutcome::New(verified_slashing)) => Some(verified_slashing),
+            Ok(ObservationOutcome::New(verified_slashing)) => Ok(verified_slashing),
             Ok(ObservationOutcome::AlreadyKnown) => {
                 debug!(
                     self.log,
@@ -996,17 +641,17 @@ impl<T: BeaconChainTypes> Processor<T> {
                     "validator_index" => validator_index,
                     "peer" => format!("{:?}", peer_id)
                 );
-                None
+                Err(MessageAcceptance::Ignore)
             }
             Err(e) => {
                 debug!(
                     self.log,
-                    "Dropping invalid proposer slashing";
+                    "Dropping proposer slashing";
                     "validator_index" => validator_index,
                     "peer" => format!("{:?}", peer_id),
                     "error" => format!("{:?}", e)
                 );
-                None
+                Err(operation_verification_failure(&e))
             }
         }
     }
@@ -1022,17 +667,18 @@ impl<T: BeaconChainTypes> Processor<T> {
 
     /// Verify an attester slashing before gossiping or processing it.
     ///
-    /// Errors are logged at debug level.
+    /// Errors are logged at debug level. On failure, returns the validation result which should be
+    /// reported for the message.
     pub fn verify_attester_slashing_for_gossip(
         &self,
         peer_id: &PeerId,
         attester_slashing: AttesterSlashing<T::EthSpec>,
-    ) -> Option<SigVerifiedOp<AttesterSlashing<T::EthSpec>>> {
+    ) -> Result<SigVerifiedOp<AttesterSlashing<T::EthSpec>>, MessageAcceptance> {
         match self
             .chain
             .verify_attester_slashing_for_gossip(attester_slashing)
         {
-            Ok(ObservationOutcome::New(verified_slashing)) => Some(verified_slashing),
+            Ok(ObservationOutcome::New(verified_slashing)) => Ok(verified_slashing),
             Ok(ObservationOutcome::AlreadyKnown) => {
                 debug!(
                     self.log,
@@ -1040,16 +686,16 @@ impl<T: BeaconChainTypes> Processor<T> {
                     "reason" => "Slashings already known for all slashed validators",
                     "peer" => format!("{:?}", peer_id)
                 );
-                None
+                Err(MessageAcceptance::Ignore)
             }
             Err(e) => {
                 debug!(
                     self.log,
-                    "Dropping invalid attester slashing";
+                    "Dropping attester slashing";
                     "peer" => format!("{:?}", peer_id),
                     "error" => format!("{:?}", e)
                 );
-                None
+                Err(operation_verification_failure(&e))
             }
         }
     }
@@ -1067,6 +713,19 @@ impl<T: BeaconChainTypes> Processor<T> {
     }
 }
 
+/// Returns the validation result for an exit or slashing which failed verification with `error`.
+///
+/// Only operations which are invalid are rejected. Other errors (e.g., being unable to read the
+/// head state) are not the fault of the peer, so the operation is ignored.
+fn operation_verification_failure(error: &BeaconChainError) -> MessageAcceptance {
+    match error {
+        BeaconChainError::ExitValidationError(_)
+        | BeaconChainError::ProposerSlashingValidationError(_)
+        | BeaconChainError::AttesterSlashingValidationError(_) => MessageAcceptance::Reject,
+        _ => MessageAcceptance::Ignore,
+    }
+}
+
 /// Build a `StatusMessage` representing the state of the given `beacon_chain`.
 pub(crate) fn status_message<T: BeaconChainTypes>(
     beacon_chain: &BeaconChain<T>,
@@ -1077,10 +736,15 @@ pub(crate) fn status_message<T: BeaconChainTypes>(
     let fork_digest =
         ChainSpec::compute_fork_digest(head_info.fork.current_version, genesis_validators_root);
 
+    // Advertise the database anchor if the head state's finalized checkpoint predates it, so that
+    // range sync starts from the anchor rather than from blocks we do not have.
+    let finalized_checkpoint =
+        beacon_chain.finalized_checkpoint_or_anchor(head_info.finalized_checkpoint);
+
     Some(StatusMessage {
         fork_digest,
-        finalized_root: head_info.finalized_checkpoint.root,
-        finalized_epoch: head_info.finalized_checkpoint.epoch,
+        finalized_root: finalized_checkpoint.root,
+        finalized_epoch: finalized_checkpoint.epoch,
         head_root: head_info.block_root,
         head_slot: head_info.slot,
     })
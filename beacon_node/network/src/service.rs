@@ -1,4 +1,4 @@
-use crate::persisted_dht::{load_dht, persist_dht};
+use crate::persisted_dht::{load_dht, load_peers, persist_dht, persist_peers};
 use crate::router::{Router, RouterMessage};
 use crate::{
     attestation_service::{AttServiceMessage, AttestationService},
@@ -9,7 +9,7 @@ use beacon_chain::{BeaconChain, BeaconChainTypes};
 use eth2_libp2p::Service as LibP2PService;
 use eth2_libp2p::{
     rpc::{RPCResponseErrorCode, RequestId},
-    Libp2pEvent, PeerRequestId, PubsubMessage, Request, Response,
+    Libp2pEvent, MessageAcceptance, PeerRequestId, PubsubMessage, Request, Response,
 };
 use eth2_libp2p::{BehaviourEvent, MessageId, NetworkGlobals, PeerId};
 use futures::prelude::*;
@@ -40,7 +40,7 @@ pub struct NetworkService<T: BeaconChainTypes> {
     /// The sending channel for the network service to send messages to be routed throughout
     /// lighthouse.
     router_send: mpsc::UnboundedSender<RouterMessage<T::EthSpec>>,
-    /// A reference to lighthouse's database to persist the DHT.
+    /// A reference to lighthouse's database to persist the DHT and peer reputations.
     store: Arc<HotColdDB<T::EthSpec, T::HotStore, T::ColdStore>>,
     /// A collection of global variables, accessible outside of the network service.
     network_globals: Arc<NetworkGlobals<T::EthSpec>>,
@@ -85,6 +85,17 @@ impl<T: BeaconChainTypes> NetworkService<T> {
             libp2p.swarm.add_enr(enr.clone());
         }
 
+        // Restore the reputations, gossip scores and bans of peers from the previous run.
+        let peers_to_load = load_peers::<T::EthSpec, T::HotStore, T::ColdStore>(store.clone());
+        debug!(
+            network_log,
+            "Loading peer reputations"; "peers" => peers_to_load.len()
+        );
+        libp2p
+            .swarm
+            .peer_manager()
+            .restore_peer_records(peers_to_load);
+
         // launch derived network services
 
         // router task
@@ -156,6 +167,19 @@ fn spawn_service<T: BeaconChainTypes>(
                         ),
                     }
 
+                    let peers = service.libp2p.swarm.peer_manager().peer_records();
+                    match persist_peers::<T::EthSpec, T::HotStore, T::ColdStore>(service.store.clone(), peers) {
+                        Err(e) => error!(
+                            service.log,
+                            "Failed to persist peer reputations on drop";
+                            "error" => format!("{:?}", e)
+                        ),
+                        Ok(_) => info!(
+                            service.log,
+                            "Saved peer reputations";
+                        ),
+                    }
+
                     info!(service.log, "Network service shutdown");
                     return;
                 }
@@ -171,18 +195,24 @@ fn spawn_service<T: BeaconChainTypes>(
                         NetworkMessage::SendError{ peer_id, error, id, reason } => {
                             service.libp2p.respond_with_error(peer_id, id, error, reason);
                         }
-                        NetworkMessage::Propagate {
+                        NetworkMessage::ValidationResult {
                             propagation_source,
                             message_id,
+                            validation_result,
                         } => {
-                                trace!(service.log, "Propagating gossipsub message";
+                                trace!(service.log, "Validated gossipsub message";
                                     "propagation_peer" => format!("{:?}", propagation_source),
                                     "message_id" => message_id.to_string(),
+                                    "validation_result" => format!("{:?}", validation_result),
                                 );
                                 service
                                     .libp2p
                                     .swarm
-                                    .propagate_message(&propagation_source, message_id);
+                                    .report_message_validation_result(
+                                        &propagation_source,
+                                        message_id,
+                                        validation_result,
+                                    );
                         }
                         NetworkMessage::Publish { messages } => {
                                 let mut topic_kinds = Vec::new();
@@ -408,10 +438,12 @@ pub enum NetworkMessage<T: EthSpec> {
     },
     /// Publish a list of messages to the gossipsub protocol.
     Publish { messages: Vec<PubsubMessage<T>> },
-    /// Propagate a received gossipsub message.
-    Propagate {
+    /// Reports the outcome of validating a received gossipsub message. Accepted messages are
+    /// propagated.
+    ValidationResult {
         propagation_source: PeerId,
         message_id: MessageId,
+        validation_result: MessageAcceptance,
     },
     /// Disconnect and bans a peer id.
     Disconnect { peer_id: PeerId },
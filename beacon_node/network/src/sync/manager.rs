@@ -23,15 +23,23 @@
 //! ## Parent Lookup
 //!
 //! When a block with an unknown parent is received and we are in `Regular` sync mode, the block is
-//! queued for lookup. A round-robin approach is used to request the parent from the known list of
-//! fully sync'd peers. If `PARENT_FAIL_TOLERANCE` attempts at requesting the block fails, we
-//! drop the propagated block and downvote the peer that sent it to us.
+//! queued for lookup. Each parent is requested from a random connected peer out of all the peers
+//! which have sent us a block of the chain, so that a single unresponsive peer cannot stall the
+//! lookup. Failed requests are retried with an exponential backoff. If `PARENT_FAIL_TOLERANCE`
+//! attempts at requesting the block fails, we drop the propagated block and downvote the peer that
+//! sent it to us. A lookup is also dropped once it has downloaded more blocks than can lie between
+//! our finalized slot and the block.
 //!
 //! Block Lookup
 //!
 //! To keep the logic maintained to the syncing thread (and manage the request_ids), when a block needs to be searched for (i.e
 //! if an attestation references an unknown block) this manager can search for the block and
-//! subsequently search for parents if needed.
+//! subsequently search for parents if needed. As with parent lookups, the block is requested from
+//! any of the peers that referenced it and failures are retried with a backoff, up to
+//! `SINGLE_BLOCK_FAIL_TOLERANCE` attempts.
+//!
+//! Concurrent searches for the same block are merged into a single lookup, which gains the peers of
+//! each search. The current lookups are published in the `NetworkGlobals`.
 
 use super::block_processor::{spawn_block_processor, BatchProcessResult, ProcessId};
 use super::network_context::SyncNetworkContext;
@@ -41,15 +49,20 @@ use super::RequestId;
 use crate::service::NetworkMessage;
 use beacon_chain::{BeaconChain, BeaconChainTypes, BlockProcessingOutcome};
 use eth2_libp2p::rpc::{methods::MAX_REQUEST_BLOCKS, BlocksByRootRequest};
-use eth2_libp2p::types::NetworkGlobals;
+use eth2_libp2p::types::{BlockLookupInfo, BlockLookupKind, NetworkGlobals};
 use eth2_libp2p::PeerId;
 use fnv::FnvHashMap;
+use futures::prelude::*;
+use hashset_delay::HashSetDelay;
+use rand::seq::IteratorRandom;
 use slog::{crit, debug, error, info, trace, warn, Logger};
 use smallvec::SmallVec;
 use ssz_types::VariableList;
 use std::boxed::Box;
+use std::collections::HashSet;
 use std::ops::Sub;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
 use types::{EthSpec, Hash256, SignedBeaconBlock, Slot};
 
@@ -59,10 +72,17 @@ use types::{EthSpec, Hash256, SignedBeaconBlock, Slot};
 pub const SLOT_IMPORT_TOLERANCE: usize = 20;
 /// How many attempts we try to find a parent of a block before we give up trying .
 const PARENT_FAIL_TOLERANCE: usize = 5;
-/// The maximum depth we will search for a parent block. In principle we should have sync'd any
-/// canonical chain to its head once the peer connects. A chain should not appear where it's depth
-/// is further back than the most recent head slot.
-const PARENT_DEPTH_TOLERANCE: usize = SLOT_IMPORT_TOLERANCE * 2;
+/// How many attempts we try to download a single block before we give up trying.
+const SINGLE_BLOCK_FAIL_TOLERANCE: usize = 3;
+/// The delay before retrying a lookup after its first failed attempt. The delay doubles with each
+/// subsequent failure, up to `MAX_LOOKUP_BACKOFF`.
+const LOOKUP_BACKOFF: Duration = Duration::from_millis(500);
+/// The maximum delay before retrying a failed lookup.
+const MAX_LOOKUP_BACKOFF: Duration = Duration::from_secs(8);
+/// The maximum depth we will search for a parent block, which bounds the blocks held in memory by
+/// a single lookup. Each lookup has a smaller depth budget if the block is closer to our finalized
+/// slot, see `parent_depth_budget`. Longer chains are left to range sync.
+const MAX_PARENT_DEPTH: usize = SLOT_IMPORT_TOLERANCE * 8;
 
 #[derive(Debug)]
 /// A message than can be sent to the sync manager thread.
@@ -111,9 +131,16 @@ pub enum SyncMessage<T: EthSpec> {
 
 /// Maintains a sequential list of parents to lookup and the lookup's current state.
 struct ParentRequests<T: EthSpec> {
+    /// The root of the block which started the lookup.
+    block_root: Hash256,
+
     /// The blocks that have currently been downloaded.
     downloaded_blocks: Vec<SignedBeaconBlock<T>>,
 
+    /// The peers which have sent us a block of this chain. Parents are requested from any of
+    /// these peers.
+    peers: HashSet<PeerId>,
+
     /// The number of failed attempts to retrieve a parent block. If too many attempts occur, this
     /// lookup is failed and rejected.
     failed_attempts: usize,
@@ -122,8 +149,25 @@ struct ParentRequests<T: EthSpec> {
     /// downvoted.
     last_submitted_peer: PeerId,
 
-    /// The request ID of this lookup is in progress.
+    /// The request ID of this lookup is in progress. If `None`, the lookup is waiting to be
+    /// retried.
     pending: Option<RequestId>,
+
+    /// The maximum number of blocks this lookup may download before it is dropped.
+    max_depth: usize,
+}
+
+impl<T: EthSpec> ParentRequests<T> {
+    fn info(&self) -> BlockLookupInfo {
+        BlockLookupInfo {
+            block_root: self.block_root,
+            kind: BlockLookupKind::Parent,
+            downloaded_blocks: self.downloaded_blocks.len(),
+            peers: self.peers.len(),
+            failed_attempts: self.failed_attempts,
+            request_pending: self.pending.is_some(),
+        }
+    }
 }
 
 /// The primary object for handling and driving all the current syncing logic. It maintains the
@@ -149,11 +193,12 @@ pub struct SyncManager<T: BeaconChainTypes> {
     /// A collection of parent block lookups.
     parent_queue: SmallVec<[ParentRequests<T::EthSpec>; 3]>,
 
-    /// A collection of block hashes being searched for and a flag indicating if a result has been
-    /// received or not.
-    ///
-    /// The flag allows us to determine if the peer returned data or sent us nothing.
-    single_block_lookups: FnvHashMap<RequestId, SingleBlockRequest>,
+    /// A collection of block hashes being searched for.
+    single_block_lookups: FnvHashMap<Hash256, SingleBlockRequest>,
+
+    /// The lookups waiting to be retried after a failed attempt, keyed by the root of the block
+    /// which started the lookup.
+    delayed_lookups: HashSetDelay<Hash256>,
 
     /// The logger for the import manager.
     log: Logger,
@@ -166,19 +211,63 @@ pub struct SyncManager<T: BeaconChainTypes> {
 struct SingleBlockRequest {
     /// The hash of the requested block.
     pub hash: Hash256,
-    /// Whether a block was received from this request, or the peer returned an empty response.
-    pub block_returned: bool,
+    /// The peers which have referenced the block. The block is requested from any of these peers.
+    pub peers: HashSet<PeerId>,
+    /// The number of failed attempts to download the block.
+    pub failed_attempts: usize,
+    /// The peer the block was last requested from.
+    pub last_peer: Option<PeerId>,
+    /// The ID of the request in progress. If `None`, the lookup is waiting to be retried.
+    pub pending: Option<RequestId>,
 }
 
 impl SingleBlockRequest {
-    pub fn new(hash: Hash256) -> Self {
+    pub fn new(hash: Hash256, peer_id: PeerId) -> Self {
+        let mut peers = HashSet::new();
+        peers.insert(peer_id);
         Self {
             hash,
-            block_returned: false,
+            peers,
+            failed_attempts: 0,
+            last_peer: None,
+            pending: None,
+        }
+    }
+
+    fn info(&self) -> BlockLookupInfo {
+        BlockLookupInfo {
+            block_root: self.hash,
+            kind: BlockLookupKind::Single,
+            downloaded_blocks: 0,
+            peers: self.peers.len(),
+            failed_attempts: self.failed_attempts,
+            request_pending: self.pending.is_some(),
         }
     }
 }
 
+/// Returns the delay before retrying a lookup which has failed `failed_attempts` times.
+fn lookup_backoff(failed_attempts: usize) -> Duration {
+    let exponent = failed_attempts.saturating_sub(1).min(16) as u32;
+    LOOKUP_BACKOFF
+        .checked_mul(2u32.pow(exponent))
+        .map_or(MAX_LOOKUP_BACKOFF, |backoff| {
+            backoff.min(MAX_LOOKUP_BACKOFF)
+        })
+}
+
+/// Returns the number of ancestors a parent lookup may download for a block at `block_slot`.
+///
+/// A valid chain joins ours at or after our finalized slot, so it cannot have more unknown blocks
+/// than there are slots between the finalized slot and the block.
+fn parent_depth_budget(block_slot: Slot, finalized_slot: Slot) -> usize {
+    block_slot
+        .saturating_sub(finalized_slot)
+        .as_usize()
+        .max(1)
+        .min(MAX_PARENT_DEPTH)
+}
+
 /// Spawns a new `SyncManager` thread which has a weak reference to underlying beacon
 /// chain. This allows the chain to be
 /// dropped during the syncing process which will gracefully end the `SyncManager`.
@@ -210,6 +299,7 @@ pub fn spawn<T: BeaconChainTypes>(
         input_channel: sync_recv,
         parent_queue: SmallVec::new(),
         single_block_lookups: FnvHashMap::default(),
+        delayed_lookups: HashSetDelay::new(LOOKUP_BACKOFF),
         log: log.clone(),
         sync_send: sync_send.clone(),
     };
@@ -306,19 +396,22 @@ impl<T: BeaconChainTypes> SyncManager<T> {
         request_id: RequestId,
         block: Option<SignedBeaconBlock<T::EthSpec>>,
     ) {
+        // check if this is a single block lookup - i.e we were searching for a specific hash
+        let single_block_hash = self
+            .single_block_lookups
+            .values()
+            .find(|single_block_request| single_block_request.pending == Some(request_id))
+            .map(|single_block_request| single_block_request.hash);
+
         match block {
             Some(block) => {
                 // data was returned, not just a stream termination
 
-                // check if this is a single block lookup - i.e we were searching for a specific hash
-                let mut single_block_hash = None;
-                if let Some(block_request) = self.single_block_lookups.get_mut(&request_id) {
-                    // update the state of the lookup indicating a block was received from the peer
-                    block_request.block_returned = true;
-                    single_block_hash = Some(block_request.hash.clone());
-                }
-                if let Some(block_hash) = single_block_hash {
-                    self.single_block_lookup_response(peer_id, block, block_hash);
+                // the lookup ends with the first block, the stream termination is ignored
+                if let Some(single_block_request) =
+                    single_block_hash.and_then(|hash| self.single_block_lookups.remove(&hash))
+                {
+                    self.single_block_lookup_response(peer_id, block, single_block_request);
                     return;
                 }
 
@@ -345,13 +438,15 @@ impl<T: BeaconChainTypes> SyncManager<T> {
             None => {
                 // this is a stream termination
 
-                // stream termination for a single block lookup, remove the key
-                if let Some(single_block_request) = self.single_block_lookups.remove(&request_id) {
-                    // the peer didn't respond with a block that it referenced
-                    if !single_block_request.block_returned {
-                        warn!(self.log, "Peer didn't respond with a block it referenced"; "referenced_block_hash" => format!("{}", single_block_request.hash), "peer_id" =>  format!("{}", peer_id));
-                        self.network.downvote_peer(peer_id);
-                    }
+                // stream termination for a single block lookup that is still pending, the peer
+                // didn't respond with a block that it referenced
+                if let Some(mut single_block_request) =
+                    single_block_hash.and_then(|hash| self.single_block_lookups.remove(&hash))
+                {
+                    warn!(self.log, "Peer didn't respond with a block it referenced"; "referenced_block_hash" => format!("{}", single_block_request.hash), "peer_id" =>  format!("{}", peer_id));
+                    single_block_request.peers.remove(&peer_id);
+                    self.network.downvote_peer(peer_id);
+                    self.single_block_lookup_failed(single_block_request);
                     return;
                 }
 
@@ -370,10 +465,11 @@ impl<T: BeaconChainTypes> SyncManager<T> {
                     }
                 };
                 // An empty response has been returned to a parent request
-                // if an empty response is given, the peer didn't have the requested block, try again
-                parent_request.failed_attempts += 1;
+                // if an empty response is given, the peer didn't have the requested block, try
+                // again with another peer
+                parent_request.peers.remove(&peer_id);
                 parent_request.last_submitted_peer = peer_id;
-                self.request_parent(parent_request);
+                self.parent_lookup_failed(parent_request);
             }
         }
     }
@@ -385,13 +481,15 @@ impl<T: BeaconChainTypes> SyncManager<T> {
         &mut self,
         peer_id: PeerId,
         block: SignedBeaconBlock<T::EthSpec>,
-        expected_block_hash: Hash256,
+        mut single_block_request: SingleBlockRequest,
     ) {
         // verify the hash is correct and try and process the block
-        if expected_block_hash != block.canonical_root() {
-            // the peer that sent this, sent us the wrong block
+        if single_block_request.hash != block.canonical_root() {
+            // the peer that sent this, sent us the wrong block, try another peer
             warn!(self.log, "Peer sent incorrect block for single block lookup"; "peer_id" => format!("{}", peer_id));
+            single_block_request.peers.remove(&peer_id);
             self.network.downvote_peer(peer_id);
+            self.single_block_lookup_failed(single_block_request);
             return;
         }
 
@@ -417,8 +515,16 @@ impl<T: BeaconChainTypes> SyncManager<T> {
                         }
                     }
                     BlockProcessingOutcome::ParentUnknown { .. } => {
-                        // We don't know of the blocks parent, begin a parent lookup search
+                        // We don't know of the blocks parent, begin a parent lookup search with
+                        // all the peers which referenced the block
                         self.add_unknown_block(peer_id, block);
+                        if let Some(parent_request) = self
+                            .parent_queue
+                            .iter_mut()
+                            .find(|request| request.block_root == single_block_request.hash)
+                        {
+                            parent_request.peers.extend(single_block_request.peers);
+                        }
                     }
                     BlockProcessingOutcome::BlockIsAlreadyKnown => {
                         trace!(self.log, "Single block lookup already known");
@@ -458,26 +564,49 @@ impl<T: BeaconChainTypes> SyncManager<T> {
             }
         }
 
-        // Make sure this block is not already being searched for
+        // Make sure this block is not already being searched for. If it is, the peer that sent it
+        // to us can also serve the existing lookup.
         // NOTE: Potentially store a hashset of blocks for O(1) lookups
-        for parent_req in self.parent_queue.iter() {
-            if parent_req
+        let block_root = block.canonical_root();
+        if let Some(parent_req) = self.parent_queue.iter_mut().find(|parent_req| {
+            parent_req
                 .downloaded_blocks
-                .iter()
-                .any(|d_block| d_block == &block)
-            {
-                // we are already searching for this block, ignore it
-                return;
-            }
+                .last()
+                .map(|d_block| d_block.parent_root())
+                == Some(block_root)
+                || parent_req
+                    .downloaded_blocks
+                    .iter()
+                    .any(|d_block| d_block == &block)
+        }) {
+            parent_req.peers.insert(peer_id);
+            return;
         }
 
-        debug!(self.log, "Unknown block received. Starting a parent lookup"; "block_slot" => block.message.slot, "block_hash" => format!("{}", block.canonical_root()));
+        debug!(self.log, "Unknown block received. Starting a parent lookup"; "block_slot" => block.message.slot, "block_hash" => format!("{}", block_root));
+
+        let mut peers = HashSet::new();
+        peers.insert(peer_id.clone());
+
+        let finalized_slot = self
+            .chain
+            .head_info()
+            .map(|info| {
+                info.finalized_checkpoint
+                    .epoch
+                    .start_slot(T::EthSpec::slots_per_epoch())
+            })
+            .unwrap_or_else(|_| Slot::from(0u64));
+        let max_depth = parent_depth_budget(block.message.slot, finalized_slot);
 
         let parent_request = ParentRequests {
+            block_root,
             downloaded_blocks: vec![block],
+            peers,
             failed_attempts: 0,
             last_submitted_peer: peer_id,
             pending: None,
+            max_depth,
         };
 
         self.request_parent(parent_request)
@@ -491,31 +620,152 @@ impl<T: BeaconChainTypes> SyncManager<T> {
             return;
         }
 
-        // Do not re-request a block that is already being requested
-        if self
-            .single_block_lookups
-            .values()
-            .find(|single_block_request| single_block_request.hash == block_hash)
-            .is_some()
-        {
+        // Do not re-request a block that is already being requested, the peer can instead serve
+        // the existing lookup
+        if let Some(single_block_request) = self.single_block_lookups.get_mut(&block_hash) {
+            single_block_request.peers.insert(peer_id);
             return;
         }
+        if let Some(parent_request) = self.parent_queue.iter_mut().find(|parent_request| {
+            parent_request.block_root == block_hash
+                || parent_request
+                    .downloaded_blocks
+                    .last()
+                    .map(|block| block.parent_root())
+                    == Some(block_hash)
+        }) {
+            parent_request.peers.insert(peer_id);
+            return;
+        }
+
+        self.request_single_block(SingleBlockRequest::new(block_hash, peer_id));
+    }
+
+    /// Requests the block of a single block lookup from one of the peers which referenced it.
+    fn request_single_block(&mut self, mut single_block_request: SingleBlockRequest) {
+        let peer_id = match self.select_lookup_peer(
+            &single_block_request.peers,
+            single_block_request.last_peer.as_ref(),
+        ) {
+            Some(peer_id) => peer_id,
+            None => return self.single_block_lookup_failed(single_block_request),
+        };
 
         let request = BlocksByRootRequest {
-            block_roots: VariableList::from(vec![block_hash]),
+            block_roots: VariableList::from(vec![single_block_request.hash]),
         };
 
-        if let Ok(request_id) = self.network.blocks_by_root_request(peer_id, request) {
-            self.single_block_lookups
-                .insert(request_id, SingleBlockRequest::new(block_hash));
+        match self
+            .network
+            .blocks_by_root_request(peer_id.clone(), request)
+        {
+            Ok(request_id) => {
+                single_block_request.pending = Some(request_id);
+                single_block_request.last_peer = Some(peer_id);
+                self.single_block_lookups
+                    .insert(single_block_request.hash, single_block_request);
+            }
+            Err(_) => self.single_block_lookup_failed(single_block_request),
+        }
+    }
+
+    /// Records a failed attempt of a single block lookup and schedules a retry, unless the lookup
+    /// has failed too many times.
+    fn single_block_lookup_failed(&mut self, mut single_block_request: SingleBlockRequest) {
+        single_block_request.failed_attempts += 1;
+        single_block_request.pending = None;
+
+        if single_block_request.failed_attempts >= SINGLE_BLOCK_FAIL_TOLERANCE {
+            debug!(self.log, "Single block lookup failed";
+                "block" => format!("{}", single_block_request.hash),
+                "peers" => single_block_request.peers.len(),
+                "reason" => "too many failed attempts"
+            );
+            return; // drop the request
+        }
+
+        self.delayed_lookups.insert_at(
+            single_block_request.hash,
+            lookup_backoff(single_block_request.failed_attempts),
+        );
+        self.single_block_lookups
+            .insert(single_block_request.hash, single_block_request);
+    }
+
+    /// Records a failed attempt of a parent lookup and schedules a retry, unless the lookup has
+    /// failed too many times.
+    fn parent_lookup_failed(&mut self, mut parent_request: ParentRequests<T::EthSpec>) {
+        parent_request.failed_attempts += 1;
+        parent_request.pending = None;
+
+        if parent_request.failed_attempts >= PARENT_FAIL_TOLERANCE {
+            // `request_parent` drops the lookup
+            self.request_parent(parent_request);
+            return;
         }
+
+        self.delayed_lookups.insert_at(
+            parent_request.block_root,
+            lookup_backoff(parent_request.failed_attempts),
+        );
+        self.parent_queue.push(parent_request);
+    }
+
+    /// The backoff of a failed lookup has elapsed, retry the lookup.
+    fn retry_lookup(&mut self, block_root: Hash256) {
+        if self
+            .single_block_lookups
+            .get(&block_root)
+            .map_or(false, |request| request.pending.is_none())
+        {
+            if let Some(single_block_request) = self.single_block_lookups.remove(&block_root) {
+                self.request_single_block(single_block_request);
+            }
+            return;
+        }
+
+        if let Some(pos) = self
+            .parent_queue
+            .iter()
+            .position(|request| request.block_root == block_root && request.pending.is_none())
+        {
+            let parent_request = self.parent_queue.remove(pos);
+            self.request_parent(parent_request);
+        }
+    }
+
+    /// Chooses a random connected peer out of `peers` to send a lookup request to. `last_peer` is
+    /// only chosen if no other peer is available, so that requests are spread across the peers.
+    fn select_lookup_peer(
+        &self,
+        peers: &HashSet<PeerId>,
+        last_peer: Option<&PeerId>,
+    ) -> Option<PeerId> {
+        let peerdb = self.network_globals.peers.read();
+        let (other_peers, last_peers): (Vec<&PeerId>, Vec<&PeerId>) = peers
+            .iter()
+            .filter(|peer_id| peerdb.is_connected(peer_id))
+            .partition(|peer_id| Some(*peer_id) != last_peer);
+
+        other_peers
+            .into_iter()
+            .choose(&mut rand::thread_rng())
+            .or_else(|| last_peers.into_iter().next())
+            .cloned()
     }
 
     fn inject_error(&mut self, peer_id: PeerId, request_id: RequestId) {
         trace!(self.log, "Sync manager received a failed RPC");
-        // remove any single block lookups
-        if self.single_block_lookups.remove(&request_id).is_some() {
-            // this was a single block request lookup, look no further
+        // retry any single block lookups
+        if let Some(block_hash) = self
+            .single_block_lookups
+            .values()
+            .find(|request| request.pending == Some(request_id))
+            .map(|request| request.hash)
+        {
+            if let Some(single_block_request) = self.single_block_lookups.remove(&block_hash) {
+                self.single_block_lookup_failed(single_block_request);
+            }
             return;
         }
 
@@ -526,9 +776,8 @@ impl<T: BeaconChainTypes> SyncManager<T> {
             .position(|request| request.pending == Some(request_id))
         {
             let mut parent_request = self.parent_queue.remove(pos);
-            parent_request.failed_attempts += 1;
             parent_request.last_submitted_peer = peer_id;
-            self.request_parent(parent_request);
+            self.parent_lookup_failed(parent_request);
             return;
         }
 
@@ -539,9 +788,50 @@ impl<T: BeaconChainTypes> SyncManager<T> {
 
     fn peer_disconnect(&mut self, peer_id: &PeerId) {
         self.range_sync.peer_disconnect(&mut self.network, peer_id);
+        self.lookup_peer_disconnected(peer_id);
         self.update_sync_state();
     }
 
+    /// Retries any lookups which are waiting on a request to a disconnected peer, as the peer will
+    /// no longer respond.
+    fn lookup_peer_disconnected(&mut self, peer_id: &PeerId) {
+        let failed_single_blocks: Vec<Hash256> = self
+            .single_block_lookups
+            .values()
+            .filter(|request| {
+                request.pending.is_some() && request.last_peer.as_ref() == Some(peer_id)
+            })
+            .map(|request| request.hash)
+            .collect();
+        for block_hash in failed_single_blocks {
+            if let Some(single_block_request) = self.single_block_lookups.remove(&block_hash) {
+                self.single_block_lookup_failed(single_block_request);
+            }
+        }
+
+        let (failed_parents, parent_queue): (Vec<_>, SmallVec<[_; 3]>) =
+            std::mem::take(&mut self.parent_queue)
+                .into_iter()
+                .partition(|request| {
+                    request.pending.is_some() && &request.last_submitted_peer == peer_id
+                });
+        self.parent_queue = parent_queue;
+        for parent_request in failed_parents {
+            self.parent_lookup_failed(parent_request);
+        }
+    }
+
+    /// Publishes the state of the current block lookups.
+    fn update_block_lookups(&self) {
+        let block_lookups = self
+            .single_block_lookups
+            .values()
+            .map(SingleBlockRequest::info)
+            .chain(self.parent_queue.iter().map(ParentRequests::info))
+            .collect();
+        *self.network_globals.block_lookups.write() = block_lookups;
+    }
+
     // TODO: Group these functions into one.
     /// Updates the syncing state of a peer to be synced.
     fn synced_peer(&mut self, peer_id: &PeerId, sync_info: PeerSyncInfo) {
@@ -628,7 +918,8 @@ impl<T: BeaconChainTypes> SyncManager<T> {
                 "expected_parent" => format!("{}", expected_hash),
             );
 
-            self.request_parent(parent_request);
+            parent_request.peers.remove(&peer);
+            self.parent_lookup_failed(parent_request);
             self.network.downvote_peer(peer);
         } else {
             // The last block in the queue is the only one that has not attempted to be processed yet.
@@ -696,12 +987,12 @@ impl<T: BeaconChainTypes> SyncManager<T> {
     fn request_parent(&mut self, mut parent_request: ParentRequests<T::EthSpec>) {
         // check to make sure this request hasn't failed
         if parent_request.failed_attempts >= PARENT_FAIL_TOLERANCE
-            || parent_request.downloaded_blocks.len() >= PARENT_DEPTH_TOLERANCE
+            || parent_request.downloaded_blocks.len() >= parent_request.max_depth
         {
             let error = if parent_request.failed_attempts >= PARENT_FAIL_TOLERANCE {
                 "too many failed attempts"
             } else {
-                "reached maximum lookup-depth"
+                "reached the lookup's depth budget"
             };
 
             debug!(self.log, "Parent import failed";
@@ -723,14 +1014,27 @@ impl<T: BeaconChainTypes> SyncManager<T> {
             block_roots: VariableList::from(vec![parent_hash]),
         };
 
-        // We continue to search for the chain of blocks from the same peer. Other peers are not
-        // guaranteed to have this chain of blocks.
-        let peer_id = parent_request.last_submitted_peer.clone();
+        // We only search for the chain of blocks from peers which have sent us a block of the
+        // chain. Other peers are not guaranteed to have this chain of blocks.
+        let peer_id = match self.select_lookup_peer(
+            &parent_request.peers,
+            Some(&parent_request.last_submitted_peer),
+        ) {
+            Some(peer_id) => peer_id,
+            None => return self.parent_lookup_failed(parent_request),
+        };
 
-        if let Ok(request_id) = self.network.blocks_by_root_request(peer_id, request) {
-            // if the request was successful add the queue back into self
-            parent_request.pending = Some(request_id);
-            self.parent_queue.push(parent_request);
+        match self
+            .network
+            .blocks_by_root_request(peer_id.clone(), request)
+        {
+            Ok(request_id) => {
+                // if the request was successful add the queue back into self
+                parent_request.pending = Some(request_id);
+                parent_request.last_submitted_peer = peer_id;
+                self.parent_queue.push(parent_request);
+            }
+            Err(_) => self.parent_lookup_failed(parent_request),
         }
     }
 
@@ -738,61 +1042,327 @@ impl<T: BeaconChainTypes> SyncManager<T> {
     async fn main(&mut self) {
         // process any inbound messages
         loop {
-            if let Some(sync_message) = self.input_channel.recv().await {
-                match sync_message {
-                    SyncMessage::AddPeer(peer_id, info) => {
-                        self.add_peer(peer_id, info);
-                    }
-                    SyncMessage::BlocksByRangeResponse {
-                        peer_id,
-                        request_id,
-                        beacon_block,
-                    } => {
-                        self.range_sync.blocks_by_range_response(
-                            &mut self.network,
+            tokio::select! {
+                Some(sync_message) = self.input_channel.recv() => {
+                    match sync_message {
+                        SyncMessage::AddPeer(peer_id, info) => {
+                            self.add_peer(peer_id, info);
+                        }
+                        SyncMessage::BlocksByRangeResponse {
                             peer_id,
                             request_id,
-                            beacon_block.map(|b| *b),
-                        );
-                    }
-                    SyncMessage::BlocksByRootResponse {
-                        peer_id,
-                        request_id,
-                        beacon_block,
-                    } => {
-                        self.blocks_by_root_response(peer_id, request_id, beacon_block.map(|b| *b));
-                    }
-                    SyncMessage::UnknownBlock(peer_id, block) => {
-                        self.add_unknown_block(peer_id, *block);
-                    }
-                    SyncMessage::UnknownBlockHash(peer_id, block_hash) => {
-                        self.search_for_block(peer_id, block_hash);
-                    }
-                    SyncMessage::Disconnect(peer_id) => {
-                        self.peer_disconnect(&peer_id);
-                    }
-                    SyncMessage::RPCError(peer_id, request_id) => {
-                        self.inject_error(peer_id, request_id);
-                    }
-                    SyncMessage::BatchProcessed {
-                        chain_id,
-                        batch_id,
-                        downloaded_blocks,
-                        result,
-                    } => {
-                        self.range_sync.handle_block_process_result(
-                            &mut self.network,
+                            beacon_block,
+                        } => {
+                            self.range_sync.blocks_by_range_response(
+                                &mut self.network,
+                                peer_id,
+                                request_id,
+                                beacon_block.map(|b| *b),
+                            );
+                        }
+                        SyncMessage::BlocksByRootResponse {
+                            peer_id,
+                            request_id,
+                            beacon_block,
+                        } => {
+                            self.blocks_by_root_response(
+                                peer_id,
+                                request_id,
+                                beacon_block.map(|b| *b),
+                            );
+                        }
+                        SyncMessage::UnknownBlock(peer_id, block) => {
+                            self.add_unknown_block(peer_id, *block);
+                        }
+                        SyncMessage::UnknownBlockHash(peer_id, block_hash) => {
+                            self.search_for_block(peer_id, block_hash);
+                        }
+                        SyncMessage::Disconnect(peer_id) => {
+                            self.peer_disconnect(&peer_id);
+                        }
+                        SyncMessage::RPCError(peer_id, request_id) => {
+                            self.inject_error(peer_id, request_id);
+                        }
+                        SyncMessage::BatchProcessed {
                             chain_id,
                             batch_id,
                             downloaded_blocks,
                             result,
-                        );
+                        } => {
+                            self.range_sync.handle_block_process_result(
+                                &mut self.network,
+                                chain_id,
+                                batch_id,
+                                downloaded_blocks,
+                                result,
+                            );
+                        }
+                        SyncMessage::ParentLookupFailed(peer_id) => {
+                            self.network.downvote_peer(peer_id);
+                        }
                     }
-                    SyncMessage::ParentLookupFailed(peer_id) => {
-                        self.network.downvote_peer(peer_id);
+                }
+                Some(result) = self.delayed_lookups.next() => {
+                    match result {
+                        Ok(block_root) => self.retry_lookup(block_root),
+                        Err(e) => {
+                            error!(self.log, "Failed to check for lookups to retry"; "error" => e);
+                        }
                     }
                 }
+                else => break,
+            }
+            self.update_block_lookups();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use beacon_chain::test_utils::{BeaconChainHarness, HarnessType};
+    use eth2_libp2p::discovery::{build_enr, CombinedKey, Keypair};
+    use eth2_libp2p::rpc::RequestId as RPCRequestId;
+    use eth2_libp2p::types::SyncState;
+    use eth2_libp2p::{CombinedKeyExt, NetworkConfig, Request};
+    use sloggers::{null::NullLoggerBuilder, Build};
+    use store::config::StoreConfig;
+    use types::{test_utils::generate_deterministic_keypairs, EnrForkId, MinimalEthSpec};
+
+    type E = MinimalEthSpec;
+
+    /// A `SyncManager` whose network context sends its requests to `network_recv` rather than to
+    /// the network service.
+    struct TestRig {
+        sync_manager: SyncManager<HarnessType<E>>,
+        network_recv: mpsc::UnboundedReceiver<NetworkMessage<E>>,
+    }
+
+    impl TestRig {
+        fn new() -> Self {
+            let log = NullLoggerBuilder.build().expect("logger should build");
+            let chain = Arc::new(
+                BeaconChainHarness::new(
+                    MinimalEthSpec,
+                    generate_deterministic_keypairs(8),
+                    StoreConfig::default(),
+                )
+                .chain,
+            );
+
+            let config = NetworkConfig::default();
+            let enr_key = CombinedKey::from_libp2p(&Keypair::generate_secp256k1()).unwrap();
+            let enr = build_enr::<E>(&enr_key, &config, EnrForkId::default()).unwrap();
+            let network_globals = Arc::new(NetworkGlobals::new(enr, 0, 0, vec![], &log));
+            // block lookups are only started once the node is synced
+            *network_globals.sync_state.write() = SyncState::Synced;
+
+            let (network_send, network_recv) = mpsc::unbounded_channel();
+            let (sync_send, sync_recv) = mpsc::unbounded_channel();
+            let sync_manager = SyncManager {
+                range_sync: RangeSync::new(
+                    chain.clone(),
+                    network_globals.clone(),
+                    sync_send.clone(),
+                    log.clone(),
+                ),
+                network: SyncNetworkContext::new(
+                    network_send,
+                    network_globals.clone(),
+                    log.clone(),
+                ),
+                chain,
+                network_globals,
+                input_channel: sync_recv,
+                parent_queue: SmallVec::new(),
+                single_block_lookups: FnvHashMap::default(),
+                delayed_lookups: HashSetDelay::new(LOOKUP_BACKOFF),
+                log,
+                sync_send,
+            };
+
+            Self {
+                sync_manager,
+                network_recv,
             }
         }
+
+        /// Adds a new connected peer to the peer-db.
+        fn connected_peer(&self) -> PeerId {
+            let peer_id = PeerId::random();
+            self.sync_manager
+                .network_globals
+                .peers
+                .write()
+                .connect_ingoing(&peer_id);
+            peer_id
+        }
+
+        /// Returns the next `BlocksByRoot` request sent to the network, skipping any other
+        /// messages (e.g. the disconnection of a downvoted peer).
+        fn blocks_by_root_request(&mut self) -> Option<(PeerId, RequestId, Vec<Hash256>)> {
+            while let Ok(message) = self.network_recv.try_recv() {
+                if let NetworkMessage::SendRequest {
+                    peer_id,
+                    request_id: RPCRequestId::Sync(request_id),
+                    request: Request::BlocksByRoot(request),
+                } = message
+                {
+                    return Some((peer_id, request_id, request.block_roots.to_vec()));
+                }
+            }
+            None
+        }
+
+        fn lookup(&self, block_root: &Hash256) -> Option<&SingleBlockRequest> {
+            self.sync_manager.single_block_lookups.get(block_root)
+        }
+    }
+
+    #[test]
+    fn parent_depth_budget_is_bounded_by_finalization() {
+        assert_eq!(parent_depth_budget(Slot::new(40), Slot::new(32)), 8);
+        assert_eq!(parent_depth_budget(Slot::new(32), Slot::new(32)), 1);
+        assert_eq!(parent_depth_budget(Slot::new(8), Slot::new(32)), 1);
+        assert_eq!(
+            parent_depth_budget(Slot::new(10_000), Slot::new(32)),
+            MAX_PARENT_DEPTH
+        );
+    }
+
+    #[tokio::test]
+    async fn empty_response_is_retried_on_another_peer() {
+        let mut rig = TestRig::new();
+        let block_root = Hash256::repeat_byte(1);
+        let peers = vec![rig.connected_peer(), rig.connected_peer()];
+        for peer_id in &peers {
+            rig.sync_manager
+                .search_for_block(peer_id.clone(), block_root);
+        }
+
+        let (first_peer, request_id, block_roots) = rig
+            .blocks_by_root_request()
+            .expect("should request the block");
+        assert_eq!(block_roots, vec![block_root]);
+
+        // the peer terminates the stream without sending the block it referenced
+        rig.sync_manager
+            .blocks_by_root_response(first_peer.clone(), request_id, None);
+        let lookup = rig.lookup(&block_root).expect("lookup should be kept");
+        assert_eq!(lookup.failed_attempts, 1);
+        assert_eq!(lookup.pending, None);
+        assert!(!lookup.peers.contains(&first_peer));
+        assert!(
+            rig.blocks_by_root_request().is_none(),
+            "should wait for the backoff before retrying"
+        );
+
+        rig.sync_manager.retry_lookup(block_root);
+        let (second_peer, second_request_id, _) = rig
+            .blocks_by_root_request()
+            .expect("should retry the lookup");
+        assert_ne!(second_peer, first_peer);
+        assert!(peers.contains(&second_peer));
+        assert_eq!(
+            rig.lookup(&block_root).unwrap().pending,
+            Some(second_request_id)
+        );
+    }
+
+    #[tokio::test]
+    async fn lookup_is_dropped_after_the_fail_tolerance() {
+        let mut rig = TestRig::new();
+        let block_root = Hash256::repeat_byte(2);
+        let peer_id = rig.connected_peer();
+        rig.sync_manager
+            .search_for_block(peer_id.clone(), block_root);
+
+        for attempt in 1..=SINGLE_BLOCK_FAIL_TOLERANCE {
+            let (request_peer, request_id, _) = rig
+                .blocks_by_root_request()
+                .expect("should request the block");
+            // the only peer is retried once no other peer is available
+            assert_eq!(request_peer, peer_id);
+            rig.sync_manager.inject_error(peer_id.clone(), request_id);
+
+            if attempt < SINGLE_BLOCK_FAIL_TOLERANCE {
+                assert_eq!(rig.lookup(&block_root).unwrap().failed_attempts, attempt);
+                rig.sync_manager.retry_lookup(block_root);
+            }
+        }
+
+        assert!(
+            rig.lookup(&block_root).is_none(),
+            "lookup should be dropped"
+        );
+        rig.sync_manager.retry_lookup(block_root);
+        assert!(rig.blocks_by_root_request().is_none());
+    }
+
+    #[tokio::test]
+    async fn concurrent_searches_for_a_block_are_merged() {
+        let mut rig = TestRig::new();
+        let block_root = Hash256::repeat_byte(3);
+        let first_peer = rig.connected_peer();
+        let second_peer = rig.connected_peer();
+
+        rig.sync_manager
+            .search_for_block(first_peer.clone(), block_root);
+        rig.sync_manager
+            .search_for_block(second_peer.clone(), block_root);
+
+        let (request_peer, _, _) = rig
+            .blocks_by_root_request()
+            .expect("should request the block");
+        assert_eq!(request_peer, first_peer);
+        assert!(
+            rig.blocks_by_root_request().is_none(),
+            "the second search should not send another request"
+        );
+
+        let lookup = rig.lookup(&block_root).expect("should have a lookup");
+        assert_eq!(lookup.peers.len(), 2);
+        assert!(lookup.peers.contains(&second_peer));
+
+        rig.sync_manager.update_block_lookups();
+        let block_lookups = rig.sync_manager.network_globals.block_lookups();
+        assert_eq!(block_lookups.len(), 1);
+        assert_eq!(block_lookups[0].block_root, block_root);
+        assert_eq!(block_lookups[0].kind, BlockLookupKind::Single);
+        assert_eq!(block_lookups[0].peers, 2);
+        assert!(block_lookups[0].request_pending);
+    }
+
+    #[tokio::test]
+    async fn disconnect_fails_a_pending_request() {
+        let mut rig = TestRig::new();
+        let block_root = Hash256::repeat_byte(4);
+        let first_peer = rig.connected_peer();
+        let second_peer = rig.connected_peer();
+
+        rig.sync_manager
+            .search_for_block(first_peer.clone(), block_root);
+        let (request_peer, _, _) = rig
+            .blocks_by_root_request()
+            .expect("should request the block");
+        assert_eq!(request_peer, first_peer);
+        rig.sync_manager
+            .search_for_block(second_peer.clone(), block_root);
+
+        rig.sync_manager
+            .network_globals
+            .peers
+            .write()
+            .disconnect(&first_peer);
+        rig.sync_manager.peer_disconnect(&first_peer);
+        let lookup = rig.lookup(&block_root).expect("lookup should be kept");
+        assert_eq!(lookup.failed_attempts, 1);
+        assert_eq!(lookup.pending, None);
+
+        rig.sync_manager.retry_lookup(block_root);
+        let (request_peer, _, _) = rig
+            .blocks_by_root_request()
+            .expect("should retry the lookup");
+        assert_eq!(request_peer, second_peer);
     }
 }
@@ -332,6 +332,34 @@ impl<T: EthSpec> OperationPool<T> {
     pub fn num_voluntary_exits(&self) -> usize {
         self.voluntary_exits.read().len()
     }
+
+    /// Returns all attestations in the pool, whether or not they are includable in a block.
+    pub fn get_all_attestations(&self) -> Vec<Attestation<T>> {
+        self.attestations
+            .read()
+            .values()
+            .flat_map(|attestations| attestations.iter().cloned())
+            .collect()
+    }
+
+    /// Returns all attester slashings in the pool.
+    pub fn get_all_attester_slashings(&self) -> Vec<AttesterSlashing<T>> {
+        self.attester_slashings
+            .read()
+            .iter()
+            .map(|(slashing, _)| slashing.clone())
+            .collect()
+    }
+
+    /// Returns all proposer slashings in the pool.
+    pub fn get_all_proposer_slashings(&self) -> Vec<ProposerSlashing> {
+        self.proposer_slashings.read().values().cloned().collect()
+    }
+
+    /// Returns all voluntary exits in the pool.
+    pub fn get_all_voluntary_exits(&self) -> Vec<SignedVoluntaryExit> {
+        self.voluntary_exits.read().values().cloned().collect()
+    }
 }
 
 /// Filter up to a maximum number of operations out of an iterator.
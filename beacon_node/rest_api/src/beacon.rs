@@ -96,12 +96,7 @@ pub fn get_block<T: BeaconChainTypes>(
         _ => return Err(ApiError::ServerError("Unexpected query parameter".into())),
     };
 
-    let block = beacon_chain.store.get_block(&block_root)?.ok_or_else(|| {
-        ApiError::NotFound(format!(
-            "Unable to find SignedBeaconBlock for root {:?}",
-            block_root
-        ))
-    })?;
+    let block = block_at_root(&beacon_chain, block_root)?;
 
     let response = BlockResponse {
         root: block_root,
@@ -165,7 +160,6 @@ pub fn stream_forks<T: BeaconChainTypes>(
         .header("Content-Type", "text/event-stream")
         .header("Connection", "Keep-Alive")
         .header("Cache-Control", "no-cache")
-        .header("Access-Control-Allow-Origin", "*")
         .body(body)
         .map_err(|e| ApiError::ServerError(format!("Failed to build response: {:?}", e)))?;
     Ok(response)
@@ -428,16 +422,7 @@ pub fn get_state<T: BeaconChainTypes>(
 
     let (root, state): (Hash256, BeaconState<T::EthSpec>) = match (key.as_ref(), value) {
         ("slot", value) => state_at_slot(&beacon_chain, parse_slot(&value)?)?,
-        ("root", value) => {
-            let root = &parse_root(&value)?;
-
-            let state = beacon_chain
-                .store
-                .get_state(root, None)?
-                .ok_or_else(|| ApiError::NotFound(format!("No state for root: {:?}", root)))?;
-
-            (*root, state)
-        }
+        ("root", value) => state_at_root(&beacon_chain, parse_root(&value)?)?,
         _ => return Err(ApiError::ServerError("Unexpected query parameter".into())),
     };
 
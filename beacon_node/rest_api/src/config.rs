@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
-use std::net::Ipv4Addr;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::PathBuf;
 
 /// Defines the encoding for the API.
 #[derive(Clone, Serialize, Deserialize, Copy)]
@@ -29,23 +30,43 @@ impl From<&str> for ApiEncodingFormat {
     }
 }
 
+/// The certificate and private key used to serve the REST API over HTTPS.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TlsConfig {
+    /// A PEM file containing the certificate chain, leaf certificate first.
+    pub cert: PathBuf,
+    /// A PEM file containing the PKCS #8 or RSA private key of the leaf certificate.
+    pub key: PathBuf,
+}
+
 /// HTTP REST API Configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     /// Enable the REST API server.
     pub enabled: bool,
-    /// The IPv4 address the REST API HTTP server will listen on.
-    pub listen_address: Ipv4Addr,
+    /// The IPv4 or IPv6 address the REST API HTTP server will listen on.
+    pub listen_address: IpAddr,
     /// The port the REST API HTTP server will listen on.
     pub port: u16,
+    /// If `Some`, serve HTTPS rather than plain HTTP.
+    pub tls_config: Option<TlsConfig>,
+    /// The origins which browsers may make cross-origin requests from. `*` allows any origin.
+    ///
+    /// If empty, no CORS headers are added to responses.
+    pub allowed_origins: Vec<String>,
+    /// If `Some`, every request must bear the token stored at this path (see `ApiToken`).
+    pub api_token_path: Option<PathBuf>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Config {
             enabled: false,
-            listen_address: Ipv4Addr::new(127, 0, 0, 1),
+            listen_address: IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
             port: 5052,
+            tls_config: None,
+            allowed_origins: vec![],
+            api_token_path: None,
         }
     }
 }
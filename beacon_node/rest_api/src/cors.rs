@@ -0,0 +1,128 @@
+use crate::{ApiError, ApiResult};
+use hyper::header::{
+    HeaderValue, ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS,
+    ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_MAX_AGE, VARY,
+};
+use hyper::{Body, Response, StatusCode};
+
+/// The origin which allows requests from any other origin.
+const ANY_ORIGIN: &str = "*";
+
+/// Decides which origins browsers may make cross-origin requests to the REST API from.
+///
+/// Handlers never set CORS headers themselves: every response (including event streams) is
+/// passed through `Self::apply` by the router.
+pub struct CorsPolicy {
+    allowed_origins: Vec<String>,
+}
+
+impl CorsPolicy {
+    pub fn new(allowed_origins: Vec<String>) -> Self {
+        Self { allowed_origins }
+    }
+
+    /// Returns `true` if any cross-origin requests are allowed.
+    ///
+    /// When disabled, responses are left exactly as the handlers built them.
+    pub fn is_enabled(&self) -> bool {
+        !self.allowed_origins.is_empty()
+    }
+
+    /// Returns the value of the `Access-Control-Allow-Origin` header for a request from `origin`,
+    /// or `None` if the origin is not allowed.
+    fn allow_origin(&self, origin: Option<&HeaderValue>) -> Option<HeaderValue> {
+        let origin = origin?;
+
+        let is_allowed =
+            |origin: &str| self.allowed_origins.iter().any(|allowed| allowed == origin);
+
+        if is_allowed(ANY_ORIGIN) {
+            Some(HeaderValue::from_static(ANY_ORIGIN))
+        } else if is_allowed(origin.to_str().ok()?) {
+            Some(origin.clone())
+        } else {
+            None
+        }
+    }
+
+    /// Adds the CORS headers for a request from `origin` to `response`, replacing any which the
+    /// handler set itself.
+    pub fn apply(&self, origin: Option<&HeaderValue>, response: &mut Response<Body>) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let headers = response.headers_mut();
+        headers.remove(ACCESS_CONTROL_ALLOW_ORIGIN);
+        if let Some(allow_origin) = self.allow_origin(origin) {
+            headers.insert(ACCESS_CONTROL_ALLOW_ORIGIN, allow_origin);
+            headers.insert(VARY, HeaderValue::from_static("Origin"));
+        }
+    }
+
+    /// Returns the response to a preflight (`OPTIONS`) request.
+    ///
+    /// The allowed origin is added afterwards by `Self::apply`, as for any other response.
+    pub fn preflight_response(&self) -> ApiResult {
+        Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .header(ACCESS_CONTROL_ALLOW_METHODS, "GET, POST, OPTIONS")
+            .header(
+                ACCESS_CONTROL_ALLOW_HEADERS,
+                "Accept, Authorization, Content-Type",
+            )
+            .header(ACCESS_CONTROL_MAX_AGE, "86400")
+            .body(Body::empty())
+            .map_err(|e| ApiError::ServerError(format!("Failed to build response: {:?}", e)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn allow_origin(policy: &CorsPolicy, origin: &'static str) -> Option<HeaderValue> {
+        policy.allow_origin(Some(&HeaderValue::from_static(origin)))
+    }
+
+    #[test]
+    fn allows_listed_origins_only() {
+        let policy = CorsPolicy::new(vec!["https://example.com".to_string()]);
+
+        assert_eq!(
+            allow_origin(&policy, "https://example.com"),
+            Some(HeaderValue::from_static("https://example.com"))
+        );
+        assert_eq!(allow_origin(&policy, "https://example.org"), None);
+        assert_eq!(policy.allow_origin(None), None);
+    }
+
+    #[test]
+    fn wildcard_allows_any_origin() {
+        let policy = CorsPolicy::new(vec![ANY_ORIGIN.to_string()]);
+
+        assert_eq!(
+            allow_origin(&policy, "https://example.org"),
+            Some(HeaderValue::from_static(ANY_ORIGIN))
+        );
+    }
+
+    #[test]
+    fn disabled_policy_leaves_responses_untouched() {
+        let policy = CorsPolicy::new(vec![]);
+        let mut response = Response::builder()
+            .header(ACCESS_CONTROL_ALLOW_ORIGIN, ANY_ORIGIN)
+            .body(Body::empty())
+            .unwrap();
+
+        policy.apply(
+            Some(&HeaderValue::from_static("https://example.org")),
+            &mut response,
+        );
+
+        assert_eq!(
+            response.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN),
+            Some(&HeaderValue::from_static(ANY_ORIGIN))
+        );
+    }
+}
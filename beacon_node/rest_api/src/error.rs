@@ -1,4 +1,5 @@
 use hyper::{Body, Response, StatusCode};
+use rest_types::standard::ErrorMessage;
 use std::error::Error as StdError;
 
 #[derive(PartialEq, Debug, Clone)]
@@ -8,6 +9,7 @@ pub enum ApiError {
     NotImplemented(String),
     BadRequest(String),
     NotFound(String),
+    Unauthorized(String),
     UnsupportedType(String),
     ImATeapot(String),       // Just in case.
     ProcessingError(String), // A 202 error, for when a block/attestation cannot be processed, but still transmitted.
@@ -23,6 +25,7 @@ impl ApiError {
             ApiError::NotImplemented(desc) => (StatusCode::NOT_IMPLEMENTED, desc),
             ApiError::BadRequest(desc) => (StatusCode::BAD_REQUEST, desc),
             ApiError::NotFound(desc) => (StatusCode::NOT_FOUND, desc),
+            ApiError::Unauthorized(desc) => (StatusCode::UNAUTHORIZED, desc),
             ApiError::UnsupportedType(desc) => (StatusCode::UNSUPPORTED_MEDIA_TYPE, desc),
             ApiError::ImATeapot(desc) => (StatusCode::IM_A_TEAPOT, desc),
             ApiError::ProcessingError(desc) => (StatusCode::ACCEPTED, desc),
@@ -41,6 +44,25 @@ impl Into<Response<Body>> for ApiError {
     }
 }
 
+impl ApiError {
+    /// Converts `self` into a response for the standard API, where the error is described by a
+    /// JSON `ErrorMessage` rather than plain text.
+    pub fn into_standard_response(self) -> Response<Body> {
+        let (status_code, message) = self.status_code();
+        let body = serde_json::to_string(&ErrorMessage {
+            code: status_code.as_u16(),
+            message,
+        })
+        .expect("ErrorMessage should always serialize.");
+
+        Response::builder()
+            .status(status_code)
+            .header("content-type", "application/json")
+            .body(Body::from(body))
+            .expect("Response should always be created.")
+    }
+}
+
 impl From<store::Error> for ApiError {
     fn from(e: store::Error) -> ApiError {
         ApiError::ServerError(format!("Database error: {:?}", e))
@@ -129,6 +129,31 @@ pub fn block_root_at_slot<T: BeaconChainTypes>(
     )?)
 }
 
+/// Returns the `SignedBeaconBlock` with the given `root`, if it is known.
+pub fn block_at_root<T: BeaconChainTypes>(
+    beacon_chain: &BeaconChain<T>,
+    root: Hash256,
+) -> Result<SignedBeaconBlock<T::EthSpec>, ApiError> {
+    beacon_chain.store.get_block(&root)?.ok_or_else(|| {
+        ApiError::NotFound(format!(
+            "Unable to find SignedBeaconBlock for root {:?}",
+            root
+        ))
+    })
+}
+
+/// Returns the `BeaconState` with the given `root` and the root, if the state is known.
+pub fn state_at_root<T: BeaconChainTypes>(
+    beacon_chain: &BeaconChain<T>,
+    root: Hash256,
+) -> Result<(Hash256, BeaconState<T::EthSpec>), ApiError> {
+    beacon_chain
+        .store
+        .get_state(&root, None)?
+        .map(|state| (root, state))
+        .ok_or_else(|| ApiError::NotFound(format!("No state for root: {:?}", root)))
+}
+
 /// Returns a `BeaconState` and it's root in the canonical chain of `beacon_chain` at the given
 /// `slot`, if possible.
 ///
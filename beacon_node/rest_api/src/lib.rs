@@ -8,6 +8,7 @@ mod advanced;
 mod beacon;
 pub mod config;
 mod consensus;
+mod cors;
 mod error;
 mod helpers;
 mod lighthouse;
@@ -17,31 +18,46 @@ mod node;
 mod response_builder;
 mod router;
 mod spec;
+mod standard;
+mod tls;
 mod url_query;
 mod validator;
 
-use beacon_chain::{BeaconChain, BeaconChainTypes};
-use bus::Bus;
+use api_token::ApiToken;
+use beacon_chain::{events::EventQueues, BeaconChain, BeaconChainTypes};
 use client_network::NetworkMessage;
 pub use config::ApiEncodingFormat;
+use cors::CorsPolicy;
 use error::{ApiError, ApiResult};
 use eth2_config::Eth2Config;
 use eth2_libp2p::NetworkGlobals;
 use futures::future::TryFutureExt;
+use futures::Future;
+use hyper::server::accept;
 use hyper::server::conn::AddrStream;
 use hyper::service::{make_service_fn, service_fn};
 use hyper::{Body, Request, Server};
-use parking_lot::Mutex;
-use slog::{info, warn};
+use slog::{debug, info, warn};
 use std::net::SocketAddr;
 use std::path::PathBuf;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::mpsc;
-use types::SignedBeaconBlockHash;
+use tokio_rustls::{server::TlsStream, TlsAcceptor};
+use types::Address;
 use url_query::UrlQuery;
 
 pub use crate::helpers::parse_pubkey_bytes;
-pub use config::Config;
+pub use api_token::API_TOKEN_FILENAME;
+pub use config::{Config, TlsConfig};
+
+/// The time to wait before accepting further HTTPS connections after failing to accept one (e.g.,
+/// because the process has run out of file descriptors).
+const ACCEPT_ERROR_DELAY: Duration = Duration::from_millis(500);
+/// The time a client has to complete the TLS handshake before its connection is dropped.
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
 
 pub type NetworkChannel<T> = mpsc::UnboundedSender<NetworkMessage<T>>;
 
@@ -60,14 +76,29 @@ pub fn start_server<T: BeaconChainTypes>(
     db_path: PathBuf,
     freezer_db_path: PathBuf,
     eth2_config: Eth2Config,
-    events: Arc<Mutex<Bus<SignedBeaconBlockHash>>>,
-) -> Result<SocketAddr, hyper::Error> {
-    let log = executor.log();
+    deposit_contract_address: Address,
+    events: EventQueues,
+) -> Result<SocketAddr, String> {
+    let log = executor.log().clone();
     let inner_log = log.clone();
     let eth2_config = Arc::new(eth2_config);
 
-    // Define the function that will build the request handler.
-    let make_service = make_service_fn(move |_socket: &AddrStream| {
+    let api_token = match &config.api_token_path {
+        Some(path) => {
+            let api_token = ApiToken::load_or_create(path)?;
+            info!(
+                log,
+                "HTTP API requires a token";
+                "path" => format!("{:?}", api_token.path()),
+            );
+            Some(Arc::new(api_token))
+        }
+        None => None,
+    };
+    let cors = Arc::new(CorsPolicy::new(config.allowed_origins.clone()));
+
+    // Define the function that will build the request handler for each connection.
+    let new_service = move || {
         let beacon_chain = beacon_chain.clone();
         let log = inner_log.clone();
         let eth2_config = eth2_config.clone();
@@ -76,6 +107,8 @@ pub fn start_server<T: BeaconChainTypes>(
         let db_path = db_path.clone();
         let freezer_db_path = freezer_db_path.clone();
         let events = events.clone();
+        let api_token = api_token.clone();
+        let cors = cors.clone();
 
         async move {
             Ok::<_, hyper::Error>(service_fn(move |req: Request<Body>| {
@@ -85,23 +118,29 @@ pub fn start_server<T: BeaconChainTypes>(
                     network_globals.clone(),
                     network_channel.clone(),
                     eth2_config.clone(),
+                    deposit_contract_address,
                     log.clone(),
                     db_path.clone(),
                     freezer_db_path.clone(),
                     events.clone(),
+                    api_token.clone(),
+                    cors.clone(),
                 )
             }))
         }
-    });
+    };
 
-    let bind_addr = (config.listen_address, config.port).into();
-    let server = Server::bind(&bind_addr).serve(make_service);
+    let bind_addr = SocketAddr::new(config.listen_address, config.port);
+    let listener = std::net::TcpListener::bind(bind_addr)
+        .map_err(|e| format!("Unable to bind HTTP API to {}: {:?}", bind_addr, e))?;
 
     // Determine the address the server is actually listening on.
     //
     // This may be different to `bind_addr` if bind port was 0 (this allows the OS to choose a free
     // port).
-    let actual_listen_addr = server.local_addr();
+    let actual_listen_addr = listener
+        .local_addr()
+        .map_err(|e| format!("Unable to read HTTP API listen address: {:?}", e))?;
 
     // Build a channel to kill the HTTP server.
     let exit = executor.exit();
@@ -111,12 +150,33 @@ pub fn start_server<T: BeaconChainTypes>(
         info!(inner_log, "HTTP service shutdown");
     };
 
+    let server: Pin<Box<dyn Future<Output = Result<(), hyper::Error>> + Send>> =
+        if let Some(tls_config) = &config.tls_config {
+            let connections = accept_tls_connections(
+                &executor,
+                listener,
+                TlsAcceptor::from(tls::server_config(tls_config)?),
+            )?;
+
+            Box::pin(
+                Server::builder(accept::from_stream(connections))
+                    .serve(make_service_fn(move |_stream: &TlsStream<TcpStream>| {
+                        new_service()
+                    }))
+                    .with_graceful_shutdown(server_exit),
+            )
+        } else {
+            Box::pin(
+                Server::from_tcp(listener)
+                    .map_err(|e| format!("Unable to start HTTP API: {:?}", e))?
+                    .serve(make_service_fn(move |_socket: &AddrStream| new_service()))
+                    .with_graceful_shutdown(server_exit),
+            )
+        };
+
     // Configure the `hyper` server to gracefully shutdown when the shutdown channel is triggered.
     let inner_log = log.clone();
     let server_future = server
-        .with_graceful_shutdown(async {
-            server_exit.await;
-        })
         .map_err(move |e| {
             warn!(
             inner_log,
@@ -130,9 +190,73 @@ pub fn start_server<T: BeaconChainTypes>(
         "HTTP API started";
         "address" => format!("{}", actual_listen_addr.ip()),
         "port" => actual_listen_addr.port(),
+        "tls" => config.tls_config.is_some(),
     );
 
     executor.spawn_without_exit(server_future, "http");
 
     Ok(actual_listen_addr)
 }
+
+/// Accepts TCP connections from `listener` and performs the TLS handshake on each, returning a
+/// stream of the established connections.
+///
+/// Each handshake runs in its own task, so that a slow or malicious client cannot prevent other
+/// connections from being accepted. Handshakes which take longer than `TLS_HANDSHAKE_TIMEOUT` are
+/// abandoned so that idle connections do not accumulate.
+fn accept_tls_connections(
+    executor: &environment::TaskExecutor,
+    listener: std::net::TcpListener,
+    acceptor: TlsAcceptor,
+) -> Result<mpsc::UnboundedReceiver<std::io::Result<TlsStream<TcpStream>>>, String> {
+    let log = executor.log().clone();
+    let mut listener = listener
+        .set_nonblocking(true)
+        .and_then(|()| TcpListener::from_std(listener))
+        .map_err(|e| format!("Unable to listen for HTTPS connections: {:?}", e))?;
+
+    let (connection_tx, connection_rx) = mpsc::unbounded_channel();
+    let handshake_executor = executor.clone();
+    let accept_future = async move {
+        loop {
+            let (stream, peer_addr) = match listener.accept().await {
+                Ok(connection) => connection,
+                Err(e) => {
+                    warn!(log, "Unable to accept HTTPS connection"; "error" => e.to_string());
+                    tokio::time::delay_for(ACCEPT_ERROR_DELAY).await;
+                    continue;
+                }
+            };
+
+            let acceptor = acceptor.clone();
+            let connection_tx = connection_tx.clone();
+            let log = log.clone();
+            handshake_executor.spawn_without_exit(
+                async move {
+                    match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await
+                    {
+                        Ok(Ok(stream)) => {
+                            let _ = connection_tx.send(Ok(stream));
+                        }
+                        Ok(Err(e)) => debug!(
+                            log,
+                            "HTTPS handshake failed";
+                            "peer" => peer_addr.to_string(),
+                            "error" => e.to_string(),
+                        ),
+                        Err(_) => debug!(
+                            log,
+                            "HTTPS handshake timed out";
+                            "peer" => peer_addr.to_string(),
+                        ),
+                    }
+                },
+                "http_tls_handshake",
+            );
+        }
+    };
+
+    executor.spawn(accept_future, "http_tls_accept");
+
+    Ok(connection_rx)
+}
@@ -2,18 +2,31 @@
 
 use crate::response_builder::ResponseBuilder;
 use crate::ApiResult;
+use eth2_libp2p::types::{BlockLookupInfo, SyncState};
 use eth2_libp2p::{NetworkGlobals, PeerInfo};
 use hyper::{Body, Request};
 use serde::Serialize;
 use std::sync::Arc;
 use types::EthSpec;
 
-/// The syncing state of the beacon node.
+/// The syncing state of the beacon node and its searches for unknown blocks.
 pub fn syncing<T: EthSpec>(
     req: Request<Body>,
     network_globals: Arc<NetworkGlobals<T>>,
 ) -> ApiResult {
-    ResponseBuilder::new(&req)?.body_no_ssz(&network_globals.sync_state())
+    let syncing = Syncing {
+        sync_state: network_globals.sync_state(),
+        block_lookups: network_globals.block_lookups(),
+    };
+    ResponseBuilder::new(&req)?.body_no_ssz(&syncing)
+}
+
+/// The attestation subnets the beacon node is subscribed to, or plans to subscribe to.
+pub fn subnets<T: EthSpec>(
+    req: Request<Body>,
+    network_globals: Arc<NetworkGlobals<T>>,
+) -> ApiResult {
+    ResponseBuilder::new(&req)?.body_no_ssz(&network_globals.subnet_subscriptions())
 }
 
 /// Returns all known peers and corresponding information
@@ -56,3 +69,12 @@ struct Peer<T: EthSpec> {
     /// The PeerInfo associated with the peer.
     peer_info: PeerInfo<T>,
 }
+
+/// Information returned by `syncing`.
+#[derive(Clone, Debug, Serialize)]
+struct Syncing {
+    /// The current sync state of the node.
+    sync_state: SyncState,
+    /// The blocks (and their ancestors) currently being searched for.
+    block_lookups: Vec<BlockLookupInfo>,
+}
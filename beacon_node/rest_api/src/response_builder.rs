@@ -2,6 +2,7 @@ use super::{ApiError, ApiResult};
 use crate::config::ApiEncodingFormat;
 use hyper::header;
 use hyper::{Body, Request, Response, StatusCode};
+use rest_types::standard::GenericResponse;
 use serde::Serialize;
 use ssz::Encode;
 
@@ -73,6 +74,21 @@ impl ResponseBuilder {
             .map_err(|e| ApiError::ServerError(format!("Failed to build response: {:?}", e)))
     }
 
+    /// Returns `item` wrapped in the standard API response envelope (i.e., `{"data": item}`).
+    ///
+    /// SSZ responses are not wrapped, since the envelope has no SSZ representation.
+    pub fn data<T: Serialize + Encode>(self, item: &T) -> ApiResult {
+        match self.encoding {
+            ApiEncodingFormat::SSZ => self.body(item),
+            _ => self.data_no_ssz(item),
+        }
+    }
+
+    /// Returns `item` wrapped in the standard API response envelope, refusing SSZ.
+    pub fn data_no_ssz<T: Serialize>(self, item: &T) -> ApiResult {
+        self.body_no_ssz(&GenericResponse { data: item })
+    }
+
     pub fn body_text(self, text: String) -> ApiResult {
         Response::builder()
             .status(StatusCode::OK)
@@ -1,18 +1,18 @@
 use crate::{
-    advanced, beacon, consensus, error::ApiError, helpers, lighthouse, metrics, network, node,
-    spec, validator, NetworkChannel,
+    advanced, beacon, consensus, cors::CorsPolicy, error::ApiError, helpers, lighthouse, metrics,
+    network, node, spec, standard, validator, NetworkChannel,
 };
-use beacon_chain::{BeaconChain, BeaconChainTypes};
-use bus::Bus;
+use api_token::ApiToken;
+use beacon_chain::{events::EventQueues, BeaconChain, BeaconChainTypes};
 use eth2_config::Eth2Config;
 use eth2_libp2p::NetworkGlobals;
+use hyper::header::{AUTHORIZATION, ORIGIN};
 use hyper::{Body, Error, Method, Request, Response};
-use parking_lot::Mutex;
 use slog::debug;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Instant;
-use types::{SignedBeaconBlockHash, Slot};
+use types::{Address, Slot};
 
 // Allowing more than 7 arguments.
 #[allow(clippy::too_many_arguments)]
@@ -22,10 +22,13 @@ pub async fn route<T: BeaconChainTypes>(
     network_globals: Arc<NetworkGlobals<T::EthSpec>>,
     network_channel: NetworkChannel<T::EthSpec>,
     eth2_config: Arc<Eth2Config>,
+    deposit_contract_address: Address,
     local_log: slog::Logger,
     db_path: PathBuf,
     freezer_db_path: PathBuf,
-    events: Arc<Mutex<Bus<SignedBeaconBlockHash>>>,
+    events: EventQueues,
+    api_token: Option<Arc<ApiToken>>,
+    cors: Arc<CorsPolicy>,
 ) -> Result<Response<Body>, Error> {
     metrics::inc_counter(&metrics::REQUEST_COUNT);
     let timer = metrics::start_timer(&metrics::REQUEST_RESPONSE_TIME);
@@ -34,7 +37,33 @@ pub async fn route<T: BeaconChainTypes>(
     let path = req.uri().path().to_string();
 
     let log = local_log.clone();
+    let is_standard = path.starts_with(standard::PATH_PREFIX);
+    let origin = req.headers().get(ORIGIN).cloned();
+    let authorized = api_token.as_ref().map_or(true, |token| {
+        token.authorizes(req.headers().get(AUTHORIZATION))
+    });
     let request_result = match (req.method(), path.as_ref()) {
+        // Browsers never send credentials with a CORS preflight request, so it must be answered
+        // before checking the API token.
+        (&Method::OPTIONS, _) if cors.is_enabled() => cors.preflight_response(),
+        _ if !authorized => Err(ApiError::Unauthorized(
+            "Missing or invalid API token".to_string(),
+        )),
+
+        // Methods for the standard API
+        _ if is_standard => {
+            standard::route(
+                req,
+                beacon_chain,
+                network_globals,
+                network_channel,
+                deposit_contract_address,
+                events,
+                log,
+            )
+            .await
+        }
+
         // Methods for Client
         (&Method::GET, "/node/health") => node::get_health(req),
         (&Method::GET, "/node/version") => node::get_version(req),
@@ -67,7 +96,7 @@ pub async fn route<T: BeaconChainTypes>(
         (&Method::GET, "/beacon/block_root") => beacon::get_block_root::<T>(req, beacon_chain),
         (&Method::GET, "/beacon/fork") => beacon::get_fork::<T>(req, beacon_chain),
         (&Method::GET, "/beacon/fork/stream") => {
-            let reader = events.lock().add_rx();
+            let reader = events.head_changed.lock().add_rx();
             beacon::stream_forks::<T>(log, reader)
         }
         (&Method::GET, "/beacon/genesis_time") => beacon::get_genesis_time::<T>(req, beacon_chain),
@@ -171,6 +200,10 @@ pub async fn route<T: BeaconChainTypes>(
             lighthouse::syncing::<T::EthSpec>(req, network_globals)
         }
 
+        (&Method::GET, "/lighthouse/subnets") => {
+            lighthouse::subnets::<T::EthSpec>(req, network_globals)
+        }
+
         (&Method::GET, "/lighthouse/peers") => {
             lighthouse::peers::<T::EthSpec>(req, network_globals)
         }
@@ -188,7 +221,7 @@ pub async fn route<T: BeaconChainTypes>(
     // (e.g., a response with a 404 or 500 status).
     let duration = Instant::now().duration_since(received_instant);
     match request_result {
-        Ok(response) => {
+        Ok(mut response) => {
             debug!(
                 local_log,
                 "HTTP API request successful";
@@ -198,10 +231,15 @@ pub async fn route<T: BeaconChainTypes>(
             metrics::inc_counter(&metrics::SUCCESS_COUNT);
             metrics::stop_timer(timer);
 
+            cors.apply(origin.as_ref(), &mut response);
             Ok(response)
         }
         Err(e) => {
-            let error_response = e.into();
+            let mut error_response = if is_standard {
+                e.into_standard_response()
+            } else {
+                e.into()
+            };
 
             debug!(
                 local_log,
@@ -211,6 +249,7 @@ pub async fn route<T: BeaconChainTypes>(
             );
             metrics::stop_timer(timer);
 
+            cors.apply(origin.as_ref(), &mut error_response);
             Ok(error_response)
         }
     }
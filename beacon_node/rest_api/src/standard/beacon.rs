@@ -0,0 +1,546 @@
+use super::{BlockId, StateId};
+use crate::helpers::{
+    block_root_at_slot, check_content_type_for_json, parse_committee_index, parse_epoch,
+    parse_pubkey_bytes, parse_slot,
+};
+use crate::response_builder::ResponseBuilder;
+use crate::validator::{get_state_for_epoch, process_unaggregated_attestation};
+use crate::{ApiError, ApiResult, NetworkChannel, UrlQuery};
+use beacon_chain::{
+    observed_operations::ObservationOutcome, BeaconChain, BeaconChainError, BeaconChainTypes,
+    StateSkipConfig,
+};
+use eth2_libp2p::PubsubMessage;
+use hyper::{Body, Request};
+use network::NetworkMessage;
+use rest_types::standard::{
+    BlockHeaderData, CommitteeData, FinalityCheckpointsData, GenesisData, RootData, ValidatorData,
+    ValidatorStatus,
+};
+use serde::de::DeserializeOwned;
+use slog::{debug, Logger};
+use state_processing::SigVerifiedOp;
+use std::collections::HashMap;
+use std::sync::Arc;
+use types::{
+    Attestation, BeaconState, Epoch, EthSpec, PublicKeyBytes, RelativeEpoch,
+    SignedBeaconBlockHeader, SubnetId,
+};
+
+/// HTTP handler to return the genesis time, validators root and fork version.
+pub fn get_genesis<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let head_info = beacon_chain.head_info()?;
+
+    ResponseBuilder::new(&req)?.data_no_ssz(&GenesisData {
+        genesis_time: head_info.genesis_time,
+        genesis_validators_root: head_info.genesis_validators_root,
+        genesis_fork_version: beacon_chain.spec.genesis_fork_version,
+    })
+}
+
+/// HTTP handler to return the root of the state identified by `state_id`.
+pub fn get_state_root<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    state_id: &str,
+) -> ApiResult {
+    let root = state_id.parse::<StateId>()?.root(&beacon_chain)?;
+
+    ResponseBuilder::new(&req)?.data_no_ssz(&RootData { root })
+}
+
+/// HTTP handler to return the `Fork` of the state identified by `state_id`.
+pub fn get_state_fork<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    state_id: &str,
+) -> ApiResult {
+    let (_root, state) = state_id.parse::<StateId>()?.state(&beacon_chain)?;
+
+    ResponseBuilder::new(&req)?.data(&state.fork)
+}
+
+/// HTTP handler to return the justified and finalized checkpoints of the state identified by
+/// `state_id`.
+pub fn get_state_finality_checkpoints<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    state_id: &str,
+) -> ApiResult {
+    let (_root, state) = state_id.parse::<StateId>()?.state(&beacon_chain)?;
+
+    ResponseBuilder::new(&req)?.data_no_ssz(&FinalityCheckpointsData::from(&state))
+}
+
+/// HTTP handler to return the validators of the state identified by `state_id`.
+///
+/// Accepts any number of `id` query parameters (either validator indices or `0x` prefixed public
+/// keys) to restrict the response. Unknown validators are omitted from the response.
+pub fn get_state_validators<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    state_id: &str,
+) -> ApiResult {
+    let (_root, mut state) = state_id.parse::<StateId>()?.state(&beacon_chain)?;
+    let ids = UrlQuery::from_request(&req)?.all_of("id")?;
+
+    let validators = if ids.is_empty() {
+        (0..state.validators.len())
+            .map(|index| validator_data(&state, index))
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        state.update_pubkey_cache()?;
+
+        ids.iter()
+            .flat_map(|id| id.split(','))
+            .filter_map(|id| validator_index(&state, id).transpose())
+            .map(|index| index.and_then(|index| validator_data(&state, index)))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+
+    ResponseBuilder::new(&req)?.data_no_ssz(&validators)
+}
+
+/// HTTP handler to return the validator identified by `validator_id` in the state identified by
+/// `state_id`.
+pub fn get_state_validator<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    state_id: &str,
+    validator_id: &str,
+) -> ApiResult {
+    let (_root, mut state) = state_id.parse::<StateId>()?.state(&beacon_chain)?;
+    state.update_pubkey_cache()?;
+
+    let index = validator_index(&state, validator_id)?
+        .ok_or_else(|| ApiError::NotFound(format!("Unknown validator: {}", validator_id)))?;
+
+    ResponseBuilder::new(&req)?.data_no_ssz(&validator_data(&state, index)?)
+}
+
+/// Resolves `validator_id` (either an index or a `0x` prefixed public key) to an index in `state`.
+///
+/// Returns `Ok(None)` if the validator is not known to `state`. The `state` must have an
+/// up-to-date pubkey cache.
+fn validator_index<E: EthSpec>(
+    state: &BeaconState<E>,
+    validator_id: &str,
+) -> Result<Option<usize>, ApiError> {
+    if validator_id.starts_with("0x") {
+        let pubkey: PublicKeyBytes = parse_pubkey_bytes(validator_id)?;
+        state
+            .get_validator_index(&pubkey)
+            .map_err(|e| ApiError::ServerError(format!("Unable to read pubkey cache: {:?}", e)))
+    } else {
+        validator_id
+            .parse::<usize>()
+            .map(|index| Some(index).filter(|index| *index < state.validators.len()))
+            .map_err(|e| ApiError::BadRequest(format!("Invalid validator id: {:?}", e)))
+    }
+}
+
+/// Returns the `ValidatorData` for the validator at `index` in `state`.
+fn validator_data<E: EthSpec>(
+    state: &BeaconState<E>,
+    index: usize,
+) -> Result<ValidatorData, ApiError> {
+    let validator = state
+        .validators
+        .get(index)
+        .ok_or_else(|| ApiError::ServerError(format!("Invalid validator index: {:?}", index)))?;
+    let balance = *state
+        .balances
+        .get(index)
+        .ok_or_else(|| ApiError::ServerError(format!("Invalid balances index: {:?}", index)))?;
+
+    Ok(ValidatorData {
+        index: index as u64,
+        balance,
+        status: ValidatorStatus::from_validator(validator, balance, state.current_epoch()),
+        validator: validator.clone(),
+    })
+}
+
+/// HTTP handler to return the committees for `epoch` in the state identified by `state_id`.
+///
+/// The optional `index` and `slot` query parameters restrict the response.
+pub fn get_state_committees<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    state_id: &str,
+    epoch: &str,
+) -> ApiResult {
+    let epoch = parse_epoch(epoch)?;
+    let (_root, mut state) = state_id.parse::<StateId>()?.state(&beacon_chain)?;
+
+    let query = UrlQuery::from_request(&req)?;
+    let index_opt = query
+        .first_of_opt(&["index"])
+        .map(|(_key, value)| parse_committee_index(&value))
+        .transpose()?;
+    let slot_opt = query
+        .first_of_opt(&["slot"])
+        .map(|(_key, value)| parse_slot(&value))
+        .transpose()?;
+
+    let relative_epoch = RelativeEpoch::from_epoch(state.current_epoch(), epoch).map_err(|_| {
+        ApiError::BadRequest(format!(
+            "Epoch {} is not within one epoch of the requested state",
+            epoch
+        ))
+    })?;
+
+    state
+        .build_committee_cache(relative_epoch, &beacon_chain.spec)
+        .map_err(|e| ApiError::ServerError(format!("Unable to build committee cache: {:?}", e)))?;
+
+    let committees = state
+        .get_beacon_committees_at_epoch(relative_epoch)
+        .map_err(|e| ApiError::ServerError(format!("Unable to get all committees: {:?}", e)))?
+        .into_iter()
+        .filter(|c| index_opt.map_or(true, |index| c.index == index))
+        .filter(|c| slot_opt.map_or(true, |slot| c.slot == slot))
+        .map(|c| CommitteeData {
+            index: c.index,
+            slot: c.slot,
+            validators: c.committee.iter().map(|i| *i as u64).collect(),
+        })
+        .collect::<Vec<_>>();
+
+    ResponseBuilder::new(&req)?.data_no_ssz(&committees)
+}
+
+/// HTTP handler to return block headers.
+///
+/// Returns the canonical header at the `slot` query parameter, or the head header if it is
+/// omitted.
+pub fn get_headers<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let block_id = match UrlQuery::from_request(&req)?.first_of_opt(&["slot"]) {
+        Some((_key, value)) => BlockId::Slot(parse_slot(&value)?),
+        None => BlockId::Head,
+    };
+
+    let header = block_header_data(&beacon_chain, block_id)?;
+
+    ResponseBuilder::new(&req)?.data_no_ssz(&vec![header])
+}
+
+/// HTTP handler to return the header of the block identified by `block_id`.
+pub fn get_header<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    block_id: &str,
+) -> ApiResult {
+    let header = block_header_data(&beacon_chain, block_id.parse()?)?;
+
+    ResponseBuilder::new(&req)?.data_no_ssz(&header)
+}
+
+/// Returns the `BlockHeaderData` for the block identified by `block_id`.
+fn block_header_data<T: BeaconChainTypes>(
+    beacon_chain: &BeaconChain<T>,
+    block_id: BlockId,
+) -> Result<BlockHeaderData, ApiError> {
+    let (root, block) = block_id.block(beacon_chain)?;
+    let canonical = block_root_at_slot(beacon_chain, block.slot())? == Some(root);
+
+    Ok(BlockHeaderData {
+        root,
+        canonical,
+        header: SignedBeaconBlockHeader {
+            message: block.message.block_header(),
+            signature: block.signature,
+        },
+    })
+}
+
+/// HTTP handler to return the block identified by `block_id`.
+pub fn get_block<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    block_id: &str,
+) -> ApiResult {
+    let (_root, block) = block_id.parse::<BlockId>()?.block(&beacon_chain)?;
+
+    ResponseBuilder::new(&req)?.data(&block)
+}
+
+/// HTTP handler to return the root of the block identified by `block_id`.
+pub fn get_block_root<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    block_id: &str,
+) -> ApiResult {
+    let root = block_id.parse::<BlockId>()?.root(&beacon_chain)?;
+
+    ResponseBuilder::new(&req)?.data_no_ssz(&RootData { root })
+}
+
+/// HTTP handler to return the attestations included in the block identified by `block_id`.
+pub fn get_block_attestations<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    block_id: &str,
+) -> ApiResult {
+    let (_root, block) = block_id.parse::<BlockId>()?.block(&beacon_chain)?;
+
+    ResponseBuilder::new(&req)?.data(&block.message.body.attestations)
+}
+
+/// HTTP handler to return all attestations in the operation pool.
+pub fn get_pool_attestations<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    ResponseBuilder::new(&req)?.data(&beacon_chain.op_pool.get_all_attestations())
+}
+
+/// HTTP handler to return all attester slashings in the operation pool.
+pub fn get_pool_attester_slashings<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    ResponseBuilder::new(&req)?.data(&beacon_chain.op_pool.get_all_attester_slashings())
+}
+
+/// HTTP handler to return all proposer slashings in the operation pool.
+pub fn get_pool_proposer_slashings<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    ResponseBuilder::new(&req)?.data(&beacon_chain.op_pool.get_all_proposer_slashings())
+}
+
+/// HTTP handler to return all voluntary exits in the operation pool.
+pub fn get_pool_voluntary_exits<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    ResponseBuilder::new(&req)?.data(&beacon_chain.op_pool.get_all_voluntary_exits())
+}
+
+/// HTTP handler to verify, import and publish a list of unaggregated attestations.
+///
+/// Unlike the `/validator/attestations` endpoint, the subnet of each attestation is computed by
+/// the beacon node.
+pub async fn post_pool_attestations<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    network_chan: NetworkChannel<T::EthSpec>,
+    log: Logger,
+) -> ApiResult {
+    try_future!(check_content_type_for_json(&req));
+    let response_builder = ResponseBuilder::new(&req);
+
+    let body = req.into_body();
+    let chunks = hyper::body::to_bytes(body)
+        .await
+        .map_err(|e| ApiError::ServerError(format!("Unable to get request body: {:?}", e)))?;
+
+    let attestations =
+        serde_json::from_slice::<Vec<Attestation<T::EthSpec>>>(&chunks).map_err(|e| {
+            ApiError::BadRequest(format!(
+                "Unable to deserialize JSON into a list of attestations: {:?}",
+                e
+            ))
+        })?;
+
+    // The committee count is the same for every slot in an epoch, so only load one state for
+    // each distinct epoch in the request.
+    let mut committee_counts: HashMap<Epoch, u64> = HashMap::new();
+
+    // Process all of the attestations _without_ exiting early if one fails, then return the first
+    // failure (if any).
+    attestations
+        .into_iter()
+        .enumerate()
+        .map(|(i, attestation)| {
+            let epoch = attestation.data.slot.epoch(T::EthSpec::slots_per_epoch());
+            let committee_count = match committee_counts.get(&epoch) {
+                Some(count) => *count,
+                None => {
+                    let count = committee_count_per_slot(&beacon_chain, epoch)?;
+                    committee_counts.insert(epoch, count);
+                    count
+                }
+            };
+            let subnet_id = SubnetId::compute_subnet_for_attestation_data::<T::EthSpec>(
+                &attestation.data,
+                committee_count,
+                &beacon_chain.spec,
+            )
+            .map_err(|e| ApiError::ServerError(format!("Unable to compute subnet: {:?}", e)))?;
+
+            process_unaggregated_attestation(
+                &beacon_chain,
+                network_chan.clone(),
+                attestation,
+                subnet_id,
+                i,
+                &log,
+            )
+        })
+        .collect::<Vec<_>>()
+        .into_iter()
+        .collect::<Result<(), _>>()?;
+
+    response_builder?.body_no_ssz(&())
+}
+
+/// Returns the number of committees in each slot of `epoch`, as required to compute an
+/// attestation subnet.
+fn committee_count_per_slot<T: BeaconChainTypes>(
+    beacon_chain: &BeaconChain<T>,
+    epoch: Epoch,
+) -> Result<u64, ApiError> {
+    let mut state = get_state_for_epoch(beacon_chain, epoch, StateSkipConfig::WithoutStateRoots)?;
+
+    let relative_epoch = RelativeEpoch::from_epoch(state.current_epoch(), epoch)
+        .map_err(|_| ApiError::ServerError(String::from("Loaded state is in the wrong epoch")))?;
+    state
+        .build_committee_cache(relative_epoch, &beacon_chain.spec)
+        .map_err(|e| ApiError::ServerError(format!("Unable to build committee cache: {:?}", e)))?;
+
+    state
+        .get_committee_count_at_slot(epoch.start_slot(T::EthSpec::slots_per_epoch()))
+        .map_err(|e| ApiError::ServerError(format!("Unable to get committee count: {:?}", e)))
+}
+
+/// HTTP handler to verify, import and publish a voluntary exit.
+pub async fn post_pool_voluntary_exits<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    network_chan: NetworkChannel<T::EthSpec>,
+    log: Logger,
+) -> ApiResult {
+    post_pool_operation(
+        req,
+        beacon_chain,
+        network_chan,
+        log,
+        "voluntary exit",
+        |beacon_chain, exit| beacon_chain.verify_voluntary_exit_for_gossip(exit),
+        |exit| PubsubMessage::VoluntaryExit(Box::new(exit)),
+        |beacon_chain, verified_exit| {
+            beacon_chain.import_voluntary_exit(verified_exit);
+            Ok(())
+        },
+    )
+    .await
+}
+
+/// HTTP handler to verify, import and publish an attester slashing.
+pub async fn post_pool_attester_slashings<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    network_chan: NetworkChannel<T::EthSpec>,
+    log: Logger,
+) -> ApiResult {
+    post_pool_operation(
+        req,
+        beacon_chain,
+        network_chan,
+        log,
+        "attester slashing",
+        |beacon_chain, slashing| beacon_chain.verify_attester_slashing_for_gossip(slashing),
+        |slashing| PubsubMessage::AttesterSlashing(Box::new(slashing)),
+        |beacon_chain, verified_slashing| {
+            beacon_chain
+                .import_attester_slashing(verified_slashing)
+                .map_err(|e| {
+                    ApiError::ServerError(format!("Unable to import attester slashing: {:?}", e))
+                })
+        },
+    )
+    .await
+}
+
+/// HTTP handler to verify, import and publish a proposer slashing.
+pub async fn post_pool_proposer_slashings<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    network_chan: NetworkChannel<T::EthSpec>,
+    log: Logger,
+) -> ApiResult {
+    post_pool_operation(
+        req,
+        beacon_chain,
+        network_chan,
+        log,
+        "proposer slashing",
+        |beacon_chain, slashing| beacon_chain.verify_proposer_slashing_for_gossip(slashing),
+        |slashing| PubsubMessage::ProposerSlashing(Box::new(slashing)),
+        |beacon_chain, verified_slashing| {
+            beacon_chain.import_proposer_slashing(verified_slashing);
+            Ok(())
+        },
+    )
+    .await
+}
+
+/// Verifies, imports and publishes an operation of type `O` from the body of `req`, where
+/// `description` names the operation in errors and logs.
+///
+/// The operation is published with the message built by `to_message` and imported with `import`,
+/// unless `verify` finds that it is already known.
+#[allow(clippy::too_many_arguments)]
+async fn post_pool_operation<T, O, V, M, I>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    network_chan: NetworkChannel<T::EthSpec>,
+    log: Logger,
+    description: &str,
+    verify: V,
+    to_message: M,
+    import: I,
+) -> ApiResult
+where
+    T: BeaconChainTypes,
+    O: DeserializeOwned + Clone,
+    V: Fn(&BeaconChain<T>, O) -> Result<ObservationOutcome<O>, BeaconChainError>,
+    M: Fn(O) -> PubsubMessage<T::EthSpec>,
+    I: Fn(&BeaconChain<T>, SigVerifiedOp<O>) -> Result<(), ApiError>,
+{
+    try_future!(check_content_type_for_json(&req));
+    let response_builder = ResponseBuilder::new(&req);
+
+    let body = req.into_body();
+    let chunks = hyper::body::to_bytes(body)
+        .await
+        .map_err(|e| ApiError::ServerError(format!("Unable to get request body: {:?}", e)))?;
+
+    let operation = serde_json::from_slice::<O>(&chunks).map_err(|e| {
+        ApiError::BadRequest(format!(
+            "Unable to parse JSON into {}: {:?}",
+            description, e
+        ))
+    })?;
+
+    let outcome = verify(&beacon_chain, operation.clone())
+        .map_err(|e| ApiError::BadRequest(format!("Invalid {}: {:?}", description, e)))?;
+
+    if let ObservationOutcome::New(verified_operation) = outcome {
+        network_chan
+            .send(NetworkMessage::Publish {
+                messages: vec![to_message(operation)],
+            })
+            .map_err(|e| {
+                ApiError::ServerError(format!(
+                    "Unable to send {} to network: {:?}",
+                    description, e
+                ))
+            })?;
+
+        import(&beacon_chain, verified_operation)?;
+    } else {
+        debug!(log, "Operation already known"; "operation" => description);
+    }
+
+    response_builder?.body_no_ssz(&())
+}
@@ -0,0 +1,37 @@
+use crate::response_builder::ResponseBuilder;
+use crate::ApiResult;
+use beacon_chain::{BeaconChain, BeaconChainTypes};
+use hyper::{Body, Request};
+use rest_types::standard::DepositContractData;
+use std::sync::Arc;
+use types::Address;
+
+/// HTTP handler to return the full spec object.
+pub fn get_spec<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    ResponseBuilder::new(&req)?.data_no_ssz(&beacon_chain.spec)
+}
+
+/// HTTP handler to return the forks known to this node.
+///
+/// Only the fork of the current head is known, since no upgrades are scheduled.
+pub fn get_fork_schedule<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    ResponseBuilder::new(&req)?.data(&vec![beacon_chain.head_info()?.fork])
+}
+
+/// HTTP handler to return the eth1 chain id and address of the deposit contract.
+pub fn get_deposit_contract<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    address: Address,
+) -> ApiResult {
+    ResponseBuilder::new(&req)?.data_no_ssz(&DepositContractData {
+        chain_id: beacon_chain.spec.deposit_chain_id,
+        address,
+    })
+}
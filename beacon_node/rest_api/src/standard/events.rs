@@ -0,0 +1,63 @@
+use crate::{ApiError, ApiResult, UrlQuery};
+use beacon_chain::events::{EventQueues, EventTopic, SseEvent};
+use futures::StreamExt;
+use hyper::body::Bytes;
+use hyper::{Body, Request, Response};
+use slog::{error, Logger};
+use std::io::Write;
+
+/// Returns the topics given by the `topics` query parameters.
+///
+/// Topics may be supplied as repeated parameters or as a comma-separated list. At least one topic
+/// is required and unknown topics are rejected.
+fn parse_topics(req: &Request<Body>) -> Result<Vec<EventTopic>, ApiError> {
+    let topics = UrlQuery::from_request(req)?
+        .all_of("topics")?
+        .iter()
+        .flat_map(|topics| topics.split(','))
+        .map(|topic| topic.parse::<EventTopic>().map_err(ApiError::BadRequest))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if topics.is_empty() {
+        Err(ApiError::BadRequest(
+            "At least one event topic is required".to_string(),
+        ))
+    } else {
+        Ok(topics)
+    }
+}
+
+fn make_sse_response_chunk(event: &SseEvent) -> std::io::Result<Bytes> {
+    let mut buffer = Vec::new();
+    {
+        let mut sse_message = uhttp_sse::SseMessage::new(&mut buffer);
+        write!(sse_message.event()?, "{}", event.topic)?;
+        write!(sse_message.data()?, "{}", event.data)?;
+    }
+    Ok(buffer.into())
+}
+
+/// HTTP handler to stream the events of the topics given by the `topics` query parameters as
+/// server-sent events.
+///
+/// The response body is driven by the subscriber's event queue, rather than by a dedicated thread.
+/// When the client disconnects, hyper drops the body and with it the subscription.
+pub fn get_events(req: Request<Body>, queues: EventQueues, log: Logger) -> ApiResult {
+    let topics = parse_topics(&req)?;
+    let events = queues.events.subscribe(topics);
+
+    let chunks = events.map(move |event| {
+        make_sse_response_chunk(&event).map_err(|e| {
+            error!(log, "Failed to make SSE chunk"; "error" => e.to_string());
+            e
+        })
+    });
+
+    Response::builder()
+        .status(200)
+        .header("Content-Type", "text/event-stream")
+        .header("Connection", "Keep-Alive")
+        .header("Cache-Control", "no-cache")
+        .body(Body::wrap_stream(chunks))
+        .map_err(|e| ApiError::ServerError(format!("Failed to build response: {:?}", e)))
+}
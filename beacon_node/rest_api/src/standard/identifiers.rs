@@ -0,0 +1,211 @@
+use crate::helpers::{
+    block_at_root, block_root_at_slot, parse_root, parse_slot, state_at_root, state_at_slot,
+    state_root_at_slot,
+};
+use crate::ApiError;
+use beacon_chain::{BeaconChain, BeaconChainTypes, StateSkipConfig};
+use std::str::FromStr;
+use types::{BeaconState, Checkpoint, EthSpec, Hash256, SignedBeaconBlock, Slot};
+
+/// Identifies a `BeaconState` in the path of a standard API request.
+///
+/// E.g., `head`, `genesis`, `finalized`, `justified`, `1234` or `0x1a2b...`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StateId {
+    Head,
+    Genesis,
+    Finalized,
+    Justified,
+    Slot(Slot),
+    Root(Hash256),
+}
+
+/// Identifies a `SignedBeaconBlock` in the path of a standard API request.
+///
+/// Accepts the same forms as `StateId`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BlockId {
+    Head,
+    Genesis,
+    Finalized,
+    Justified,
+    Slot(Slot),
+    Root(Hash256),
+}
+
+impl FromStr for StateId {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "head" => Ok(StateId::Head),
+            "genesis" => Ok(StateId::Genesis),
+            "finalized" => Ok(StateId::Finalized),
+            "justified" => Ok(StateId::Justified),
+            other if other.starts_with("0x") => parse_root(other).map(StateId::Root),
+            other => parse_slot(other).map(StateId::Slot),
+        }
+    }
+}
+
+impl FromStr for BlockId {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse::<StateId>().map(|state_id| match state_id {
+            StateId::Head => BlockId::Head,
+            StateId::Genesis => BlockId::Genesis,
+            StateId::Finalized => BlockId::Finalized,
+            StateId::Justified => BlockId::Justified,
+            StateId::Slot(slot) => BlockId::Slot(slot),
+            StateId::Root(root) => BlockId::Root(root),
+        })
+    }
+}
+
+/// Returns the first slot of the epoch of `checkpoint`.
+fn checkpoint_slot<E: EthSpec>(checkpoint: &Checkpoint) -> Slot {
+    checkpoint.epoch.start_slot(E::slots_per_epoch())
+}
+
+impl StateId {
+    /// Returns the root of the identified state.
+    pub fn root<T: BeaconChainTypes>(
+        &self,
+        beacon_chain: &BeaconChain<T>,
+    ) -> Result<Hash256, ApiError> {
+        match self {
+            StateId::Head => Ok(beacon_chain.head_info()?.state_root),
+            StateId::Root(_) => self.state(beacon_chain).map(|(root, _)| root),
+            other => state_root_at_slot(
+                beacon_chain,
+                other.slot(beacon_chain)?,
+                StateSkipConfig::WithStateRoots,
+            ),
+        }
+    }
+
+    /// Returns the identified state and its root.
+    pub fn state<T: BeaconChainTypes>(
+        &self,
+        beacon_chain: &BeaconChain<T>,
+    ) -> Result<(Hash256, BeaconState<T::EthSpec>), ApiError> {
+        match self {
+            StateId::Head => {
+                let head = beacon_chain.head()?;
+                Ok((head.beacon_state_root, head.beacon_state))
+            }
+            StateId::Root(root) => state_at_root(beacon_chain, *root),
+            other => state_at_slot(beacon_chain, other.slot(beacon_chain)?),
+        }
+    }
+
+    /// Returns the slot of the identified state.
+    ///
+    /// The finalized and justified states are those at the first slot of their checkpoint epoch.
+    fn slot<T: BeaconChainTypes>(&self, beacon_chain: &BeaconChain<T>) -> Result<Slot, ApiError> {
+        match self {
+            StateId::Head => Ok(beacon_chain.head_info()?.slot),
+            StateId::Genesis => Ok(Slot::new(0)),
+            StateId::Finalized => Ok(checkpoint_slot::<T::EthSpec>(
+                &beacon_chain.head_info()?.finalized_checkpoint,
+            )),
+            StateId::Justified => Ok(checkpoint_slot::<T::EthSpec>(
+                &beacon_chain.head_info()?.current_justified_checkpoint,
+            )),
+            StateId::Slot(slot) => Ok(*slot),
+            StateId::Root(root) => self
+                .state(beacon_chain)
+                .map(|(_, state)| state.slot)
+                .map_err(|_| ApiError::NotFound(format!("No state exists with root: {}", root))),
+        }
+    }
+}
+
+impl BlockId {
+    /// Returns the root of the identified block.
+    pub fn root<T: BeaconChainTypes>(
+        &self,
+        beacon_chain: &BeaconChain<T>,
+    ) -> Result<Hash256, ApiError> {
+        match self {
+            BlockId::Head => Ok(beacon_chain.head_info()?.block_root),
+            BlockId::Genesis => Ok(beacon_chain.genesis_block_root),
+            BlockId::Finalized => {
+                let checkpoint = beacon_chain.head_info()?.finalized_checkpoint;
+                Ok(checkpoint_block_root(beacon_chain, &checkpoint))
+            }
+            BlockId::Justified => {
+                let checkpoint = beacon_chain.head_info()?.current_justified_checkpoint;
+                Ok(checkpoint_block_root(beacon_chain, &checkpoint))
+            }
+            BlockId::Slot(_) | BlockId::Root(_) => self.block(beacon_chain).map(|(root, _)| root),
+        }
+    }
+
+    /// Returns the identified block and its root.
+    ///
+    /// A slot which does not contain a block (i.e., a skip slot) is reported as not found.
+    pub fn block<T: BeaconChainTypes>(
+        &self,
+        beacon_chain: &BeaconChain<T>,
+    ) -> Result<(Hash256, SignedBeaconBlock<T::EthSpec>), ApiError> {
+        let root = match self {
+            BlockId::Root(root) => *root,
+            BlockId::Slot(slot) => block_root_at_slot(beacon_chain, *slot)?.ok_or_else(|| {
+                ApiError::NotFound(format!("No canonical block exists at slot {}", slot))
+            })?,
+            other => other.root(beacon_chain)?,
+        };
+
+        let block = block_at_root(beacon_chain, root)?;
+
+        match self {
+            BlockId::Slot(slot) if block.slot() != *slot => Err(ApiError::NotFound(format!(
+                "No canonical block exists at slot {}",
+                slot
+            ))),
+            _ => Ok((root, block)),
+        }
+    }
+}
+
+/// Returns the block root of `checkpoint`, mapping the zero root used prior to the first
+/// justification or finalization to the genesis block root.
+fn checkpoint_block_root<T: BeaconChainTypes>(
+    beacon_chain: &BeaconChain<T>,
+    checkpoint: &Checkpoint,
+) -> Hash256 {
+    if checkpoint.root == Hash256::zero() {
+        beacon_chain.genesis_block_root
+    } else {
+        checkpoint.root
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_state_id() {
+        assert_eq!("head".parse(), Ok(StateId::Head));
+        assert_eq!("genesis".parse(), Ok(StateId::Genesis));
+        assert_eq!("finalized".parse(), Ok(StateId::Finalized));
+        assert_eq!("justified".parse(), Ok(StateId::Justified));
+        assert_eq!("42".parse(), Ok(StateId::Slot(Slot::new(42))));
+        assert_eq!(
+            "0x000000000000000000000000000000000000000000000000000000000000002a".parse(),
+            Ok(StateId::Root(Hash256::from_low_u64_be(42)))
+        );
+        assert!("cats".parse::<StateId>().is_err());
+        assert!("0x2a".parse::<StateId>().is_err());
+    }
+
+    #[test]
+    fn parse_block_id() {
+        assert_eq!("head".parse(), Ok(BlockId::Head));
+        assert_eq!("0".parse(), Ok(BlockId::Slot(Slot::new(0))));
+        assert!("".parse::<BlockId>().is_err());
+    }
+}
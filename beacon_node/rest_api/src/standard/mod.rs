@@ -0,0 +1,161 @@
+//! Implements the Ethereum standard Beacon Node API, served under `/eth/v1/`.
+//!
+//! These endpoints live alongside the Lighthouse-specific API (e.g., `/beacon/head`) and differ
+//! from it in two ways:
+//!
+//! - Successful responses wrap their payload in a `{"data": ...}` envelope.
+//! - Errors are returned as a JSON `ErrorMessage`, rather than as plain text.
+
+mod beacon;
+mod config;
+mod events;
+mod identifiers;
+mod node;
+mod validator;
+
+use crate::{ApiError, ApiResult, NetworkChannel};
+use beacon_chain::{events::EventQueues, BeaconChain, BeaconChainTypes};
+use eth2_libp2p::NetworkGlobals;
+use hyper::{Body, Method, Request};
+use std::sync::Arc;
+use types::Address;
+
+pub use identifiers::{BlockId, StateId};
+
+/// All standard API paths start with this prefix.
+pub const PATH_PREFIX: &str = "/eth/v1/";
+
+/// Routes a request for a path starting with `PATH_PREFIX`.
+pub async fn route<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    network_globals: Arc<NetworkGlobals<T::EthSpec>>,
+    network_channel: NetworkChannel<T::EthSpec>,
+    deposit_contract_address: Address,
+    events: EventQueues,
+    log: slog::Logger,
+) -> ApiResult {
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    let segments = path
+        .trim_start_matches(PATH_PREFIX)
+        .trim_end_matches('/')
+        .split('/')
+        .collect::<Vec<_>>();
+
+    match (&method, segments.as_slice()) {
+        // Beacon
+        (&Method::GET, ["beacon", "genesis"]) => beacon::get_genesis::<T>(req, beacon_chain),
+        (&Method::GET, ["beacon", "states", state_id, "root"]) => {
+            beacon::get_state_root::<T>(req, beacon_chain, state_id)
+        }
+        (&Method::GET, ["beacon", "states", state_id, "fork"]) => {
+            beacon::get_state_fork::<T>(req, beacon_chain, state_id)
+        }
+        (&Method::GET, ["beacon", "states", state_id, "finality_checkpoints"]) => {
+            beacon::get_state_finality_checkpoints::<T>(req, beacon_chain, state_id)
+        }
+        (&Method::GET, ["beacon", "states", state_id, "validators"]) => {
+            beacon::get_state_validators::<T>(req, beacon_chain, state_id)
+        }
+        (&Method::GET, ["beacon", "states", state_id, "validators", validator_id]) => {
+            beacon::get_state_validator::<T>(req, beacon_chain, state_id, validator_id)
+        }
+        (&Method::GET, ["beacon", "states", state_id, "committees", epoch]) => {
+            beacon::get_state_committees::<T>(req, beacon_chain, state_id, epoch)
+        }
+        (&Method::GET, ["beacon", "headers"]) => beacon::get_headers::<T>(req, beacon_chain),
+        (&Method::GET, ["beacon", "headers", block_id]) => {
+            beacon::get_header::<T>(req, beacon_chain, block_id)
+        }
+        (&Method::POST, ["beacon", "blocks"]) => {
+            crate::validator::publish_beacon_block::<T>(req, beacon_chain, network_channel, log)
+                .await
+        }
+        (&Method::GET, ["beacon", "blocks", block_id]) => {
+            beacon::get_block::<T>(req, beacon_chain, block_id)
+        }
+        (&Method::GET, ["beacon", "blocks", block_id, "root"]) => {
+            beacon::get_block_root::<T>(req, beacon_chain, block_id)
+        }
+        (&Method::GET, ["beacon", "blocks", block_id, "attestations"]) => {
+            beacon::get_block_attestations::<T>(req, beacon_chain, block_id)
+        }
+        (&Method::GET, ["beacon", "pool", "attestations"]) => {
+            beacon::get_pool_attestations::<T>(req, beacon_chain)
+        }
+        (&Method::POST, ["beacon", "pool", "attestations"]) => {
+            beacon::post_pool_attestations::<T>(req, beacon_chain, network_channel, log).await
+        }
+        (&Method::GET, ["beacon", "pool", "attester_slashings"]) => {
+            beacon::get_pool_attester_slashings::<T>(req, beacon_chain)
+        }
+        (&Method::POST, ["beacon", "pool", "attester_slashings"]) => {
+            beacon::post_pool_attester_slashings::<T>(req, beacon_chain, network_channel, log).await
+        }
+        (&Method::GET, ["beacon", "pool", "proposer_slashings"]) => {
+            beacon::get_pool_proposer_slashings::<T>(req, beacon_chain)
+        }
+        (&Method::POST, ["beacon", "pool", "proposer_slashings"]) => {
+            beacon::post_pool_proposer_slashings::<T>(req, beacon_chain, network_channel, log).await
+        }
+        (&Method::GET, ["beacon", "pool", "voluntary_exits"]) => {
+            beacon::get_pool_voluntary_exits::<T>(req, beacon_chain)
+        }
+        (&Method::POST, ["beacon", "pool", "voluntary_exits"]) => {
+            beacon::post_pool_voluntary_exits::<T>(req, beacon_chain, network_channel, log).await
+        }
+
+        // Node
+        (&Method::GET, ["node", "identity"]) => node::get_identity::<T>(req, network_globals),
+        (&Method::GET, ["node", "peers"]) => node::get_peers::<T>(req, network_globals),
+        (&Method::GET, ["node", "version"]) => node::get_version(req),
+        (&Method::GET, ["node", "syncing"]) => node::get_syncing::<T>(req, beacon_chain),
+        (&Method::GET, ["node", "health"]) => node::get_health::<T>(req, network_globals),
+
+        // Config
+        (&Method::GET, ["config", "spec"]) => config::get_spec::<T>(req, beacon_chain),
+        (&Method::GET, ["config", "fork_schedule"]) => {
+            config::get_fork_schedule::<T>(req, beacon_chain)
+        }
+        (&Method::GET, ["config", "deposit_contract"]) => {
+            config::get_deposit_contract::<T>(req, beacon_chain, deposit_contract_address)
+        }
+
+        // Validator
+        (&Method::GET, ["validator", "duties", "attester", epoch]) => {
+            validator::get_attester_duties::<T>(req, beacon_chain, epoch)
+        }
+        (&Method::GET, ["validator", "duties", "proposer", epoch]) => {
+            validator::get_proposer_duties::<T>(req, beacon_chain, epoch)
+        }
+        (&Method::GET, ["validator", "blocks", slot]) => {
+            validator::get_block::<T>(req, beacon_chain, slot, log)
+        }
+        (&Method::GET, ["validator", "attestation_data"]) => {
+            validator::get_attestation_data::<T>(req, beacon_chain)
+        }
+        (&Method::GET, ["validator", "aggregate_attestation"]) => {
+            validator::get_aggregate_attestation::<T>(req, beacon_chain)
+        }
+        (&Method::POST, ["validator", "aggregate_and_proofs"]) => {
+            crate::validator::publish_aggregate_and_proofs::<T>(
+                req,
+                beacon_chain,
+                network_channel,
+                log,
+            )
+            .await
+        }
+        (&Method::POST, ["validator", "beacon_committee_subscriptions"]) => {
+            validator::post_beacon_committee_subscriptions::<T>(req, network_channel).await
+        }
+
+        // Events
+        (&Method::GET, ["events"]) => events::get_events(req, events, log),
+
+        _ => Err(ApiError::NotFound(
+            "Request path and/or method not found.".to_owned(),
+        )),
+    }
+}
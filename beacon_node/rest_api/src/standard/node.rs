@@ -0,0 +1,106 @@
+use crate::response_builder::ResponseBuilder;
+use crate::{ApiError, ApiResult};
+use beacon_chain::{BeaconChain, BeaconChainTypes};
+use eth2_libp2p::NetworkGlobals;
+use hyper::{Body, Request, Response, StatusCode};
+use rest_types::standard::{
+    IdentityData, PeerData, PeerDirection, PeerState, SyncingData, VersionData,
+};
+use std::sync::Arc;
+use types::Slot;
+
+/// HTTP handler to return the identity of this node on the p2p network.
+pub fn get_identity<T: BeaconChainTypes>(
+    req: Request<Body>,
+    network: Arc<NetworkGlobals<T::EthSpec>>,
+) -> ApiResult {
+    ResponseBuilder::new(&req)?.data_no_ssz(&IdentityData {
+        peer_id: network.local_peer_id().to_base58(),
+        enr: network.local_enr().to_base64(),
+        p2p_addresses: network
+            .listen_multiaddrs()
+            .iter()
+            .map(ToString::to_string)
+            .collect(),
+    })
+}
+
+/// HTTP handler to return all peers known to this node which are connected, being dialed or
+/// disconnected. Banned peers are omitted.
+pub fn get_peers<T: BeaconChainTypes>(
+    req: Request<Body>,
+    network: Arc<NetworkGlobals<T::EthSpec>>,
+) -> ApiResult {
+    let peers = network
+        .peers
+        .read()
+        .peers()
+        .filter_map(|(peer_id, info)| {
+            let status = &info.connection_status;
+            let (state, direction) = if status.is_connected() {
+                let (n_in, _n_out) = status.connections();
+                let direction = if n_in > 0 {
+                    PeerDirection::Inbound
+                } else {
+                    PeerDirection::Outbound
+                };
+                (PeerState::Connected, Some(direction))
+            } else if status.is_dialing() {
+                (PeerState::Connecting, Some(PeerDirection::Outbound))
+            } else if status.is_banned() {
+                return None;
+            } else {
+                (PeerState::Disconnected, None)
+            };
+
+            Some(PeerData {
+                peer_id: peer_id.to_string(),
+                address: info.listening_addresses.first().map(ToString::to_string),
+                state,
+                direction,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    ResponseBuilder::new(&req)?.data_no_ssz(&peers)
+}
+
+/// HTTP handler to return the version string of the current Lighthouse build.
+pub fn get_version(req: Request<Body>) -> ApiResult {
+    ResponseBuilder::new(&req)?.data_no_ssz(&VersionData {
+        version: version::version(),
+    })
+}
+
+/// HTTP handler to return the head slot and the distance between it and the current slot.
+pub fn get_syncing<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let head_slot = beacon_chain.head_info()?.slot;
+    let current_slot = beacon_chain.slot().unwrap_or_else(|_| Slot::new(0));
+
+    ResponseBuilder::new(&req)?.data_no_ssz(&SyncingData {
+        head_slot,
+        sync_distance: current_slot.saturating_sub(head_slot),
+    })
+}
+
+/// HTTP handler to report the health of the node via the status code alone.
+///
+/// Responds with `200 OK` when synced and `206 Partial Content` when syncing.
+pub fn get_health<T: BeaconChainTypes>(
+    _req: Request<Body>,
+    network: Arc<NetworkGlobals<T::EthSpec>>,
+) -> ApiResult {
+    let status = if network.is_syncing() {
+        StatusCode::PARTIAL_CONTENT
+    } else {
+        StatusCode::OK
+    };
+
+    Response::builder()
+        .status(status)
+        .body(Body::empty())
+        .map_err(|e| ApiError::ServerError(format!("Failed to build response: {:?}", e)))
+}
@@ -0,0 +1,237 @@
+use crate::helpers::{check_content_type_for_json, parse_epoch, parse_root, parse_slot};
+use crate::response_builder::ResponseBuilder;
+use crate::validator::get_state_for_epoch;
+use crate::{ApiError, ApiResult, NetworkChannel, UrlQuery};
+use beacon_chain::{BeaconChain, BeaconChainTypes, StateSkipConfig};
+use hyper::{Body, Request};
+use network::NetworkMessage;
+use rest_types::standard::{AttesterData, BeaconCommitteeSubscription, ProposerData};
+use rest_types::ValidatorSubscription;
+use slog::{error, Logger};
+use std::sync::Arc;
+use types::{BeaconState, Epoch, EthSpec, RelativeEpoch};
+
+/// HTTP handler to return the attestation duties in `epoch` for the validators given by the
+/// `index` query parameters.
+///
+/// Indices may be supplied as repeated parameters or as a comma-separated list. Unknown
+/// validators are omitted from the response.
+pub fn get_attester_duties<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    epoch: &str,
+) -> ApiResult {
+    let epoch = parse_epoch(epoch)?;
+    let indices = UrlQuery::from_request(&req)?
+        .all_of("index")?
+        .iter()
+        .flat_map(|index| index.split(','))
+        .map(|index| {
+            index
+                .parse::<usize>()
+                .map_err(|e| ApiError::BadRequest(format!("Invalid validator index: {:?}", e)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (state, relative_epoch) = state_for_duties(&beacon_chain, epoch)?;
+
+    let duties = indices
+        .into_iter()
+        .filter_map(|validator_index| {
+            let validator = state.validators.get(validator_index)?;
+            let duty = match state.get_attestation_duties(validator_index, relative_epoch) {
+                Ok(duty) => duty?,
+                Err(e) => {
+                    return Some(Err(ApiError::ServerError(format!(
+                        "Unable to obtain attestation duties: {:?}",
+                        e
+                    ))))
+                }
+            };
+
+            Some(
+                state
+                    .get_committee_count_at_slot(duty.slot)
+                    .map(|committees_at_slot| AttesterData {
+                        pubkey: validator.pubkey.clone(),
+                        validator_index: validator_index as u64,
+                        committee_index: duty.index,
+                        committee_length: duty.committee_len as u64,
+                        committees_at_slot,
+                        validator_committee_index: duty.committee_position as u64,
+                        slot: duty.slot,
+                    })
+                    .map_err(|e| {
+                        ApiError::ServerError(format!(
+                            "Unable to find committee count at slot: {:?}",
+                            e
+                        ))
+                    }),
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    ResponseBuilder::new(&req)?.data_no_ssz(&duties)
+}
+
+/// HTTP handler to return the block proposers for every slot of `epoch`.
+pub fn get_proposer_duties<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    epoch: &str,
+) -> ApiResult {
+    let epoch = parse_epoch(epoch)?;
+    let (state, _relative_epoch) = state_for_duties(&beacon_chain, epoch)?;
+
+    let duties = epoch
+        .slot_iter(T::EthSpec::slots_per_epoch())
+        .map(|slot| {
+            let validator_index = state
+                .get_beacon_proposer_index(slot, &beacon_chain.spec)
+                .map_err(|e| {
+                    ApiError::ServerError(format!("Unable to get proposer index: {:?}", e))
+                })?;
+            let validator = state.validators.get(validator_index).ok_or_else(|| {
+                ApiError::ServerError(format!("Invalid validator index: {:?}", validator_index))
+            })?;
+
+            Ok(ProposerData {
+                pubkey: validator.pubkey.clone(),
+                validator_index: validator_index as u64,
+                slot,
+            })
+        })
+        .collect::<Result<Vec<_>, ApiError>>()?;
+
+    ResponseBuilder::new(&req)?.data_no_ssz(&duties)
+}
+
+/// Returns a state suitable for computing duties in `epoch`, with its committee cache built.
+fn state_for_duties<T: BeaconChainTypes>(
+    beacon_chain: &BeaconChain<T>,
+    epoch: Epoch,
+) -> Result<(BeaconState<T::EthSpec>, RelativeEpoch), ApiError> {
+    let mut state = get_state_for_epoch(beacon_chain, epoch, StateSkipConfig::WithoutStateRoots)?;
+
+    let relative_epoch = RelativeEpoch::from_epoch(state.current_epoch(), epoch)
+        .map_err(|_| ApiError::ServerError(String::from("Loaded state is in the wrong epoch")))?;
+    state
+        .build_committee_cache(relative_epoch, &beacon_chain.spec)
+        .map_err(|e| ApiError::ServerError(format!("Unable to build committee cache: {:?}", e)))?;
+
+    Ok((state, relative_epoch))
+}
+
+/// HTTP handler to produce an unsigned `BeaconBlock` at `slot`, using the `randao_reveal` query
+/// parameter.
+pub fn get_block<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+    slot: &str,
+    log: Logger,
+) -> ApiResult {
+    let slot = parse_slot(slot)?;
+    let randao_reveal = UrlQuery::from_request(&req)?.randao_reveal()?;
+
+    let (block, _state) = beacon_chain
+        .produce_block(randao_reveal, slot)
+        .map_err(|e| {
+            error!(
+                log,
+                "Error whilst producing block";
+                "error" => format!("{:?}", e)
+            );
+
+            ApiError::ServerError(format!(
+                "Beacon node is not able to produce a block: {:?}",
+                e
+            ))
+        })?;
+
+    ResponseBuilder::new(&req)?.data(&block)
+}
+
+/// HTTP handler to produce the `AttestationData` for the `slot` and `committee_index` query
+/// parameters.
+pub fn get_attestation_data<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let query = UrlQuery::from_request(&req)?;
+
+    let slot = query.slot()?;
+    let index = query.committee_index()?;
+
+    let attestation = beacon_chain
+        .produce_unaggregated_attestation(slot, index)
+        .map_err(|e| ApiError::BadRequest(format!("Unable to produce attestation: {:?}", e)))?;
+
+    ResponseBuilder::new(&req)?.data(&attestation.data)
+}
+
+/// HTTP handler to return the aggregate attestation for the `slot` and `attestation_data_root`
+/// query parameters.
+pub fn get_aggregate_attestation<T: BeaconChainTypes>(
+    req: Request<Body>,
+    beacon_chain: Arc<BeaconChain<T>>,
+) -> ApiResult {
+    let query = UrlQuery::from_request(&req)?;
+
+    let slot = query.slot()?;
+    let attestation_data_root = query
+        .first_of(&["attestation_data_root"])
+        .and_then(|(_key, value)| parse_root(&value))?;
+
+    let attestation = beacon_chain
+        .get_aggregated_attestation_by_slot_and_root(slot, &attestation_data_root)
+        .ok_or_else(|| {
+            ApiError::NotFound(format!(
+                "No matching aggregate attestation for slot {} and root {}",
+                slot, attestation_data_root
+            ))
+        })?;
+
+    ResponseBuilder::new(&req)?.data(&attestation)
+}
+
+/// HTTP handler to subscribe to the attestation subnets of a list of committees.
+pub async fn post_beacon_committee_subscriptions<T: BeaconChainTypes>(
+    req: Request<Body>,
+    network_chan: NetworkChannel<T::EthSpec>,
+) -> ApiResult {
+    try_future!(check_content_type_for_json(&req));
+    let response_builder = ResponseBuilder::new(&req);
+
+    let body = req.into_body();
+    let chunks = hyper::body::to_bytes(body)
+        .await
+        .map_err(|e| ApiError::ServerError(format!("Unable to get request body: {:?}", e)))?;
+
+    let subscriptions = serde_json::from_slice::<Vec<BeaconCommitteeSubscription>>(&chunks)
+        .map_err(|e| {
+            ApiError::BadRequest(format!(
+                "Unable to parse JSON into BeaconCommitteeSubscriptions: {:?}",
+                e
+            ))
+        })?
+        .into_iter()
+        .map(|subscription| ValidatorSubscription {
+            validator_index: subscription.validator_index,
+            attestation_committee_index: subscription.committee_index,
+            slot: subscription.slot,
+            committee_count_at_slot: subscription.committees_at_slot,
+            is_aggregator: subscription.is_aggregator,
+        })
+        .collect();
+
+    network_chan
+        .send(NetworkMessage::Subscribe { subscriptions })
+        .map_err(|e| {
+            ApiError::ServerError(format!(
+                "Unable to send subscriptions to the network: {:?}",
+                e
+            ))
+        })?;
+
+    response_builder?.body_no_ssz(&())
+}
@@ -0,0 +1,57 @@
+use crate::config::TlsConfig;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::Arc;
+use tokio_rustls::rustls::internal::pemfile::{certs, pkcs8_private_keys, rsa_private_keys};
+use tokio_rustls::rustls::{Certificate, NoClientAuth, PrivateKey, ServerConfig};
+
+/// Builds the `rustls` configuration for serving HTTPS with the certificate and key in `config`.
+///
+/// Client certificates are not requested.
+pub fn server_config(config: &TlsConfig) -> Result<Arc<ServerConfig>, String> {
+    let certs = load_certs(&config.cert)?;
+    let key = load_private_key(&config.key)?;
+
+    let mut server_config = ServerConfig::new(NoClientAuth::new());
+    server_config
+        .set_single_cert(certs, key)
+        .map_err(|e| format!("Invalid TLS certificate or key: {:?}", e))?;
+
+    Ok(Arc::new(server_config))
+}
+
+fn open(path: &Path) -> Result<BufReader<File>, String> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|e| format!("Unable to open {:?}: {:?}", path, e))
+}
+
+fn load_certs(path: &Path) -> Result<Vec<Certificate>, String> {
+    let certs = certs(&mut open(path)?)
+        .map_err(|()| format!("Unable to parse PEM certificates in {:?}", path))?;
+
+    if certs.is_empty() {
+        Err(format!("No certificates found in {:?}", path))
+    } else {
+        Ok(certs)
+    }
+}
+
+/// Loads the first private key in `path`, which may be in either PKCS #8 or RSA (PKCS #1) form.
+fn load_private_key(path: &Path) -> Result<PrivateKey, String> {
+    let parse_error = |()| format!("Unable to parse PEM private key in {:?}", path);
+
+    pkcs8_private_keys(&mut open(path)?)
+        .map_err(parse_error)?
+        .into_iter()
+        .next()
+        .map(Ok)
+        .unwrap_or_else(|| {
+            rsa_private_keys(&mut open(path)?)
+                .map_err(parse_error)?
+                .into_iter()
+                .next()
+                .ok_or_else(|| format!("No private key found in {:?}", path))
+        })
+}
@@ -483,7 +483,7 @@ pub async fn publish_attestations<T: BeaconChainTypes>(
 /// Processes an unaggregrated attestation that was included in a list of attestations with the
 /// index `i`.
 #[allow(clippy::redundant_clone)] // false positives in this function.
-fn process_unaggregated_attestation<T: BeaconChainTypes>(
+pub(crate) fn process_unaggregated_attestation<T: BeaconChainTypes>(
     beacon_chain: &BeaconChain<T>,
     network_chan: NetworkChannel<T::EthSpec>,
     attestation: Attestation<T::EthSpec>,
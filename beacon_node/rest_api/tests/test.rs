@@ -9,12 +9,16 @@ use node_test_rig::{
     testing_client_config, ClientConfig, ClientGenesis, LocalBeaconNode,
 };
 use remote_beacon_node::{
-    Committee, HeadBeaconBlock, PersistedOperationPool, PublishStatus, ValidatorResponse,
+    Committee, HeadBeaconBlock, PersistedOperationPool, PublishStatus, RemoteBeaconNode,
+    ValidatorResponse, REQUEST_TIMEOUT_SECONDS,
 };
+use rest_api::TlsConfig;
 use rest_types::ValidatorDutyBytes;
 use std::convert::TryInto;
+use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tempdir::TempDir;
 use types::{
     test_utils::{
         build_double_vote_attester_slashing, build_proposer_slashing,
@@ -1285,3 +1289,485 @@ fn get_health() {
         .block_on(remote_node.http.node().get_health())
         .unwrap();
 }
+
+#[test]
+fn api_token_is_required_when_enabled() {
+    let mut env = build_env();
+
+    let token_dir = TempDir::new("rest_api_token").expect("should create temp dir");
+    let token_path = token_dir.path().join(rest_api::API_TOKEN_FILENAME);
+    let mut config = testing_client_config();
+    config.rest_api.api_token_path = Some(token_path.clone());
+
+    let node = build_node(&mut env, config);
+
+    let unauthorized_node = node.remote_node().expect("should produce remote node");
+    let result = env
+        .runtime()
+        .block_on(unauthorized_node.http.beacon().get_genesis_time());
+    assert_matches!(
+        result.expect_err("should not succeed without a token"),
+        remote_beacon_node::Error::DidNotSucceed { status, .. } => {
+            assert_eq!(status, http::StatusCode::UNAUTHORIZED)
+        }
+    );
+
+    let socket_addr = node
+        .client
+        .http_listen_addr()
+        .expect("A remote beacon node must have a http server");
+    let token = std::fs::read_to_string(&token_path).expect("should read api token");
+    let authorized_node = RemoteBeaconNode::<E>::new_with_api_token(
+        format!("http://{}", socket_addr),
+        Duration::from_secs(REQUEST_TIMEOUT_SECONDS),
+        Some(token),
+    )
+    .expect("should produce remote node");
+
+    env.runtime()
+        .block_on(authorized_node.http.beacon().get_genesis_time())
+        .expect("should succeed with a token");
+}
+
+/// Returns the path of a file in the `tests/tls` directory, which contains a self-signed
+/// certificate for `localhost`.
+fn tls_fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("tls")
+        .join(name)
+}
+
+#[test]
+fn serves_https_when_tls_is_enabled() {
+    let mut env = build_env();
+
+    let mut config = testing_client_config();
+    config.rest_api.tls_config = Some(TlsConfig {
+        cert: tls_fixture("cert.pem"),
+        key: tls_fixture("key.pem"),
+    });
+
+    let node = build_node(&mut env, config);
+    let port = node
+        .client
+        .http_listen_addr()
+        .expect("A remote beacon node must have a http server")
+        .port();
+
+    let cert = std::fs::read(tls_fixture("cert.pem")).expect("should read certificate");
+    let client = reqwest::Client::builder()
+        .add_root_certificate(
+            reqwest::Certificate::from_pem(&cert).expect("should parse certificate"),
+        )
+        .build()
+        .expect("should build client");
+
+    let response = env
+        .runtime()
+        .block_on(
+            client
+                .get(&format!("https://localhost:{}/node/health", port))
+                .send(),
+        )
+        .expect("should connect over https");
+    assert_eq!(response.status(), http::StatusCode::OK);
+
+    let result = env.runtime().block_on(
+        client
+            .get(&format!("http://localhost:{}/node/health", port))
+            .send(),
+    );
+    assert!(result.is_err(), "plain http should not be served");
+}
+
+#[test]
+fn cors_headers_follow_the_policy() {
+    let origin = "https://example.com";
+    let paths = [
+        "/node/version",
+        "/beacon/fork/stream",
+        "/eth/v1/events?topics=head",
+    ];
+
+    for (allowed_origins, expected) in vec![
+        (vec![], None),
+        (vec!["https://example.org".to_string()], None),
+        (vec![origin.to_string()], Some(origin)),
+        (vec!["*".to_string()], Some("*")),
+    ] {
+        let mut env = build_env();
+
+        let mut config = testing_client_config();
+        config.rest_api.allowed_origins = allowed_origins.clone();
+
+        let node = build_node(&mut env, config);
+        let socket_addr = node
+            .client
+            .http_listen_addr()
+            .expect("A remote beacon node must have a http server");
+
+        let client = reqwest::Client::new();
+        for path in &paths {
+            // Only the headers are read, so the event streams are not consumed.
+            let response = env
+                .runtime()
+                .block_on(
+                    client
+                        .get(&format!("http://{}{}", socket_addr, path))
+                        .header(http::header::ORIGIN, origin)
+                        .send(),
+                )
+                .expect("request should succeed");
+
+            assert_eq!(response.status(), http::StatusCode::OK, "{}", path);
+            assert_eq!(
+                response
+                    .headers()
+                    .get(http::header::ACCESS_CONTROL_ALLOW_ORIGIN)
+                    .map(|value| value.to_str().expect("header should be a string")),
+                expected,
+                "{} with allowed origins {:?}",
+                path,
+                allowed_origins
+            );
+        }
+    }
+}
+
+mod standard_api {
+    use super::*;
+    use http::StatusCode;
+    use remote_beacon_node::Error::DidNotSucceed;
+    use rest_types::standard::{ErrorMessage, ValidatorStatus};
+    use types::Address;
+    use url::Url;
+
+    #[test]
+    fn get_genesis() {
+        let mut env = build_env();
+
+        let node = build_node(&mut env, testing_client_config());
+        let remote_node = node.remote_node().expect("should produce remote node");
+
+        let genesis = env
+            .runtime()
+            .block_on(remote_node.http.standard().get_genesis())
+            .expect("should fetch from http api");
+
+        let chain = node
+            .client
+            .beacon_chain()
+            .expect("node should have beacon chain");
+        let head_info = chain.head_info().expect("should get head info");
+
+        assert_eq!(genesis.genesis_time, head_info.genesis_time);
+        assert_eq!(
+            genesis.genesis_validators_root,
+            head_info.genesis_validators_root
+        );
+        assert_eq!(
+            genesis.genesis_fork_version,
+            chain.spec.genesis_fork_version
+        );
+    }
+
+    #[test]
+    fn state_and_block_identifiers() {
+        let mut env = build_env();
+
+        let node = build_node(&mut env, testing_client_config());
+        let remote_node = node.remote_node().expect("should produce remote node");
+        let standard = remote_node.http.standard();
+
+        let chain = node
+            .client
+            .beacon_chain()
+            .expect("node should have beacon chain");
+        let head = chain.head().expect("should get head");
+
+        for state_id in &["head", "0", "genesis", "finalized", "justified"] {
+            let root = env
+                .runtime()
+                .block_on(standard.get_state_root(state_id))
+                .expect("should fetch state root");
+            let (expected_root, _) = chain
+                .rev_iter_state_roots()
+                .expect("should get iter")
+                .map(Result::unwrap)
+                .last()
+                .expect("should have genesis state root");
+
+            if *state_id == "head" {
+                assert_eq!(root, head.beacon_state_root, "head state root");
+            } else {
+                // The testing chain has not progressed past genesis.
+                assert_eq!(root, expected_root, "{} state root", state_id);
+            }
+        }
+
+        let head_root = env
+            .runtime()
+            .block_on(standard.get_block_root("head"))
+            .expect("should fetch head block root");
+        assert_eq!(head_root, head.beacon_block_root);
+
+        let root_id = format!("0x{}", hex::encode(head_root.as_bytes()));
+        let block = env
+            .runtime()
+            .block_on(standard.get_block(&root_id))
+            .expect("should fetch block by root");
+        assert_eq!(block, head.beacon_block);
+
+        let header = env
+            .runtime()
+            .block_on(standard.get_block_header("head"))
+            .expect("should fetch head header");
+        assert_eq!(header.root, head_root);
+        assert!(header.canonical, "head should be canonical");
+        assert_eq!(
+            header.header.message,
+            head.beacon_block.message.block_header()
+        );
+
+        let fork = env
+            .runtime()
+            .block_on(standard.get_state_fork("head"))
+            .expect("should fetch fork");
+        assert_eq!(fork, head.beacon_state.fork);
+    }
+
+    #[test]
+    fn get_state_validators() {
+        let mut env = build_env();
+
+        let node = build_node(&mut env, testing_client_config());
+        let remote_node = node.remote_node().expect("should produce remote node");
+
+        let validators = env
+            .runtime()
+            .block_on(remote_node.http.standard().get_state_validators("head"))
+            .expect("should fetch validators");
+
+        let state = node
+            .client
+            .beacon_chain()
+            .expect("node should have beacon chain")
+            .head()
+            .expect("should get head")
+            .beacon_state;
+
+        assert_eq!(validators.len(), state.validators.len());
+        for (i, validator) in validators.iter().enumerate() {
+            assert_eq!(validator.index, i as u64);
+            assert_eq!(validator.validator, state.validators[i]);
+            assert_eq!(validator.balance, state.balances[i]);
+            assert_eq!(validator.status, ValidatorStatus::ActiveOngoing);
+        }
+    }
+
+    #[test]
+    fn get_proposer_duties() {
+        let mut env = build_env();
+
+        let node = build_node(&mut env, testing_client_config());
+        let remote_node = node.remote_node().expect("should produce remote node");
+        let chain = node
+            .client
+            .beacon_chain()
+            .expect("node should have beacon chain");
+
+        let epoch = Epoch::new(0);
+        let duties = env
+            .runtime()
+            .block_on(remote_node.http.standard().get_proposer_duties(epoch))
+            .expect("should fetch proposer duties");
+
+        assert_eq!(duties.len() as u64, E::slots_per_epoch());
+        for (duty, slot) in duties.iter().zip(epoch.slot_iter(E::slots_per_epoch())) {
+            assert_eq!(duty.slot, slot);
+            assert_eq!(
+                duty.validator_index as usize,
+                chain.block_proposer(slot).expect("should get proposer")
+            );
+        }
+    }
+
+    #[test]
+    fn get_node_version() {
+        let mut env = build_env();
+
+        let node = build_node(&mut env, testing_client_config());
+        let remote_node = node.remote_node().expect("should produce remote node");
+
+        let version = env
+            .runtime()
+            .block_on(remote_node.http.standard().get_node_version())
+            .expect("should fetch version");
+
+        assert_eq!(version, version::version());
+    }
+
+    #[test]
+    fn errors_are_json() {
+        let mut env = build_env();
+
+        let node = build_node(&mut env, testing_client_config());
+        let remote_node = node.remote_node().expect("should produce remote node");
+        let socket_addr = node
+            .client
+            .http_listen_addr()
+            .expect("A remote beacon node must have a http server");
+        let url = Url::parse(&format!(
+            "http://{}:{}/eth/v1/beacon/states/cats/root",
+            socket_addr.ip(),
+            socket_addr.port()
+        ))
+        .expect("should be valid endpoint");
+
+        let result = env
+            .runtime()
+            .block_on(remote_node.http.json_get::<serde_json::Value>(url, vec![]));
+
+        assert_matches!(
+            result.expect_err("should not succeed"),
+            DidNotSucceed { status, body } => {
+                assert_eq!(status, StatusCode::BAD_REQUEST);
+                let error: ErrorMessage =
+                    serde_json::from_str(&body).expect("body should be an ErrorMessage");
+                assert_eq!(error.code, 400);
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_event_topics_are_rejected() {
+        let mut env = build_env();
+
+        let node = build_node(&mut env, testing_client_config());
+        let remote_node = node.remote_node().expect("should produce remote node");
+        let socket_addr = node
+            .client
+            .http_listen_addr()
+            .expect("A remote beacon node must have a http server");
+
+        for query in &["", "?topics=head,cats", "?topics=head&topics=cats"] {
+            let url = Url::parse(&format!(
+                "http://{}:{}/eth/v1/events{}",
+                socket_addr.ip(),
+                socket_addr.port(),
+                query
+            ))
+            .expect("should be valid endpoint");
+
+            let result = env
+                .runtime()
+                .block_on(remote_node.http.json_get::<serde_json::Value>(url, vec![]));
+
+            assert_matches!(
+                result.expect_err("should not succeed"),
+                DidNotSucceed { status, .. } => assert_eq!(status, StatusCode::BAD_REQUEST)
+            );
+        }
+    }
+
+    #[test]
+    fn get_deposit_contract() {
+        let mut env = build_env();
+
+        let config = testing_client_config();
+        let expected_address = config
+            .eth1
+            .deposit_contract_address
+            .trim_start_matches("0x")
+            .parse::<Address>()
+            .expect("should parse address");
+        let node = build_node(&mut env, config);
+        let remote_node = node.remote_node().expect("should produce remote node");
+        let chain = node
+            .client
+            .beacon_chain()
+            .expect("node should have beacon chain");
+
+        let deposit_contract = env
+            .runtime()
+            .block_on(remote_node.http.standard().get_deposit_contract())
+            .expect("should fetch deposit contract");
+
+        assert_eq!(deposit_contract.chain_id, chain.spec.deposit_chain_id);
+        assert_eq!(deposit_contract.address, expected_address);
+    }
+
+    #[test]
+    fn post_pool_slashings() {
+        let mut env = build_env();
+
+        let node = build_node(&mut env, testing_client_config());
+        let remote_node = node.remote_node().expect("should produce remote node");
+        let standard = remote_node.http.standard();
+        let chain = node
+            .client
+            .beacon_chain()
+            .expect("node should have beacon chain");
+
+        let state = chain.head().expect("should get head").beacon_state;
+        let proposer_index = chain
+            .block_proposer(state.slot)
+            .expect("should get proposer index");
+        let keypair = generate_deterministic_keypair(proposer_index);
+        // The op pool won't return an attester slashing for a validator that a proposer slashing
+        // already slashes, so slash a different validator.
+        let attester_index = (proposer_index + 1) % state.validators.len();
+        let attester_keypair = generate_deterministic_keypair(attester_index);
+
+        let proposer_slashing = build_proposer_slashing::<E>(
+            ProposerSlashingTestTask::Valid,
+            proposer_index as u64,
+            &keypair.sk,
+            &state.fork,
+            state.genesis_validators_root,
+            &chain.spec,
+        );
+        let attester_slashing = build_double_vote_attester_slashing(
+            AttesterSlashingTestTask::Valid,
+            &[attester_index as u64],
+            &[&attester_keypair.sk],
+            &state.fork,
+            state.genesis_validators_root,
+            &chain.spec,
+        );
+
+        // Posting the same slashing twice succeeds, but only imports it once.
+        for _ in 0..2 {
+            env.runtime()
+                .block_on(standard.post_pool_proposer_slashing(proposer_slashing.clone()))
+                .expect("should post proposer slashing");
+            env.runtime()
+                .block_on(standard.post_pool_attester_slashing(attester_slashing.clone()))
+                .expect("should post attester slashing");
+        }
+
+        let (proposer_slashings, attester_slashings) = chain.op_pool.get_slashings(&state);
+        assert_eq!(proposer_slashings, vec![proposer_slashing.clone()]);
+        assert_eq!(attester_slashings, vec![attester_slashing.clone()]);
+
+        let mut invalid_proposer_slashing = proposer_slashing;
+        invalid_proposer_slashing.signed_header_2 =
+            invalid_proposer_slashing.signed_header_1.clone();
+        assert_matches!(
+            env.runtime()
+                .block_on(standard.post_pool_proposer_slashing(invalid_proposer_slashing))
+                .expect_err("should reject invalid proposer slashing"),
+            DidNotSucceed { status, .. } => assert_eq!(status, StatusCode::BAD_REQUEST)
+        );
+
+        let mut invalid_attester_slashing = attester_slashing;
+        invalid_attester_slashing.attestation_2 = invalid_attester_slashing.attestation_1.clone();
+        assert_matches!(
+            env.runtime()
+                .block_on(standard.post_pool_attester_slashing(invalid_attester_slashing))
+                .expect_err("should reject invalid attester slashing"),
+            DidNotSucceed { status, .. } => assert_eq!(status, StatusCode::BAD_REQUEST)
+        );
+    }
+}
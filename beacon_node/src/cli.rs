@@ -60,6 +60,26 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("The UDP port that discovery will listen on. Defaults to `port`")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("listen-transports")
+                .long("listen-transports")
+                .value_name("TRANSPORTS")
+                .help("One or more comma-delimited transports that libp2p will listen on. \
+                       Possible values are: tcp, ws. The ENR only advertises the ports of the \
+                       listed transports, and peers which only advertise ws are dialed over \
+                       WebSockets.")
+                .default_value("tcp")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("libp2p-ws-port")
+                .long("libp2p-ws-port")
+                .value_name("PORT")
+                .help("The TCP port to listen on for libp2p WebSocket connections, if the ws \
+                       transport is enabled with --listen-transports.")
+                .default_value("9001")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("max-peers")
                 .long("max-peers")
@@ -90,6 +110,14 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                     The --port flag is used if this is not set.")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("enr-ws-port")
+                .long("enr-ws-port")
+                .value_name("PORT")
+                .help("The WebSocket port of the local ENR. Set this only if you are sure other nodes can connect to your local node on this port.\
+                    The --libp2p-ws-port flag is used if this is not set.")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("enr-address")
                 .long("enr-address")
@@ -125,6 +153,17 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                        without an ENR.")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("trusted-peers")
+                .long("trusted-peers")
+                .value_name("PEER_ID")
+                .help("One or more comma-delimited peer ids which are never scored down or \
+                       disconnected to make room for other peers, and are redialed with a \
+                       backoff whenever they disconnect. A trusted peer can only be redialed once \
+                       it has connected at least once, since its addresses are learned from that \
+                       connection; supply its address with --libp2p-addresses to connect to it at startup.")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("disable-discovery")
                 .long("disable-discovery")
@@ -143,7 +182,7 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
             Arg::with_name("http-address")
                 .long("http-address")
                 .value_name("ADDRESS")
-                .help("Set the listen address for the RESTful HTTP API server.")
+                .help("Set the listen address (IPv4 or IPv6) for the RESTful HTTP API server.")
                 .default_value("127.0.0.1")
                 .takes_value(true),
         )
@@ -155,6 +194,41 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .default_value("5052")
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("http-tls-cert")
+                .long("http-tls-cert")
+                .value_name("PATH")
+                .help("Serve the RESTful HTTP API over HTTPS, using the PEM certificate chain at \
+                       this path. Requires --http-tls-key.")
+                .requires("http-tls-key")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("http-tls-key")
+                .long("http-tls-key")
+                .value_name("PATH")
+                .help("The PEM private key for the certificate given by --http-tls-cert.")
+                .requires("http-tls-cert")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("http-allow-origin")
+                .long("http-allow-origin")
+                .value_name("ORIGINS")
+                .help("Comma-separated origins (e.g., https://example.com) which browsers may \
+                       make cross-origin requests to the RESTful HTTP API from. Use * to allow \
+                       any origin. By default, no CORS headers are sent.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("http-require-token")
+                .long("http-require-token")
+                .help("Require every request to the RESTful HTTP API to include an \
+                       `Authorization: Bearer <token>` header. The token is read from \
+                       api-token.txt in the beacon node datadir, which is generated if it does \
+                       not exist.")
+                .takes_value(false),
+        )
         /* Websocket related arguments */
         .arg(
             Arg::with_name("ws")
@@ -179,6 +253,54 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .takes_value(true),
         )
 
+        /*
+         * Eth1 Integration
+         */
+        .arg(
+            Arg::with_name("eth1")
+                .long("eth1")
+                .help("If present the node will connect to an eth1 node. This is only required to \
+                       produce blocks with real eth1 data and deposits, without it blocks are \
+                       produced with dummy eth1 data.")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("dummy-eth1")
+                .long("dummy-eth1")
+                .conflicts_with("eth1")
+                .help("If present, uses an eth1 backend that generates static dummy data. \
+                       Identical to the method used at the 2019 Canada interop. This backend is \
+                       also used when --eth1 is not present.")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("eth1-endpoints")
+                .long("eth1-endpoints")
+                .alias("eth1-endpoint")
+                .value_name("HTTP-ENDPOINTS")
+                .help("One or more comma-delimited server endpoints for web3 connection. If \
+                       multiple endpoints are given the endpoints are used as fallback in the \
+                       given order. [default: http://localhost:8545]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("eth1-follow-distance")
+                .long("eth1-follow-distance")
+                .value_name("BLOCKS")
+                .help("Specifies how many blocks behind the eth1 head the node should follow. \
+                       Must not be greater than the spec's ETH1_FOLLOW_DISTANCE. [default: \
+                       ETH1_FOLLOW_DISTANCE]")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("eth1-cache-size")
+                .long("eth1-cache-size")
+                .value_name("BLOCKS")
+                .help("Specifies how many eth1 blocks should be kept in the block cache. \
+                       [default: 4096]")
+                .takes_value(true),
+        )
+
         .arg(
             Arg::with_name("slots-per-restore-point")
                 .long("slots-per-restore-point")
@@ -194,6 +316,74 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 .help("Specifies how many blocks the database should cache in memory [default: 5]")
                 .takes_value(true)
         )
+        .arg(
+            Arg::with_name("historic-state-cache-size")
+                .long("historic-state-cache-size")
+                .value_name("SIZE")
+                .help("Specifies how many restore points, and separately how many states between \
+                       restore points, from the freezer database should be cached in memory, to \
+                       speed up repeated queries for historic states. [default: 4]")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("db-backend")
+                .long("db-backend")
+                .value_name("BACKEND")
+                .help("The key-value store used when creating a new database. Existing databases \
+                       must be opened with the backend they were created with. [default: leveldb]")
+                .possible_values(&["leveldb", "lmdb"])
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("prune-historic-states")
+                .long("prune-historic-states")
+                .help("If present, finalized states are deleted from the freezer database, \
+                       keeping only blocks and the most recent finalized states. Requests for \
+                       pruned states will fail. States pruned from the database cannot be \
+                       recovered without re-syncing.")
+                .takes_value(false)
+        )
+        .arg(
+            Arg::with_name("historic-state-retention")
+                .long("historic-state-retention")
+                .value_name("EPOCHS")
+                .help("The number of epochs of finalized states to keep when pruning historic \
+                       states, rounded up to a whole restore point. [default: 0]")
+                .requires("prune-historic-states")
+                .takes_value(true)
+        )
+
+        /*
+         * Checkpoint sync.
+         */
+        .arg(
+            Arg::with_name("checkpoint-state")
+                .long("checkpoint-state")
+                .value_name("STATE_SSZ")
+                .help("Set a checkpoint state to start syncing from. Must be aligned and match \
+                       --checkpoint-block. Using --checkpoint-sync-url instead is recommended.")
+                .takes_value(true)
+                .requires("checkpoint-block")
+        )
+        .arg(
+            Arg::with_name("checkpoint-block")
+                .long("checkpoint-block")
+                .value_name("BLOCK_SSZ")
+                .help("Set a checkpoint block to start syncing from. Must be aligned and match \
+                       --checkpoint-state. Using --checkpoint-sync-url instead is recommended.")
+                .takes_value(true)
+                .requires("checkpoint-state")
+        )
+        .arg(
+            Arg::with_name("checkpoint-sync-url")
+                .long("checkpoint-sync-url")
+                .value_name("BEACON_NODE")
+                .help("Set the remote beacon node HTTP endpoint to use for checkpoint sync. The \
+                       latest finalized state and block are downloaded and used as the starting \
+                       point for the chain. Ignored if the database already contains a chain.")
+                .takes_value(true)
+                .conflicts_with("checkpoint-state")
+        )
 
         /*
          * Purge.
@@ -206,10 +396,13 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
 
         .arg(Arg::with_name("validator-count")
              .value_name("VALIDATOR_COUNT")
-             .required(true)
-             .help("The number of validators in the genesis state."))
+             .requires("genesis-time")
+             .help("If present, starts an interop chain with this number of validators in the \
+                    genesis state. When absent, the genesis state is read from the database, the \
+                    genesis.ssz file in the testnet directory or the eth1 deposit contract, in \
+                    that order of preference."))
         .arg(Arg::with_name("genesis-time")
              .value_name("GENESIS_TIME")
-             .required(true)
-             .help("The genesis time for the given state."))
+             .requires("validator-count")
+             .help("The genesis time for the interop genesis state."))
 }
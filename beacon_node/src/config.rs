@@ -1,10 +1,11 @@
 use beacon_chain::builder::PUBKEY_CACHE_FILENAME;
 use clap::ArgMatches;
-use clap_utils::{parse_required, BAD_TESTNET_DIR_MESSAGE};
+use clap_utils::{parse_optional, parse_required, BAD_TESTNET_DIR_MESSAGE};
 use client::{config::DEFAULT_DATADIR, ClientConfig, ClientGenesis};
-use eth2_libp2p::{Enr, Multiaddr};
+use eth2_libp2p::{Enr, ListenTransport, Multiaddr, PeerId};
 use eth2_testnet_config::Eth2TestnetConfig;
 use slog::{crit, info, Logger};
+use ssz::Encode;
 use std::fs;
 use std::net::{IpAddr, Ipv4Addr, ToSocketAddrs};
 use std::net::{TcpListener, UdpSocket};
@@ -102,6 +103,19 @@ pub fn get_config<E: EthSpec>(
         client_config.network.discovery_port = port;
     }
 
+    if let Some(transports_str) = cli_args.value_of("listen-transports") {
+        client_config.network.listen_transports = transports_str
+            .split(',')
+            .map(|transport| transport.parse())
+            .collect::<Result<Vec<ListenTransport>, _>>()?;
+    }
+
+    if let Some(port_str) = cli_args.value_of("libp2p-ws-port") {
+        client_config.network.ws_port = port_str
+            .parse::<u16>()
+            .map_err(|_| format!("Invalid WebSocket port: {}", port_str))?;
+    }
+
     if let Some(port_str) = cli_args.value_of("discovery-port") {
         let port = port_str
             .parse::<u16>()
@@ -127,6 +141,17 @@ pub fn get_config<E: EthSpec>(
             .collect::<Result<Vec<Multiaddr>, _>>()?;
     }
 
+    if let Some(trusted_peers_str) = cli_args.value_of("trusted-peers") {
+        client_config.network.trusted_peers = trusted_peers_str
+            .split(',')
+            .map(|peer_id| {
+                peer_id
+                    .parse()
+                    .map_err(|_| format!("Invalid trusted peer id: {}", peer_id))
+            })
+            .collect::<Result<Vec<PeerId>, _>>()?;
+    }
+
     if let Some(enr_udp_port_str) = cli_args.value_of("enr-udp-port") {
         client_config.network.enr_udp_port = Some(
             enr_udp_port_str
@@ -143,6 +168,14 @@ pub fn get_config<E: EthSpec>(
         );
     }
 
+    if let Some(enr_ws_port_str) = cli_args.value_of("enr-ws-port") {
+        client_config.network.enr_ws_port = Some(
+            enr_ws_port_str
+                .parse::<u16>()
+                .map_err(|_| format!("Invalid ENR WebSocket port: {}", enr_ws_port_str))?,
+        );
+    }
+
     if cli_args.is_present("enr-match") {
         // set the enr address to localhost if the address is 0.0.0.0
         if client_config.network.listen_address
@@ -209,8 +242,8 @@ pub fn get_config<E: EthSpec>(
 
     if let Some(address) = cli_args.value_of("http-address") {
         client_config.rest_api.listen_address = address
-            .parse::<Ipv4Addr>()
-            .map_err(|_| "http-address is not a valid IPv4 address.")?;
+            .parse::<IpAddr>()
+            .map_err(|_| "http-address is not a valid IPv4 or IPv6 address.")?;
     }
 
     if let Some(port) = cli_args.value_of("http-port") {
@@ -219,6 +252,27 @@ pub fn get_config<E: EthSpec>(
             .map_err(|_| "http-port is not a valid u16.")?;
     }
 
+    if let (Some(cert), Some(key)) = (
+        parse_optional::<PathBuf>(cli_args, "http-tls-cert")?,
+        parse_optional::<PathBuf>(cli_args, "http-tls-key")?,
+    ) {
+        client_config.rest_api.tls_config = Some(rest_api::TlsConfig { cert, key });
+    }
+
+    if let Some(allowed_origins) = cli_args.value_of("http-allow-origin") {
+        client_config.rest_api.allowed_origins = allowed_origins
+            .split(',')
+            .map(str::trim)
+            .filter(|origin| !origin.is_empty())
+            .map(String::from)
+            .collect();
+    }
+
+    if cli_args.is_present("http-require-token") {
+        client_config.rest_api.api_token_path =
+            Some(client_config.data_dir.join(rest_api::API_TOKEN_FILENAME));
+    }
+
     /*
      * Websocket server
      */
@@ -239,6 +293,41 @@ pub fn get_config<E: EthSpec>(
             .map_err(|_| "ws-port is not a valid u16.")?;
     }
 
+    /*
+     * Eth1
+     */
+
+    // When present, use an eth1 backend that generates deterministic junk.
+    //
+    // Useful for running testnets without the overhead of a deposit contract.
+    if cli_args.is_present("dummy-eth1") {
+        client_config.dummy_eth1_backend = true;
+    }
+
+    // When present, attempt to sync to an eth1 node.
+    //
+    // Required for block production.
+    if cli_args.is_present("eth1") {
+        client_config.sync_eth1_chain = true;
+    }
+
+    // Defines the URLs to reach the eth1 nodes.
+    if let Some(endpoints) = cli_args.value_of("eth1-endpoints") {
+        client_config.eth1.endpoints = endpoints
+            .split(',')
+            .map(|endpoint| endpoint.trim().to_string())
+            .filter(|endpoint| !endpoint.is_empty())
+            .collect();
+
+        if client_config.eth1.endpoints.is_empty() {
+            return Err("eth1-endpoints must contain at least one endpoint".into());
+        }
+    }
+
+    if let Some(cache_size) = parse_optional::<usize>(cli_args, "eth1-cache-size")? {
+        client_config.eth1.block_cache_truncation = Some(cache_size);
+    }
+
     if let Some(freezer_dir) = cli_args.value_of("freezer-dir") {
         client_config.freezer_db_path = Some(PathBuf::from(freezer_dir));
     }
@@ -260,6 +349,24 @@ pub fn get_config<E: EthSpec>(
             .map_err(|_| "block-cache-size is not a valid integer".to_string())?;
     }
 
+    if let Some(cache_size) = cli_args.value_of("historic-state-cache-size") {
+        client_config.store.historic_state_cache_size = cache_size
+            .parse()
+            .map_err(|_| "historic-state-cache-size is not a valid integer".to_string())?;
+    }
+
+    if let Some(backend) = cli_args.value_of("db-backend") {
+        client_config.store.backend = backend.parse()?;
+    }
+
+    client_config.store.prune_historic_states = cli_args.is_present("prune-historic-states");
+
+    if let Some(retention) = cli_args.value_of("historic-state-retention") {
+        client_config.store.historic_state_retention_epochs = retention
+            .parse()
+            .map_err(|_| "historic-state-retention is not a valid integer".to_string())?;
+    }
+
     if spec_constants != client_config.spec_constants {
         crit!(log, "Specification constants do not match.";
               "client_config" => client_config.spec_constants,
@@ -285,18 +392,12 @@ pub fn get_config<E: EthSpec>(
             unused_port("tcp").map_err(|e| format!("Failed to get port for libp2p: {}", e))?;
         client_config.network.discovery_port =
             unused_port("udp").map_err(|e| format!("Failed to get port for discovery: {}", e))?;
+        client_config.network.ws_port = unused_port("tcp")
+            .map_err(|e| format!("Failed to get port for libp2p WebSockets: {}", e))?;
         client_config.rest_api.port = 0;
         client_config.websocket_server.port = 0;
     }
 
-    let validator_count = parse_required(cli_args, "validator-count")?;
-    let genesis_time = parse_required(cli_args, "genesis-time")?;
-
-    client_config.genesis = ClientGenesis::Interop {
-        validator_count,
-        genesis_time,
-    };
-
     /*
      * Load the eth2 testnet dir to obtain some additional config values.
      */
@@ -311,13 +412,84 @@ pub fn get_config<E: EthSpec>(
         client_config.eth1.deposit_contract_deploy_block;
     client_config.eth1.follow_distance = spec.eth1_follow_distance;
 
+    if let Some(follow_distance) = parse_optional::<u64>(cli_args, "eth1-follow-distance")? {
+        if follow_distance > spec.eth1_follow_distance {
+            return Err(format!(
+                "eth1-follow-distance ({}) must not exceed ETH1_FOLLOW_DISTANCE ({})",
+                follow_distance, spec.eth1_follow_distance
+            ));
+        }
+        client_config.eth1.follow_distance = follow_distance;
+    }
+
     if let Some(mut boot_nodes) = eth2_testnet_config.boot_enr {
         client_config.network.boot_nodes.append(&mut boot_nodes)
     }
 
+    /*
+     * Genesis.
+     *
+     * If the database already contains a beacon chain the client builder will always resume
+     * from it, regardless of the value set here.
+     */
+    client_config.genesis =
+        if let Some(validator_count) = parse_optional(cli_args, "validator-count")? {
+            ClientGenesis::Interop {
+                validator_count,
+                genesis_time: parse_required(cli_args, "genesis-time")?,
+            }
+        } else if let Some(checkpoint_genesis) = checkpoint_genesis {
+            checkpoint_genesis
+        } else if let Some(genesis_state) = eth2_testnet_config.genesis_state {
+            // Note: re-serializing the genesis state is not so efficient, however it avoids adding
+            // trait bounds to the `ClientGenesis` enum.
+            ClientGenesis::SszBytes {
+                genesis_state_bytes: genesis_state.as_ssz_bytes(),
+            }
+        } else {
+            ClientGenesis::DepositContract
+        };
+
     Ok(client_config)
 }
 
+/// Returns a `ClientGenesis` which starts from a finalized checkpoint if either
+/// `--checkpoint-state` or `--checkpoint-sync-url` was supplied.
+///
+/// Checkpoint sync still requires the genesis state, which is read from the testnet directory.
+fn get_checkpoint_genesis<E: EthSpec>(
+    cli_args: &ArgMatches,
+    eth2_testnet_config: &Eth2TestnetConfig<E>,
+) -> Result<Option<ClientGenesis>, String> {
+    if !cli_args.is_present("checkpoint-state") && !cli_args.is_present("checkpoint-sync-url") {
+        return Ok(None);
+    }
+
+    let genesis_state_bytes = eth2_testnet_config
+        .genesis_state
+        .as_ref()
+        .map(|genesis_state| genesis_state.as_ssz_bytes())
+        .ok_or_else(|| "Checkpoint sync requires a genesis state in the testnet dir".to_string())?;
+
+    if let Some(url) = cli_args.value_of("checkpoint-sync-url") {
+        return Ok(Some(ClientGenesis::CheckpointSyncUrl {
+            genesis_state_bytes,
+            url: url.to_string(),
+        }));
+    }
+
+    let read = |arg: &str| -> Result<Vec<u8>, String> {
+        let path: PathBuf = parse_required(cli_args, arg)?;
+        fs::read(&path).map_err(|e| format!("Unable to read {:?}: {:?}", path, e))
+    };
+
+    Ok(Some(ClientGenesis::WeakSubjSszBytes {
+        genesis_state_bytes,
+        anchor_state_bytes: read("checkpoint-state")?,
+        anchor_block_bytes: read("checkpoint-block")?,
+    }))
+}
+
 /// Gets the datadir which should be used.
 pub fn get_data_dir(cli_args: &ArgMatches) -> PathBuf {
     // Read the `--datadir` flag.
@@ -12,27 +12,27 @@ pub use eth2_config::Eth2Config;
 
 use beacon_chain::events::TeeEventHandler;
 use beacon_chain::migrate::BackgroundMigrator;
-use beacon_chain::store::LevelDB;
+use beacon_chain::store::DiskStore;
 use beacon_chain::{
     builder::Witness, eth1_chain::CachingEth1Backend, slot_clock::SystemTimeSlotClock,
 };
 use clap::ArgMatches;
 use config::get_config;
 use environment::RuntimeContext;
-use slog::warn;
+use slog::{info, warn};
 use std::ops::{Deref, DerefMut};
 use types::EthSpec;
 
 /// A type-alias to the tighten the definition of a production-intended `Client`.
 pub type ProductionClient<E> = Client<
     Witness<
-        BackgroundMigrator<E, LevelDB<E>, LevelDB<E>>,
+        BackgroundMigrator<E, DiskStore<E>, DiskStore<E>>,
         SystemTimeSlotClock,
         CachingEth1Backend<E>,
         E,
         TeeEventHandler<E>,
-        LevelDB<E>,
-        LevelDB<E>,
+        DiskStore<E>,
+        DiskStore<E>,
     >,
 >;
 
@@ -92,14 +92,30 @@ impl<E: EthSpec> ProductionBeaconNode<E> {
 
         let builder = builder
             .beacon_chain_builder(client_genesis, client_config_1)
-            .await?
-            .dummy_eth1_backend()?;
-
-        warn!(
-            log,
-            "Block production impaired";
-            "reason" => "dummy eth1 backend is enabled"
-        );
+            .await?;
+        let builder = if client_config.sync_eth1_chain && !client_config.dummy_eth1_backend {
+            info!(
+                log,
+                "Block production enabled";
+                "endpoints" => format!("{:?}", &client_config.eth1.endpoints),
+                "method" => "json rpc via http"
+            );
+            builder.caching_eth1_backend(client_config.eth1.clone())?
+        } else {
+            // Without `--eth1` the dummy backend is used, as it was before the eth1 flags were
+            // added, so that block production keeps working for existing users.
+            let reason = if client_config.dummy_eth1_backend {
+                "dummy eth1 backend is enabled"
+            } else {
+                "no eth1 node configured, using the dummy eth1 backend (use --eth1 to connect)"
+            };
+            warn!(
+                log,
+                "Block production impaired";
+                "reason" => reason
+            );
+            builder.dummy_eth1_backend()?
+        };
 
         let (builder, events) = builder
             .system_time_slot_clock()?
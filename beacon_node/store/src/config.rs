@@ -1,8 +1,12 @@
 use serde_derive::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
 use types::{EthSpec, MinimalEthSpec};
 
 pub const DEFAULT_SLOTS_PER_RESTORE_POINT: u64 = 2048;
 pub const DEFAULT_BLOCK_CACHE_SIZE: usize = 5;
+pub const DEFAULT_HISTORIC_STATE_CACHE_SIZE: usize = 4;
 
 /// Database configuration parameters.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -11,6 +15,16 @@ pub struct StoreConfig {
     pub slots_per_restore_point: u64,
     /// Maximum number of blocks to store in the in-memory block cache.
     pub block_cache_size: usize,
+    /// Maximum number of restore points, and separately of states between restore points, from
+    /// the freezer database to store in the in-memory state caches.
+    pub historic_state_cache_size: usize,
+    /// Whether to delete finalized states from the freezer database, keeping only blocks and the
+    /// states of the most recent `historic_state_retention_epochs`.
+    pub prune_historic_states: bool,
+    /// The number of epochs prior to the split point for which states are kept when pruning.
+    pub historic_state_retention_epochs: u64,
+    /// The key-value store used for new databases.
+    pub backend: DatabaseBackend,
 }
 
 impl Default for StoreConfig {
@@ -19,6 +33,57 @@ impl Default for StoreConfig {
             // Safe default for tests, shouldn't ever be read by a CLI node.
             slots_per_restore_point: MinimalEthSpec::slots_per_historical_root() as u64,
             block_cache_size: DEFAULT_BLOCK_CACHE_SIZE,
+            historic_state_cache_size: DEFAULT_HISTORIC_STATE_CACHE_SIZE,
+            prune_historic_states: false,
+            historic_state_retention_epochs: 0,
+            backend: DatabaseBackend::default(),
+        }
+    }
+}
+
+/// The key-value stores which may be used for the hot and cold databases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DatabaseBackend {
+    LevelDB,
+    Lmdb,
+}
+
+impl DatabaseBackend {
+    /// Returns the backend of the existing database at `path`, or `None` if there is no database.
+    pub fn detect(path: &Path) -> Option<Self> {
+        if path.join("CURRENT").exists() {
+            Some(DatabaseBackend::LevelDB)
+        } else if path.join("data.mdb").exists() {
+            Some(DatabaseBackend::Lmdb)
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for DatabaseBackend {
+    fn default() -> Self {
+        DatabaseBackend::LevelDB
+    }
+}
+
+impl FromStr for DatabaseBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "leveldb" => Ok(DatabaseBackend::LevelDB),
+            "lmdb" => Ok(DatabaseBackend::Lmdb),
+            other => Err(format!("Unknown database backend: {}", other)),
+        }
+    }
+}
+
+impl fmt::Display for DatabaseBackend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DatabaseBackend::LevelDB => write!(f, "leveldb"),
+            DatabaseBackend::Lmdb => write!(f, "lmdb"),
         }
     }
 }
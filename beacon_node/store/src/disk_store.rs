@@ -0,0 +1,95 @@
+use super::*;
+use crate::config::DatabaseBackend;
+use crate::errors::DatabaseBackendMismatch;
+use crate::leveldb_store::LevelDB;
+use crate::lmdb_store::Lmdb;
+use std::path::Path;
+
+/// An on-disk key-value store, using the backend chosen when the database was created.
+pub enum DiskStore<E: EthSpec> {
+    LevelDB(LevelDB<E>),
+    Lmdb(Lmdb<E>),
+}
+
+impl<E: EthSpec> DiskStore<E> {
+    /// Open the database at `path`, creating a new database with `backend` if one does not already
+    /// exist.
+    ///
+    /// Returns an error if an existing database uses a different backend.
+    pub fn open(path: &Path, backend: DatabaseBackend) -> Result<Self, Error> {
+        if let Some(existing) = DatabaseBackend::detect(path) {
+            if existing != backend {
+                return Err(Error::DatabaseBackendMismatch(DatabaseBackendMismatch {
+                    configured: backend,
+                    existing,
+                }));
+            }
+        }
+
+        match backend {
+            DatabaseBackend::LevelDB => LevelDB::open(path).map(DiskStore::LevelDB),
+            DatabaseBackend::Lmdb => Lmdb::open(path).map(DiskStore::Lmdb),
+        }
+    }
+
+    /// Returns the backend of the database.
+    pub fn backend(&self) -> DatabaseBackend {
+        match self {
+            DiskStore::LevelDB(_) => DatabaseBackend::LevelDB,
+            DiskStore::Lmdb(_) => DatabaseBackend::Lmdb,
+        }
+    }
+
+    /// Call `f` with every key-value pair in the database, in key order.
+    ///
+    /// Keys are prefixed by their column (see `get_key_for_col`).
+    pub fn for_each_raw_entry<F: FnMut(&[u8], &[u8])>(&self, mut f: F) -> Result<(), Error> {
+        match self {
+            DiskStore::LevelDB(db) => {
+                db.iter_raw_entries()
+                    .for_each(|(key, value)| f(&key, &value));
+                Ok(())
+            }
+            DiskStore::Lmdb(db) => db.for_each_raw_entry(f),
+        }
+    }
+}
+
+impl<E: EthSpec> KeyValueStore<E> for DiskStore<E> {
+    fn get_bytes(&self, column: &str, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        match self {
+            DiskStore::LevelDB(db) => db.get_bytes(column, key),
+            DiskStore::Lmdb(db) => db.get_bytes(column, key),
+        }
+    }
+
+    fn put_bytes(&self, column: &str, key: &[u8], value: &[u8]) -> Result<(), Error> {
+        match self {
+            DiskStore::LevelDB(db) => db.put_bytes(column, key, value),
+            DiskStore::Lmdb(db) => db.put_bytes(column, key, value),
+        }
+    }
+
+    fn key_exists(&self, column: &str, key: &[u8]) -> Result<bool, Error> {
+        match self {
+            DiskStore::LevelDB(db) => db.key_exists(column, key),
+            DiskStore::Lmdb(db) => db.key_exists(column, key),
+        }
+    }
+
+    fn key_delete(&self, column: &str, key: &[u8]) -> Result<(), Error> {
+        match self {
+            DiskStore::LevelDB(db) => db.key_delete(column, key),
+            DiskStore::Lmdb(db) => db.key_delete(column, key),
+        }
+    }
+
+    fn do_atomically(&self, batch: Vec<KeyValueStoreOp>) -> Result<(), Error> {
+        match self {
+            DiskStore::LevelDB(db) => db.do_atomically(batch),
+            DiskStore::Lmdb(db) => db.do_atomically(batch),
+        }
+    }
+}
+
+impl<E: EthSpec> ItemStore<E> for DiskStore<E> {}
@@ -1,4 +1,5 @@
 use crate::chunked_vector::ChunkError;
+use crate::config::DatabaseBackend;
 use crate::hot_cold_store::HotColdDBError;
 use ssz::DecodeError;
 use types::{BeaconStateError, Hash256};
@@ -16,6 +17,14 @@ pub enum Error {
     RlpError(String),
     BlockNotFound(Hash256),
     NoContinuationData,
+    DatabaseBackendMismatch(DatabaseBackendMismatch),
+}
+
+/// The backend of an existing database differs from the one configured.
+#[derive(Debug, PartialEq)]
+pub struct DatabaseBackendMismatch {
+    pub configured: DatabaseBackend,
+    pub existing: DatabaseBackend,
 }
 
 impl From<DecodeError> for Error {
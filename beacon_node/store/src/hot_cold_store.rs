@@ -1,13 +1,18 @@
 use crate::chunked_vector::{
     store_updated_vector, BlockRoots, HistoricalRoots, RandaoMixes, StateRoots,
 };
-use crate::config::StoreConfig;
+use crate::config::{DatabaseBackend, StoreConfig};
+use crate::disk_store::DiskStore;
 use crate::forwards_iter::HybridForwardsBlockRootsIterator;
 use crate::impls::beacon_state::{get_full_state, store_full_state};
 use crate::iter::{ParentRootBlockIterator, StateRootsIterator};
-use crate::leveldb_store::LevelDB;
 use crate::memory_store::MemoryStore;
+use crate::metadata::{
+    AnchorInfo, PruningInfo, SchemaVersion, ANCHOR_INFO_KEY, CURRENT_SCHEMA_VERSION,
+    INITIAL_SCHEMA_VERSION, PRUNING_INFO_KEY, SCHEMA_VERSION_KEY,
+};
 use crate::metrics;
+use crate::schema_change::migrate_schema;
 use crate::{
     get_key_for_col, DBColumn, Error, ItemStore, KeyValueStoreOp, PartialBeaconState, StoreItem,
     StoreOp,
@@ -41,6 +46,10 @@ pub struct HotColdDB<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> {
     /// States with slots less than `split.slot` are in the cold DB, while states with slots
     /// greater than or equal are in the hot DB.
     split: RwLock<Split>,
+    /// The point from which the database was initialised, if it was not initialised from genesis.
+    anchor_info: RwLock<Option<AnchorInfo>>,
+    /// The extent of the state history that has been pruned, if any.
+    pruning_info: RwLock<Option<PruningInfo>>,
     config: StoreConfig,
     /// Cold database containing compact historical data.
     pub(crate) cold_db: Cold,
@@ -50,6 +59,11 @@ pub struct HotColdDB<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> {
     pub(crate) hot_db: Hot,
     /// LRU cache of deserialized blocks. Updated whenever a block is loaded.
     block_cache: Mutex<LruCache<Hash256, SignedBeaconBlock<E>>>,
+    /// LRU cache of restore point states loaded from the freezer database, keyed by restore point
+    /// index.
+    restore_point_cache: Mutex<LruCache<u64, BeaconState<E>>>,
+    /// LRU cache of states between restore points loaded from the freezer database, keyed by slot.
+    historic_state_cache: Mutex<LruCache<Slot, BeaconState<E>>>,
     /// Chain spec.
     spec: ChainSpec,
     /// Logger.
@@ -85,6 +99,35 @@ pub enum HotColdDBError {
         slots_per_epoch: u64,
     },
     RestorePointBlockHashError(BeaconStateError),
+    /// The requested state lies prior to the oldest state that can be reconstructed following a
+    /// checkpoint sync.
+    StateBeforeAnchor {
+        slot: Slot,
+        state_lower_limit: Slot,
+    },
+    /// An anchor can only be set on a database that does not yet contain a finalized chain.
+    AnchorOnNonEmptyDatabase {
+        split_slot: Slot,
+    },
+    /// The anchor state must lie on an epoch boundary.
+    AnchorSlotUnaligned(Slot),
+    /// The requested state has been deleted from the freezer database by historic state pruning.
+    StatePruned {
+        slot: Slot,
+        state_lower_limit: Slot,
+    },
+    /// The database was written by a newer version of Lighthouse with an unknown schema.
+    UnsupportedSchemaVersion {
+        schema_version: SchemaVersion,
+        current_version: SchemaVersion,
+    },
+    /// The database must be migrated to the current schema version before it can be read.
+    SchemaMigrationRequired {
+        schema_version: SchemaVersion,
+        current_version: SchemaVersion,
+    },
+    /// No migration exists from the given schema version to the next.
+    MissingSchemaMigration(SchemaVersion),
 }
 
 impl<E: EthSpec> HotColdDB<E, MemoryStore<E>, MemoryStore<E>> {
@@ -97,9 +140,13 @@ impl<E: EthSpec> HotColdDB<E, MemoryStore<E>, MemoryStore<E>> {
 
         let db = HotColdDB {
             split: RwLock::new(Split::default()),
+            anchor_info: RwLock::new(None),
+            pruning_info: RwLock::new(None),
             cold_db: MemoryStore::open(),
             hot_db: MemoryStore::open(),
             block_cache: Mutex::new(LruCache::new(config.block_cache_size)),
+            restore_point_cache: Mutex::new(LruCache::new(config.historic_state_cache_size)),
+            historic_state_cache: Mutex::new(LruCache::new(config.historic_state_cache_size)),
             config,
             spec,
             log,
@@ -110,9 +157,12 @@ impl<E: EthSpec> HotColdDB<E, MemoryStore<E>, MemoryStore<E>> {
     }
 }
 
-impl<E: EthSpec> HotColdDB<E, LevelDB<E>, LevelDB<E>> {
+impl<E: EthSpec> HotColdDB<E, DiskStore<E>, DiskStore<E>> {
     /// Open a new or existing database, with the given paths to the hot and cold DBs.
     ///
+    /// New databases are created with the backend in `config`, which must match the backend of
+    /// existing databases. Existing databases are migrated to the current schema version.
+    ///
     /// The `slots_per_restore_point` parameter must be a divisor of `SLOTS_PER_HISTORICAL_ROOT`.
     pub fn open(
         hot_path: &Path,
@@ -120,26 +170,112 @@ impl<E: EthSpec> HotColdDB<E, LevelDB<E>, LevelDB<E>> {
         config: StoreConfig,
         spec: ChainSpec,
         log: Logger,
-    ) -> Result<HotColdDB<E, LevelDB<E>, LevelDB<E>>, Error> {
+    ) -> Result<HotColdDB<E, DiskStore<E>, DiskStore<E>>, Error> {
+        let db = Self::open_disk_stores(hot_path, cold_path, config, spec, log)?;
+
+        // Upgrade the database to the current schema before any of its items are read.
+        let schema_version = db.load_schema_version()?;
+        let from_version = schema_version.unwrap_or(INITIAL_SCHEMA_VERSION);
+        if from_version > CURRENT_SCHEMA_VERSION {
+            return Err(HotColdDBError::UnsupportedSchemaVersion {
+                schema_version: from_version,
+                current_version: CURRENT_SCHEMA_VERSION,
+            }
+            .into());
+        }
+        migrate_schema(&db, from_version, CURRENT_SCHEMA_VERSION)?;
+        if schema_version.is_none() {
+            db.store_schema_version(CURRENT_SCHEMA_VERSION)?;
+        }
+
+        db.load_persisted_metadata()?;
+        Ok(db)
+    }
+
+    /// Open an existing database without writing to it, for inspecting the database of a beacon
+    /// node which is not running.
+    ///
+    /// Unlike `Self::open`, the database is never migrated: an error is returned if it is not at
+    /// the current schema version.
+    pub fn open_read_only(
+        hot_path: &Path,
+        cold_path: &Path,
+        config: StoreConfig,
+        spec: ChainSpec,
+        log: Logger,
+    ) -> Result<HotColdDB<E, DiskStore<E>, DiskStore<E>>, Error> {
+        let db = Self::open_disk_stores(hot_path, cold_path, config, spec, log)?;
+
+        let schema_version = db.load_schema_version()?.unwrap_or(INITIAL_SCHEMA_VERSION);
+        if schema_version > CURRENT_SCHEMA_VERSION {
+            return Err(HotColdDBError::UnsupportedSchemaVersion {
+                schema_version,
+                current_version: CURRENT_SCHEMA_VERSION,
+            }
+            .into());
+        } else if schema_version < CURRENT_SCHEMA_VERSION {
+            return Err(HotColdDBError::SchemaMigrationRequired {
+                schema_version,
+                current_version: CURRENT_SCHEMA_VERSION,
+            }
+            .into());
+        }
+
+        db.load_persisted_metadata()?;
+        Ok(db)
+    }
+
+    /// Open the hot and cold databases, without reading or writing any of their items.
+    fn open_disk_stores(
+        hot_path: &Path,
+        cold_path: &Path,
+        config: StoreConfig,
+        spec: ChainSpec,
+        log: Logger,
+    ) -> Result<HotColdDB<E, DiskStore<E>, DiskStore<E>>, Error> {
         Self::verify_slots_per_restore_point(config.slots_per_restore_point)?;
 
-        let db = HotColdDB {
+        Ok(HotColdDB {
             split: RwLock::new(Split::default()),
-            cold_db: LevelDB::open(cold_path)?,
-            hot_db: LevelDB::open(hot_path)?,
+            anchor_info: RwLock::new(None),
+            pruning_info: RwLock::new(None),
+            cold_db: DiskStore::open(cold_path, config.backend)?,
+            hot_db: DiskStore::open(hot_path, config.backend)?,
             block_cache: Mutex::new(LruCache::new(config.block_cache_size)),
+            restore_point_cache: Mutex::new(LruCache::new(config.historic_state_cache_size)),
+            historic_state_cache: Mutex::new(LruCache::new(config.historic_state_cache_size)),
             config,
             spec,
             log,
             _phantom: PhantomData,
-        };
+        })
+    }
 
-        // Load the previous split slot from the database (if any). This ensures we can
-        // stop and restart correctly.
-        if let Some(split) = db.load_split()? {
-            *db.split.write() = split;
+    /// Load the split point, anchor and pruning info written when the database was last used.
+    ///
+    /// This ensures we can stop and restart correctly.
+    fn load_persisted_metadata(&self) -> Result<(), Error> {
+        if let Some(split) = self.load_split()? {
+            *self.split.write() = split;
         }
-        Ok(db)
+        *self.anchor_info.write() = self.load_anchor_info()?;
+        *self.pruning_info.write() = self.load_pruning_info()?;
+        Ok(())
+    }
+
+    /// The backend of the hot and cold databases.
+    pub fn backend(&self) -> DatabaseBackend {
+        self.hot_db.backend()
+    }
+
+    /// Call `f` with every raw key-value pair in the hot database, in key order.
+    pub fn for_each_raw_hot_entry<F: FnMut(&[u8], &[u8])>(&self, f: F) -> Result<(), Error> {
+        self.hot_db.for_each_raw_entry(f)
+    }
+
+    /// Call `f` with every raw key-value pair in the cold database, in key order.
+    pub fn for_each_raw_cold_entry<F: FnMut(&[u8], &[u8])>(&self, f: F) -> Result<(), Error> {
+        self.cold_db.for_each_raw_entry(f)
     }
 }
 
@@ -467,11 +603,7 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
         ops.push(op);
 
         // 2. Store updated vector entries.
-        let db = &self.cold_db;
-        store_updated_vector(BlockRoots, db, state, &self.spec, ops)?;
-        store_updated_vector(StateRoots, db, state, &self.spec, ops)?;
-        store_updated_vector(HistoricalRoots, db, state, &self.spec, ops)?;
-        store_updated_vector(RandaoMixes, db, state, &self.spec, ops)?;
+        self.store_cold_state_vectors(state, ops)?;
 
         // 3. Store restore point.
         let restore_point_index = state.slot.as_u64() / self.config.slots_per_restore_point;
@@ -480,6 +612,21 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
         Ok(())
     }
 
+    /// Store the entries of the vector fields of `state` (like `block_roots`) in the freezer
+    /// database, without storing the state itself.
+    fn store_cold_state_vectors(
+        &self,
+        state: &BeaconState<E>,
+        ops: &mut Vec<KeyValueStoreOp>,
+    ) -> Result<(), Error> {
+        let db = &self.cold_db;
+        store_updated_vector(BlockRoots, db, state, &self.spec, ops)?;
+        store_updated_vector(StateRoots, db, state, &self.spec, ops)?;
+        store_updated_vector(HistoricalRoots, db, state, &self.spec, ops)?;
+        store_updated_vector(RandaoMixes, db, state, &self.spec, ops)?;
+        Ok(())
+    }
+
     /// Try to load a pre-finalization state from the freezer database.
     ///
     /// Return `None` if no state with `state_root` lies in the freezer.
@@ -494,16 +641,76 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
     ///
     /// Will reconstruct the state if it lies between restore points.
     pub fn load_cold_state_by_slot(&self, slot: Slot) -> Result<BeaconState<E>, Error> {
+        if let Some(pruning_info) = self.get_pruning_info() {
+            if slot < pruning_info.state_lower_limit {
+                return Err(HotColdDBError::StatePruned {
+                    slot,
+                    state_lower_limit: pruning_info.state_lower_limit,
+                }
+                .into());
+            }
+        }
+
+        if let Some(anchor) = self.get_anchor_info() {
+            let state_lower_limit = anchor.state_lower_limit(self.config.slots_per_restore_point);
+            if slot < state_lower_limit {
+                return Err(HotColdDBError::StateBeforeAnchor {
+                    slot,
+                    state_lower_limit,
+                }
+                .into());
+            }
+        }
+
         if slot % self.config.slots_per_restore_point == 0 {
             let restore_point_idx = slot.as_u64() / self.config.slots_per_restore_point;
             self.load_restore_point_by_index(restore_point_idx)
         } else {
+            if let Some(state) = self.historic_state_cache.lock().get(&slot) {
+                metrics::inc_counter(&metrics::HISTORIC_STATE_CACHE_HIT_COUNT);
+                return Ok(state.clone());
+            }
+            metrics::inc_counter(&metrics::HISTORIC_STATE_CACHE_MISS_COUNT);
+
             self.load_cold_intermediate_state(slot)
         }
     }
 
+    /// Add a state between restore points loaded from the freezer database to the historic state
+    /// cache.
+    fn cache_historic_state(&self, state: &BeaconState<E>) {
+        if self.config.historic_state_cache_size == 0 {
+            return;
+        }
+
+        let mut cache = self.historic_state_cache.lock();
+        cache.put(state.slot, state.clone());
+        metrics::set_gauge(&metrics::HISTORIC_STATE_CACHE_SIZE, cache.len() as i64);
+    }
+
+    /// Return the cached historic state with the highest slot in `start_slot..end_slot`, if any.
+    ///
+    /// Only the states between restore points are searched, restore points themselves are looked
+    /// up by index in the restore point cache.
+    fn get_closest_cached_historic_state(
+        &self,
+        start_slot: Slot,
+        end_slot: Slot,
+    ) -> Option<BeaconState<E>> {
+        let mut cache = self.historic_state_cache.lock();
+        let closest_slot = cache
+            .iter()
+            .map(|(slot, _)| *slot)
+            .filter(|slot| *slot >= start_slot && *slot < end_slot)
+            .max()?;
+        cache.get(&closest_slot).cloned()
+    }
+
     /// Load a restore point state by its `state_root`.
     fn load_restore_point(&self, state_root: &Hash256) -> Result<BeaconState<E>, Error> {
+        let _timer = metrics::start_timer(&metrics::BEACON_STATE_READ_TIMES);
+        metrics::inc_counter(&metrics::BEACON_STATE_READ_COUNT);
+
         let mut partial_state: PartialBeaconState<E> = self
             .cold_db
             .get(state_root)?
@@ -518,17 +725,36 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
         Ok(partial_state.try_into()?)
     }
 
-    /// Load a restore point state by its `restore_point_index`.
+    /// Load a restore point state by its `restore_point_index`, from the cache if possible.
     fn load_restore_point_by_index(
         &self,
         restore_point_index: u64,
     ) -> Result<BeaconState<E>, Error> {
+        if let Some(state) = self.restore_point_cache.lock().get(&restore_point_index) {
+            metrics::inc_counter(&metrics::HISTORIC_STATE_CACHE_HIT_COUNT);
+            return Ok(state.clone());
+        }
+        metrics::inc_counter(&metrics::HISTORIC_STATE_CACHE_MISS_COUNT);
+
         let state_root = self.load_restore_point_hash(restore_point_index)?;
-        self.load_restore_point(&state_root)
+        let state = self.load_restore_point(&state_root)?;
+
+        if self.config.historic_state_cache_size > 0 {
+            let mut cache = self.restore_point_cache.lock();
+            cache.put(restore_point_index, state.clone());
+            metrics::set_gauge(&metrics::RESTORE_POINT_CACHE_SIZE, cache.len() as i64);
+        }
+
+        Ok(state)
     }
 
     /// Load a frozen state that lies between restore points.
+    ///
+    /// Blocks are replayed from the closest prior state in the historic state cache, which is
+    /// usually much closer than the low restore point when loading a run of nearby slots.
     fn load_cold_intermediate_state(&self, slot: Slot) -> Result<BeaconState<E>, Error> {
+        let _timer = metrics::start_timer(&metrics::HISTORIC_STATE_RECONSTRUCTION_TIMES);
+
         // 1. Load the restore points either side of the intermediate state.
         let low_restore_point_idx = slot.as_u64() / self.config.slots_per_restore_point;
         let high_restore_point_idx = low_restore_point_idx + 1;
@@ -536,7 +762,16 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
         // Acquire the read lock, so that the split can't change while this is happening.
         let split = self.split.read();
 
-        let low_restore_point = self.load_restore_point_by_index(low_restore_point_idx)?;
+        let low_restore_point_slot =
+            Slot::new(low_restore_point_idx * self.config.slots_per_restore_point);
+        let cached_state = self.get_closest_cached_historic_state(low_restore_point_slot, slot);
+        let base_state = match cached_state {
+            Some(state) => {
+                metrics::inc_counter(&metrics::HISTORIC_STATE_REPLAY_FROM_CACHE_COUNT);
+                state
+            }
+            None => self.load_restore_point_by_index(low_restore_point_idx)?,
+        };
         // If the slot of the high point lies outside the freezer, use the split state
         // as the upper restore point.
         let high_restore_point = if high_restore_point_idx * self.config.slots_per_restore_point
@@ -548,15 +783,21 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
             self.load_restore_point_by_index(high_restore_point_idx)?
         };
 
-        // 2. Load the blocks from the high restore point back to the low restore point.
+        // 2. Load the blocks from the high restore point back to the base state.
         let blocks = self.load_blocks_to_replay(
-            low_restore_point.slot,
+            base_state.slot,
             slot,
             self.get_high_restore_point_block_root(&high_restore_point, slot)?,
         )?;
+        metrics::inc_counter_by(
+            &metrics::HISTORIC_STATE_REPLAYED_BLOCKS,
+            blocks.len() as i64,
+        );
 
-        // 3. Replay the blocks on top of the low restore point.
-        self.replay_blocks(low_restore_point, blocks, slot)
+        // 3. Replay the blocks on top of the base state.
+        let state = self.replay_blocks(base_state, blocks, slot)?;
+        self.cache_historic_state(&state);
+        Ok(state)
     }
 
     /// Get a suitable block root for backtracking from `high_restore_point` to the state at `slot`.
@@ -658,6 +899,11 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
         Ok(state)
     }
 
+    /// Returns the configuration the database was opened with.
+    pub fn get_config(&self) -> &StoreConfig {
+        &self.config
+    }
+
     /// Fetch a copy of the current split slot from memory.
     pub fn get_split_slot(&self) -> Slot {
         self.split.read().slot
@@ -683,6 +929,167 @@ impl<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>> HotColdDB<E, Hot, Cold>
         Ok(())
     }
 
+    /// Initialise the database from a finalized, non-genesis state (e.g., for a checkpoint sync).
+    ///
+    /// Moves the split point to the anchor so that the anchor state is stored in the hot database
+    /// and is the first state to be migrated to the freezer. Must be called before any states are
+    /// stored.
+    pub fn init_anchor_info(&self, anchor: AnchorInfo) -> Result<(), Error> {
+        let split_slot = self.get_split_slot();
+        if split_slot != 0 || self.get_anchor_info().is_some() {
+            return Err(HotColdDBError::AnchorOnNonEmptyDatabase { split_slot }.into());
+        }
+
+        if anchor.anchor_slot % E::slots_per_epoch() != 0 {
+            return Err(HotColdDBError::AnchorSlotUnaligned(anchor.anchor_slot).into());
+        }
+
+        *self.split.write() = Split {
+            slot: anchor.anchor_slot,
+            state_root: anchor.anchor_state_root,
+        };
+        self.store_split()?;
+
+        self.hot_db
+            .put(&Hash256::from_slice(ANCHOR_INFO_KEY.as_bytes()), &anchor)?;
+        *self.anchor_info.write() = Some(anchor);
+
+        Ok(())
+    }
+
+    /// Returns the anchor of the database, or `None` if it was initialised from genesis.
+    pub fn get_anchor_info(&self) -> Option<AnchorInfo> {
+        *self.anchor_info.read()
+    }
+
+    /// Load the anchor info from disk.
+    fn load_anchor_info(&self) -> Result<Option<AnchorInfo>, Error> {
+        self.hot_db
+            .get(&Hash256::from_slice(ANCHOR_INFO_KEY.as_bytes()))
+    }
+
+    /// Returns the extent of the state history that has been pruned, or `None` if no states have
+    /// been pruned.
+    pub fn get_pruning_info(&self) -> Option<PruningInfo> {
+        *self.pruning_info.read()
+    }
+
+    /// Load the pruning info from disk.
+    fn load_pruning_info(&self) -> Result<Option<PruningInfo>, Error> {
+        self.hot_db
+            .get(&Hash256::from_slice(PRUNING_INFO_KEY.as_bytes()))
+    }
+
+    /// Returns the slot prior to which states should be pruned once the split point reaches
+    /// `split_slot`, or `None` if historic states are not pruned.
+    ///
+    /// The slot is rounded down to a restore point, so that at least
+    /// `historic_state_retention_epochs` of states can be reconstructed.
+    fn state_pruning_limit(&self, split_slot: Slot) -> Option<Slot> {
+        if !self.config.prune_historic_states {
+            return None;
+        }
+
+        let retained_slots = self.config.historic_state_retention_epochs * E::slots_per_epoch();
+        let oldest_retained_slot = split_slot.as_u64().saturating_sub(retained_slots);
+        Some(Slot::new(
+            oldest_retained_slot / self.config.slots_per_restore_point
+                * self.config.slots_per_restore_point,
+        ))
+    }
+
+    /// Delete the restore points of all states prior to `state_lower_limit` from the freezer
+    /// database, after which those states can no longer be loaded.
+    ///
+    /// The block and state roots of pruned slots are kept, as are the slots of pruned states so
+    /// that requests for them fail with `HotColdDBError::StatePruned`.
+    fn prune_historic_states(&self, state_lower_limit: Slot) -> Result<(), Error> {
+        let previous_limit = self
+            .get_pruning_info()
+            .map_or(Slot::new(0), |info| info.state_lower_limit);
+        if state_lower_limit <= previous_limit {
+            return Ok(());
+        }
+
+        let slots_per_restore_point = self.config.slots_per_restore_point;
+        let restore_point_indices = previous_limit.as_u64() / slots_per_restore_point
+            ..state_lower_limit.as_u64() / slots_per_restore_point;
+
+        let mut ops = vec![];
+        for restore_point_index in restore_point_indices {
+            let key = Self::restore_point_key(restore_point_index);
+            // Restore points prior to a checkpoint sync anchor were never stored.
+            if let Some(restore_point) = self.cold_db.get::<RestorePointHash>(&key)? {
+                ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                    DBColumn::BeaconState.into(),
+                    restore_point.state_root.as_bytes(),
+                )));
+            }
+            ops.push(KeyValueStoreOp::DeleteKey(get_key_for_col(
+                DBColumn::BeaconRestorePoint.into(),
+                key.as_bytes(),
+            )));
+        }
+        self.cold_db.do_atomically(ops)?;
+
+        let mut historic_state_cache = self.historic_state_cache.lock();
+        let pruned_slots = historic_state_cache
+            .iter()
+            .map(|(slot, _)| *slot)
+            .filter(|slot| *slot < state_lower_limit)
+            .collect::<Vec<_>>();
+        for slot in pruned_slots {
+            historic_state_cache.pop(&slot);
+        }
+        drop(historic_state_cache);
+
+        let mut restore_point_cache = self.restore_point_cache.lock();
+        let pruned_indices = restore_point_cache
+            .iter()
+            .map(|(restore_point_index, _)| *restore_point_index)
+            .filter(|restore_point_index| {
+                restore_point_index * self.config.slots_per_restore_point
+                    < state_lower_limit.as_u64()
+            })
+            .collect::<Vec<_>>();
+        for restore_point_index in pruned_indices {
+            restore_point_cache.pop(&restore_point_index);
+        }
+        drop(restore_point_cache);
+
+        let pruning_info = PruningInfo { state_lower_limit };
+        self.hot_db.put(
+            &Hash256::from_slice(PRUNING_INFO_KEY.as_bytes()),
+            &pruning_info,
+        )?;
+        *self.pruning_info.write() = Some(pruning_info);
+
+        debug!(
+            self.log,
+            "Pruned historic states";
+            "previous_limit" => previous_limit,
+            "state_lower_limit" => state_lower_limit,
+        );
+
+        Ok(())
+    }
+
+    /// Load the schema version from disk, or `None` if it has not been recorded.
+    pub fn load_schema_version(&self) -> Result<Option<SchemaVersion>, Error> {
+        self.hot_db
+            .get(&Hash256::from_slice(SCHEMA_VERSION_KEY.as_bytes()))
+    }
+
+    /// Store the schema version on disk.
+    ///
+    /// Overwriting the schema version without migrating the database will corrupt it.
+    pub fn store_schema_version(&self, schema_version: SchemaVersion) -> Result<(), Error> {
+        self.hot_db.put(
+            &Hash256::from_slice(SCHEMA_VERSION_KEY.as_bytes()),
+            &schema_version,
+        )
+    }
+
     /// Load the state root of a restore point.
     fn load_restore_point_hash(&self, restore_point_index: u64) -> Result<Hash256, Error> {
         let key = Self::restore_point_key(restore_point_index);
@@ -792,6 +1199,9 @@ pub fn process_finalization<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
         return Err(HotColdDBError::FreezeSlotUnaligned(frozen_head.slot).into());
     }
 
+    // Restore points prior to this slot are pruned instead of being stored.
+    let state_pruning_limit = store.state_pruning_limit(frozen_head.slot);
+
     // 1. Copy all of the states between the head and the split slot, from the hot DB
     // to the cold DB.
     let state_root_iter = StateRootsIterator::new(store.clone(), frozen_head);
@@ -807,7 +1217,12 @@ pub fn process_finalization<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
                 .ok_or_else(|| HotColdDBError::MissingStateToFreeze(state_root))?;
 
             let mut ops: Vec<KeyValueStoreOp> = Vec::new();
-            store.store_cold_state(&state_root, &state, &mut ops)?;
+            if state_pruning_limit.map_or(false, |limit| slot < limit) {
+                // The block and state roots are still required to iterate the finalized chain.
+                store.store_cold_state_vectors(&state, &mut ops)?;
+            } else {
+                store.store_cold_state(&state_root, &state, &mut ops)?;
+            }
             store.cold_db.do_atomically(ops)?;
         }
 
@@ -831,6 +1246,11 @@ pub fn process_finalization<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
         store.delete_state(&state_root, slot)?;
     }
 
+    // 4. Prune the restore points which are no longer retained
+    if let Some(state_lower_limit) = state_pruning_limit {
+        store.prune_historic_states(state_lower_limit)?;
+    }
+
     debug!(
         store.log,
         "Freezer migration complete";
@@ -5,6 +5,7 @@ use leveldb::database::batch::{Batch, Writebatch};
 use leveldb::database::kv::KV;
 use leveldb::database::Database;
 use leveldb::error::Error as LevelDBError;
+use leveldb::iterator::Iterable;
 use leveldb::options::{Options, ReadOptions, WriteOptions};
 use std::marker::PhantomData;
 use std::path::Path;
@@ -30,6 +31,15 @@ impl<E: EthSpec> LevelDB<E> {
         })
     }
 
+    /// Iterate over every key-value pair in the database, in key order.
+    ///
+    /// Keys are prefixed by their column (see `get_key_for_col`).
+    pub fn iter_raw_entries(&self) -> impl Iterator<Item = (Vec<u8>, Vec<u8>)> + '_ {
+        self.db
+            .iter(self.read_options())
+            .map(|(key, value)| (key.key, value))
+    }
+
     fn read_options(&self) -> ReadOptions<BytesKey> {
         ReadOptions::new()
     }
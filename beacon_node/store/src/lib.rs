@@ -13,23 +13,30 @@ extern crate lazy_static;
 pub mod chunked_iter;
 pub mod chunked_vector;
 pub mod config;
+mod disk_store;
 pub mod errors;
 mod forwards_iter;
 pub mod hot_cold_store;
 mod impls;
 mod leveldb_store;
+mod lmdb_store;
 mod memory_store;
+pub mod metadata;
 mod metrics;
 mod partial_beacon_state;
+mod schema_change;
 
 pub mod iter;
 
 use std::borrow::Cow;
 
-pub use self::config::StoreConfig;
+pub use self::config::{DatabaseBackend, StoreConfig};
+pub use self::disk_store::DiskStore;
 pub use self::hot_cold_store::{HotColdDB, HotStateSummary};
 pub use self::leveldb_store::LevelDB;
+pub use self::lmdb_store::Lmdb;
 pub use self::memory_store::MemoryStore;
+pub use self::metadata::{AnchorInfo, PruningInfo, SchemaVersion, CURRENT_SCHEMA_VERSION};
 pub use self::partial_beacon_state::PartialBeaconState;
 pub use errors::Error;
 pub use impls::beacon_state::StorageContainer as BeaconStateStorageContainer;
@@ -136,6 +143,26 @@ pub enum DBColumn {
     DhtEnrs,
 }
 
+impl DBColumn {
+    /// Every column of the database.
+    pub const ALL: [DBColumn; 14] = [
+        DBColumn::BeaconMeta,
+        DBColumn::BeaconBlock,
+        DBColumn::BeaconState,
+        DBColumn::BeaconChain,
+        DBColumn::OpPool,
+        DBColumn::Eth1Cache,
+        DBColumn::ForkChoice,
+        DBColumn::BeaconRestorePoint,
+        DBColumn::BeaconStateSummary,
+        DBColumn::BeaconBlockRoots,
+        DBColumn::BeaconStateRoots,
+        DBColumn::BeaconHistoricalRoots,
+        DBColumn::BeaconRandaoMixes,
+        DBColumn::DhtEnrs,
+    ];
+}
+
 impl Into<&'static str> for DBColumn {
     /// Returns a `&str` that can be used for keying a key-value data base.
     fn into(self) -> &'static str {
@@ -233,6 +260,15 @@ mod tests {
         test_impl(store);
     }
 
+    #[test]
+    fn lmdb() {
+        let dir = tempdir().unwrap();
+        let path = dir.path();
+        let store = Lmdb::open(&path).unwrap();
+
+        test_impl(store);
+    }
+
     #[test]
     fn memorydb() {
         let store = MemoryStore::open();
@@ -0,0 +1,160 @@
+use super::*;
+use crate::metrics;
+use lmdb::{Cursor, Database, Environment, RwTransaction, Transaction, WriteFlags};
+use std::convert::TryFrom;
+use std::fs;
+use std::marker::PhantomData;
+use std::path::Path;
+
+/// The maximum size of an LMDB database.
+///
+/// The space is only reserved in the virtual address space, the database file grows as required.
+/// It does not fit in the address space of 32-bit targets, so LMDB is unavailable on them.
+pub const LMDB_MAP_SIZE: u64 = 1 << 40;
+
+/// A wrapped LMDB database.
+///
+/// All columns share a single LMDB database, with keys prefixed by their column in the same way as
+/// `LevelDB`.
+pub struct Lmdb<E: EthSpec> {
+    env: Environment,
+    db: Database,
+    _phantom: PhantomData<E>,
+}
+
+impl<E: EthSpec> Lmdb<E> {
+    /// Open a database at `path`, creating a new database if one does not already exist.
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        fs::create_dir_all(path).map_err(|e| Error::DBError {
+            message: format!("Unable to create {:?}: {:?}", path, e),
+        })?;
+
+        let map_size = usize::try_from(LMDB_MAP_SIZE).map_err(|_| Error::DBError {
+            message: format!(
+                "LMDB map size of {} bytes exceeds the address space of this platform",
+                LMDB_MAP_SIZE
+            ),
+        })?;
+
+        let env = Environment::new().set_map_size(map_size).open(path)?;
+        let db = env.open_db(None)?;
+
+        Ok(Self {
+            env,
+            db,
+            _phantom: PhantomData,
+        })
+    }
+
+    /// Call `f` with every key-value pair in the database, in key order.
+    ///
+    /// Keys are prefixed by their column (see `get_key_for_col`).
+    pub fn for_each_raw_entry<F: FnMut(&[u8], &[u8])>(&self, mut f: F) -> Result<(), Error> {
+        let txn = self.env.begin_ro_txn()?;
+        let mut cursor = txn.open_ro_cursor(self.db)?;
+        for (key, value) in cursor.iter() {
+            f(key, value);
+        }
+        Ok(())
+    }
+
+    /// Delete `key` within `txn`, ignoring keys which are not present.
+    fn delete_in_txn(&self, txn: &mut RwTransaction, key: &[u8]) -> Result<(), Error> {
+        match txn.del(self.db, &key, None) {
+            Ok(()) | Err(lmdb::Error::NotFound) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl<E: EthSpec> KeyValueStore<E> for Lmdb<E> {
+    /// Retrieve some bytes in `column` with `key`.
+    fn get_bytes(&self, col: &str, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        let column_key = get_key_for_col(col, key);
+
+        metrics::inc_counter(&metrics::DISK_DB_READ_COUNT);
+        let timer = metrics::start_timer(&metrics::DISK_DB_READ_TIMES);
+
+        let txn = self.env.begin_ro_txn()?;
+        let bytes = match txn.get(self.db, &column_key) {
+            Ok(bytes) => bytes.to_vec(),
+            Err(lmdb::Error::NotFound) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        metrics::inc_counter_by(&metrics::DISK_DB_READ_BYTES, bytes.len() as i64);
+        metrics::stop_timer(timer);
+
+        Ok(Some(bytes))
+    }
+
+    /// Store some `value` in `column`, indexed with `key`.
+    fn put_bytes(&self, col: &str, key: &[u8], val: &[u8]) -> Result<(), Error> {
+        let column_key = get_key_for_col(col, key);
+
+        metrics::inc_counter(&metrics::DISK_DB_WRITE_COUNT);
+        metrics::inc_counter_by(&metrics::DISK_DB_WRITE_BYTES, val.len() as i64);
+        let timer = metrics::start_timer(&metrics::DISK_DB_WRITE_TIMES);
+
+        let mut txn = self.env.begin_rw_txn()?;
+        txn.put(self.db, &column_key, &val, WriteFlags::empty())?;
+        txn.commit()?;
+
+        metrics::stop_timer(timer);
+
+        Ok(())
+    }
+
+    /// Return `true` if `key` exists in `column`.
+    fn key_exists(&self, col: &str, key: &[u8]) -> Result<bool, Error> {
+        let column_key = get_key_for_col(col, key);
+
+        metrics::inc_counter(&metrics::DISK_DB_EXISTS_COUNT);
+
+        let txn = self.env.begin_ro_txn()?;
+        match txn.get(self.db, &column_key) {
+            Ok(_) => Ok(true),
+            Err(lmdb::Error::NotFound) => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Removes `key` from `column`.
+    fn key_delete(&self, col: &str, key: &[u8]) -> Result<(), Error> {
+        let column_key = get_key_for_col(col, key);
+
+        metrics::inc_counter(&metrics::DISK_DB_DELETE_COUNT);
+
+        let mut txn = self.env.begin_rw_txn()?;
+        self.delete_in_txn(&mut txn, &column_key)?;
+        txn.commit().map_err(Into::into)
+    }
+
+    fn do_atomically(&self, ops_batch: Vec<KeyValueStoreOp>) -> Result<(), Error> {
+        // The transaction is aborted when dropped, unless committed.
+        let mut txn = self.env.begin_rw_txn()?;
+        for op in ops_batch {
+            match op {
+                KeyValueStoreOp::PutKeyValue(key, value) => {
+                    txn.put(self.db, &key, &value, WriteFlags::empty())?;
+                }
+
+                KeyValueStoreOp::DeleteKey(key) => {
+                    self.delete_in_txn(&mut txn, &key)?;
+                }
+            }
+        }
+        txn.commit()?;
+        Ok(())
+    }
+}
+
+impl<E: EthSpec> ItemStore<E> for Lmdb<E> {}
+
+impl From<lmdb::Error> for Error {
+    fn from(e: lmdb::Error) -> Error {
+        Error::DBError {
+            message: format!("{:?}", e),
+        }
+    }
+}
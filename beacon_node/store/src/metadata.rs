@@ -0,0 +1,139 @@
+use crate::{DBColumn, Error, StoreItem};
+use ssz::{Decode, Encode};
+use ssz_derive::{Decode, Encode};
+use types::{Checkpoint, EthSpec, Hash256, Slot};
+
+/// The schema version written by this version of Lighthouse.
+///
+/// Must be incremented whenever the layout of a persisted item changes, along with a migration
+/// from the previous version in `schema_change`.
+pub const CURRENT_SCHEMA_VERSION: SchemaVersion = SchemaVersion(1);
+
+/// The schema version of databases created before the schema version was recorded.
+pub const INITIAL_SCHEMA_VERSION: SchemaVersion = SchemaVersion(1);
+
+/// 32-byte key for accessing the `SchemaVersion` of the database.
+pub const SCHEMA_VERSION_KEY: &str = "SCHEMAVERSIONSCHEMAVERSIONSCHEMA";
+
+/// 32-byte key for accessing the `AnchorInfo` of the database.
+pub const ANCHOR_INFO_KEY: &str = "ANCHORINFOANCHORINFOANCHORINFOAN";
+
+/// 32-byte key for accessing the `PruningInfo` of the database.
+pub const PRUNING_INFO_KEY: &str = "PRUNINGINFOPRUNINGINFOPRUNINGINF";
+
+/// The version of the layout of the items stored in the database.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SchemaVersion(pub u64);
+
+impl SchemaVersion {
+    pub fn as_u64(self) -> u64 {
+        self.0
+    }
+}
+
+impl StoreItem for SchemaVersion {
+    fn db_column() -> DBColumn {
+        DBColumn::BeaconMeta
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.0.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(SchemaVersion(u64::from_ssz_bytes(bytes)?))
+    }
+}
+
+/// Describes the point from which a database was initialised, when it was not initialised from
+/// genesis (e.g., following a checkpoint sync).
+///
+/// Blocks and states prior to the anchor are not present in the database.
+#[derive(Debug, Clone, Copy, PartialEq, Encode, Decode)]
+pub struct AnchorInfo {
+    /// The slot of the finalized state and block that the database was initialised from.
+    pub anchor_slot: Slot,
+    /// The root of the block at `anchor_slot`.
+    pub anchor_block_root: Hash256,
+    /// The root of the state at `anchor_slot`.
+    pub anchor_state_root: Hash256,
+}
+
+impl AnchorInfo {
+    /// Returns the finalized checkpoint represented by the anchor.
+    ///
+    /// The anchor always lies on an epoch boundary, so the block is the checkpoint block.
+    pub fn checkpoint<E: EthSpec>(&self) -> Checkpoint {
+        Checkpoint {
+            epoch: self.anchor_slot.epoch(E::slots_per_epoch()),
+            root: self.anchor_block_root,
+        }
+    }
+
+    /// Returns the lowest slot for which the freezer database is able to reconstruct a state.
+    ///
+    /// This is the first restore point at or after the anchor slot, since states are
+    /// reconstructed by replaying blocks forwards from a restore point.
+    pub fn state_lower_limit(&self, slots_per_restore_point: u64) -> Slot {
+        let slot = self.anchor_slot.as_u64();
+        let limit = (slot + slots_per_restore_point - 1) / slots_per_restore_point
+            * slots_per_restore_point;
+        Slot::new(limit)
+    }
+}
+
+impl StoreItem for AnchorInfo {
+    fn db_column() -> DBColumn {
+        DBColumn::BeaconMeta
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(Self::from_ssz_bytes(bytes)?)
+    }
+}
+
+/// Records how much of the state history in the freezer database has been pruned.
+#[derive(Debug, Clone, Copy, PartialEq, Encode, Decode)]
+pub struct PruningInfo {
+    /// The restore points of all states prior to this slot have been deleted.
+    ///
+    /// Always lies on a restore point boundary.
+    pub state_lower_limit: Slot,
+}
+
+impl StoreItem for PruningInfo {
+    fn db_column() -> DBColumn {
+        DBColumn::BeaconMeta
+    }
+
+    fn as_store_bytes(&self) -> Vec<u8> {
+        self.as_ssz_bytes()
+    }
+
+    fn from_store_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(Self::from_ssz_bytes(bytes)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn state_lower_limit() {
+        let anchor = |slot: u64| AnchorInfo {
+            anchor_slot: Slot::new(slot),
+            anchor_block_root: Hash256::zero(),
+            anchor_state_root: Hash256::zero(),
+        };
+
+        assert_eq!(anchor(0).state_lower_limit(64), Slot::new(0));
+        assert_eq!(anchor(64).state_lower_limit(64), Slot::new(64));
+        assert_eq!(anchor(72).state_lower_limit(64), Slot::new(128));
+        assert_eq!(anchor(127).state_lower_limit(64), Slot::new(128));
+    }
+}
@@ -90,6 +90,37 @@ lazy_static! {
         "store_beacon_state_write_bytes_total",
         "Total number of beacon state bytes written to the DB"
     );
+    /*
+     * Historic States
+     */
+    pub static ref HISTORIC_STATE_CACHE_HIT_COUNT: Result<IntCounter> = try_create_int_counter(
+        "store_historic_state_cache_hit_total",
+        "Number of hits to the store's historic state cache"
+    );
+    pub static ref HISTORIC_STATE_CACHE_MISS_COUNT: Result<IntCounter> = try_create_int_counter(
+        "store_historic_state_cache_miss_total",
+        "Number of misses to the store's historic state cache"
+    );
+    pub static ref HISTORIC_STATE_CACHE_SIZE: Result<IntGauge> = try_create_int_gauge(
+        "store_historic_state_cache_size",
+        "Number of states between restore points in the store's historic state cache"
+    );
+    pub static ref RESTORE_POINT_CACHE_SIZE: Result<IntGauge> = try_create_int_gauge(
+        "store_restore_point_cache_size",
+        "Number of restore point states in the store's restore point cache"
+    );
+    pub static ref HISTORIC_STATE_RECONSTRUCTION_TIMES: Result<Histogram> = try_create_histogram(
+        "store_historic_state_reconstruction_seconds",
+        "Time taken to reconstruct a state between restore points by replaying blocks"
+    );
+    pub static ref HISTORIC_STATE_REPLAYED_BLOCKS: Result<IntCounter> = try_create_int_counter(
+        "store_historic_state_replayed_blocks_total",
+        "Total number of blocks replayed to reconstruct states between restore points"
+    );
+    pub static ref HISTORIC_STATE_REPLAY_FROM_CACHE_COUNT: Result<IntCounter> = try_create_int_counter(
+        "store_historic_state_replay_from_cache_total",
+        "Number of state reconstructions which started from a cached state"
+    );
     /*
      * Beacon Block
      */
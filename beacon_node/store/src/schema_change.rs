@@ -0,0 +1,156 @@
+//! Migrations which upgrade an existing database to the current schema version.
+//!
+//! A migration is required whenever the layout of a persisted item changes. To add one, increment
+//! `CURRENT_SCHEMA_VERSION` and add an arm to `migration_ops` for the previous version.
+use crate::hot_cold_store::{HotColdDB, HotColdDBError};
+use crate::metadata::{SchemaVersion, SCHEMA_VERSION_KEY};
+use crate::{Error, ItemStore, KeyValueStore, KeyValueStoreOp, StoreItem};
+use slog::info;
+use types::{EthSpec, Hash256};
+
+/// Upgrade the database from schema version `from` to `to`, one version at a time.
+///
+/// Each step is written atomically along with its new schema version, so an interrupted migration
+/// resumes from the last completed step when the database is next opened.
+pub fn migrate_schema<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
+    db: &HotColdDB<E, Hot, Cold>,
+    from: SchemaVersion,
+    to: SchemaVersion,
+) -> Result<(), Error> {
+    let mut version = from;
+
+    while version < to {
+        let next = SchemaVersion(version.as_u64() + 1);
+
+        let mut ops = migration_ops(db, version)?;
+        ops.push(next.as_kv_store_op(Hash256::from_slice(SCHEMA_VERSION_KEY.as_bytes())));
+        db.hot_db.do_atomically(ops)?;
+
+        info!(
+            db.log,
+            "Database schema migrated";
+            "from" => version.as_u64(),
+            "to" => next.as_u64(),
+        );
+
+        version = next;
+    }
+
+    Ok(())
+}
+
+/// Returns the operations which rewrite the items in a database at schema `version` to the layout
+/// of `version + 1`.
+fn migration_ops<E: EthSpec, Hot: ItemStore<E>, Cold: ItemStore<E>>(
+    _db: &HotColdDB<E, Hot, Cold>,
+    version: SchemaVersion,
+) -> Result<Vec<KeyValueStoreOp>, Error> {
+    // There are no migrations yet, version 1 is the layout used before schema versioning.
+    Err(HotColdDBError::MissingSchemaMigration(version).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DiskStore, MemoryStore, StoreConfig};
+    use sloggers::{null::NullLoggerBuilder, Build};
+    use std::path::Path;
+    use tempfile::tempdir;
+    use types::{ChainSpec, MinimalEthSpec};
+
+    type E = MinimalEthSpec;
+    type Store = HotColdDB<E, MemoryStore<E>, MemoryStore<E>>;
+
+    fn get_store() -> Store {
+        let log = NullLoggerBuilder.build().expect("logger should build");
+        HotColdDB::open_ephemeral(StoreConfig::default(), ChainSpec::minimal(), log)
+            .expect("store should open")
+    }
+
+    /// Create a database in `dir` at the given schema `version`.
+    fn create_disk_store(dir: &Path, version: SchemaVersion) {
+        let store = open_disk_store(dir, false).expect("store should open");
+        store.store_schema_version(version).unwrap();
+    }
+
+    fn open_disk_store(
+        dir: &Path,
+        read_only: bool,
+    ) -> Result<HotColdDB<E, DiskStore<E>, DiskStore<E>>, Error> {
+        let log = NullLoggerBuilder.build().expect("logger should build");
+        let (hot_path, cold_path) = (dir.join("hot_db"), dir.join("cold_db"));
+        let (config, spec) = (StoreConfig::default(), ChainSpec::minimal());
+        if read_only {
+            HotColdDB::open_read_only(&hot_path, &cold_path, config, spec, log)
+        } else {
+            HotColdDB::open(&hot_path, &cold_path, config, spec, log)
+        }
+    }
+
+    #[test]
+    fn migrate_to_same_version() {
+        let store = get_store();
+
+        migrate_schema(&store, SchemaVersion(1), SchemaVersion(1)).unwrap();
+        assert_eq!(store.load_schema_version().unwrap(), None);
+    }
+
+    #[test]
+    fn missing_migration() {
+        let store = get_store();
+
+        match migrate_schema(&store, SchemaVersion(1), SchemaVersion(2)) {
+            Err(Error::HotColdDBError(HotColdDBError::MissingSchemaMigration(version))) => {
+                assert_eq!(version, SchemaVersion(1))
+            }
+            other => panic!("expected missing migration, got {:?}", other),
+        }
+        assert_eq!(store.load_schema_version().unwrap(), None);
+    }
+
+    #[test]
+    fn read_only_open_at_current_version() {
+        let dir = tempdir().unwrap();
+        create_disk_store(dir.path(), CURRENT_SCHEMA_VERSION);
+
+        let store = open_disk_store(dir.path(), true).expect("store should open");
+        assert_eq!(
+            store.load_schema_version().unwrap(),
+            Some(CURRENT_SCHEMA_VERSION)
+        );
+    }
+
+    #[test]
+    fn read_only_open_refuses_to_migrate() {
+        let dir = tempdir().unwrap();
+        let old_version = SchemaVersion(CURRENT_SCHEMA_VERSION.as_u64() - 1);
+        create_disk_store(dir.path(), old_version);
+
+        match open_disk_store(dir.path(), true) {
+            Err(Error::HotColdDBError(HotColdDBError::SchemaMigrationRequired {
+                schema_version,
+                ..
+            })) => assert_eq!(schema_version, old_version),
+            other => panic!("expected migration required, got {:?}", other.map(|_| ())),
+        }
+
+        // The database is left untouched.
+        let store = open_disk_store(dir.path(), false).map(|_| ());
+        assert!(store.is_err(), "no migration exists from the old version");
+    }
+
+    #[test]
+    fn read_only_open_refuses_newer_version() {
+        let dir = tempdir().unwrap();
+        let new_version = SchemaVersion(CURRENT_SCHEMA_VERSION.as_u64() + 1);
+        create_disk_store(dir.path(), new_version);
+
+        match open_disk_store(dir.path(), true) {
+            Err(Error::HotColdDBError(HotColdDBError::UnsupportedSchemaVersion {
+                schema_version,
+                ..
+            })) => assert_eq!(schema_version, new_version),
+            other => panic!("expected unsupported version, got {:?}", other.map(|_| ())),
+        }
+    }
+}
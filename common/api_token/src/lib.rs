@@ -0,0 +1,131 @@
+//! A bearer token which must be presented with every request to an HTTP API.
+//!
+//! Shared by the beacon node REST API and the validator client HTTP API.
+
+use http::header::HeaderValue;
+use rand::RngCore;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// The name of the file within a datadir which stores the API token.
+pub const API_TOKEN_FILENAME: &str = "api-token.txt";
+
+/// The number of random bytes in a newly generated token.
+const TOKEN_BYTES: usize = 32;
+
+/// The prefix expected in the value of the `Authorization` header.
+const BEARER_PREFIX: &str = "Bearer ";
+
+/// A secret which must be presented with every request to an HTTP API.
+pub struct ApiToken {
+    token: String,
+    path: PathBuf,
+}
+
+impl ApiToken {
+    /// Read the token from `path`, generating and saving a new one if the file does not exist.
+    ///
+    /// New token files are readable and writable only by their owner (0600).
+    pub fn load_or_create(path: &Path) -> Result<Self, String> {
+        let token = if path.exists() {
+            let token = fs::read_to_string(path)
+                .map_err(|e| format!("Unable to read API token at {:?}: {:?}", path, e))?
+                .trim()
+                .to_string();
+
+            if token.is_empty() {
+                return Err(format!("API token at {:?} is empty", path));
+            }
+
+            token
+        } else {
+            let mut bytes = [0; TOKEN_BYTES];
+            rand::thread_rng().fill_bytes(&mut bytes);
+            let token = hex::encode(bytes);
+
+            let mut file = File::create(path)
+                .map_err(|e| format!("Unable to create API token at {:?}: {:?}", path, e))?;
+            set_file_permissions(&file)
+                .map_err(|e| format!("Unable to set API token permissions: {:?}", e))?;
+            file.write_all(token.as_bytes())
+                .map_err(|e| format!("Unable to write API token at {:?}: {:?}", path, e))?;
+
+            token
+        };
+
+        Ok(Self {
+            token,
+            path: path.into(),
+        })
+    }
+
+    /// The path to the file which stores the token.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns `true` if `header` is an `Authorization` header bearing this token.
+    pub fn authorizes(&self, header: Option<&HeaderValue>) -> bool {
+        header
+            .and_then(|value| value.to_str().ok())
+            .filter(|value| value.starts_with(BEARER_PREFIX))
+            .map_or(false, |value| {
+                constant_time_eq(
+                    value[BEARER_PREFIX.len()..].trim().as_bytes(),
+                    self.token.as_bytes(),
+                )
+            })
+    }
+}
+
+/// Compare two byte strings in time independent of their contents, so that the token cannot be
+/// guessed one byte at a time.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(unix)]
+fn set_file_permissions(file: &File) -> Result<(), std::io::Error> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut perm = file.metadata()?.permissions();
+    perm.set_mode(0o600);
+    file.set_permissions(perm)
+}
+
+// TODO: add support for Windows ACLs
+#[cfg(windows)]
+fn set_file_permissions(_file: &File) -> Result<(), std::io::Error> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempdir::TempDir;
+
+    #[test]
+    fn token_is_persisted() {
+        let dir = TempDir::new("api_token").unwrap();
+        let token = ApiToken::load_or_create(&dir.path().join(API_TOKEN_FILENAME)).unwrap();
+        assert_eq!(token.token.len(), TOKEN_BYTES * 2);
+
+        let reloaded = ApiToken::load_or_create(token.path()).unwrap();
+        assert_eq!(token.token, reloaded.token);
+    }
+
+    #[test]
+    fn authorizes_bearer_token_only() {
+        let dir = TempDir::new("api_token").unwrap();
+        let token = ApiToken::load_or_create(&dir.path().join(API_TOKEN_FILENAME)).unwrap();
+
+        let header = |value: String| HeaderValue::from_str(&value).unwrap();
+
+        assert!(token.authorizes(Some(&header(format!("Bearer {}", token.token)))));
+        assert!(!token.authorizes(None));
+        assert!(!token.authorizes(Some(&header(token.token.clone()))));
+        assert!(!token.authorizes(Some(&header(format!("Bearer {}0", token.token)))));
+        assert!(!token.authorizes(Some(&header("Bearer ".to_string()))));
+    }
+}
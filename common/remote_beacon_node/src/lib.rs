@@ -4,7 +4,9 @@
 //! Presently, this is only used for testing but it _could_ become a user-facing library.
 
 use eth2_config::Eth2Config;
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
 use reqwest::{Client, ClientBuilder, Response, StatusCode};
+use rest_types::standard::GenericResponse;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use ssz::Encode;
 use std::marker::PhantomData;
@@ -18,6 +20,10 @@ use url::Url;
 
 pub use operation_pool::PersistedOperationPool;
 pub use proto_array::core::ProtoArray;
+pub use rest_types::standard::{
+    BlockHeaderData, DepositContractData, FinalityCheckpointsData, GenesisData, ProposerData,
+    RootData, ValidatorData, VersionData,
+};
 pub use rest_types::{
     CanonicalHeadResponse, Committee, HeadBeaconBlock, Health, IndividualVotesRequest,
     IndividualVotesResponse, SyncingResponse, ValidatorDutiesRequest, ValidatorDutyBytes,
@@ -44,8 +50,17 @@ impl<E: EthSpec> RemoteBeaconNode<E> {
     }
 
     pub fn new_with_timeout(http_endpoint: String, timeout: Duration) -> Result<Self, String> {
+        Self::new_with_api_token(http_endpoint, timeout, None)
+    }
+
+    /// Sends `api_token` as a bearer token with every request, if it is `Some`.
+    pub fn new_with_api_token(
+        http_endpoint: String,
+        timeout: Duration,
+        api_token: Option<String>,
+    ) -> Result<Self, String> {
         Ok(Self {
-            http: HttpClient::new(http_endpoint, timeout)
+            http: HttpClient::new_with_api_token(http_endpoint, timeout, api_token)
                 .map_err(|e| format!("Unable to create http client: {:?}", e))?,
         })
     }
@@ -55,6 +70,8 @@ impl<E: EthSpec> RemoteBeaconNode<E> {
 pub enum Error {
     /// Unable to parse a URL. Check the server URL.
     UrlParseError(url::ParseError),
+    /// The API token contains characters which cannot be sent in a HTTP header.
+    InvalidApiToken,
     /// The `reqwest` library returned an error.
     ReqwestError(reqwest::Error),
     /// There was an error when encoding/decoding an object using serde.
@@ -76,9 +93,27 @@ pub struct HttpClient<E> {
 impl<E: EthSpec> HttpClient<E> {
     /// Creates a new instance (without connecting to the node).
     pub fn new(server_url: String, timeout: Duration) -> Result<Self, Error> {
+        Self::new_with_api_token(server_url, timeout, None)
+    }
+
+    /// Creates a new instance which sends `api_token` in the `Authorization` header of every
+    /// request, if it is `Some`.
+    pub fn new_with_api_token(
+        server_url: String,
+        timeout: Duration,
+        api_token: Option<String>,
+    ) -> Result<Self, Error> {
+        let mut headers = HeaderMap::new();
+        if let Some(api_token) = api_token {
+            let value = HeaderValue::from_str(&format!("Bearer {}", api_token))
+                .map_err(|_| Error::InvalidApiToken)?;
+            headers.insert(AUTHORIZATION, value);
+        }
+
         Ok(Self {
             client: ClientBuilder::new()
                 .timeout(timeout)
+                .default_headers(headers)
                 .build()
                 .expect("should build from static configuration"),
             url: Url::parse(&server_url)?,
@@ -111,6 +146,10 @@ impl<E: EthSpec> HttpClient<E> {
         Consensus(self.clone())
     }
 
+    pub fn standard(&self) -> Standard<E> {
+        Standard(self.clone())
+    }
+
     fn url(&self, path: &str) -> Result<Url, Error> {
         self.url.join(path).map_err(|e| e.into())
     }
@@ -669,7 +708,7 @@ impl<E: EthSpec> Consensus<E> {
         &self,
         epoch: Epoch,
         pubkeys: Vec<PublicKeyBytes>,
-    ) -> Result<IndividualVotesResponse, Error> {
+    ) -> Result<Vec<IndividualVotesResponse>, Error> {
         let client = self.0.clone();
         let req_body = IndividualVotesRequest { epoch, pubkeys };
 
@@ -688,6 +727,113 @@ impl<E: EthSpec> Consensus<E> {
     }
 }
 
+/// Provides the functions on the standard `/eth/v1` endpoints of the node.
+///
+/// Each `state_id` or `block_id` may be `head`, `genesis`, `finalized`, `justified`, a slot or a
+/// `0x` prefixed root.
+#[derive(Clone)]
+pub struct Standard<E>(HttpClient<E>);
+
+impl<E: EthSpec> Standard<E> {
+    fn url(&self, path: &str) -> Result<Url, Error> {
+        self.0
+            .url("eth/v1/")
+            .and_then(move |url| url.join(path).map_err(Error::from))
+            .map_err(Into::into)
+    }
+
+    /// Performs a `GET` on `path`, removing the `data` envelope from the response.
+    async fn get_data<T: DeserializeOwned>(&self, path: &str) -> Result<T, Error> {
+        let client = self.0.clone();
+        let url = self.url(path)?;
+        client
+            .json_get::<GenericResponse<T>>(url, vec![])
+            .await
+            .map(|response| response.data)
+    }
+
+    /// Performs a `POST` of `body` to `path`, expecting an empty response.
+    async fn post<T: Serialize>(&self, path: &str, body: T) -> Result<(), Error> {
+        let client = self.0.clone();
+        let url = self.url(path)?;
+        let response = client.json_post(url, body).await?;
+        error_for_status(response).await.map(|_| ())
+    }
+
+    pub async fn get_genesis(&self) -> Result<GenesisData, Error> {
+        self.get_data("beacon/genesis").await
+    }
+
+    pub async fn get_state_root(&self, state_id: &str) -> Result<Hash256, Error> {
+        self.get_data::<RootData>(&format!("beacon/states/{}/root", state_id))
+            .await
+            .map(|data| data.root)
+    }
+
+    pub async fn get_state_fork(&self, state_id: &str) -> Result<Fork, Error> {
+        self.get_data(&format!("beacon/states/{}/fork", state_id))
+            .await
+    }
+
+    pub async fn get_state_finality_checkpoints(
+        &self,
+        state_id: &str,
+    ) -> Result<FinalityCheckpointsData, Error> {
+        self.get_data(&format!("beacon/states/{}/finality_checkpoints", state_id))
+            .await
+    }
+
+    pub async fn get_state_validators(&self, state_id: &str) -> Result<Vec<ValidatorData>, Error> {
+        self.get_data(&format!("beacon/states/{}/validators", state_id))
+            .await
+    }
+
+    pub async fn get_block_header(&self, block_id: &str) -> Result<BlockHeaderData, Error> {
+        self.get_data(&format!("beacon/headers/{}", block_id)).await
+    }
+
+    pub async fn get_block(&self, block_id: &str) -> Result<SignedBeaconBlock<E>, Error> {
+        self.get_data(&format!("beacon/blocks/{}", block_id)).await
+    }
+
+    pub async fn get_block_root(&self, block_id: &str) -> Result<Hash256, Error> {
+        self.get_data::<RootData>(&format!("beacon/blocks/{}/root", block_id))
+            .await
+            .map(|data| data.root)
+    }
+
+    pub async fn post_pool_attester_slashing(
+        &self,
+        attester_slashing: AttesterSlashing<E>,
+    ) -> Result<(), Error> {
+        self.post("beacon/pool/attester_slashings", attester_slashing)
+            .await
+    }
+
+    pub async fn post_pool_proposer_slashing(
+        &self,
+        proposer_slashing: ProposerSlashing,
+    ) -> Result<(), Error> {
+        self.post("beacon/pool/proposer_slashings", proposer_slashing)
+            .await
+    }
+
+    pub async fn get_node_version(&self) -> Result<String, Error> {
+        self.get_data::<VersionData>("node/version")
+            .await
+            .map(|data| data.version)
+    }
+
+    pub async fn get_proposer_duties(&self, epoch: Epoch) -> Result<Vec<ProposerData>, Error> {
+        self.get_data(&format!("validator/duties/proposer/{}", epoch))
+            .await
+    }
+
+    pub async fn get_deposit_contract(&self) -> Result<DepositContractData, Error> {
+        self.get_data("config/deposit_contract").await
+    }
+}
+
 #[derive(Deserialize)]
 #[serde(bound = "T: EthSpec")]
 pub struct BlockResponse<T: EthSpec> {
@@ -5,6 +5,7 @@
 mod beacon;
 mod consensus;
 mod node;
+pub mod standard;
 mod validator;
 
 pub use beacon::{
@@ -0,0 +1,217 @@
+//! Types for the Ethereum standard Beacon Node API, served under `/eth/v1`.
+//!
+//! Every successful response wraps its payload in a `GenericResponse`, whilst errors are returned
+//! as an `ErrorMessage`.
+
+use bls::PublicKeyBytes;
+use serde::{Deserialize, Serialize};
+use types::utils::{fork_from_hex_str, fork_to_hex_str};
+use types::{
+    Address, BeaconState, Checkpoint, CommitteeIndex, Epoch, EthSpec, Hash256,
+    SignedBeaconBlockHeader, Slot, Validator,
+};
+
+/// The envelope around the payload of every successful response.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GenericResponse<T> {
+    pub data: T,
+}
+
+impl<T> From<T> for GenericResponse<T> {
+    fn from(data: T) -> Self {
+        Self { data }
+    }
+}
+
+/// The body of every unsuccessful response.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ErrorMessage {
+    pub code: u16,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RootData {
+    pub root: Hash256,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GenesisData {
+    pub genesis_time: u64,
+    pub genesis_validators_root: Hash256,
+    #[serde(
+        serialize_with = "fork_to_hex_str",
+        deserialize_with = "fork_from_hex_str"
+    )]
+    pub genesis_fork_version: [u8; 4],
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct FinalityCheckpointsData {
+    pub previous_justified: Checkpoint,
+    pub current_justified: Checkpoint,
+    pub finalized: Checkpoint,
+}
+
+impl<T: EthSpec> From<&BeaconState<T>> for FinalityCheckpointsData {
+    fn from(state: &BeaconState<T>) -> Self {
+        Self {
+            previous_justified: state.previous_justified_checkpoint.clone(),
+            current_justified: state.current_justified_checkpoint.clone(),
+            finalized: state.finalized_checkpoint.clone(),
+        }
+    }
+}
+
+/// The lifecycle stage of a validator, relative to some epoch.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidatorStatus {
+    /// The deposit has been processed but the validator is not yet eligible for activation.
+    PendingInitialized,
+    /// The validator is queued for activation.
+    PendingQueued,
+    /// The validator is active and has not initiated an exit.
+    ActiveOngoing,
+    /// The validator is active and has initiated a voluntary exit.
+    ActiveExiting,
+    /// The validator is active and has been slashed.
+    ActiveSlashed,
+    /// The validator has exited without being slashed.
+    ExitedUnslashed,
+    /// The validator has exited after being slashed.
+    ExitedSlashed,
+    /// The validator's balance may be withdrawn.
+    WithdrawalPossible,
+    /// The validator's balance has been withdrawn.
+    WithdrawalDone,
+}
+
+impl ValidatorStatus {
+    /// Returns the status of `validator` at `epoch`.
+    pub fn from_validator(validator: &Validator, balance: u64, epoch: Epoch) -> Self {
+        if validator.is_withdrawable_at(epoch) {
+            if balance == 0 {
+                ValidatorStatus::WithdrawalDone
+            } else {
+                ValidatorStatus::WithdrawalPossible
+            }
+        } else if validator.is_exited_at(epoch) {
+            if validator.slashed {
+                ValidatorStatus::ExitedSlashed
+            } else {
+                ValidatorStatus::ExitedUnslashed
+            }
+        } else if validator.is_active_at(epoch) {
+            if validator.slashed {
+                ValidatorStatus::ActiveSlashed
+            } else if validator.exit_epoch != Epoch::max_value() {
+                ValidatorStatus::ActiveExiting
+            } else {
+                ValidatorStatus::ActiveOngoing
+            }
+        } else if validator.activation_eligibility_epoch == Epoch::max_value() {
+            ValidatorStatus::PendingInitialized
+        } else {
+            ValidatorStatus::PendingQueued
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorData {
+    pub index: u64,
+    pub balance: u64,
+    pub status: ValidatorStatus,
+    pub validator: Validator,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CommitteeData {
+    pub index: CommitteeIndex,
+    pub slot: Slot,
+    pub validators: Vec<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BlockHeaderData {
+    pub root: Hash256,
+    pub canonical: bool,
+    pub header: SignedBeaconBlockHeader,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IdentityData {
+    pub peer_id: String,
+    pub enr: String,
+    pub p2p_addresses: Vec<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerState {
+    Connected,
+    Connecting,
+    Disconnected,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PeerDirection {
+    Inbound,
+    Outbound,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PeerData {
+    pub peer_id: String,
+    pub address: Option<String>,
+    pub state: PeerState,
+    /// The direction of the connection, if the peer is connected.
+    pub direction: Option<PeerDirection>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct VersionData {
+    pub version: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SyncingData {
+    pub head_slot: Slot,
+    pub sync_distance: Slot,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DepositContractData {
+    /// The id of the eth1 chain on which the deposit contract is deployed.
+    pub chain_id: u64,
+    pub address: Address,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AttesterData {
+    pub pubkey: PublicKeyBytes,
+    pub validator_index: u64,
+    pub committee_index: CommitteeIndex,
+    pub committee_length: u64,
+    pub committees_at_slot: u64,
+    pub validator_committee_index: u64,
+    pub slot: Slot,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProposerData {
+    pub pubkey: PublicKeyBytes,
+    pub validator_index: u64,
+    pub slot: Slot,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BeaconCommitteeSubscription {
+    pub validator_index: u64,
+    pub committee_index: CommitteeIndex,
+    pub committees_at_slot: u64,
+    pub slot: Slot,
+    pub is_aggregator: bool,
+}
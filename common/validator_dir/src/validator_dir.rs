@@ -16,6 +16,9 @@ const LOCK_FILE: &str = ".lock";
 /// The file used to save the Eth1 transaction hash from a deposit.
 pub const ETH1_DEPOSIT_TX_HASH_FILE: &str = "eth1-deposit-tx-hash.txt";
 
+/// The file used to indicate that a validator should not perform duties when it is loaded.
+pub const DISABLED_FILE: &str = "disabled";
+
 #[derive(Debug)]
 pub enum Error {
     DirectoryDoesNotExist(PathBuf),
@@ -37,6 +40,8 @@ pub enum Error {
     UnableToParseDepositData(deposit_contract::DecodeError),
     Eth1TxHashExists(PathBuf),
     UnableToWriteEth1TxHash(io::Error),
+    UnableToWriteDisabledFile(io::Error),
+    UnableToRemoveDisabledFile(io::Error),
     /// The deposit root in the deposit data file does not match the one generated locally. This is
     /// generally caused by supplying an `amount` at deposit-time that is different to the one used
     /// at generation-time.
@@ -59,7 +64,7 @@ pub struct Eth1DepositData {
 /// Provides a wrapper around a directory containing validator information.
 ///
 /// Creates/deletes a lockfile in `self.dir` to attempt to prevent concurrent access from multiple
-/// processes.
+/// processes. It is safe to delete `self.dir` whilst it is open.
 #[derive(Debug, PartialEq)]
 pub struct ValidatorDir {
     dir: PathBuf,
@@ -181,6 +186,30 @@ impl ValidatorDir {
         write(path, tx_hash.as_bytes()).map_err(Error::UnableToWriteEth1TxHash)
     }
 
+    /// Returns `false` if the validator has been disabled with `Self::set_enabled`.
+    pub fn is_enabled(&self) -> bool {
+        !self.dir.join(DISABLED_FILE).exists()
+    }
+
+    /// Persist whether or not the validator should perform duties, by creating or removing a
+    /// `DISABLED_FILE` in `self.dir`.
+    ///
+    /// ## Errors
+    ///
+    /// If there is a file-system error.
+    pub fn set_enabled(&self, enabled: bool) -> Result<(), Error> {
+        let path = self.dir.join(DISABLED_FILE);
+
+        if enabled {
+            if path.exists() {
+                remove_file(path).map_err(Error::UnableToRemoveDisabledFile)?;
+            }
+            Ok(())
+        } else {
+            write(path, &[]).map_err(Error::UnableToWriteDisabledFile)
+        }
+    }
+
     /// Attempts to read files in `self.dir` and return an `Eth1DepositData` that can be used for
     /// submitting an Eth1 deposit.
     ///
@@ -235,6 +264,12 @@ impl ValidatorDir {
 
 impl Drop for ValidatorDir {
     fn drop(&mut self) {
+        // There is no lockfile to remove if the whole directory has been deleted whilst open (e.g.,
+        // to remove a validator from a running validator client).
+        if !self.dir.exists() {
+            return;
+        }
+
         let lockfile = self.dir.clone().join(LOCK_FILE);
         if let Err(e) = remove_file(&lockfile) {
             eprintln!(
@@ -6,8 +6,8 @@ use std::path::Path;
 use tempfile::{tempdir, TempDir};
 use types::{test_utils::generate_deterministic_keypair, EthSpec, Keypair, MainnetEthSpec};
 use validator_dir::{
-    Builder, BuilderError, ValidatorDir, ETH1_DEPOSIT_DATA_FILE, ETH1_DEPOSIT_TX_HASH_FILE,
-    VOTING_KEYSTORE_FILE, WITHDRAWAL_KEYSTORE_FILE,
+    Builder, BuilderError, ValidatorDir, DISABLED_FILE, ETH1_DEPOSIT_DATA_FILE,
+    ETH1_DEPOSIT_TX_HASH_FILE, VOTING_KEYSTORE_FILE, WITHDRAWAL_KEYSTORE_FILE,
 };
 
 /// A very weak password with which to encrypt the keystores.
@@ -203,6 +203,44 @@ fn concurrency() {
     ValidatorDir::open(&path).unwrap();
 }
 
+#[test]
+fn directory_removed_whilst_open() {
+    let harness = Harness::new();
+
+    let val_dir = harness.create_and_test(&BuildConfig::default());
+    let path = val_dir.dir().clone();
+
+    fs::remove_dir_all(&path).unwrap();
+    drop(val_dir);
+
+    // Dropping should not re-create the directory or lockfile.
+    assert!(!path.exists());
+}
+
+#[test]
+fn enabled_persists() {
+    let harness = Harness::new();
+
+    let val_dir = harness.create_and_test(&BuildConfig::default());
+    let path = val_dir.dir().clone();
+    assert!(val_dir.is_enabled());
+
+    val_dir.set_enabled(false).unwrap();
+    assert!(path.join(DISABLED_FILE).exists());
+    drop(val_dir);
+
+    // Should still be disabled after re-opening.
+    let val_dir = ValidatorDir::open(&path).unwrap();
+    assert!(!val_dir.is_enabled());
+
+    val_dir.set_enabled(true).unwrap();
+    assert!(val_dir.is_enabled());
+
+    // Enabling an enabled validator is a no-op.
+    val_dir.set_enabled(true).unwrap();
+    assert!(val_dir.is_enabled());
+}
+
 #[test]
 fn without_voting_keystore() {
     let harness = Harness::new();
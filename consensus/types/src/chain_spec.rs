@@ -114,6 +114,7 @@ pub struct ChainSpec {
      */
     pub eth1_follow_distance: u64,
     pub seconds_per_eth1_block: u64,
+    pub deposit_chain_id: u64,
 
     /*
      * Networking
@@ -324,6 +325,7 @@ impl ChainSpec {
              */
             eth1_follow_distance: 1_024,
             seconds_per_eth1_block: 14,
+            deposit_chain_id: 1, // Ethereum mainnet
 
             /*
              * Network specific
@@ -352,6 +354,7 @@ impl ChainSpec {
             shuffle_round_count: 10,
             min_genesis_active_validator_count: 64,
             eth1_follow_distance: 16,
+            deposit_chain_id: 5, // Goerli testnet
             genesis_fork_version: [0x00, 0x00, 0x00, 0x01],
             shard_committee_period: 64,
             genesis_delay: 300,
@@ -551,6 +554,7 @@ pub struct YamlConfig {
     random_subnets_per_validator: u64,
     epochs_per_random_subnet_subscription: u64,
     seconds_per_eth1_block: u64,
+    deposit_chain_id: u64,
 }
 
 impl Default for YamlConfig {
@@ -631,6 +635,7 @@ impl YamlConfig {
             random_subnets_per_validator: spec.random_subnets_per_validator,
             epochs_per_random_subnet_subscription: spec.epochs_per_random_subnet_subscription,
             seconds_per_eth1_block: spec.seconds_per_eth1_block,
+            deposit_chain_id: spec.deposit_chain_id,
         }
     }
 
@@ -705,6 +710,7 @@ impl YamlConfig {
             boot_nodes: chain_spec.boot_nodes.clone(),
             genesis_fork_version: self.genesis_fork_version,
             eth1_follow_distance: self.eth1_follow_distance,
+            deposit_chain_id: self.deposit_chain_id,
             ..*chain_spec
         })
     }
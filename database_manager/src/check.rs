@@ -0,0 +1,116 @@
+use crate::Store;
+use clap::App;
+use store::chunked_iter::ChunkedVectorIter;
+use store::chunked_vector::{BlockRoots, StateRoots};
+use types::{ChainSpec, EthSpec, Hash256, SignedBeaconBlock, Slot};
+
+pub const CMD: &str = "check";
+
+/// The maximum number of inconsistencies to print.
+const MAX_REPORTED_ERRORS: usize = 32;
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD).about(
+        "Checks that the block and state roots stored in the freezer database agree with the \
+        stored blocks. Exits with an error if any inconsistency is found.",
+    )
+}
+
+pub fn cli_run<E: EthSpec>(store: Store<E>, spec: &ChainSpec) -> Result<(), String> {
+    let split_slot = store.get_split_slot();
+    if split_slot == 0 {
+        println!("Nothing to check, the freezer database is empty");
+        return Ok(());
+    }
+
+    // Blocks prior to the anchor of a checkpoint-synced database are never stored.
+    let start_slot = store
+        .get_anchor_info()
+        .map_or(Slot::new(0), |anchor| anchor.anchor_slot);
+    let latest_restore_point_slot = store.get_latest_restore_point_slot();
+
+    let block_roots = ChunkedVectorIter::<BlockRoots, _, _, _>::new(
+        store.clone(),
+        start_slot.as_usize(),
+        latest_restore_point_slot,
+        spec,
+    );
+    let mut state_roots = ChunkedVectorIter::<StateRoots, _, _, _>::new(
+        store.clone(),
+        start_slot.as_usize(),
+        latest_restore_point_slot,
+        spec,
+    );
+
+    let mut errors = vec![];
+    let mut checked_slots = 0;
+    // The most recently loaded block, re-used across skipped slots.
+    let mut previous_block: Option<(Hash256, SignedBeaconBlock<E>)> = None;
+
+    for (slot, block_root) in block_roots {
+        let slot = Slot::from(slot);
+        checked_slots += 1;
+
+        let state_root = match state_roots.next() {
+            Some((_, state_root)) => state_root,
+            None => {
+                errors.push(format!("Missing state root at slot {}", slot));
+                break;
+            }
+        };
+
+        let is_previous_block = previous_block
+            .as_ref()
+            .map_or(false, |(root, _)| *root == block_root);
+        if !is_previous_block {
+            previous_block = store
+                .get_block(&block_root)
+                .map_err(|e| format!("Unable to read block: {:?}", e))?
+                .map(|block| (block_root, block));
+        }
+
+        let block = match &previous_block {
+            Some((_, block)) => block,
+            None => {
+                errors.push(format!("Missing block {:?} at slot {}", block_root, slot));
+                continue;
+            }
+        };
+
+        if block.slot() > slot {
+            errors.push(format!(
+                "Block {:?} at slot {} is stored for earlier slot {}",
+                block_root,
+                block.slot(),
+                slot
+            ));
+        } else if block.slot() == slot && block.state_root() != state_root {
+            errors.push(format!(
+                "State root {:?} at slot {} does not match block {:?} with state root {:?}",
+                state_root,
+                slot,
+                block_root,
+                block.state_root()
+            ));
+        }
+    }
+
+    for error in errors.iter().take(MAX_REPORTED_ERRORS) {
+        println!("{}", error);
+    }
+
+    if errors.is_empty() {
+        println!(
+            "Checked {} slots from slot {}, no inconsistencies found",
+            checked_slots, start_slot
+        );
+        Ok(())
+    } else {
+        Err(format!(
+            "Checked {} slots from slot {}, found {} inconsistencies",
+            checked_slots,
+            start_slot,
+            errors.len()
+        ))
+    }
+}
@@ -0,0 +1,179 @@
+use crate::{load_head, Store};
+use clap::{App, Arg, ArgMatches};
+use clap_utils::{parse_optional, parse_required, parse_ssz_optional};
+use ssz::Encode;
+use std::fs;
+use std::path::PathBuf;
+use store::iter::StateRootsIterator;
+use store::HotColdDB;
+use types::{BeaconState, ChainSpec, EthSpec, Hash256, SignedBeaconBlock, Slot};
+
+pub const BLOCK_CMD: &str = "export-block";
+pub const STATE_CMD: &str = "export-state";
+pub const ROOT_FLAG: &str = "root";
+pub const SLOT_FLAG: &str = "slot";
+pub const OUTPUT_FLAG: &str = "output";
+
+fn cli_app<'a, 'b>(name: &'static str, about: &'static str) -> App<'a, 'b> {
+    App::new(name)
+        .about(about)
+        .arg(
+            Arg::with_name(ROOT_FLAG)
+                .long(ROOT_FLAG)
+                .value_name("ROOT")
+                .help("The 0x-prefixed root of the object to export.")
+                .conflicts_with(SLOT_FLAG)
+                .required_unless(SLOT_FLAG)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(SLOT_FLAG)
+                .long(SLOT_FLAG)
+                .value_name("SLOT")
+                .help("The slot of the object to export, on the canonical chain.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(OUTPUT_FLAG)
+                .long(OUTPUT_FLAG)
+                .value_name("FILE")
+                .help("The file to write the SSZ bytes to.")
+                .required(true)
+                .takes_value(true),
+        )
+}
+
+pub fn block_cli_app<'a, 'b>() -> App<'a, 'b> {
+    cli_app(
+        BLOCK_CMD,
+        "Writes a signed block from the database to a file as SSZ.",
+    )
+}
+
+pub fn state_cli_app<'a, 'b>() -> App<'a, 'b> {
+    cli_app(
+        STATE_CMD,
+        "Writes a state from the database to a file as SSZ.",
+    )
+}
+
+pub fn block_cli_run<E: EthSpec>(
+    matches: &ArgMatches,
+    store: Store<E>,
+    spec: &ChainSpec,
+) -> Result<(), String> {
+    let output: PathBuf = parse_required(matches, OUTPUT_FLAG)?;
+
+    let slot = parse_optional::<u64>(matches, SLOT_FLAG)?.map(Slot::new);
+
+    let block_root = match (parse_ssz_optional::<Hash256>(matches, ROOT_FLAG)?, slot) {
+        (Some(root), _) => root,
+        (None, Some(slot)) => {
+            let (head_block_root, _, head_state) = load_head(&store)?;
+            HotColdDB::forwards_block_roots_iterator(
+                store.clone(),
+                slot,
+                head_state,
+                head_block_root,
+                spec,
+            )
+            .map_err(|e| format!("Unable to iterate block roots: {:?}", e))?
+            .next()
+            .transpose()
+            .map_err(|e| format!("Unable to iterate block roots: {:?}", e))?
+            .ok_or_else(|| format!("Slot {} is after the head", slot))?
+            .0
+        }
+        (None, None) => {
+            return Err(format!(
+                "One of --{} or --{} is required",
+                ROOT_FLAG, SLOT_FLAG
+            ))
+        }
+    };
+
+    let block: SignedBeaconBlock<E> = store
+        .get_block(&block_root)
+        .map_err(|e| format!("Unable to read block: {:?}", e))?
+        .ok_or_else(|| format!("Block {:?} is not in the database", block_root))?;
+
+    // The block root of a skipped slot is that of the previous block.
+    if let Some(slot) = slot {
+        if block.slot() != slot {
+            return Err(format!("Slot {} was skipped", slot));
+        }
+    }
+
+    fs::write(&output, block.as_ssz_bytes())
+        .map_err(|e| format!("Unable to write {:?}: {:?}", output, e))?;
+
+    println!(
+        "Wrote block at slot {} with root {:?} to {:?}",
+        block.slot(),
+        block_root,
+        output
+    );
+
+    Ok(())
+}
+
+pub fn state_cli_run<E: EthSpec>(matches: &ArgMatches, store: Store<E>) -> Result<(), String> {
+    let output: PathBuf = parse_required(matches, OUTPUT_FLAG)?;
+
+    let (state_root, state) = match parse_ssz_optional::<Hash256>(matches, ROOT_FLAG)? {
+        Some(state_root) => {
+            let state = store
+                .get_state(&state_root, None)
+                .map_err(|e| format!("Unable to read state: {:?}", e))?
+                .ok_or_else(|| format!("State {:?} is not in the database", state_root))?;
+            (state_root, state)
+        }
+        None => load_state_by_slot(&store, Slot::new(parse_required(matches, SLOT_FLAG)?))?,
+    };
+
+    fs::write(&output, state.as_ssz_bytes())
+        .map_err(|e| format!("Unable to write {:?}: {:?}", output, e))?;
+
+    println!(
+        "Wrote state at slot {} with root {:?} to {:?}",
+        state.slot, state_root, output
+    );
+
+    Ok(())
+}
+
+/// Load the canonical state at `slot`, from the freezer database if it has been finalized.
+fn load_state_by_slot<E: EthSpec>(
+    store: &Store<E>,
+    slot: Slot,
+) -> Result<(Hash256, BeaconState<E>), String> {
+    if slot < store.get_split_slot() {
+        let state = store
+            .load_cold_state_by_slot(slot)
+            .map_err(|e| format!("Unable to load state: {:?}", e))?;
+        return Ok((state.canonical_root(), state));
+    }
+
+    let (_, head_block, head_state) = load_head(store)?;
+    if slot > head_state.slot {
+        return Err(format!("Slot {} is after the head", slot));
+    }
+
+    let state_root = if slot == head_state.slot {
+        head_block.state_root()
+    } else {
+        StateRootsIterator::owned(store.clone(), head_state)
+            .find(|result| result.as_ref().map_or(true, |(_, s)| *s == slot))
+            .transpose()
+            .map_err(|e| format!("Unable to iterate state roots: {:?}", e))?
+            .ok_or_else(|| format!("No state root found for slot {}", slot))?
+            .0
+    };
+
+    let state = store
+        .get_state(&state_root, Some(slot))
+        .map_err(|e| format!("Unable to read state: {:?}", e))?
+        .ok_or_else(|| format!("State {:?} is not in the database", state_root))?;
+
+    Ok((state_root, state))
+}
@@ -0,0 +1,121 @@
+use crate::{load_head, Store};
+use clap::App;
+use store::DBColumn;
+use types::{EthSpec, Slot};
+
+pub const CMD: &str = "inspect";
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD).about(
+        "Prints the split point, restore points, pruned states, head and finalized checkpoint \
+        of the database, along with the number and size of the keys in each column.",
+    )
+}
+
+pub fn cli_run<E: EthSpec>(store: Store<E>) -> Result<(), String> {
+    let schema_version = store
+        .load_schema_version()
+        .map_err(|e| format!("Unable to read schema version: {:?}", e))?;
+    let split_slot = store.get_split_slot();
+    let slots_per_restore_point = store.get_config().slots_per_restore_point;
+
+    println!("Schema version: {:?}", schema_version.map(|v| v.as_u64()));
+    println!("Backend: {}", store.backend());
+    println!("Split slot: {}", split_slot);
+
+    match store.get_anchor_info() {
+        Some(anchor) => println!(
+            "Anchor: slot {}, block root {:?}, state root {:?}",
+            anchor.anchor_slot, anchor.anchor_block_root, anchor.anchor_state_root
+        ),
+        None => println!("Anchor: none (initialised from genesis)"),
+    }
+
+    match store.get_pruning_info() {
+        Some(pruning_info) => println!(
+            "Pruned states: all prior to slot {}",
+            pruning_info.state_lower_limit
+        ),
+        None => println!("Pruned states: none"),
+    }
+
+    if split_slot == 0 {
+        println!("Restore points: none stored");
+    } else {
+        let latest_restore_point_slot = store.get_latest_restore_point_slot();
+        let first_restore_point_slot = store
+            .get_pruning_info()
+            .map_or(Slot::new(0), |info| info.state_lower_limit);
+        println!(
+            "Restore points: {} stored every {} slots, the latest at slot {}",
+            (latest_restore_point_slot - first_restore_point_slot) / slots_per_restore_point + 1,
+            slots_per_restore_point,
+            latest_restore_point_slot
+        );
+    }
+
+    match load_head(&store) {
+        Ok((head_block_root, head_block, head_state)) => {
+            println!(
+                "Head: slot {}, block root {:?}, state root {:?}",
+                head_block.slot(),
+                head_block_root,
+                head_block.state_root()
+            );
+            println!(
+                "Justified: epoch {}, root {:?}",
+                head_state.current_justified_checkpoint.epoch,
+                head_state.current_justified_checkpoint.root
+            );
+            println!(
+                "Finalized: epoch {}, root {:?}",
+                head_state.finalized_checkpoint.epoch, head_state.finalized_checkpoint.root
+            );
+        }
+        // The remaining information is still useful when the head cannot be loaded.
+        Err(e) => println!("Head: unavailable ({})", e),
+    }
+
+    println!();
+    println!("Hot database:");
+    print_column_stats(|f| store.for_each_raw_hot_entry(f))?;
+    println!();
+    println!("Freezer database:");
+    print_column_stats(|f| store.for_each_raw_cold_entry(f))?;
+
+    Ok(())
+}
+
+/// Print the number of keys in each column of a database and the total size of their keys and
+/// values, given a function which visits each of its raw entries.
+fn print_column_stats<F>(for_each_raw_entry: F) -> Result<(), String>
+where
+    F: FnOnce(&mut dyn FnMut(&[u8], &[u8])) -> Result<(), store::Error>,
+{
+    let mut stats = vec![(0, 0); DBColumn::ALL.len()];
+    let mut other = (0, 0);
+
+    for_each_raw_entry(&mut |key, value| {
+        let column_index = DBColumn::ALL.iter().position(|column| {
+            let prefix: &str = (*column).into();
+            key.starts_with(prefix.as_bytes())
+        });
+        let (count, size) = match column_index {
+            Some(i) => &mut stats[i],
+            None => &mut other,
+        };
+        *count += 1;
+        *size += key.len() + value.len();
+    })
+    .map_err(|e| format!("Unable to read database: {:?}", e))?;
+
+    println!("{:<24} {:>12} {:>16}", "Column", "Keys", "Bytes");
+    for (column, (count, size)) in DBColumn::ALL.iter().zip(stats) {
+        println!("{:<24} {:>12} {:>16}", format!("{:?}", column), count, size);
+    }
+    if other.0 > 0 {
+        println!("{:<24} {:>12} {:>16}", "Unknown", other.0, other.1);
+    }
+
+    Ok(())
+}
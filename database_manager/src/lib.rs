@@ -0,0 +1,138 @@
+//! Offline inspection and maintenance of the beacon node database.
+//!
+//! The database is opened directly, without starting the client, so the beacon node which uses
+//! it must be stopped first.
+mod check;
+mod export;
+mod inspect;
+
+use beacon_chain::{PersistedBeaconChain, BEACON_CHAIN_DB_KEY};
+use beacon_node::{get_data_dir, ClientConfig};
+use clap::{App, Arg, ArgMatches};
+use clap_utils::parse_optional;
+use environment::Environment;
+use std::cmp;
+use std::path::PathBuf;
+use std::sync::Arc;
+use store::config::DEFAULT_SLOTS_PER_RESTORE_POINT;
+use store::{DatabaseBackend, DiskStore, HotColdDB, StoreConfig};
+use types::{BeaconState, EthSpec, Hash256, SignedBeaconBlock};
+
+pub const CMD: &str = "database_manager";
+pub const FREEZER_DIR_FLAG: &str = "freezer-dir";
+pub const SLOTS_PER_RESTORE_POINT_FLAG: &str = "slots-per-restore-point";
+
+type Store<E> = Arc<HotColdDB<E, DiskStore<E>, DiskStore<E>>>;
+
+pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
+    App::new(CMD)
+        .visible_aliases(&["db"])
+        .about(
+            "Utilities for inspecting and checking the beacon node database. The beacon node \
+            must not be running.",
+        )
+        .arg(
+            Arg::with_name(FREEZER_DIR_FLAG)
+                .long(FREEZER_DIR_FLAG)
+                .value_name("DIR")
+                .help("Data directory for the freezer database, if not the default.")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name(SLOTS_PER_RESTORE_POINT_FLAG)
+                .long(SLOTS_PER_RESTORE_POINT_FLAG)
+                .value_name("SLOT_COUNT")
+                .help(
+                    "The restore point interval the database was created with. Must match the \
+                    value given to the beacon node. [default: 2048 (mainnet) or 64 (minimal)]",
+                )
+                .takes_value(true),
+        )
+        .subcommand(inspect::cli_app())
+        .subcommand(export::block_cli_app())
+        .subcommand(export::state_cli_app())
+        .subcommand(check::cli_app())
+}
+
+/// Run the database manager, returning an error if the operation did not succeed.
+pub fn run<T: EthSpec>(matches: &ArgMatches<'_>, mut env: Environment<T>) -> Result<(), String> {
+    let spec = env.eth2_config().spec.clone();
+    let log = env.core_context().log().clone();
+
+    let (subcommand, sub_matches) = match matches.subcommand() {
+        (subcommand, Some(sub_matches)) => (subcommand, sub_matches),
+        (unknown, None) => {
+            return Err(format!(
+                "{} is not a valid {} command. See --help.",
+                unknown, CMD
+            ))
+        }
+    };
+
+    let mut client_config = ClientConfig::default();
+    client_config.data_dir = get_data_dir(matches);
+    client_config.freezer_db_path = parse_optional(matches, FREEZER_DIR_FLAG)?;
+
+    let hot_path = client_config
+        .get_db_path()
+        .ok_or_else(|| "Failed to get db_path".to_string())?;
+    let cold_path = client_config
+        .get_freezer_db_path()
+        .ok_or_else(|| "Failed to get freezer db path".to_string())?;
+
+    // Opening a database creates it if it is missing, which is never wanted here.
+    let backend = DatabaseBackend::detect(&hot_path)
+        .ok_or_else(|| format!("No database found at {:?}", hot_path))?;
+    if DatabaseBackend::detect(&cold_path).is_none() {
+        return Err(format!("No database found at {:?}", cold_path));
+    }
+
+    let mut store_config = StoreConfig::default();
+    store_config.backend = backend;
+    store_config.slots_per_restore_point = parse_optional(matches, SLOTS_PER_RESTORE_POINT_FLAG)?
+        .unwrap_or_else(|| {
+            cmp::min(
+                T::slots_per_historical_root() as u64,
+                DEFAULT_SLOTS_PER_RESTORE_POINT,
+            )
+        });
+
+    // Never migrate or otherwise write to the database, it belongs to the beacon node.
+    let store = HotColdDB::open_read_only(&hot_path, &cold_path, store_config, spec.clone(), log)
+        .map(Arc::new)
+        .map_err(|e| format!("Unable to open database: {:?}", e))?;
+
+    match subcommand {
+        inspect::CMD => inspect::cli_run(store),
+        export::BLOCK_CMD => export::block_cli_run(sub_matches, store, &spec),
+        export::STATE_CMD => export::state_cli_run(sub_matches, store),
+        check::CMD => check::cli_run(store, &spec),
+        unknown => Err(format!(
+            "{} is not a valid {} command. See --help.",
+            unknown, CMD
+        )),
+    }
+}
+
+/// Returns the root, block and state of the head persisted by the beacon node when it last shut
+/// down.
+fn load_head<E: EthSpec>(
+    store: &Store<E>,
+) -> Result<(Hash256, SignedBeaconBlock<E>, BeaconState<E>), String> {
+    let persisted_chain = store
+        .get_item::<PersistedBeaconChain>(&Hash256::from_slice(&BEACON_CHAIN_DB_KEY))
+        .map_err(|e| format!("Unable to read the persisted beacon chain: {:?}", e))?
+        .ok_or_else(|| "No beacon chain has been persisted in the database".to_string())?;
+
+    let head_block_root = persisted_chain.canonical_head_block_root;
+    let head_block = store
+        .get_block(&head_block_root)
+        .map_err(|e| format!("Unable to read head block: {:?}", e))?
+        .ok_or_else(|| format!("Missing head block {:?}", head_block_root))?;
+    let head_state = store
+        .get_state(&head_block.state_root(), Some(head_block.slot()))
+        .map_err(|e| format!("Unable to read head state: {:?}", e))?
+        .ok_or_else(|| format!("Missing head state {:?}", head_block.state_root()))?;
+
+    Ok((head_block_root, head_block, head_state))
+}
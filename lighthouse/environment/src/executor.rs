@@ -1,4 +1,5 @@
 use crate::metrics;
+use futures::channel::mpsc::Sender;
 use futures::prelude::*;
 use slog::{debug, trace};
 use tokio::runtime::Handle;
@@ -11,6 +12,8 @@ pub struct TaskExecutor {
     /// The receiver exit future which on receiving shuts down the task
     pub(crate) exit: exit_future::Exit,
     pub(crate) log: slog::Logger,
+    /// Sender used by services to request that the process shuts down.
+    pub(crate) signal_tx: Sender<&'static str>,
 }
 
 impl TaskExecutor {
@@ -18,8 +21,18 @@ impl TaskExecutor {
     ///
     /// Note: this function is mainly useful in tests. A `TaskExecutor` should be normally obtained from
     /// a [`RuntimeContext`](struct.RuntimeContext.html)
-    pub fn new(handle: Handle, exit: exit_future::Exit, log: slog::Logger) -> Self {
-        Self { handle, exit, log }
+    pub fn new(
+        handle: Handle,
+        exit: exit_future::Exit,
+        log: slog::Logger,
+        signal_tx: Sender<&'static str>,
+    ) -> Self {
+        Self {
+            handle,
+            exit,
+            log,
+            signal_tx,
+        }
     }
 
     /// Spawn a future on the tokio runtime wrapped in an `exit_future::Exit`. The task is canceled
@@ -125,6 +138,13 @@ impl TaskExecutor {
     pub fn log(&self) -> &slog::Logger {
         &self.log
     }
+
+    /// Returns a sender which requests that the process shuts down, giving the reason.
+    ///
+    /// Used by services which detect a condition under which it is unsafe to continue.
+    pub fn shutdown_sender(&self) -> Sender<&'static str> {
+        self.signal_tx.clone()
+    }
 }
 
 impl discv5::Executor for TaskExecutor {
@@ -9,7 +9,11 @@
 
 use eth2_config::Eth2Config;
 use eth2_testnet_config::Eth2TestnetConfig;
-use futures::channel::oneshot;
+use futures::channel::{
+    mpsc::{channel, Receiver, Sender},
+    oneshot,
+};
+use futures::{future, StreamExt};
 
 pub use executor::TaskExecutor;
 use slog::{info, o, Drain, Level, Logger};
@@ -189,12 +193,15 @@ impl<E: EthSpec> EnvironmentBuilder<E> {
     /// Consumes the builder, returning an `Environment`.
     pub fn build(self) -> Result<Environment<E>, String> {
         let (signal, exit) = exit_future::signal();
+        let (signal_tx, signal_rx) = channel(1);
         Ok(Environment {
             runtime: self
                 .runtime
                 .ok_or_else(|| "Cannot build environment without runtime".to_string())?,
             signal: Some(signal),
             exit,
+            signal_tx,
+            signal_rx: Some(signal_rx),
             log: self
                 .log
                 .ok_or_else(|| "Cannot build environment without log".to_string())?,
@@ -226,6 +233,7 @@ impl<E: EthSpec> RuntimeContext<E> {
                 handle: self.executor.handle.clone(),
                 exit: self.executor.exit.clone(),
                 log: self.executor.log.new(o!("service" => service_name)),
+                signal_tx: self.executor.signal_tx.clone(),
             },
             eth_spec_instance: self.eth_spec_instance.clone(),
             eth2_config: self.eth2_config.clone(),
@@ -249,6 +257,10 @@ pub struct Environment<E: EthSpec> {
     runtime: Runtime,
     signal: Option<exit_future::Signal>,
     exit: exit_future::Exit,
+    /// Sender given to each `TaskExecutor`, allowing services to request a shutdown.
+    signal_tx: Sender<&'static str>,
+    /// Receiver for shutdown requests from services.
+    signal_rx: Option<Receiver<&'static str>>,
     log: Logger,
     eth_spec_instance: E,
     pub eth2_config: Eth2Config,
@@ -271,6 +283,7 @@ impl<E: EthSpec> Environment<E> {
                 exit: self.exit.clone(),
                 handle: self.runtime().handle().clone(),
                 log: self.log.clone(),
+                signal_tx: self.signal_tx.clone(),
             },
             eth_spec_instance: self.eth_spec_instance.clone(),
             eth2_config: self.eth2_config.clone(),
@@ -284,14 +297,15 @@ impl<E: EthSpec> Environment<E> {
                 exit: self.exit.clone(),
                 handle: self.runtime().handle().clone(),
                 log: self.log.new(o!("service" => service_name)),
+                signal_tx: self.signal_tx.clone(),
             },
             eth_spec_instance: self.eth_spec_instance.clone(),
             eth2_config: self.eth2_config.clone(),
         }
     }
 
-    /// Block the current thread until Ctrl+C is received.
-    pub fn block_until_ctrl_c(&mut self) -> Result<(), String> {
+    /// Block the current thread until Ctrl+C is received or a service requests a shutdown.
+    pub fn block_until_shutdown_requested(&mut self) -> Result<(), String> {
         let (ctrlc_send, ctrlc_oneshot) = oneshot::channel();
         let ctrlc_send_c = RefCell::new(Some(ctrlc_send));
         ctrlc::set_handler(move || {
@@ -301,10 +315,25 @@ impl<E: EthSpec> Environment<E> {
         })
         .map_err(|e| format!("Could not set ctrlc handler: {:?}", e))?;
 
-        // Block this thread until Crtl+C is pressed.
-        self.runtime()
-            .block_on(ctrlc_oneshot)
-            .map_err(|e| format!("Ctrlc oneshot failed: {:?}", e))
+        let mut signal_rx = self
+            .signal_rx
+            .take()
+            .ok_or_else(|| "Shutdown channel has already been taken".to_string())?;
+
+        // Block this thread until Crtl+C is pressed or a shutdown is requested.
+        match self
+            .runtime()
+            .block_on(future::select(ctrlc_oneshot, signal_rx.next()))
+        {
+            future::Either::Left((ctrlc_result, _)) => {
+                ctrlc_result.map_err(|e| format!("Ctrlc oneshot failed: {:?}", e))
+            }
+            future::Either::Right((Some(reason), _)) => {
+                info!(self.log, "Internal shutdown received"; "reason" => reason);
+                Ok(())
+            }
+            future::Either::Right((None, _)) => Err("Shutdown channel closed".to_string()),
+        }
     }
 
     /// Shutdown the `tokio` runtime when all tasks are idle.
@@ -98,6 +98,7 @@ fn main() {
         .subcommand(boot_node::cli_app())
         .subcommand(validator_client::cli_app())
         .subcommand(account_manager::cli_app())
+        .subcommand(database_manager::cli_app())
         .get_matches();
 
     // boot node subcommand circumvents the environment
@@ -199,6 +200,13 @@ fn run<E: EthSpec>(
         return Ok(());
     };
 
+    if let Some(sub_matches) = matches.subcommand_matches(database_manager::CMD) {
+        database_manager::run(sub_matches, environment)?;
+
+        // Exit as soon as database manager returns control.
+        return Ok(());
+    };
+
     warn!(
         log,
         "Ethereum 2.0 is pre-release. This software is experimental."
@@ -260,8 +268,8 @@ fn run<E: EthSpec>(
         return Err("No subcommand supplied.".into());
     }
 
-    // Block this thread until Crtl+C is pressed.
-    environment.block_until_ctrl_c()?;
+    // Block this thread until Crtl+C is pressed or a service requests a shutdown.
+    environment.block_until_shutdown_requested()?;
     info!(log, "Shutting down..");
 
     environment.fire_signal();
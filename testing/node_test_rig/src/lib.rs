@@ -75,6 +75,7 @@ pub fn testing_client_config() -> ClientConfig {
     client_config.rest_api.port = 0;
     client_config.websocket_server.enabled = true;
     client_config.websocket_server.port = 0;
+    client_config.dummy_eth1_backend = true;
 
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
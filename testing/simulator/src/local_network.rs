@@ -127,8 +127,11 @@ impl<E: EthSpec> LocalNetwork<E> {
                 .expect("Must have http started")
         };
 
-        validator_config.http_server =
-            format!("http://{}:{}", socket_addr.ip(), socket_addr.port());
+        validator_config.beacon_nodes = vec![format!(
+            "http://{}:{}",
+            socket_addr.ip(),
+            socket_addr.port()
+        )];
         let validator_client = LocalValidatorClient::production_with_insecure_keypairs(
             context,
             validator_config,
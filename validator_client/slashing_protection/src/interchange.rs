@@ -0,0 +1,93 @@
+use serde_derive::{Deserialize, Serialize};
+use std::io;
+use types::{Epoch, Hash256, PublicKey, Slot};
+
+/// The version of the slashing protection interchange format supported by this crate.
+///
+/// See: https://eips.ethereum.org/EIPS/eip-3076
+pub const INTERCHANGE_FORMAT_VERSION: u64 = 5;
+
+/// A portable record of the slashing protection history of a set of validators.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Interchange {
+    pub metadata: InterchangeMetadata,
+    pub data: Vec<InterchangeData>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InterchangeMetadata {
+    #[serde(with = "quoted_u64")]
+    pub interchange_format_version: u64,
+    pub genesis_validators_root: Hash256,
+}
+
+/// The signing history of a single validator.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InterchangeData {
+    pub pubkey: PublicKey,
+    pub signed_blocks: Vec<InterchangeBlock>,
+    pub signed_attestations: Vec<InterchangeAttestation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InterchangeBlock {
+    #[serde(with = "quoted_u64")]
+    pub slot: Slot,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub signing_root: Option<Hash256>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InterchangeAttestation {
+    #[serde(with = "quoted_u64")]
+    pub source_epoch: Epoch,
+    #[serde(with = "quoted_u64")]
+    pub target_epoch: Epoch,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub signing_root: Option<Hash256>,
+}
+
+impl Interchange {
+    pub fn from_json_str(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn from_json_reader(reader: impl io::Read) -> Result<Self, serde_json::Error> {
+        serde_json::from_reader(reader)
+    }
+
+    pub fn write_to(&self, writer: impl io::Write) -> Result<(), serde_json::Error> {
+        serde_json::to_writer_pretty(writer, self)
+    }
+}
+
+/// The interchange format encodes integers as decimal strings, to avoid precision loss in
+/// languages which represent all numbers as floats.
+mod quoted_u64 {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: Copy + Into<u64>,
+        S: Serializer,
+    {
+        let value: u64 = (*value).into();
+        serializer.serialize_str(&value.to_string())
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: From<u64>,
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse::<u64>()
+            .map(T::from)
+            .map_err(|e| serde::de::Error::custom(format!("invalid integer {:?}: {}", s, e)))
+    }
+}
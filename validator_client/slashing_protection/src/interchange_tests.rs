@@ -0,0 +1,227 @@
+#![cfg(test)]
+
+use crate::attestation_tests::attestation_data_builder;
+use crate::block_tests::block;
+use crate::interchange::*;
+use crate::test_utils::*;
+use crate::*;
+use tempfile::tempdir;
+use types::{Epoch, Hash256, Slot};
+
+const GENESIS_VALIDATORS_ROOT: Hash256 = Hash256::repeat_byte(0x42);
+
+fn interchange(data: Vec<InterchangeData>) -> Interchange {
+    Interchange {
+        metadata: InterchangeMetadata {
+            interchange_format_version: INTERCHANGE_FORMAT_VERSION,
+            genesis_validators_root: GENESIS_VALIDATORS_ROOT,
+        },
+        data,
+    }
+}
+
+fn new_db() -> (tempfile::TempDir, SlashingDatabase) {
+    let dir = tempdir().unwrap();
+    let file = dir.path().join(SLASHING_PROTECTION_FILENAME);
+    let db = SlashingDatabase::create(&file).unwrap();
+    (dir, db)
+}
+
+#[test]
+fn export_import_round_trip() {
+    let (_dir1, db1) = new_db();
+    db1.register_validators([pubkey(0), pubkey(1)].iter())
+        .unwrap();
+    db1.check_and_insert_block_proposal(&pubkey(0), &block(1), DEFAULT_DOMAIN)
+        .unwrap();
+    db1.check_and_insert_attestation(&pubkey(1), &attestation_data_builder(0, 1), DEFAULT_DOMAIN)
+        .unwrap();
+
+    let exported = db1
+        .export_interchange_info(GENESIS_VALIDATORS_ROOT)
+        .unwrap();
+    assert_eq!(exported.data.len(), 2);
+
+    // Round trip via JSON.
+    let mut json = vec![];
+    exported.write_to(&mut json).unwrap();
+    let parsed = Interchange::from_json_reader(&json[..]).unwrap();
+    assert_eq!(parsed, exported);
+
+    // The earliest records of each validator become watermarks upon import, and are exported
+    // without signing roots. Subsequent round trips are lossless.
+    let (_dir2, db2) = new_db();
+    db2.import_interchange_info(&parsed, GENESIS_VALIDATORS_ROOT)
+        .unwrap();
+    let exported2 = db2
+        .export_interchange_info(GENESIS_VALIDATORS_ROOT)
+        .unwrap();
+    assert_eq!(exported2.data.len(), 2);
+    assert_eq!(
+        exported2.data[0].signed_blocks,
+        vec![InterchangeBlock {
+            slot: Slot::new(1),
+            signing_root: None
+        }]
+    );
+
+    let (_dir3, db3) = new_db();
+    db3.import_interchange_info(&exported2, GENESIS_VALIDATORS_ROOT)
+        .unwrap();
+    assert_eq!(
+        db3.export_interchange_info(GENESIS_VALIDATORS_ROOT)
+            .unwrap(),
+        exported2
+    );
+}
+
+#[test]
+fn import_protects_against_slashing() {
+    let (_dir, db) = new_db();
+    db.import_interchange_info(
+        &interchange(vec![InterchangeData {
+            pubkey: pubkey(0),
+            signed_blocks: vec![InterchangeBlock {
+                slot: Slot::new(10),
+                signing_root: None,
+            }],
+            signed_attestations: vec![InterchangeAttestation {
+                source_epoch: Epoch::new(2),
+                target_epoch: Epoch::new(5),
+                signing_root: None,
+            }],
+        }]),
+        GENESIS_VALIDATORS_ROOT,
+    )
+    .unwrap();
+
+    assert!(matches!(
+        db.check_and_insert_block_proposal(&pubkey(0), &block(10), DEFAULT_DOMAIN),
+        Err(NotSafe::InvalidBlock(
+            InvalidBlock::SlotViolatesLowerBound { .. }
+        ))
+    ));
+    assert!(matches!(
+        db.check_and_insert_block_proposal(&pubkey(0), &block(9), DEFAULT_DOMAIN),
+        Err(NotSafe::InvalidBlock(
+            InvalidBlock::SlotViolatesLowerBound { .. }
+        ))
+    ));
+    assert!(matches!(
+        db.check_and_insert_attestation(
+            &pubkey(0),
+            &attestation_data_builder(3, 4),
+            DEFAULT_DOMAIN
+        ),
+        Err(NotSafe::InvalidAttestation(
+            InvalidAttestation::TargetLessThanOrEqLowerBound { .. }
+        ))
+    ));
+    assert!(matches!(
+        db.check_and_insert_attestation(
+            &pubkey(0),
+            &attestation_data_builder(1, 6),
+            DEFAULT_DOMAIN
+        ),
+        Err(NotSafe::InvalidAttestation(
+            InvalidAttestation::SourceLessThanLowerBound { .. }
+        ))
+    ));
+    assert_eq!(
+        db.check_and_insert_attestation(
+            &pubkey(0),
+            &attestation_data_builder(5, 6),
+            DEFAULT_DOMAIN
+        ),
+        Ok(Safe::Valid)
+    );
+}
+
+#[test]
+fn import_merges_with_existing() {
+    let (_dir, db) = new_db();
+    db.register_validator(&pubkey(0)).unwrap();
+    db.check_and_insert_block_proposal(&pubkey(0), &block(1), DEFAULT_DOMAIN)
+        .unwrap();
+    let existing = db.export_interchange_info(GENESIS_VALIDATORS_ROOT).unwrap();
+
+    db.import_interchange_info(
+        &interchange(vec![InterchangeData {
+            pubkey: pubkey(0),
+            signed_blocks: vec![
+                // Conflicts with the existing block, which must be kept.
+                InterchangeBlock {
+                    slot: Slot::new(1),
+                    signing_root: Some(Hash256::repeat_byte(1)),
+                },
+                InterchangeBlock {
+                    slot: Slot::new(2),
+                    signing_root: Some(Hash256::repeat_byte(2)),
+                },
+            ],
+            signed_attestations: vec![],
+        }]),
+        GENESIS_VALIDATORS_ROOT,
+    )
+    .unwrap();
+
+    // The existing block at slot 1 is kept (although it is now summarised by the watermark),
+    // and the block at slot 2 is added.
+    let merged = db.export_interchange_info(GENESIS_VALIDATORS_ROOT).unwrap();
+    assert_eq!(merged.data.len(), 1);
+    let blocks = &merged.data[0].signed_blocks;
+    assert_eq!(blocks.len(), 2);
+    assert_eq!(blocks[0].slot, existing.data[0].signed_blocks[0].slot);
+    assert_eq!(blocks[1].slot, Slot::new(2));
+    assert_eq!(blocks[1].signing_root, Some(Hash256::repeat_byte(2)));
+}
+
+#[test]
+fn import_wrong_genesis_validators_root() {
+    let (_dir, db) = new_db();
+    let result = db.import_interchange_info(
+        &interchange(vec![InterchangeData {
+            pubkey: pubkey(0),
+            signed_blocks: vec![],
+            signed_attestations: vec![],
+        }]),
+        Hash256::repeat_byte(0xff),
+    );
+    assert!(matches!(
+        result,
+        Err(InterchangeError::GenesisValidatorsMismatch { .. })
+    ));
+
+    // Nothing should have been imported.
+    assert!(db
+        .export_interchange_info(GENESIS_VALIDATORS_ROOT)
+        .unwrap()
+        .data
+        .is_empty());
+}
+
+#[test]
+fn import_unsupported_version() {
+    let (_dir, db) = new_db();
+    let mut interchange = interchange(vec![]);
+    interchange.metadata.interchange_format_version = INTERCHANGE_FORMAT_VERSION + 1;
+    assert!(matches!(
+        db.import_interchange_info(&interchange, GENESIS_VALIDATORS_ROOT),
+        Err(InterchangeError::UnsupportedVersion(_))
+    ));
+}
+
+#[test]
+fn parse_quoted_integers() {
+    let json = r#"{
+        "metadata": {
+            "interchange_format_version": "5",
+            "genesis_validators_root": "0x4242424242424242424242424242424242424242424242424242424242424242"
+        },
+        "data": []
+    }"#;
+    assert_eq!(
+        Interchange::from_json_str(json).unwrap(),
+        interchange(vec![])
+    );
+}
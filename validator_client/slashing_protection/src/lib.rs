@@ -1,6 +1,9 @@
 mod attestation_tests;
 mod block_tests;
+pub mod interchange;
+mod interchange_tests;
 mod parallel_tests;
+mod pruning_tests;
 mod signed_attestation;
 mod signed_block;
 mod slashing_database;
@@ -8,12 +11,18 @@ mod test_utils;
 
 pub use crate::signed_attestation::{InvalidAttestation, SignedAttestation};
 pub use crate::signed_block::{InvalidBlock, SignedBlock};
-pub use crate::slashing_database::SlashingDatabase;
+pub use crate::slashing_database::{
+    InterchangeError, SigningHistorySummary, SlashingDatabase, CURRENT_SCHEMA_VERSION,
+};
 use rusqlite::Error as SQLError;
 use std::io::{Error as IOError, ErrorKind};
 use std::string::ToString;
 use types::{Hash256, PublicKey};
 
+/// The filename within the validator client data directory that holds the slashing protection
+/// database.
+pub const SLASHING_PROTECTION_FILENAME: &str = "slashing_protection.sqlite";
+
 /// The attestation or block is not safe to sign.
 ///
 /// This could be because it's slashable, or because an error occurred.
@@ -25,6 +34,11 @@ pub enum NotSafe {
     IOError(ErrorKind),
     SQLError(String),
     SQLPoolError(String),
+    /// The database was written by a newer version of this crate.
+    UnsupportedSchemaVersion {
+        schema_version: u32,
+        current_version: u32,
+    },
 }
 
 /// The attestation or block is safe to sign, and will not cause the signer to be slashed.
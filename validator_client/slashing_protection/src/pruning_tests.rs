@@ -0,0 +1,169 @@
+#![cfg(test)]
+
+use crate::attestation_tests::attestation_data_builder;
+use crate::block_tests::block;
+use crate::test_utils::*;
+use crate::*;
+use tempfile::{tempdir, TempDir};
+use types::{Epoch, Slot};
+
+/// Create a database for a single validator with the given blocks and attestations, optionally
+/// pruning it.
+fn db_with_history(
+    blocks: &[u64],
+    attestations: &[(u64, u64)],
+    prune: Option<(u64, u64)>,
+) -> (TempDir, SlashingDatabase) {
+    let dir = tempdir().unwrap();
+    let db = SlashingDatabase::create(&dir.path().join(SLASHING_PROTECTION_FILENAME)).unwrap();
+    db.register_validator(&pubkey(0)).unwrap();
+
+    for &slot in blocks {
+        db.check_and_insert_block_proposal(&pubkey(0), &block(slot), DEFAULT_DOMAIN)
+            .unwrap();
+    }
+    for &(source, target) in attestations {
+        db.check_and_insert_attestation(
+            &pubkey(0),
+            &attestation_data_builder(source, target),
+            DEFAULT_DOMAIN,
+        )
+        .unwrap();
+    }
+
+    if let Some((block_window, attestation_window)) = prune {
+        db.prune(block_window, attestation_window).unwrap();
+    }
+
+    (dir, db)
+}
+
+#[test]
+fn prune_blocks() {
+    let blocks = (1..=10).collect::<Vec<_>>();
+    let (_dir, db) = db_with_history(&blocks, &[], Some((3, 3)));
+
+    assert_eq!(
+        db.check_and_insert_block_proposal(&pubkey(0), &block(6), DEFAULT_DOMAIN),
+        Err(NotSafe::InvalidBlock(
+            InvalidBlock::SlotViolatesLowerBound {
+                block_slot: Slot::new(6),
+                bound_slot: Slot::new(6),
+            }
+        ))
+    );
+    assert!(matches!(
+        db.check_and_insert_block_proposal(&pubkey(0), &block(7), DEFAULT_DOMAIN),
+        Err(NotSafe::InvalidBlock(InvalidBlock::DoubleBlockProposal(_)))
+    ));
+    assert_eq!(
+        db.check_and_insert_block_proposal(&pubkey(0), &block(11), DEFAULT_DOMAIN),
+        Ok(Safe::Valid)
+    );
+}
+
+#[test]
+fn prune_attestations() {
+    let attestations = (0..10).map(|i| (i, i + 1)).collect::<Vec<_>>();
+    let (_dir, db) = db_with_history(&[], &attestations, Some((3, 3)));
+
+    assert_eq!(
+        db.check_and_insert_attestation(
+            &pubkey(0),
+            &attestation_data_builder(5, 6),
+            DEFAULT_DOMAIN
+        ),
+        Err(NotSafe::InvalidAttestation(
+            InvalidAttestation::TargetLessThanOrEqLowerBound {
+                target_epoch: Epoch::new(6),
+                bound_epoch: Epoch::new(6),
+            }
+        ))
+    );
+    assert_eq!(
+        db.check_and_insert_attestation(
+            &pubkey(0),
+            &attestation_data_builder(4, 11),
+            DEFAULT_DOMAIN
+        ),
+        Err(NotSafe::InvalidAttestation(
+            InvalidAttestation::SourceLessThanLowerBound {
+                source_epoch: Epoch::new(4),
+                bound_epoch: Epoch::new(5),
+            }
+        ))
+    );
+    assert!(matches!(
+        db.check_and_insert_attestation(
+            &pubkey(0),
+            &attestation_data_builder(6, 8),
+            DEFAULT_DOMAIN
+        ),
+        Err(NotSafe::InvalidAttestation(InvalidAttestation::DoubleVote(
+            _
+        )))
+    ));
+    assert_eq!(
+        db.check_and_insert_attestation(
+            &pubkey(0),
+            &attestation_data_builder(10, 11),
+            DEFAULT_DOMAIN
+        ),
+        Ok(Safe::Valid)
+    );
+}
+
+#[test]
+fn prune_twice_does_not_lower_watermarks() {
+    let attestations = (0..10).map(|i| (i, i + 1)).collect::<Vec<_>>();
+    let (_dir, db) = db_with_history(&[1, 2, 3], &attestations, Some((1, 3)));
+    db.prune(100, 100).unwrap();
+
+    assert!(matches!(
+        db.check_and_insert_block_proposal(&pubkey(0), &block(1), DEFAULT_DOMAIN),
+        Err(NotSafe::InvalidBlock(
+            InvalidBlock::SlotViolatesLowerBound { .. }
+        ))
+    ));
+    assert!(matches!(
+        db.check_and_insert_attestation(
+            &pubkey(0),
+            &attestation_data_builder(5, 6),
+            DEFAULT_DOMAIN
+        ),
+        Err(NotSafe::InvalidAttestation(
+            InvalidAttestation::TargetLessThanOrEqLowerBound { .. }
+        ))
+    ));
+}
+
+/// Every attestation that is unsafe to sign with the full history must also be refused after
+/// pruning.
+#[test]
+fn pruned_attestations_no_less_safe() {
+    let history = [(0, 1), (1, 2), (1, 3), (3, 4), (4, 6), (6, 7), (6, 8)];
+    let max_epoch = 10;
+
+    for source in 0..max_epoch {
+        for target in source..max_epoch {
+            let attestation = attestation_data_builder(source, target);
+
+            let (_full_dir, full) = db_with_history(&[], &history, None);
+            let (_pruned_dir, pruned) = db_with_history(&[], &history, Some((0, 2)));
+
+            let full_result =
+                full.check_and_insert_attestation(&pubkey(0), &attestation, DEFAULT_DOMAIN);
+            let pruned_result =
+                pruned.check_and_insert_attestation(&pubkey(0), &attestation, DEFAULT_DOMAIN);
+
+            if full_result.is_err() {
+                assert!(
+                    pruned_result.is_err(),
+                    "attestation ({}, {}) refused by full history but not after pruning",
+                    source,
+                    target
+                );
+            }
+        }
+    }
+}
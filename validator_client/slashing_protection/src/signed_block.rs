@@ -12,6 +12,12 @@ pub struct SignedBlock {
 #[derive(PartialEq, Debug)]
 pub enum InvalidBlock {
     DoubleBlockProposal(SignedBlock),
+    /// The block slot is at or below the validator's watermark, below which records may have
+    /// been pruned.
+    SlotViolatesLowerBound {
+        block_slot: Slot,
+        bound_slot: Slot,
+    },
 }
 
 impl SignedBlock {
@@ -1,12 +1,19 @@
+use crate::interchange::{
+    Interchange, InterchangeAttestation, InterchangeBlock, InterchangeData, InterchangeMetadata,
+    INTERCHANGE_FORMAT_VERSION,
+};
 use crate::signed_attestation::InvalidAttestation;
 use crate::signed_block::InvalidBlock;
-use crate::{NotSafe, Safe, SignedAttestation, SignedBlock};
+use crate::{hash256_from_row, NotSafe, Safe, SignedAttestation, SignedBlock};
 use r2d2_sqlite::SqliteConnectionManager;
 use rusqlite::{params, OptionalExtension, Transaction, TransactionBehavior};
+use serde_derive::{Deserialize, Serialize};
+use ssz::Decode;
+use std::cmp::max;
 use std::fs::{File, OpenOptions};
 use std::path::Path;
 use std::time::Duration;
-use types::{AttestationData, BeaconBlockHeader, Hash256, PublicKey, SignedRoot};
+use types::{AttestationData, BeaconBlockHeader, Epoch, Hash256, PublicKey, SignedRoot, Slot};
 
 type Pool = r2d2::Pool<SqliteConnectionManager>;
 
@@ -20,6 +27,12 @@ pub const CONNECTION_TIMEOUT: Duration = Duration::from_secs(5);
 #[cfg(test)]
 pub const CONNECTION_TIMEOUT: Duration = Duration::from_millis(100);
 
+/// The version of the database schema, stored in SQLite's `user_version` field.
+///
+/// - Version 0: the `validators`, `signed_blocks` and `signed_attestations` tables.
+/// - Version 1: adds the `watermarks` table, used by pruning and interchange import.
+pub const CURRENT_SCHEMA_VERSION: u32 = 1;
+
 #[derive(Debug, Clone)]
 pub struct SlashingDatabase {
     conn_pool: Pool,
@@ -80,15 +93,76 @@ impl SlashingDatabase {
             params![],
         )?;
 
+        Self::create_watermarks_table(&conn)?;
+        Self::set_schema_version(&conn, CURRENT_SCHEMA_VERSION)?;
+
         Ok(Self { conn_pool })
     }
 
-    /// Open an existing `SlashingDatabase` from disk.
+    /// Open an existing `SlashingDatabase` from disk, migrating it to the current schema.
     pub fn open(path: &Path) -> Result<Self, NotSafe> {
         let conn_pool = Self::open_conn_pool(&path)?;
+        Self::migrate(&mut *conn_pool.get()?)?;
         Ok(Self { conn_pool })
     }
 
+    /// Migrate the database to `CURRENT_SCHEMA_VERSION`, refusing databases written by a newer
+    /// version of this crate.
+    ///
+    /// Migrations are one-way: once migrated (and pruned), the database must not be used with
+    /// older versions of this crate that are unaware of watermarks.
+    fn migrate(conn: &mut rusqlite::Connection) -> Result<(), NotSafe> {
+        let txn = conn.transaction_with_behavior(TransactionBehavior::Exclusive)?;
+        let schema_version = Self::get_schema_version(&txn)?;
+
+        if schema_version > CURRENT_SCHEMA_VERSION {
+            return Err(NotSafe::UnsupportedSchemaVersion {
+                schema_version,
+                current_version: CURRENT_SCHEMA_VERSION,
+            });
+        }
+
+        if schema_version < 1 {
+            Self::create_watermarks_table(&txn)?;
+        }
+
+        if schema_version < CURRENT_SCHEMA_VERSION {
+            Self::set_schema_version(&txn, CURRENT_SCHEMA_VERSION)?;
+        }
+
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Read the schema version of the database, which is 0 for databases predating versioning.
+    fn get_schema_version(conn: &rusqlite::Connection) -> Result<u32, NotSafe> {
+        Ok(conn.pragma_query_value(None, "user_version", |row| row.get(0))?)
+    }
+
+    /// Write the schema version of the database.
+    fn set_schema_version(conn: &rusqlite::Connection, version: u32) -> Result<(), NotSafe> {
+        conn.pragma_update(None, "user_version", &version)?;
+        Ok(())
+    }
+
+    /// Create the `watermarks` table, introduced in schema version 1.
+    ///
+    /// Development versions of this crate created the table on demand, so it may already exist
+    /// in a database at version 0.
+    fn create_watermarks_table(conn: &rusqlite::Connection) -> Result<(), NotSafe> {
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS watermarks (
+                validator_id INTEGER PRIMARY KEY,
+                block_slot INTEGER,
+                source_epoch INTEGER,
+                target_epoch INTEGER,
+                FOREIGN KEY(validator_id) REFERENCES validators(id)
+            )",
+            params![],
+        )?;
+        Ok(())
+    }
+
     /// Open a new connection pool with all of the necessary settings and tweaks.
     fn open_conn_pool(path: &Path) -> Result<Pool, NotSafe> {
         let manager = SqliteConnectionManager::file(path)
@@ -156,6 +230,16 @@ impl SlashingDatabase {
         Ok(())
     }
 
+    /// Register a validator with the slashing protection database, unless it is already
+    /// registered.
+    pub fn register_validator_if_absent(&self, validator_pk: &PublicKey) -> Result<(), NotSafe> {
+        let mut conn = self.conn_pool.get()?;
+        let txn = conn.transaction()?;
+        Self::get_or_insert_validator_id(&txn, validator_pk)?;
+        txn.commit()?;
+        Ok(())
+    }
+
     /// Get the database-internal ID for a validator.
     ///
     /// This is NOT the same as a validator index, and depends on the ordering that validators
@@ -180,6 +264,19 @@ impl SlashingDatabase {
     ) -> Result<Safe, NotSafe> {
         let validator_id = Self::get_validator_id(txn, validator_pubkey)?;
 
+        // Refuse to sign any block at or below the watermark, since records below it may have
+        // been pruned.
+        if let Some(bound_slot) = Self::get_watermark(txn, validator_id)?.block_slot {
+            if block_header.slot <= bound_slot {
+                return Err(NotSafe::InvalidBlock(
+                    InvalidBlock::SlotViolatesLowerBound {
+                        block_slot: block_header.slot,
+                        bound_slot,
+                    },
+                ));
+            }
+        }
+
         let existing_block = txn
             .prepare(
                 "SELECT slot, signing_root
@@ -228,6 +325,32 @@ impl SlashingDatabase {
 
         let validator_id = Self::get_validator_id(txn, validator_pubkey)?;
 
+        // 0. Check against the watermarks, which summarise any attestations that have been
+        //    pruned. An attestation with `source >= source_bound` cannot surround a pruned
+        //    attestation, and one with `target > target_bound` can neither double vote with nor
+        //    be surrounded by a pruned attestation.
+        let watermark = Self::get_watermark(txn, validator_id)?;
+        if let Some(bound_epoch) = watermark.source_epoch {
+            if att_source_epoch < bound_epoch {
+                return Err(NotSafe::InvalidAttestation(
+                    InvalidAttestation::SourceLessThanLowerBound {
+                        source_epoch: att_source_epoch,
+                        bound_epoch,
+                    },
+                ));
+            }
+        }
+        if let Some(bound_epoch) = watermark.target_epoch {
+            if att_target_epoch <= bound_epoch {
+                return Err(NotSafe::InvalidAttestation(
+                    InvalidAttestation::TargetLessThanOrEqLowerBound {
+                        target_epoch: att_target_epoch,
+                        bound_epoch,
+                    },
+                ));
+            }
+        }
+
         // 1. Check for a double vote. Namely, an existing attestation with the same target epoch,
         //    and a different signing root.
         let same_target_att = txn
@@ -403,6 +526,478 @@ impl SlashingDatabase {
         txn.commit()?;
         Ok(safe)
     }
+
+    /// Summarise the most recent block and attestation signed by a validator, including any
+    /// records that have since been summarised by its watermarks.
+    pub fn signing_history_summary(
+        &self,
+        validator_pubkey: &PublicKey,
+    ) -> Result<SigningHistorySummary, NotSafe> {
+        let mut conn = self.conn_pool.get()?;
+        let txn = conn.transaction()?;
+
+        let validator_id = Self::get_validator_id(&txn, validator_pubkey)?;
+        let watermark = Self::get_watermark(&txn, validator_id)?;
+
+        let block_slot: Option<Slot> = txn.query_row(
+            "SELECT MAX(slot) FROM signed_blocks WHERE validator_id = ?1",
+            params![validator_id],
+            |row| row.get(0),
+        )?;
+        let attestation: Option<(Epoch, Epoch)> = txn
+            .query_row(
+                "SELECT source_epoch, target_epoch FROM signed_attestations
+                 WHERE validator_id = ?1
+                 ORDER BY target_epoch DESC
+                 LIMIT 1",
+                params![validator_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+
+        // Records that remain in the database always lie above the watermarks.
+        let (last_source_epoch, last_target_epoch) = match attestation {
+            Some((source, target)) => (Some(source), Some(target)),
+            None => (watermark.source_epoch, watermark.target_epoch),
+        };
+
+        Ok(SigningHistorySummary {
+            last_block_slot: max(block_slot, watermark.block_slot),
+            last_source_epoch,
+            last_target_epoch,
+        })
+    }
+
+    /// Get the watermarks for a validator, which are empty if none have been set.
+    fn get_watermark(txn: &Transaction, validator_id: i64) -> Result<Watermark, NotSafe> {
+        let watermark = txn
+            .query_row(
+                "SELECT block_slot, source_epoch, target_epoch
+                 FROM watermarks
+                 WHERE validator_id = ?1",
+                params![validator_id],
+                |row| {
+                    Ok(Watermark {
+                        block_slot: row.get(0)?,
+                        source_epoch: row.get(1)?,
+                        target_epoch: row.get(2)?,
+                    })
+                },
+            )
+            .optional()?;
+
+        Ok(watermark.unwrap_or_default())
+    }
+
+    /// Raise the watermarks of a validator to at least the given values, deleting all of the
+    /// records that lie at or below them.
+    ///
+    /// The deleted attestations are folded into the source watermark, so that the watermarks
+    /// always satisfy `source_epoch >= a.source_epoch` and `target_epoch >= a.target_epoch` for
+    /// every attestation `a` that has been deleted. Similarly, `block_slot >= b.slot` for every
+    /// deleted block `b`. Any remaining records lie strictly above the watermarks.
+    ///
+    /// Watermarks never decrease.
+    fn raise_watermark(
+        txn: &Transaction,
+        validator_id: i64,
+        new: Watermark,
+    ) -> Result<(), NotSafe> {
+        let current = Self::get_watermark(txn, validator_id)?;
+
+        let block_slot = max(current.block_slot, new.block_slot);
+        if let Some(block_slot) = block_slot {
+            txn.execute(
+                "DELETE FROM signed_blocks WHERE validator_id = ?1 AND slot <= ?2",
+                params![validator_id, block_slot],
+            )?;
+        }
+
+        let target_epoch = max(current.target_epoch, new.target_epoch);
+        let mut source_epoch = max(current.source_epoch, new.source_epoch);
+        if let Some(target_epoch) = target_epoch {
+            let folded_source: Option<Epoch> = txn.query_row(
+                "SELECT MAX(source_epoch)
+                 FROM signed_attestations
+                 WHERE validator_id = ?1 AND target_epoch <= ?2",
+                params![validator_id, target_epoch],
+                |row| row.get(0),
+            )?;
+            source_epoch = max(source_epoch, folded_source);
+
+            txn.execute(
+                "DELETE FROM signed_attestations WHERE validator_id = ?1 AND target_epoch <= ?2",
+                params![validator_id, target_epoch],
+            )?;
+        }
+
+        txn.execute(
+            "INSERT OR REPLACE INTO watermarks (validator_id, block_slot, source_epoch, target_epoch)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![validator_id, block_slot, source_epoch, target_epoch],
+        )?;
+
+        Ok(())
+    }
+
+    /// Prune the signing history of every registered validator, keeping only the records within
+    /// `block_window` slots of each validator's latest block and `attestation_window` epochs of
+    /// each validator's latest attestation target. Pruned records are summarised by watermarks.
+    ///
+    /// This bounds the size of the database, and the cost of each check, at the cost of refusing
+    /// to sign any message that falls below the watermarks. It is never less safe than keeping
+    /// the full history: see `raise_watermark` and `check_attestation`.
+    ///
+    /// Each validator is pruned in its own transaction, and the connection is released between
+    /// validators, so that pruning only ever delays signing by the time taken to prune a single
+    /// validator.
+    ///
+    /// Pruning cannot be undone.
+    pub fn prune(&self, block_window: u64, attestation_window: u64) -> Result<(), NotSafe> {
+        let validator_ids = {
+            let conn = self.conn_pool.get()?;
+            let mut stmt = conn.prepare("SELECT id FROM validators")?;
+            let rows = stmt.query_map(params![], |row| row.get(0))?;
+            rows.collect::<Result<Vec<i64>, _>>()?
+        };
+
+        for validator_id in validator_ids {
+            self.prune_validator(validator_id, block_window, attestation_window)?;
+        }
+
+        Ok(())
+    }
+
+    /// Prune the signing history of a single validator, see `prune`.
+    fn prune_validator(
+        &self,
+        validator_id: i64,
+        block_window: u64,
+        attestation_window: u64,
+    ) -> Result<(), NotSafe> {
+        let mut conn = self.conn_pool.get()?;
+        let txn = conn.transaction_with_behavior(TransactionBehavior::Exclusive)?;
+
+        let block_slot: Option<Slot> = txn.query_row(
+            "SELECT MAX(slot) FROM signed_blocks
+             WHERE validator_id = ?1
+             AND slot < (SELECT MAX(slot) FROM signed_blocks WHERE validator_id = ?1) - ?2",
+            params![validator_id, block_window as i64],
+            |row| row.get(0),
+        )?;
+        let target_epoch: Option<Epoch> = txn.query_row(
+            "SELECT MAX(target_epoch) FROM signed_attestations
+             WHERE validator_id = ?1
+             AND target_epoch <
+                (SELECT MAX(target_epoch) FROM signed_attestations WHERE validator_id = ?1)
+                - ?2",
+            params![validator_id, attestation_window as i64],
+            |row| row.get(0),
+        )?;
+
+        if block_slot.is_some() || target_epoch.is_some() {
+            Self::raise_watermark(
+                &txn,
+                validator_id,
+                Watermark {
+                    block_slot,
+                    source_epoch: None,
+                    target_epoch,
+                },
+            )?;
+        }
+
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Get the database-internal ID for a validator, registering it if it is unknown.
+    fn get_or_insert_validator_id(
+        txn: &Transaction,
+        public_key: &PublicKey,
+    ) -> Result<i64, NotSafe> {
+        match Self::get_validator_id(txn, public_key) {
+            Ok(id) => Ok(id),
+            Err(NotSafe::UnregisteredValidator(_)) => {
+                txn.execute(
+                    "INSERT INTO validators (public_key) VALUES (?1)",
+                    params![&public_key.as_hex_string()],
+                )?;
+                Ok(txn.last_insert_rowid())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Import the slashing protection history in `interchange`, merging it with any existing
+    /// records.
+    ///
+    /// Validators in the interchange that are unknown to the database are registered. Where an
+    /// imported block or attestation conflicts with an existing record (i.e. same slot, or same
+    /// target epoch) the existing record is kept, which continues to prevent any further signing
+    /// at that slot or target epoch.
+    ///
+    /// The earliest imported block slot, attestation source and attestation target of each
+    /// validator are used to raise its watermarks (see `raise_watermark`).
+    ///
+    /// The import is atomic: if any error occurs no records are imported.
+    pub fn import_interchange_info(
+        &self,
+        interchange: &Interchange,
+        genesis_validators_root: Hash256,
+    ) -> Result<(), InterchangeError> {
+        let version = interchange.metadata.interchange_format_version;
+        if version != INTERCHANGE_FORMAT_VERSION {
+            return Err(InterchangeError::UnsupportedVersion(version));
+        }
+
+        if interchange.metadata.genesis_validators_root != genesis_validators_root {
+            return Err(InterchangeError::GenesisValidatorsMismatch {
+                client: genesis_validators_root,
+                interchange_file: interchange.metadata.genesis_validators_root,
+            });
+        }
+
+        let mut conn = self.conn_pool.get()?;
+        let txn = conn.transaction_with_behavior(TransactionBehavior::Exclusive)?;
+
+        for record in &interchange.data {
+            let validator_id = Self::get_or_insert_validator_id(&txn, &record.pubkey)?;
+
+            for block in &record.signed_blocks {
+                txn.execute(
+                    "INSERT OR IGNORE INTO signed_blocks (validator_id, slot, signing_root)
+                     VALUES (?1, ?2, ?3)",
+                    params![
+                        validator_id,
+                        block.slot,
+                        block.signing_root.unwrap_or_else(Hash256::zero).as_bytes()
+                    ],
+                )?;
+            }
+
+            for attestation in &record.signed_attestations {
+                if attestation.source_epoch > attestation.target_epoch {
+                    return Err(InterchangeError::SourceExceedsTarget {
+                        pubkey: record.pubkey.clone(),
+                        attestation: attestation.clone(),
+                    });
+                }
+
+                txn.execute(
+                    "INSERT OR IGNORE INTO signed_attestations
+                     (validator_id, source_epoch, target_epoch, signing_root)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![
+                        validator_id,
+                        attestation.source_epoch,
+                        attestation.target_epoch,
+                        attestation
+                            .signing_root
+                            .unwrap_or_else(Hash256::zero)
+                            .as_bytes()
+                    ],
+                )?;
+            }
+
+            // The imported history may be incomplete below its earliest records (e.g. if it was
+            // pruned), so treat them as lower bounds.
+            Self::raise_watermark(
+                &txn,
+                validator_id,
+                Watermark {
+                    block_slot: record.signed_blocks.iter().map(|b| b.slot).min(),
+                    source_epoch: record
+                        .signed_attestations
+                        .iter()
+                        .map(|a| a.source_epoch)
+                        .min(),
+                    target_epoch: record
+                        .signed_attestations
+                        .iter()
+                        .map(|a| a.target_epoch)
+                        .min(),
+                },
+            )?;
+        }
+
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Export the slashing protection history of every registered validator.
+    pub fn export_interchange_info(
+        &self,
+        genesis_validators_root: Hash256,
+    ) -> Result<Interchange, InterchangeError> {
+        let mut conn = self.conn_pool.get()?;
+        let txn = conn.transaction()?;
+
+        let validators = txn
+            .prepare("SELECT id, public_key FROM validators ORDER BY id ASC")?
+            .query_map(params![], |row| {
+                let id: i64 = row.get(0)?;
+                let public_key: String = row.get(1)?;
+                Ok((id, public_key))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut data = Vec::with_capacity(validators.len());
+
+        for (validator_id, public_key) in validators {
+            let pubkey = parse_pubkey(&public_key)?;
+            let watermark = Self::get_watermark(&txn, validator_id)?;
+
+            let mut signed_blocks: Vec<_> = txn
+                .prepare(
+                    "SELECT slot, signing_root
+                     FROM signed_blocks
+                     WHERE validator_id = ?1
+                     ORDER BY slot ASC",
+                )?
+                .query_map(params![validator_id], |row| {
+                    Ok(InterchangeBlock {
+                        slot: row.get(0)?,
+                        signing_root: signing_root_from_row(1, row)?,
+                    })
+                })?
+                .collect::<Result<_, _>>()?;
+
+            let mut signed_attestations: Vec<_> = txn
+                .prepare(
+                    "SELECT source_epoch, target_epoch, signing_root
+                     FROM signed_attestations
+                     WHERE validator_id = ?1
+                     ORDER BY target_epoch ASC",
+                )?
+                .query_map(params![validator_id], |row| {
+                    Ok(InterchangeAttestation {
+                        source_epoch: row.get(0)?,
+                        target_epoch: row.get(1)?,
+                        signing_root: signing_root_from_row(2, row)?,
+                    })
+                })?
+                .collect::<Result<_, _>>()?;
+
+            // Represent the watermarks as records without signing roots, which the importer will
+            // treat as lower bounds. All other records lie strictly above the watermarks.
+            if let Some(slot) = watermark.block_slot {
+                signed_blocks.insert(
+                    0,
+                    InterchangeBlock {
+                        slot,
+                        signing_root: None,
+                    },
+                );
+            }
+            if let Some(target_epoch) = watermark.target_epoch {
+                signed_attestations.insert(
+                    0,
+                    InterchangeAttestation {
+                        source_epoch: watermark.source_epoch.unwrap_or_else(|| Epoch::new(0)),
+                        target_epoch,
+                        signing_root: None,
+                    },
+                );
+            }
+
+            data.push(InterchangeData {
+                pubkey,
+                signed_blocks,
+                signed_attestations,
+            });
+        }
+
+        Ok(Interchange {
+            metadata: InterchangeMetadata {
+                interchange_format_version: INTERCHANGE_FORMAT_VERSION,
+                genesis_validators_root,
+            },
+            data,
+        })
+    }
+}
+
+/// The most recent messages signed by a validator.
+///
+/// A value of `None` indicates that no such message has been signed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SigningHistorySummary {
+    /// The slot of the highest block signed.
+    pub last_block_slot: Option<Slot>,
+    /// The source epoch of the attestation with the highest target epoch signed.
+    pub last_source_epoch: Option<Epoch>,
+    /// The highest target epoch of any attestation signed.
+    pub last_target_epoch: Option<Epoch>,
+}
+
+/// Lower bounds on the messages that a validator may sign, summarising pruned records.
+///
+/// A value of `None` imposes no bound.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Watermark {
+    /// Refuse to sign blocks with `slot <= block_slot`.
+    block_slot: Option<Slot>,
+    /// Refuse to sign attestations with `source_epoch < source_epoch`.
+    source_epoch: Option<Epoch>,
+    /// Refuse to sign attestations with `target_epoch <= target_epoch`.
+    target_epoch: Option<Epoch>,
+}
+
+/// Read a signing root, mapping the zero hash (used for imported records that lack a signing
+/// root) to `None`.
+fn signing_root_from_row(column: usize, row: &rusqlite::Row) -> rusqlite::Result<Option<Hash256>> {
+    let signing_root = hash256_from_row(column, row)?;
+    if signing_root.is_zero() {
+        Ok(None)
+    } else {
+        Ok(Some(signing_root))
+    }
+}
+
+/// Parse a public key stored in the `validators` table as a 0x-prefixed hex string.
+fn parse_pubkey(hex_str: &str) -> Result<PublicKey, InterchangeError> {
+    if !hex_str.starts_with("0x") {
+        return Err(InterchangeError::InvalidPubkey(hex_str.to_string()));
+    }
+    let bytes = hex::decode(&hex_str[2..])
+        .map_err(|e| InterchangeError::InvalidPubkey(format!("{:?}", e)))?;
+    PublicKey::from_ssz_bytes(&bytes)
+        .map_err(|e| InterchangeError::InvalidPubkey(format!("{:?}", e)))
+}
+
+/// Reasons why importing or exporting interchange data may fail.
+#[derive(Debug)]
+pub enum InterchangeError {
+    UnsupportedVersion(u64),
+    GenesisValidatorsMismatch {
+        interchange_file: Hash256,
+        client: Hash256,
+    },
+    SourceExceedsTarget {
+        pubkey: PublicKey,
+        attestation: InterchangeAttestation,
+    },
+    InvalidPubkey(String),
+    NotSafe(NotSafe),
+}
+
+impl From<NotSafe> for InterchangeError {
+    fn from(error: NotSafe) -> Self {
+        InterchangeError::NotSafe(error)
+    }
+}
+
+impl From<rusqlite::Error> for InterchangeError {
+    fn from(error: rusqlite::Error) -> Self {
+        InterchangeError::NotSafe(error.into())
+    }
+}
+
+impl From<r2d2::Error> for InterchangeError {
+    fn from(error: r2d2::Error) -> Self {
+        InterchangeError::NotSafe(error.into())
+    }
 }
 
 #[cfg(test)]
@@ -418,15 +1013,122 @@ mod tests {
         assert!(SlashingDatabase::open(&file).is_err());
     }
 
-    // Due to the exclusive locking, trying to use an already open database should error.
+    // Due to the exclusive locking, trying to open an already open database should error, as
+    // opening reads the schema version.
     #[test]
     fn double_open_error() {
         let dir = tempdir().unwrap();
         let file = dir.path().join("db.sqlite");
         let _db1 = SlashingDatabase::create(&file).unwrap();
 
-        let db2 = SlashingDatabase::open(&file).unwrap();
-        db2.register_validator(&pubkey(0)).unwrap_err();
+        SlashingDatabase::open(&file).unwrap_err();
+    }
+
+    #[test]
+    fn schema_version_written_on_create() {
+        let dir = tempdir().unwrap();
+        let db = SlashingDatabase::create(&dir.path().join("db.sqlite")).unwrap();
+        let conn = db.conn_pool.get().unwrap();
+        assert_eq!(
+            SlashingDatabase::get_schema_version(&conn).unwrap(),
+            CURRENT_SCHEMA_VERSION
+        );
+    }
+
+    // Databases created before schema versioning lack the watermarks table, which must be
+    // created when they are opened.
+    #[test]
+    fn migrate_from_version_0() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("db.sqlite");
+
+        let db = SlashingDatabase::create(&file).unwrap();
+        db.register_validator(&pubkey(0)).unwrap();
+        {
+            let conn = db.conn_pool.get().unwrap();
+            conn.execute("DROP TABLE watermarks", params![]).unwrap();
+            SlashingDatabase::set_schema_version(&conn, 0).unwrap();
+        }
+        drop(db);
+
+        let db = SlashingDatabase::open(&file).unwrap();
+        {
+            let conn = db.conn_pool.get().unwrap();
+            assert_eq!(
+                SlashingDatabase::get_schema_version(&conn).unwrap(),
+                CURRENT_SCHEMA_VERSION
+            );
+        }
+        // Pruning and summarising the history both require the watermarks table.
+        db.prune(0, 0).unwrap();
+        assert_eq!(
+            db.signing_history_summary(&pubkey(0)).unwrap(),
+            SigningHistorySummary::default()
+        );
+    }
+
+    #[test]
+    fn newer_schema_version_refused() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("db.sqlite");
+
+        let db = SlashingDatabase::create(&file).unwrap();
+        {
+            let conn = db.conn_pool.get().unwrap();
+            SlashingDatabase::set_schema_version(&conn, CURRENT_SCHEMA_VERSION + 1).unwrap();
+        }
+        drop(db);
+
+        assert_eq!(
+            SlashingDatabase::open(&file).err(),
+            Some(NotSafe::UnsupportedSchemaVersion {
+                schema_version: CURRENT_SCHEMA_VERSION + 1,
+                current_version: CURRENT_SCHEMA_VERSION,
+            })
+        );
+    }
+
+    #[test]
+    fn signing_history_summary() {
+        use crate::attestation_tests::attestation_data_builder;
+        use crate::block_tests::block;
+        use crate::test_utils::DEFAULT_DOMAIN;
+
+        let dir = tempdir().unwrap();
+        let db = SlashingDatabase::create(&dir.path().join("db.sqlite")).unwrap();
+
+        assert!(db.signing_history_summary(&pubkey(0)).is_err());
+
+        db.register_validator_if_absent(&pubkey(0)).unwrap();
+        db.register_validator_if_absent(&pubkey(0)).unwrap();
+        assert_eq!(
+            db.signing_history_summary(&pubkey(0)).unwrap(),
+            SigningHistorySummary::default()
+        );
+
+        for slot in &[1, 5, 3] {
+            db.check_and_insert_block_proposal(&pubkey(0), &block(*slot), DEFAULT_DOMAIN)
+                .unwrap();
+        }
+        for (source, target) in &[(0, 1), (1, 2), (2, 4)] {
+            db.check_and_insert_attestation(
+                &pubkey(0),
+                &attestation_data_builder(*source, *target),
+                DEFAULT_DOMAIN,
+            )
+            .unwrap();
+        }
+
+        let expected = SigningHistorySummary {
+            last_block_slot: Some(Slot::new(5)),
+            last_source_epoch: Some(Epoch::new(2)),
+            last_target_epoch: Some(Epoch::new(4)),
+        };
+        assert_eq!(db.signing_history_summary(&pubkey(0)).unwrap(), expected);
+
+        // Pruning everything below the latest messages must not change the summary.
+        db.prune(0, 0).unwrap();
+        assert_eq!(db.signing_history_summary(&pubkey(0)).unwrap(), expected);
     }
 
     // Attempting to create the same database twice should error.
@@ -1,10 +1,11 @@
 use crate::{
+    beacon_node_fallback::BeaconNodeFallback,
     duties_service::{DutiesService, DutyAndProof},
     validator_store::ValidatorStore,
 };
 use environment::RuntimeContext;
 use futures::StreamExt;
-use remote_beacon_node::{PublishStatus, RemoteBeaconNode};
+use remote_beacon_node::PublishStatus;
 use slog::{crit, debug, error, info, trace};
 use slot_clock::SlotClock;
 use std::collections::HashMap;
@@ -18,7 +19,7 @@ pub struct AttestationServiceBuilder<T, E: EthSpec> {
     duties_service: Option<DutiesService<T, E>>,
     validator_store: Option<ValidatorStore<T, E>>,
     slot_clock: Option<T>,
-    beacon_node: Option<RemoteBeaconNode<E>>,
+    beacon_nodes: Option<Arc<BeaconNodeFallback<T, E>>>,
     context: Option<RuntimeContext<E>>,
 }
 
@@ -28,7 +29,7 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationServiceBuilder<T, E> {
             duties_service: None,
             validator_store: None,
             slot_clock: None,
-            beacon_node: None,
+            beacon_nodes: None,
             context: None,
         }
     }
@@ -48,8 +49,8 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationServiceBuilder<T, E> {
         self
     }
 
-    pub fn beacon_node(mut self, beacon_node: RemoteBeaconNode<E>) -> Self {
-        self.beacon_node = Some(beacon_node);
+    pub fn beacon_nodes(mut self, beacon_nodes: Arc<BeaconNodeFallback<T, E>>) -> Self {
+        self.beacon_nodes = Some(beacon_nodes);
         self
     }
 
@@ -70,9 +71,9 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationServiceBuilder<T, E> {
                 slot_clock: self
                     .slot_clock
                     .ok_or_else(|| "Cannot build AttestationService without slot_clock")?,
-                beacon_node: self
-                    .beacon_node
-                    .ok_or_else(|| "Cannot build AttestationService without beacon_node")?,
+                beacon_nodes: self
+                    .beacon_nodes
+                    .ok_or_else(|| "Cannot build AttestationService without beacon_nodes")?,
                 context: self
                     .context
                     .ok_or_else(|| "Cannot build AttestationService without runtime_context")?,
@@ -86,7 +87,7 @@ pub struct Inner<T, E: EthSpec> {
     duties_service: DutiesService<T, E>,
     validator_store: ValidatorStore<T, E>,
     slot_clock: T,
-    beacon_node: RemoteBeaconNode<E>,
+    beacon_nodes: Arc<BeaconNodeFallback<T, E>>,
     context: RuntimeContext<E>,
 }
 
@@ -216,6 +217,17 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
                 );
             });
 
+        // Prune the slashing protection database once per epoch, during the last slot so that
+        // it does not coincide with the first attestations of the next epoch.
+        if (slot + 1) % E::slots_per_epoch() == 0 {
+            let validator_store = self.validator_store.clone();
+            self.inner
+                .context
+                .executor
+                .runtime_handle()
+                .spawn_blocking(move || validator_store.prune_slashing_protection_db());
+        }
+
         Ok(())
     }
 
@@ -319,77 +331,87 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
             .epoch(E::slots_per_epoch());
 
         let attestation = self
-            .beacon_node
-            .http
-            .validator()
-            .produce_attestation(slot, committee_index)
+            .beacon_nodes
+            .first_success(|beacon_node| async move {
+                beacon_node
+                    .http
+                    .validator()
+                    .produce_attestation(slot, committee_index)
+                    .await
+                    .map_err(|e| format!("{:?}", e))
+            })
             .await
-            .map_err(|e| format!("Failed to produce attestation: {:?}", e))?;
+            .map_err(|e| format!("Failed to produce attestation: {}", e))?;
 
         // For each validator in `validator_duties`, clone the `attestation` and add
         // their signature.
         //
         // If any validator is unable to sign, they are simply skipped.
-        let signed_attestations = validator_duties
-            .iter()
-            .filter_map(|duty| {
-                // Ensure that all required fields are present in the validator duty.
-                let (
-                    duty_slot,
-                    duty_committee_index,
-                    validator_committee_position,
-                    _,
-                    committee_count_at_slot,
-                ) = if let Some(tuple) = duty.attestation_duties() {
-                    tuple
-                } else {
-                    crit!(
-                        log,
-                        "Missing validator duties when signing";
-                        "duties" => format!("{:?}", duty)
-                    );
-                    return None;
-                };
+        let mut signed_attestations = Vec::with_capacity(validator_duties.len());
+        for duty in validator_duties {
+            // Ensure that all required fields are present in the validator duty.
+            let (
+                duty_slot,
+                duty_committee_index,
+                validator_committee_position,
+                _,
+                committee_count_at_slot,
+            ) = if let Some(tuple) = duty.attestation_duties() {
+                tuple
+            } else {
+                crit!(
+                    log,
+                    "Missing validator duties when signing";
+                    "duties" => format!("{:?}", duty)
+                );
+                continue;
+            };
 
-                // Ensure that the attestation matches the duties.
-                if duty_slot != attestation.data.slot
-                    || duty_committee_index != attestation.data.index
-                {
-                    crit!(
+            // Ensure that the attestation matches the duties.
+            if duty_slot != attestation.data.slot || duty_committee_index != attestation.data.index
+            {
+                crit!(
+                    log,
+                    "Inconsistent validator duties during signing";
+                    "validator" => format!("{:?}", duty.validator_pubkey()),
+                    "duty_slot" => duty_slot,
+                    "attestation_slot" => attestation.data.slot,
+                    "duty_index" => duty_committee_index,
+                    "attestation_index" => attestation.data.index,
+                );
+                continue;
+            }
+
+            let mut attestation = attestation.clone();
+            let subnet_id = match SubnetId::compute_subnet_for_attestation_data::<E>(
+                &attestation.data,
+                committee_count_at_slot,
+                &self.context.eth2_config().spec,
+            ) {
+                Ok(subnet_id) => subnet_id,
+                Err(e) => {
+                    error!(
                         log,
-                        "Inconsistent validator duties during signing";
-                        "validator" => format!("{:?}", duty.validator_pubkey()),
-                        "duty_slot" => duty_slot,
-                        "attestation_slot" => attestation.data.slot,
-                        "duty_index" => duty_committee_index,
-                        "attestation_index" => attestation.data.index,
+                        "Failed to compute subnet id to publish attestation: {:?}", e
                     );
-                    return None;
+                    continue;
                 }
+            };
 
-                let mut attestation = attestation.clone();
-                let subnet_id = SubnetId::compute_subnet_for_attestation_data::<E>(
-                    &attestation.data,
-                    committee_count_at_slot,
-                    &self.context.eth2_config().spec,
+            if self
+                .validator_store
+                .sign_attestation(
+                    duty.validator_pubkey(),
+                    validator_committee_position,
+                    &mut attestation,
+                    current_epoch,
                 )
-                .map_err(|e| {
-                    error!(
-                        log,
-                        "Failed to compute subnet id to publish attestation: {:?}", e
-                    )
-                })
-                .ok()?;
-                self.validator_store
-                    .sign_attestation(
-                        duty.validator_pubkey(),
-                        validator_committee_position,
-                        &mut attestation,
-                        current_epoch,
-                    )
-                    .map(|_| (attestation, subnet_id))
-            })
-            .collect::<Vec<_>>();
+                .await
+                .is_some()
+            {
+                signed_attestations.push((attestation, subnet_id));
+            }
+        }
 
         // If there are any signed attestations, publish them to the BN. Otherwise,
         // just return early.
@@ -397,12 +419,20 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
             let num_attestations = signed_attestations.len();
             let beacon_block_root = attestation.0.data.beacon_block_root;
 
-            self.beacon_node
-                .http
-                .validator()
-                .publish_attestations(signed_attestations)
+            self.beacon_nodes
+                .broadcast(|beacon_node| {
+                    let signed_attestations = signed_attestations.clone();
+                    async move {
+                        beacon_node
+                            .http
+                            .validator()
+                            .publish_attestations(signed_attestations)
+                            .await
+                            .map_err(|e| format!("{:?}", e))
+                    }
+                })
                 .await
-                .map_err(|e| format!("Failed to publish attestation: {:?}", e))
+                .map_err(|e| format!("Failed to publish attestation: {}", e))
                 .map(move |publish_status| match publish_status {
                     PublishStatus::Valid => info!(
                         log,
@@ -459,52 +489,65 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
         let log = self.context.log();
 
         let aggregated_attestation = self
-            .beacon_node
-            .http
-            .validator()
-            .produce_aggregate_attestation(&attestation.data)
+            .beacon_nodes
+            .first_success(|beacon_node| {
+                let attestation_data = attestation.data.clone();
+                async move {
+                    beacon_node
+                        .http
+                        .validator()
+                        .produce_aggregate_attestation(&attestation_data)
+                        .await
+                        .map_err(|e| format!("{:?}", e))
+                }
+            })
             .await
-            .map_err(|e| format!("Failed to produce an aggregate attestation: {:?}", e))?;
+            .map_err(|e| format!("Failed to produce an aggregate attestation: {}", e))?;
 
         // For each validator, clone the `aggregated_attestation` and convert it into
         // a `SignedAggregateAndProof`
-        let signed_aggregate_and_proofs = validator_duties
-            .iter()
-            .filter_map(|duty_and_proof| {
-                // Do not produce a signed aggregator for validators that are not
-                // subscribed aggregators.
-                let selection_proof = duty_and_proof.selection_proof.as_ref()?.clone();
-
-                let (duty_slot, duty_committee_index, _, validator_index, _) =
-                    duty_and_proof.attestation_duties().or_else(|| {
+        let mut signed_aggregate_and_proofs = Vec::with_capacity(validator_duties.len());
+        for duty_and_proof in validator_duties {
+            // Do not produce a signed aggregator for validators that are not
+            // subscribed aggregators.
+            let selection_proof = match duty_and_proof.selection_proof.as_ref() {
+                Some(selection_proof) => selection_proof.clone(),
+                None => continue,
+            };
+
+            let (duty_slot, duty_committee_index, _, validator_index, _) =
+                match duty_and_proof.attestation_duties() {
+                    Some(duties) => duties,
+                    None => {
                         crit!(log, "Missing duties when signing aggregate");
-                        None
-                    })?;
+                        continue;
+                    }
+                };
 
-                let pubkey = &duty_and_proof.duty.validator_pubkey;
-                let slot = attestation.data.slot;
-                let committee_index = attestation.data.index;
+            let pubkey = &duty_and_proof.duty.validator_pubkey;
+            let slot = attestation.data.slot;
+            let committee_index = attestation.data.index;
 
-                if duty_slot != slot || duty_committee_index != committee_index {
-                    crit!(log, "Inconsistent validator duties during signing");
-                    return None;
-                }
+            if duty_slot != slot || duty_committee_index != committee_index {
+                crit!(log, "Inconsistent validator duties during signing");
+                continue;
+            }
 
-                if let Some(signed_aggregate_and_proof) =
-                    self.validator_store.produce_signed_aggregate_and_proof(
-                        pubkey,
-                        validator_index,
-                        aggregated_attestation.clone(),
-                        selection_proof,
-                    )
-                {
-                    Some(signed_aggregate_and_proof)
-                } else {
-                    crit!(log, "Failed to sign attestation");
-                    None
-                }
-            })
-            .collect::<Vec<_>>();
+            if let Some(signed_aggregate_and_proof) = self
+                .validator_store
+                .produce_signed_aggregate_and_proof(
+                    pubkey,
+                    validator_index,
+                    aggregated_attestation.clone(),
+                    selection_proof,
+                )
+                .await
+            {
+                signed_aggregate_and_proofs.push(signed_aggregate_and_proof);
+            } else {
+                crit!(log, "Failed to sign attestation");
+            }
+        }
 
         // If there any signed aggregates and proofs were produced, publish them to the
         // BN.
@@ -512,12 +555,20 @@ impl<T: SlotClock + 'static, E: EthSpec> AttestationService<T, E> {
             let attestation = first.message.aggregate;
 
             let publish_status = self
-                .beacon_node
-                .http
-                .validator()
-                .publish_aggregate_and_proof(signed_aggregate_and_proofs)
+                .beacon_nodes
+                .broadcast(|beacon_node| {
+                    let signed_aggregate_and_proofs = signed_aggregate_and_proofs.clone();
+                    async move {
+                        beacon_node
+                            .http
+                            .validator()
+                            .publish_aggregate_and_proof(signed_aggregate_and_proofs)
+                            .await
+                            .map_err(|e| format!("{:?}", e))
+                    }
+                })
                 .await
-                .map_err(|e| format!("Failed to publish aggregate and proofs: {:?}", e))?;
+                .map_err(|e| format!("Failed to publish aggregate and proofs: {}", e))?;
             match publish_status {
                 PublishStatus::Valid => info!(
                     log,
@@ -0,0 +1,457 @@
+//! Allows the validator client to use several beacon nodes, failing over to the next healthy node
+//! whenever a request to the current one fails.
+
+use crate::is_synced::is_synced;
+use environment::RuntimeContext;
+use futures::future::join_all;
+use futures::StreamExt;
+use parking_lot::RwLock;
+use remote_beacon_node::RemoteBeaconNode;
+use slog::{debug, info, warn, Logger};
+use slot_clock::SlotClock;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::time::{interval_at, Instant};
+use types::EthSpec;
+
+/// A beacon node which the validator client may use, along with the outcome of its most recent
+/// health check.
+pub struct CandidateBeaconNode<E: EthSpec> {
+    /// The address of the beacon node, used for logging.
+    pub endpoint: String,
+    pub beacon_node: RemoteBeaconNode<E>,
+    synced: RwLock<bool>,
+}
+
+impl<E: EthSpec> CandidateBeaconNode<E> {
+    /// Instantiate a candidate which is considered unsynced until its first health check.
+    pub fn new(endpoint: String, beacon_node: RemoteBeaconNode<E>) -> Self {
+        Self {
+            endpoint,
+            beacon_node,
+            synced: RwLock::new(false),
+        }
+    }
+
+    /// Returns `true` if the beacon node was reachable and synced at the last health check.
+    pub fn is_synced(&self) -> bool {
+        *self.synced.read()
+    }
+
+    /// Check the sync status of the beacon node, returning the previous and the new status.
+    async fn refresh_status<T: SlotClock>(&self, slot_clock: &T) -> (bool, bool) {
+        let synced = is_synced(&self.beacon_node, slot_clock, None).await;
+        let previous = std::mem::replace(&mut *self.synced.write(), synced);
+        (previous, synced)
+    }
+}
+
+/// A list of beacon nodes, in order of preference.
+///
+/// Requests for information (e.g., duties, blocks and attestations to sign) are sent to the first
+/// synced node which responds successfully. Signed messages are published to all nodes, so that a
+/// single unhealthy node cannot prevent them from reaching the network.
+pub struct BeaconNodeFallback<T, E: EthSpec> {
+    candidates: Vec<CandidateBeaconNode<E>>,
+    slot_clock: T,
+    /// If `true`, unsynced beacon nodes will be used once all synced nodes have failed.
+    allow_unsynced: bool,
+    log: Logger,
+}
+
+impl<T: SlotClock + 'static, E: EthSpec> BeaconNodeFallback<T, E> {
+    pub fn new(
+        candidates: Vec<CandidateBeaconNode<E>>,
+        slot_clock: T,
+        allow_unsynced: bool,
+        log: Logger,
+    ) -> Self {
+        Self {
+            candidates,
+            slot_clock,
+            allow_unsynced,
+            log,
+        }
+    }
+
+    /// The total number of beacon nodes, regardless of their status.
+    pub fn num_total(&self) -> usize {
+        self.candidates.len()
+    }
+
+    /// The number of beacon nodes which were synced at the last health check.
+    pub fn num_synced(&self) -> usize {
+        self.candidates.iter().filter(|c| c.is_synced()).count()
+    }
+
+    /// Check the health of all beacon nodes concurrently, logging any changes in status.
+    pub async fn update_all_candidates(&self) {
+        let slot_clock = &self.slot_clock;
+        let statuses = join_all(
+            self.candidates
+                .iter()
+                .map(|candidate| candidate.refresh_status(slot_clock)),
+        )
+        .await;
+
+        for (candidate, (previous, synced)) in self.candidates.iter().zip(statuses) {
+            match (previous, synced) {
+                (false, true) => info!(
+                    self.log,
+                    "Beacon node is available";
+                    "endpoint" => &candidate.endpoint,
+                ),
+                (true, false) => warn!(
+                    self.log,
+                    "Beacon node is unavailable";
+                    "msg" => "offline or not synced",
+                    "endpoint" => &candidate.endpoint,
+                ),
+                _ => (),
+            }
+        }
+    }
+
+    /// Starts the service that checks the health of each beacon node at the start of every slot.
+    pub fn start_update_service(
+        self: Arc<Self>,
+        context: &RuntimeContext<E>,
+    ) -> Result<(), String> {
+        let duration_to_next_slot = self
+            .slot_clock
+            .duration_to_next_slot()
+            .ok_or_else(|| "Unable to determine duration to next slot".to_string())?;
+
+        let mut interval = interval_at(
+            Instant::now() + duration_to_next_slot,
+            self.slot_clock.slot_duration(),
+        );
+
+        let interval_fut = async move {
+            while interval.next().await.is_some() {
+                self.update_all_candidates().await;
+            }
+        };
+
+        context.executor.spawn(interval_fut, "beacon_node_fallback");
+
+        Ok(())
+    }
+
+    /// Run `func` against each synced beacon node in turn, returning the first successful result.
+    ///
+    /// If no synced node succeeds and unsynced nodes are allowed, they are then tried in the same
+    /// way. Returns an error describing every failure if no node succeeds.
+    pub async fn first_success<F, R, O>(&self, func: F) -> Result<O, String>
+    where
+        F: Fn(RemoteBeaconNode<E>) -> R,
+        R: Future<Output = Result<O, String>>,
+    {
+        let (synced, unsynced): (Vec<_>, Vec<_>) =
+            self.candidates.iter().partition(|c| c.is_synced());
+
+        let mut to_try = synced;
+        if self.allow_unsynced {
+            to_try.extend(unsynced);
+        }
+
+        if to_try.is_empty() {
+            return Err("No synced beacon nodes".to_string());
+        }
+
+        let mut errors = vec![];
+        for candidate in to_try {
+            match func(candidate.beacon_node.clone()).await {
+                Ok(value) => return Ok(value),
+                Err(e) => {
+                    debug!(
+                        self.log,
+                        "Request to beacon node failed";
+                        "error" => &e,
+                        "endpoint" => &candidate.endpoint,
+                    );
+                    errors.push((candidate.endpoint.as_str(), e));
+                }
+            }
+        }
+
+        Err(format_errors(&errors))
+    }
+
+    /// Run `func` against all beacon nodes concurrently, regardless of their sync status.
+    ///
+    /// Returns the result from the first node (in order of preference) that succeeded, or an error
+    /// describing every failure if no node succeeds.
+    pub async fn broadcast<F, R, O>(&self, func: F) -> Result<O, String>
+    where
+        F: Fn(RemoteBeaconNode<E>) -> R,
+        R: Future<Output = Result<O, String>>,
+    {
+        let results = join_all(
+            self.candidates
+                .iter()
+                .map(|candidate| func(candidate.beacon_node.clone())),
+        )
+        .await;
+
+        let mut first_success = None;
+        let mut errors = vec![];
+        for (candidate, result) in self.candidates.iter().zip(results) {
+            match result {
+                Ok(value) => {
+                    if first_success.is_none() {
+                        first_success = Some(value);
+                    }
+                }
+                Err(e) => errors.push((candidate.endpoint.as_str(), e)),
+            }
+        }
+
+        match first_success {
+            Some(value) => {
+                if !errors.is_empty() {
+                    warn!(
+                        self.log,
+                        "Some beacon nodes failed to receive message";
+                        "errors" => format_errors(&errors),
+                    );
+                }
+                Ok(value)
+            }
+            None => Err(format_errors(&errors)),
+        }
+    }
+}
+
+/// Combine the errors from several beacon nodes into a single message.
+fn format_errors(errors: &[(&str, String)]) -> String {
+    let errors = errors
+        .iter()
+        .map(|(endpoint, e)| format!("{} => {}", endpoint, e))
+        .collect::<Vec<_>>()
+        .join(", ");
+    format!("All beacon nodes failed: {}", errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Request, Response, Server, StatusCode};
+    use rest_types::{SyncingResponse, SyncingStatus};
+    use slot_clock::TestingSlotClock;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::time::Duration;
+    use types::{MinimalEthSpec, Slot};
+
+    type E = MinimalEthSpec;
+
+    /// The current slot of the slot clock used in these tests.
+    const CURRENT_SLOT: u64 = 100;
+
+    /// A beacon node HTTP server which can be taken offline or made to fall out of sync.
+    struct MockBeaconNode {
+        name: String,
+        online: AtomicBool,
+        synced: AtomicBool,
+        /// The number of `/node/version` requests served.
+        version_requests: AtomicUsize,
+    }
+
+    impl MockBeaconNode {
+        fn respond(&self, req: Request<Body>) -> Response<Body> {
+            if !self.online.load(Ordering::Relaxed) {
+                return Response::builder()
+                    .status(StatusCode::SERVICE_UNAVAILABLE)
+                    .body(Body::empty())
+                    .unwrap();
+            }
+
+            let body = match req.uri().path() {
+                "/node/syncing" => {
+                    let current_slot = if self.synced.load(Ordering::Relaxed) {
+                        CURRENT_SLOT
+                    } else {
+                        0
+                    };
+                    serde_json::to_string(&SyncingResponse {
+                        is_syncing: current_slot != CURRENT_SLOT,
+                        sync_status: SyncingStatus {
+                            starting_slot: Slot::new(0),
+                            current_slot: Slot::new(current_slot),
+                            highest_slot: Slot::new(CURRENT_SLOT),
+                        },
+                    })
+                    .unwrap()
+                }
+                "/node/version" => {
+                    self.version_requests.fetch_add(1, Ordering::Relaxed);
+                    serde_json::to_string(&self.name).unwrap()
+                }
+                _ => {
+                    return Response::builder()
+                        .status(StatusCode::NOT_FOUND)
+                        .body(Body::empty())
+                        .unwrap()
+                }
+            };
+
+            Response::new(Body::from(body))
+        }
+
+        fn set_online(&self, online: bool) {
+            self.online.store(online, Ordering::Relaxed);
+        }
+
+        fn set_synced(&self, synced: bool) {
+            self.synced.store(synced, Ordering::Relaxed);
+        }
+
+        fn version_requests(&self) -> usize {
+            self.version_requests.load(Ordering::Relaxed)
+        }
+    }
+
+    /// Start a mock beacon node, returning it along with a candidate which connects to it.
+    fn mock_beacon_node(name: &str) -> (Arc<MockBeaconNode>, CandidateBeaconNode<E>) {
+        let node = Arc::new(MockBeaconNode {
+            name: name.to_string(),
+            online: AtomicBool::new(true),
+            synced: AtomicBool::new(true),
+            version_requests: AtomicUsize::new(0),
+        });
+
+        let server_node = node.clone();
+        let make_service = make_service_fn(move |_| {
+            let node = server_node.clone();
+            async move {
+                Ok::<_, hyper::Error>(service_fn(move |req| {
+                    let response = node.respond(req);
+                    async move { Ok::<_, hyper::Error>(response) }
+                }))
+            }
+        });
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+        let endpoint = format!("http://{}/", server.local_addr());
+        tokio::spawn(server);
+
+        let beacon_node =
+            RemoteBeaconNode::new_with_timeout(endpoint.clone(), Duration::from_secs(1))
+                .expect("should create beacon node client");
+
+        (node, CandidateBeaconNode::new(endpoint, beacon_node))
+    }
+
+    /// Start `n` mock beacon nodes and a fallback which uses them in order.
+    fn fallback(
+        n: usize,
+        allow_unsynced: bool,
+    ) -> (
+        Vec<Arc<MockBeaconNode>>,
+        BeaconNodeFallback<TestingSlotClock, E>,
+    ) {
+        let (nodes, candidates) = (0..n)
+            .map(|i| mock_beacon_node(&format!("node-{}", i)))
+            .unzip();
+
+        let slot_clock =
+            TestingSlotClock::new(Slot::new(0), Duration::from_secs(0), Duration::from_secs(1));
+        slot_clock.set_slot(CURRENT_SLOT);
+        let log = Logger::root(slog::Discard, slog::o!());
+
+        (
+            nodes,
+            BeaconNodeFallback::new(candidates, slot_clock, allow_unsynced, log),
+        )
+    }
+
+    /// Returns the name of the first beacon node which serves its version.
+    async fn first_version(
+        fallback: &BeaconNodeFallback<TestingSlotClock, E>,
+    ) -> Result<String, String> {
+        fallback
+            .first_success(|beacon_node| async move {
+                beacon_node
+                    .http
+                    .node()
+                    .get_version()
+                    .await
+                    .map_err(|e| format!("{:?}", e))
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn nodes_tried_in_order() {
+        let (nodes, fallback) = fallback(3, false);
+
+        fallback.update_all_candidates().await;
+        assert_eq!(fallback.num_synced(), 3);
+
+        assert_eq!(first_version(&fallback).await, Ok("node-0".to_string()));
+        assert_eq!(nodes[1].version_requests(), 0);
+
+        // A node which fails between health checks is skipped in favour of the next node.
+        nodes[0].set_online(false);
+        assert_eq!(first_version(&fallback).await, Ok("node-1".to_string()));
+        assert_eq!(nodes[2].version_requests(), 0);
+
+        nodes[1].set_online(false);
+        assert_eq!(first_version(&fallback).await, Ok("node-2".to_string()));
+
+        nodes[2].set_online(false);
+        assert!(first_version(&fallback).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn offline_and_unsynced_nodes_skipped() {
+        let (nodes, fallback) = fallback(3, false);
+        nodes[0].set_online(false);
+        nodes[1].set_synced(false);
+
+        fallback.update_all_candidates().await;
+        assert_eq!(fallback.num_synced(), 1);
+
+        // Only the synced node is sent requests.
+        assert_eq!(first_version(&fallback).await, Ok("node-2".to_string()));
+        assert_eq!(nodes[1].version_requests(), 0);
+
+        // Unsynced nodes are never used unless allowed.
+        nodes[2].set_online(false);
+        assert!(first_version(&fallback).await.is_err());
+        assert_eq!(nodes[1].version_requests(), 0);
+    }
+
+    #[tokio::test]
+    async fn unsynced_nodes_used_last_if_allowed() {
+        let (nodes, fallback) = fallback(2, true);
+        nodes[0].set_synced(false);
+
+        fallback.update_all_candidates().await;
+        assert_eq!(fallback.num_synced(), 1);
+
+        // The synced node is preferred, even though it was configured later.
+        assert_eq!(first_version(&fallback).await, Ok("node-1".to_string()));
+        assert_eq!(nodes[0].version_requests(), 0);
+
+        nodes[1].set_online(false);
+        assert_eq!(first_version(&fallback).await, Ok("node-0".to_string()));
+    }
+
+    #[tokio::test]
+    async fn recovered_node_used_again() {
+        let (nodes, fallback) = fallback(2, false);
+        nodes[0].set_online(false);
+
+        fallback.update_all_candidates().await;
+        assert_eq!(fallback.num_synced(), 1);
+        assert_eq!(first_version(&fallback).await, Ok("node-1".to_string()));
+
+        // Once the preferred node recovers, the next health check makes it usable again.
+        nodes[0].set_online(true);
+        assert_eq!(first_version(&fallback).await, Ok("node-1".to_string()));
+        fallback.update_all_candidates().await;
+        assert_eq!(fallback.num_synced(), 2);
+        assert_eq!(first_version(&fallback).await, Ok("node-0".to_string()));
+    }
+}
@@ -9,13 +9,29 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
                 validator (e.g., proposing blocks and attestations).",
         )
         .arg(
-            Arg::with_name("server")
-                .long("server")
-                .value_name("NETWORK_ADDRESS")
-                .help("Address to connect to BeaconNode.")
+            Arg::with_name("beacon-nodes")
+                .long("beacon-nodes")
+                .alias("server")
+                .value_name("NETWORK_ADDRESSES")
+                .help(
+                    "Comma-separated addresses of one or more beacon nodes, in order of \
+                    preference. Requests fail over to the next synced node when one is \
+                    unavailable, and signed blocks and attestations are published to all nodes.",
+                )
                 .default_value(&DEFAULT_HTTP_SERVER)
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("beacon-node-api-token-file")
+                .long("beacon-node-api-token-file")
+                .value_name("PATH")
+                .help(
+                    "A file containing the API token of the beacon nodes (e.g., a copy of \
+                    api-token.txt from a beacon node datadir). Required when the beacon nodes \
+                    are started with --http-require-token.",
+                )
+                .takes_value(true),
+        )
         .arg(Arg::with_name("auto-register").long("auto-register").help(
             "If present, the validator client will register any new signing keys with \
                        the slashing protection database so that they may be used. WARNING: \
@@ -28,20 +44,103 @@ pub fn cli_app<'a, 'b>() -> App<'a, 'b> {
             Arg::with_name("allow-unsynced")
                 .long("allow-unsynced")
                 .help(
-                    "If present, the validator client will still poll for duties if the beacon
-                      node is not synced.",
+                    "If present, the validator client will still poll for duties if none of the
+                      beacon nodes are synced.",
+                ),
+        )
+        .arg(
+            Arg::with_name("minimal-slashing-protection")
+                .long("minimal-slashing-protection")
+                .help(
+                    "If present, the slashing protection database will be pruned every epoch, \
+                    keeping only recently signed messages and a low watermark for each \
+                    validator. Messages below the watermark will never be signed. Pruning is \
+                    irreversible and the database must not be used with older versions of \
+                    Lighthouse afterwards.",
+                ),
+        )
+        .arg(
+            Arg::with_name("remote-signer")
+                .long("remote-signer")
+                .value_name("URL")
+                .help(
+                    "The address of a remote signer which holds the keys of all validators. \
+                    Slashing protection is still enforced by this validator client before any \
+                    message is sent to the signer.",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("validators-dir")
+                .long("validators-dir")
+                .value_name("VALIDATORS_DIRECTORY")
+                .help(
+                    "A directory of validators created by the account manager. Validators are \
+                    added or removed while running as their directories are added to or removed \
+                    from this directory. The directory is scanned every 12 seconds, or \
+                    immediately upon SIGHUP.",
+                )
+                .conflicts_with("remote-signer")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("secrets-dir")
+                .long("secrets-dir")
+                .value_name("SECRETS_DIRECTORY")
+                .help(
+                    "The directory which contains the password to unlock the validator voting \
+                    keypairs in --validators-dir. Defaults to ~/.lighthouse/secrets",
+                )
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("enable-doppelganger-detection")
+                .long("enable-doppelganger-detection")
+                .help(
+                    "If present, the validator client will watch the chain for attestations and \
+                    blocks from its validators for 2 whole epochs after starting, before signing \
+                    any message. If any are found, another validator client is using the same \
+                    keys and this validator client will shut down. Validators added while running \
+                    are not checked.",
                 ),
         )
+        /* HTTP API related arguments */
+        .arg(
+            Arg::with_name("http")
+                .long("http")
+                .help(
+                    "Enable the HTTP API server, used to inspect and manage validators at \
+                    runtime. Requests must bear the token stored in api-token.txt in the \
+                    datadir. Disabled by default.",
+                )
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("http-address")
+                .long("http-address")
+                .value_name("ADDRESS")
+                .help("Set the listen address for the HTTP API server.")
+                .default_value("127.0.0.1")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("http-port")
+                .long("http-port")
+                .value_name("PORT")
+                .help("Set the listen TCP port for the HTTP API server.")
+                .default_value("5062")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("first-validator")
                 .value_name("VALIDATOR_INDEX")
-                .required(true)
+                .required_unless_one(&["remote-signer", "validators-dir"])
                 .help("The first validator public key to be generated for this client."),
         )
         .arg(
             Arg::with_name("last-validator")
                 .value_name("VALIDATOR_INDEX")
-                .required(true)
+                .required_unless_one(&["remote-signer", "validators-dir"])
                 .help("The last validator public key to be generated for this client."),
         )
 }
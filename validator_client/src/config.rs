@@ -1,3 +1,4 @@
+use crate::http_api;
 use clap::ArgMatches;
 use clap_utils::{parse_optional, parse_path_with_default_in_home_dir, parse_required};
 use serde_derive::{Deserialize, Serialize};
@@ -5,23 +6,47 @@ use std::path::PathBuf;
 
 pub const DEFAULT_HTTP_SERVER: &str = "http://localhost:5052/";
 pub const DEFAULT_DATA_DIR: &str = ".lighthouse/validators";
+pub const DEFAULT_SECRETS_DIR: &str = ".lighthouse/secrets";
 /// Path to the slashing protection database within the datadir.
-pub const SLASHING_PROTECTION_FILENAME: &str = "slashing_protection.sqlite";
+pub use slashing_protection::SLASHING_PROTECTION_FILENAME;
 
 /// Stores the core configuration for this validator instance.
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Config {
     /// The data directory, which stores all validator databases
     pub data_dir: PathBuf,
-    /// The http endpoint of the beacon node API.
+    /// The http endpoints of the beacon node APIs, in order of preference.
     ///
-    /// Should be similar to `http://localhost:8080`
-    pub http_server: String,
-    /// If true, the validator client will still poll for duties and produce blocks even if the
-    /// beacon node is not synced at startup.
+    /// Each should be similar to `http://localhost:8080`
+    pub beacon_nodes: Vec<String>,
+    /// A file containing the token to send to the beacon nodes with every request, for nodes
+    /// started with `--http-require-token`.
+    pub beacon_node_api_token_file: Option<PathBuf>,
+    /// If true, the validator client will still poll for duties and produce blocks even if none
+    /// of the beacon nodes are synced.
     pub allow_unsynced_beacon_node: bool,
     /// If true, register new validator keys with the slashing protection database.
     pub auto_register: bool,
+    /// If true, periodically prune the slashing protection database, keeping only a recent window
+    /// of signed messages and low watermarks for each validator.
+    pub minimal_slashing_protection: bool,
+    /// The URL of a remote signer which holds the keys of all validators.
+    ///
+    /// If `None`, the validator keys are generated locally from `first_validator` and
+    /// `last_validator`.
+    pub remote_signer: Option<String>,
+    /// A directory containing one sub-directory per validator, as created by the account manager.
+    ///
+    /// If `Some`, validators are loaded from this directory and added or removed while the
+    /// validator client is running, as sub-directories are added or removed.
+    pub validators_dir: Option<PathBuf>,
+    /// The directory containing the passwords of the keystores in `validators_dir`.
+    pub secrets_dir: PathBuf,
+    /// If true, watch the chain for messages from our validators before signing anything, and
+    /// shut down if any are found.
+    pub enable_doppelganger_detection: bool,
+    /// Configuration for the HTTP API used to manage validators at runtime.
+    pub http_api: http_api::Config,
 
     pub first_validator: usize,
     pub last_validator: usize,
@@ -33,11 +58,21 @@ impl Default for Config {
         let data_dir = dirs::home_dir()
             .map(|home| home.join(DEFAULT_DATA_DIR))
             .unwrap_or_else(|| PathBuf::from("."));
+        let secrets_dir = dirs::home_dir()
+            .map(|home| home.join(DEFAULT_SECRETS_DIR))
+            .unwrap_or_else(|| PathBuf::from("."));
         Self {
             data_dir,
-            http_server: DEFAULT_HTTP_SERVER.to_string(),
+            beacon_nodes: vec![DEFAULT_HTTP_SERVER.to_string()],
+            beacon_node_api_token_file: None,
             allow_unsynced_beacon_node: false,
             auto_register: false,
+            minimal_slashing_protection: false,
+            remote_signer: None,
+            validators_dir: None,
+            secrets_dir,
+            enable_doppelganger_detection: false,
+            http_api: http_api::Config::default(),
 
             first_validator: 0,
             last_validator: 0,
@@ -64,15 +99,61 @@ impl Config {
             ));
         }
 
-        if let Some(server) = parse_optional(cli_args, "server")? {
-            config.http_server = server;
+        if let Some(beacon_nodes) = parse_optional::<String>(cli_args, "beacon-nodes")? {
+            config.beacon_nodes = beacon_nodes
+                .split(',')
+                .map(str::trim)
+                .filter(|endpoint| !endpoint.is_empty())
+                .map(String::from)
+                .collect();
+
+            if config.beacon_nodes.is_empty() {
+                return Err("At least one beacon node must be supplied (--beacon-nodes)".into());
+            }
         }
 
+        config.beacon_node_api_token_file = parse_optional(cli_args, "beacon-node-api-token-file")?;
+
         config.allow_unsynced_beacon_node = cli_args.is_present("allow-unsynced");
         config.auto_register = cli_args.is_present("auto-register");
+        config.minimal_slashing_protection = cli_args.is_present("minimal-slashing-protection");
 
-        config.first_validator = parse_required(cli_args, "first-validator")?;
-        config.last_validator = parse_required(cli_args, "last-validator")?;
+        config.remote_signer = parse_optional(cli_args, "remote-signer")?;
+
+        if let Some(validators_dir) = parse_optional::<PathBuf>(cli_args, "validators-dir")? {
+            if !validators_dir.exists() {
+                return Err(format!(
+                    "The validators directory (--validators-dir) does not exist: {:?}",
+                    validators_dir
+                ));
+            }
+            config.validators_dir = Some(validators_dir);
+        }
+
+        config.secrets_dir = parse_path_with_default_in_home_dir(
+            cli_args,
+            "secrets-dir",
+            PathBuf::from(".lighthouse").join("secrets"),
+        )?;
+
+        config.enable_doppelganger_detection = cli_args.is_present("enable-doppelganger-detection");
+
+        if cli_args.is_present("http") {
+            config.http_api.enabled = true;
+        }
+
+        if let Some(address) = parse_optional(cli_args, "http-address")? {
+            config.http_api.listen_address = address;
+        }
+
+        if let Some(port) = parse_optional(cli_args, "http-port")? {
+            config.http_api.port = port;
+        }
+
+        if config.remote_signer.is_none() && config.validators_dir.is_none() {
+            config.first_validator = parse_required(cli_args, "first-validator")?;
+            config.last_validator = parse_required(cli_args, "last-validator")?;
+        }
 
         Ok(config)
     }
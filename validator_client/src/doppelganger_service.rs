@@ -0,0 +1,403 @@
+//! Detects whether any of our validators are already active on another validator client, by
+//! watching the chain for their attestations and blocks before anything is signed.
+//!
+//! Detection cannot distinguish our own messages from those of another instance, so the epoch in
+//! which the validator client started (during which a previous run may have signed messages) is
+//! never checked. Validators added at runtime are not subject to detection.
+
+use crate::beacon_node_fallback::BeaconNodeFallback;
+use crate::duties_service::DutiesService;
+use crate::validator_store::ValidatorStore;
+use environment::RuntimeContext;
+use futures::future::join_all;
+use rest_types::IndividualVotesResponse;
+use slog::{crit, error, info, Logger};
+use slot_clock::SlotClock;
+use std::sync::Arc;
+use tokio::time::{delay_for, Duration};
+use types::{Epoch, EthSpec, PublicKey, Slot};
+
+/// The default number of epochs which must pass without any sign of our validators before they
+/// start signing.
+pub const DEFAULT_DOPPELGANGER_DETECTION_EPOCHS: u64 = 2;
+
+/// Watches the chain for messages from our validators, running a callback once none have been
+/// seen for `detection_epochs` whole epochs.
+pub struct DoppelgangerService<T, E: EthSpec> {
+    duties_service: DutiesService<T, E>,
+    validator_store: ValidatorStore<T, E>,
+    beacon_nodes: Arc<BeaconNodeFallback<T, E>>,
+    slot_clock: T,
+    detection_epochs: u64,
+    context: RuntimeContext<E>,
+}
+
+/// A message from one of our validators, seen on chain.
+enum Sighting {
+    Attestation {
+        validator_index: usize,
+        epoch: Epoch,
+    },
+    Block {
+        validator_index: u64,
+        slot: Slot,
+    },
+}
+
+impl<T: SlotClock + 'static, E: EthSpec> DoppelgangerService<T, E> {
+    pub fn new(
+        duties_service: DutiesService<T, E>,
+        validator_store: ValidatorStore<T, E>,
+        beacon_nodes: Arc<BeaconNodeFallback<T, E>>,
+        slot_clock: T,
+        detection_epochs: u64,
+        context: RuntimeContext<E>,
+    ) -> Self {
+        Self {
+            duties_service,
+            validator_store,
+            beacon_nodes,
+            slot_clock,
+            detection_epochs,
+            context,
+        }
+    }
+
+    /// Start watching the chain, calling `on_success` once detection has completed.
+    ///
+    /// If any of our validators are seen on chain, `on_success` is never called and the process
+    /// is shut down.
+    pub fn start<F>(self, on_success: F) -> Result<(), String>
+    where
+        F: FnOnce() -> Result<(), String> + Send + 'static,
+    {
+        let start_epoch = self
+            .slot_clock
+            .now()
+            .ok_or_else(|| "Unable to read slot clock".to_string())?
+            .epoch(E::slots_per_epoch());
+        let first_epoch = start_epoch + 1;
+        let last_epoch = start_epoch + self.detection_epochs;
+
+        info!(
+            self.context.log(),
+            "Doppelganger detection started";
+            "msg" => "validators will not sign until detection completes",
+            "first_checked_epoch" => first_epoch.as_u64(),
+            "last_checked_epoch" => last_epoch.as_u64(),
+        );
+
+        let executor = self.context.executor.clone();
+
+        let detection_fut = async move {
+            let log = self.context.log().clone();
+
+            // Attestations for an epoch may be included until the end of the next epoch, so the
+            // messages of epoch `e` are all known at the start of epoch `e + 2`.
+            let mut current_epoch = first_epoch + 1;
+            while current_epoch <= last_epoch + 2 {
+                if !self.wait_for_epoch(current_epoch).await {
+                    return;
+                }
+
+                let previous_epoch = current_epoch - 1;
+                let checked_epochs = [previous_epoch - 1, previous_epoch]
+                    .iter()
+                    .map(|epoch| *epoch >= first_epoch && *epoch <= last_epoch)
+                    .collect::<Vec<_>>();
+
+                match self.sightings(previous_epoch, &checked_epochs).await {
+                    Ok(sightings) if sightings.is_empty() => {
+                        info!(
+                            log,
+                            "No doppelganger found";
+                            "epoch" => previous_epoch.as_u64(),
+                        );
+                        current_epoch += 1;
+                    }
+                    Ok(sightings) => {
+                        shutdown(&self.context, &log, sightings);
+                        return;
+                    }
+                    Err(e) => {
+                        // The check is repeated until it succeeds, signing can't start without it.
+                        error!(
+                            log,
+                            "Unable to check for doppelganger";
+                            "msg" => "will retry next slot",
+                            "error" => e,
+                        );
+                        delay_for(self.slot_clock.slot_duration()).await;
+                    }
+                }
+            }
+
+            info!(
+                log,
+                "Doppelganger detection complete";
+                "msg" => "starting to sign messages",
+            );
+
+            if let Err(e) = on_success() {
+                crit!(log, "Unable to start signing after doppelganger detection"; "error" => e);
+            }
+        };
+
+        executor.spawn(detection_fut, "doppelganger_service");
+
+        Ok(())
+    }
+
+    /// Wait until shortly after the start of `epoch`, giving the beacon node time to process its
+    /// first slot. Returns `false` if the slot clock could not be read.
+    async fn wait_for_epoch(&self, epoch: Epoch) -> bool {
+        let start_slot = epoch.start_slot(E::slots_per_epoch());
+        match self.slot_clock.duration_to_slot(start_slot) {
+            Some(duration) => {
+                delay_for(duration + self.slot_clock.slot_duration() / 2).await;
+                true
+            }
+            // The slot has already started.
+            None if self
+                .slot_clock
+                .now()
+                .map_or(false, |slot| slot >= start_slot) =>
+            {
+                true
+            }
+            None => {
+                crit!(
+                    self.context.log(),
+                    "Doppelganger service unable to read slot clock"
+                );
+                false
+            }
+        }
+    }
+
+    /// Returns all the messages from our validators seen in `previous_epoch - 1` and
+    /// `previous_epoch`, for which `checked_epochs` is `true`.
+    async fn sightings(
+        &self,
+        previous_epoch: Epoch,
+        checked_epochs: &[bool],
+    ) -> Result<Vec<Sighting>, String> {
+        let pubkeys = self.validator_store.voting_pubkeys();
+
+        let votes = self
+            .beacon_nodes
+            .first_success(|beacon_node| {
+                let pubkeys = pubkeys.iter().cloned().map(Into::into).collect::<Vec<_>>();
+                async move {
+                    beacon_node
+                        .http
+                        .consensus()
+                        .get_individual_votes(previous_epoch, pubkeys)
+                        .await
+                        .map_err(|e| format!("Failed to get individual votes: {:?}", e))
+                }
+            })
+            .await?;
+
+        let mut sightings = attestation_sightings(&votes, previous_epoch, checked_epochs);
+
+        if checked_epochs[1] {
+            sightings.extend(self.block_sightings(&pubkeys, previous_epoch).await?);
+        }
+
+        Ok(sightings)
+    }
+
+    /// Returns any blocks in `epoch` proposed by our validators.
+    ///
+    /// Only the slots at which our validators are due to propose are checked.
+    async fn block_sightings(
+        &self,
+        pubkeys: &[PublicKey],
+        epoch: Epoch,
+    ) -> Result<Vec<Sighting>, String> {
+        let proposals = pubkeys
+            .iter()
+            .filter_map(|pubkey| self.duties_service.validator_duties(pubkey, epoch))
+            .filter_map(|duty| {
+                let validator_index = duty.validator_index?;
+                Some(
+                    duty.block_proposal_slots
+                        .into_iter()
+                        .map(move |slot| (validator_index, slot)),
+                )
+            })
+            .flatten()
+            .collect::<Vec<_>>();
+
+        let results = join_all(proposals.into_iter().map(|(validator_index, slot)| {
+            self.beacon_nodes
+                .first_success(move |beacon_node| async move {
+                    let (block, _root) = beacon_node
+                        .http
+                        .beacon()
+                        .get_block_by_slot(slot)
+                        .await
+                        .map_err(|e| format!("Failed to get block at slot {}: {:?}", slot, e))?;
+
+                    // The beacon node returns the previous block if `slot` was skipped.
+                    let proposed = block.message.slot == slot
+                        && block.message.proposer_index == validator_index;
+                    Ok::<_, String>(if proposed {
+                        Some(Sighting::Block {
+                            validator_index,
+                            slot,
+                        })
+                    } else {
+                        None
+                    })
+                })
+        }))
+        .await;
+
+        results.into_iter().filter_map(Result::transpose).collect()
+    }
+}
+
+/// Returns any attestations from our validators in `previous_epoch - 1` or `previous_epoch`, for
+/// which `checked_epochs` is `true`.
+fn attestation_sightings(
+    votes: &[IndividualVotesResponse],
+    previous_epoch: Epoch,
+    checked_epochs: &[bool],
+) -> Vec<Sighting> {
+    votes
+        .iter()
+        .filter_map(|response| Some((response.validator_index?, response.vote.as_ref()?)))
+        .flat_map(|(validator_index, vote)| {
+            let attested = [
+                vote.is_previous_epoch_attester,
+                vote.is_current_epoch_attester,
+            ];
+            [previous_epoch - 1, previous_epoch]
+                .iter()
+                .zip(attested.iter().zip(checked_epochs))
+                .filter(|(_epoch, (attested, checked))| **attested && **checked)
+                .map(|(epoch, _)| Sighting::Attestation {
+                    validator_index,
+                    epoch: *epoch,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Log the `sightings` and request that the process shuts down.
+fn shutdown<E: EthSpec>(context: &RuntimeContext<E>, log: &Logger, sightings: Vec<Sighting>) {
+    for sighting in sightings {
+        match sighting {
+            Sighting::Attestation {
+                validator_index,
+                epoch,
+            } => crit!(
+                log,
+                "Doppelganger attestation detected";
+                "validator_index" => validator_index,
+                "epoch" => epoch.as_u64(),
+            ),
+            Sighting::Block {
+                validator_index,
+                slot,
+            } => crit!(
+                log,
+                "Doppelganger block detected";
+                "validator_index" => validator_index,
+                "slot" => slot.as_u64(),
+            ),
+        }
+    }
+
+    crit!(
+        log,
+        "Doppelganger detected";
+        "msg" => "another validator client is using the same keys. Refusing to sign and \
+        shutting down. Ensure the keys are only active in one place before restarting",
+    );
+
+    if let Err(e) = context
+        .executor
+        .shutdown_sender()
+        .try_send("Doppelganger detected")
+    {
+        crit!(log, "Unable to request shutdown"; "error" => format!("{:?}", e));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rest_types::IndividualVote;
+    use types::test_utils::generate_deterministic_keypair;
+
+    fn vote(validator_index: usize, previous: bool, current: bool) -> IndividualVotesResponse {
+        IndividualVotesResponse {
+            epoch: Epoch::new(5),
+            pubkey: generate_deterministic_keypair(validator_index).pk.into(),
+            validator_index: Some(validator_index),
+            vote: Some(IndividualVote {
+                is_slashed: false,
+                is_withdrawable_in_current_epoch: false,
+                is_active_in_current_epoch: true,
+                is_active_in_previous_epoch: true,
+                current_epoch_effective_balance_gwei: 32_000_000_000,
+                is_current_epoch_attester: current,
+                is_current_epoch_target_attester: current,
+                is_previous_epoch_attester: previous,
+                is_previous_epoch_target_attester: previous,
+                is_previous_epoch_head_attester: previous,
+            }),
+        }
+    }
+
+    fn epochs(sightings: Vec<Sighting>) -> Vec<(usize, u64)> {
+        sightings
+            .into_iter()
+            .map(|sighting| match sighting {
+                Sighting::Attestation {
+                    validator_index,
+                    epoch,
+                } => (validator_index, epoch.as_u64()),
+                Sighting::Block { .. } => panic!("unexpected block"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn attestations_only_in_checked_epochs() {
+        let votes = vec![
+            vote(0, true, true),
+            vote(1, false, true),
+            vote(2, true, false),
+        ];
+
+        assert_eq!(
+            epochs(attestation_sightings(&votes, Epoch::new(5), &[true, true])),
+            vec![(0, 4), (0, 5), (1, 5), (2, 4)]
+        );
+        assert_eq!(
+            epochs(attestation_sightings(&votes, Epoch::new(5), &[false, true])),
+            vec![(0, 5), (1, 5)]
+        );
+        assert_eq!(
+            epochs(attestation_sightings(&votes, Epoch::new(5), &[true, false])),
+            vec![(0, 4), (2, 4)]
+        );
+    }
+
+    #[test]
+    fn unknown_validators_ignored() {
+        let mut unknown = vote(0, true, true);
+        unknown.validator_index = None;
+        let mut no_vote = vote(1, true, true);
+        no_vote.vote = None;
+
+        assert!(
+            attestation_sightings(&[unknown, no_vote], Epoch::new(5), &[true, true]).is_empty()
+        );
+    }
+}
@@ -1,12 +1,12 @@
-use crate::{is_synced::is_synced, validator_store::ValidatorStore};
+use crate::{beacon_node_fallback::BeaconNodeFallback, validator_store::ValidatorStore};
 use environment::RuntimeContext;
 use futures::StreamExt;
 use parking_lot::RwLock;
-use remote_beacon_node::{PublishStatus, RemoteBeaconNode};
+use remote_beacon_node::PublishStatus;
 use rest_types::{ValidatorDuty, ValidatorDutyBytes, ValidatorSubscription};
 use slog::{debug, error, trace, warn};
 use slot_clock::SlotClock;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
 use std::ops::Deref;
 use std::sync::Arc;
@@ -38,7 +38,7 @@ impl DutyAndProof {
     ///
     /// - `self.validator_pubkey` is not known in `validator_store`.
     /// - There's an arith error during computation.
-    pub fn compute_selection_proof<T: SlotClock + 'static, E: EthSpec>(
+    pub async fn compute_selection_proof<T: SlotClock + 'static, E: EthSpec>(
         &mut self,
         validator_store: &ValidatorStore<T, E>,
     ) -> Result<(), String> {
@@ -55,6 +55,7 @@ impl DutyAndProof {
 
         let selection_proof = validator_store
             .produce_selection_proof(&self.duty.validator_pubkey, slot)
+            .await
             .ok_or_else(|| "Failed to produce selection proof".to_string())?;
 
         self.selection_proof = selection_proof
@@ -243,17 +244,33 @@ impl DutiesStore {
         )
     }
 
-    fn insert<T: SlotClock + 'static, E: EthSpec>(
+    fn validator_duties(
         &self,
+        validator_pubkey: &PublicKey,
         epoch: Epoch,
-        mut duties: DutyAndProof,
-        slots_per_epoch: u64,
-        validator_store: &ValidatorStore<T, E>,
-    ) -> Result<InsertOutcome, String> {
+    ) -> Option<ValidatorDuty> {
+        self.store
+            .read()
+            .get(validator_pubkey)?
+            .get(&epoch)
+            .map(|duties| duties.duty.clone())
+    }
+
+    /// Returns `true` if identical duties for the validator are already stored for `epoch`.
+    fn is_known(&self, epoch: Epoch, duty: &ValidatorDuty) -> bool {
+        self.store
+            .read()
+            .get(&duty.validator_pubkey)
+            .and_then(|validator_map| validator_map.get(&epoch))
+            .map_or(false, |known_duties| known_duties.duty == *duty)
+    }
+
+    /// Insert `duties`, which must already have their selection proof computed.
+    fn insert(&self, epoch: Epoch, duties: DutyAndProof, slots_per_epoch: u64) -> InsertOutcome {
         let mut store = self.store.write();
 
         if !duties_match_epoch(&duties.duty, epoch, slots_per_epoch) {
-            return Ok(InsertOutcome::Invalid);
+            return InsertOutcome::Invalid;
         }
 
         // TODO: refactor with Entry.
@@ -261,31 +278,22 @@ impl DutiesStore {
         if let Some(validator_map) = store.get_mut(&duties.duty.validator_pubkey) {
             if let Some(known_duties) = validator_map.get_mut(&epoch) {
                 if known_duties.duty == duties.duty {
-                    Ok(InsertOutcome::Identical)
+                    InsertOutcome::Identical
                 } else {
-                    // Compute the selection proof.
-                    duties.compute_selection_proof(validator_store)?;
-
                     // Determine if a re-subscription is required.
                     let should_resubscribe = duties.subscription_eq(known_duties);
 
                     // Replace the existing duties.
                     *known_duties = duties;
 
-                    Ok(InsertOutcome::Replaced { should_resubscribe })
+                    InsertOutcome::Replaced { should_resubscribe }
                 }
             } else {
-                // Compute the selection proof.
-                duties.compute_selection_proof(validator_store)?;
-
                 validator_map.insert(epoch, duties);
 
-                Ok(InsertOutcome::NewEpoch)
+                InsertOutcome::NewEpoch
             }
         } else {
-            // Compute the selection proof.
-            duties.compute_selection_proof(validator_store)?;
-
             let validator_pubkey = duties.duty.validator_pubkey.clone();
 
             let mut validator_map = HashMap::new();
@@ -293,10 +301,20 @@ impl DutiesStore {
 
             store.insert(validator_pubkey, validator_map);
 
-            Ok(InsertOutcome::NewValidator)
+            InsertOutcome::NewValidator
         }
     }
 
+    /// Remove the duties of any validator which is not in `validator_pubkeys`.
+    fn retain_validators(&self, validator_pubkeys: &[PublicKey]) {
+        let validator_pubkeys = validator_pubkeys.iter().collect::<HashSet<_>>();
+        self.store
+            .write()
+            .retain(|validator_pubkey, _validator_map| {
+                validator_pubkeys.contains(validator_pubkey)
+            });
+    }
+
     fn prune(&self, prior_to: Epoch) {
         self.store
             .write()
@@ -310,9 +328,8 @@ impl DutiesStore {
 pub struct DutiesServiceBuilder<T, E: EthSpec> {
     validator_store: Option<ValidatorStore<T, E>>,
     slot_clock: Option<T>,
-    beacon_node: Option<RemoteBeaconNode<E>>,
+    beacon_nodes: Option<Arc<BeaconNodeFallback<T, E>>>,
     context: Option<RuntimeContext<E>>,
-    allow_unsynced_beacon_node: bool,
 }
 
 impl<T: SlotClock + 'static, E: EthSpec> DutiesServiceBuilder<T, E> {
@@ -320,9 +337,8 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesServiceBuilder<T, E> {
         Self {
             validator_store: None,
             slot_clock: None,
-            beacon_node: None,
+            beacon_nodes: None,
             context: None,
-            allow_unsynced_beacon_node: false,
         }
     }
 
@@ -336,8 +352,8 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesServiceBuilder<T, E> {
         self
     }
 
-    pub fn beacon_node(mut self, beacon_node: RemoteBeaconNode<E>) -> Self {
-        self.beacon_node = Some(beacon_node);
+    pub fn beacon_nodes(mut self, beacon_nodes: Arc<BeaconNodeFallback<T, E>>) -> Self {
+        self.beacon_nodes = Some(beacon_nodes);
         self
     }
 
@@ -346,12 +362,6 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesServiceBuilder<T, E> {
         self
     }
 
-    /// Set to `true` to allow polling for duties when the beacon node is not synced.
-    pub fn allow_unsynced_beacon_node(mut self, allow_unsynced_beacon_node: bool) -> Self {
-        self.allow_unsynced_beacon_node = allow_unsynced_beacon_node;
-        self
-    }
-
     pub fn build(self) -> Result<DutiesService<T, E>, String> {
         Ok(DutiesService {
             inner: Arc::new(Inner {
@@ -362,13 +372,12 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesServiceBuilder<T, E> {
                 slot_clock: self
                     .slot_clock
                     .ok_or_else(|| "Cannot build DutiesService without slot_clock")?,
-                beacon_node: self
-                    .beacon_node
-                    .ok_or_else(|| "Cannot build DutiesService without beacon_node")?,
+                beacon_nodes: self
+                    .beacon_nodes
+                    .ok_or_else(|| "Cannot build DutiesService without beacon_nodes")?,
                 context: self
                     .context
                     .ok_or_else(|| "Cannot build DutiesService without runtime_context")?,
-                allow_unsynced_beacon_node: self.allow_unsynced_beacon_node,
             }),
         })
     }
@@ -379,11 +388,8 @@ pub struct Inner<T, E: EthSpec> {
     store: Arc<DutiesStore>,
     validator_store: ValidatorStore<T, E>,
     pub(crate) slot_clock: T,
-    pub(crate) beacon_node: RemoteBeaconNode<E>,
+    pub(crate) beacon_nodes: Arc<BeaconNodeFallback<T, E>>,
     context: RuntimeContext<E>,
-    /// If true, the duties service will poll for duties from the beacon node even if it is not
-    /// synced.
-    allow_unsynced_beacon_node: bool,
 }
 
 /// Maintains a store of the duties for all voting validators in the `validator_store`.
@@ -439,6 +445,15 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
         self.store.attesters(slot, E::slots_per_epoch())
     }
 
+    /// Returns the duties of the given validator in `epoch`, if they are known.
+    pub fn validator_duties(
+        &self,
+        validator_pubkey: &PublicKey,
+        epoch: Epoch,
+    ) -> Option<ValidatorDuty> {
+        self.store.validator_duties(validator_pubkey, epoch)
+    }
+
     /// Start the service that periodically polls the beacon node for validator duties.
     pub fn start_update_service(self, spec: &ChainSpec) -> Result<(), String> {
         let duration_to_next_slot = self
@@ -479,12 +494,6 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
     async fn do_update(self) -> Result<(), ()> {
         let log = self.context.log();
 
-        if !is_synced(&self.beacon_node, &self.slot_clock, None).await
-            && !self.allow_unsynced_beacon_node
-        {
-            return Ok(());
-        }
-
         let current_epoch = self
             .slot_clock
             .now()
@@ -536,13 +545,26 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
     /// Attempt to download the duties of all managed validators for the given `epoch`.
     async fn update_epoch(self, epoch: Epoch) -> Result<(), String> {
         let pubkeys = self.validator_store.voting_pubkeys();
+
+        // Forget the duties of validators which have been removed or disabled since the last
+        // update, duties for new validators are requested below.
+        self.store.retain_validators(&pubkeys);
+
         let all_duties = self
-            .beacon_node
-            .http
-            .validator()
-            .get_duties(epoch, pubkeys.as_slice())
+            .beacon_nodes
+            .first_success(|beacon_node| {
+                let pubkeys = pubkeys.clone();
+                async move {
+                    beacon_node
+                        .http
+                        .validator()
+                        .get_duties(epoch, pubkeys.as_slice())
+                        .await
+                        .map_err(|e| format!("{:?}", e))
+                }
+            })
             .await
-            .map_err(move |e| format!("Failed to get duties for epoch {}: {:?}", epoch, e))?;
+            .map_err(move |e| format!("Failed to get duties for epoch {}: {}", epoch, e))?;
 
         let log = self.context.log().clone();
 
@@ -552,38 +574,50 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
         let mut replaced = 0;
         let mut invalid = 0;
 
+        // Convert the remote duties into our local representation, computing selection proofs
+        // for any duties that have changed.
+        //
+        // Selection proofs are computed before the store is locked, since signing may require a
+        // request to a remote signer.
+        let mut duties_and_proofs = Vec::with_capacity(all_duties.len());
+        for remote_duties in all_duties {
+            let mut duties: DutyAndProof = match remote_duties.clone().try_into() {
+                Ok(duties) => duties,
+                Err(e) => {
+                    error!(
+                        log,
+                        "Unable to convert remote duties";
+                        "error" => e
+                    );
+                    continue;
+                }
+            };
+
+            if !self.store.is_known(epoch, &duties.duty) {
+                if let Err(e) = duties.compute_selection_proof(&self.validator_store).await {
+                    error!(
+                        log,
+                        "Unable to store duties";
+                        "error" => e
+                    );
+                    continue;
+                }
+            }
+
+            duties_and_proofs.push((remote_duties, duties));
+        }
+
         // For each of the duties, attempt to insert them into our local store and build a
         // list of new or changed selections proofs for any aggregating validators.
-        let validator_subscriptions = all_duties
+        let validator_subscriptions = duties_and_proofs
             .into_iter()
-            .filter_map(|remote_duties| {
-                // Convert the remote duties into our local representation.
-                let duties: DutyAndProof = remote_duties
-                    .clone()
-                    .try_into()
-                    .map_err(|e| {
-                        error!(
-                            log,
-                            "Unable to convert remote duties";
-                            "error" => e
-                        )
-                    })
-                    .ok()?;
-
+            .filter_map(|(remote_duties, duties)| {
                 let validator_pubkey = duties.duty.validator_pubkey.clone();
 
                 // Attempt to update our local store.
                 let outcome = self
                     .store
-                    .insert(epoch, duties, E::slots_per_epoch(), &self.validator_store)
-                    .map_err(|e| {
-                        error!(
-                            log,
-                            "Unable to store duties";
-                            "error" => e
-                        )
-                    })
-                    .ok()?;
+                    .insert(epoch, duties, E::slots_per_epoch());
 
                 match &outcome {
                     InsertOutcome::NewValidator => {
@@ -655,12 +689,20 @@ impl<T: SlotClock + 'static, E: EthSpec> DutiesService<T, E> {
 
             Ok(())
         } else {
-            self.beacon_node
-                .http
-                .validator()
-                .subscribe(validator_subscriptions)
+            self.beacon_nodes
+                .broadcast(|beacon_node| {
+                    let validator_subscriptions = validator_subscriptions.clone();
+                    async move {
+                        beacon_node
+                            .http
+                            .validator()
+                            .subscribe(validator_subscriptions)
+                            .await
+                            .map_err(|e| format!("{:?}", e))
+                    }
+                })
                 .await
-                .map_err(|e| format!("Failed to subscribe validators: {:?}", e))
+                .map_err(|e| format!("Failed to subscribe validators: {}", e))
                 .map(move |status| {
                     match status {
                         PublishStatus::Valid => debug!(
@@ -0,0 +1,39 @@
+use hyper::{Body, Response, StatusCode};
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum ApiError {
+    BadRequest(String),
+    Unauthorized(String),
+    NotFound(String),
+    ServerError(String),
+}
+
+pub type ApiResult = Result<Response<Body>, ApiError>;
+
+impl ApiError {
+    pub fn status_code(self) -> (StatusCode, String) {
+        match self {
+            ApiError::BadRequest(desc) => (StatusCode::BAD_REQUEST, desc),
+            ApiError::Unauthorized(desc) => (StatusCode::UNAUTHORIZED, desc),
+            ApiError::NotFound(desc) => (StatusCode::NOT_FOUND, desc),
+            ApiError::ServerError(desc) => (StatusCode::INTERNAL_SERVER_ERROR, desc),
+        }
+    }
+}
+
+impl Into<Response<Body>> for ApiError {
+    fn into(self) -> Response<Body> {
+        let (status_code, desc) = self.status_code();
+        Response::builder()
+            .status(status_code)
+            .header("content-type", "text/plain; charset=utf-8")
+            .body(Body::from(desc))
+            .expect("Response should always be created.")
+    }
+}
+
+impl From<hyper::error::Error> for ApiError {
+    fn from(e: hyper::error::Error) -> ApiError {
+        ApiError::ServerError(format!("Networking error: {:?}", e))
+    }
+}
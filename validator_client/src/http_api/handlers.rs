@@ -0,0 +1,177 @@
+use super::error::{ApiError, ApiResult};
+use super::types::{KeystoreImportRequest, ValidatorData, ValidatorPatchRequest, ValidatorStatus};
+use super::Context;
+use hyper::{Body, Request, Response};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use slog::info;
+use slot_clock::SlotClock;
+use types::{EthSpec, PublicKey};
+
+/// Returns every validator known to the `ValidatorStore`.
+pub fn get_validators<T: SlotClock + 'static, E: EthSpec>(ctx: &Context<T, E>) -> ApiResult {
+    let mut validators = ctx
+        .validator_store
+        .validators()
+        .into_iter()
+        .map(|(voting_pubkey, enabled)| ValidatorData {
+            voting_pubkey,
+            enabled,
+        })
+        .collect::<Vec<_>>();
+
+    // Provide a stable ordering, the store is unordered.
+    validators.sort_by_key(|validator| validator.voting_pubkey.as_hex_string());
+
+    json_response(&validators)
+}
+
+/// Returns the duties in the current epoch and the signing history of a single validator.
+pub fn get_validator<T: SlotClock + 'static, E: EthSpec>(
+    voting_pubkey: PublicKey,
+    ctx: &Context<T, E>,
+) -> ApiResult {
+    let enabled = ctx
+        .validator_store
+        .is_enabled(&voting_pubkey)
+        .ok_or_else(|| unknown_validator(&voting_pubkey))?;
+
+    let epoch = ctx
+        .duties_service
+        .slot_clock
+        .now()
+        .ok_or_else(|| ApiError::ServerError("Unable to read slot clock".to_string()))?
+        .epoch(E::slots_per_epoch());
+
+    let duties = ctx.duties_service.validator_duties(&voting_pubkey, epoch);
+    let signing_history = ctx
+        .validator_store
+        .signing_history(&voting_pubkey)
+        .map_err(ApiError::ServerError)?;
+
+    json_response(&ValidatorStatus {
+        voting_pubkey,
+        enabled,
+        epoch,
+        duties,
+        signing_history,
+    })
+}
+
+/// Enable or disable a single validator.
+///
+/// The change is saved in the directory of validators loaded from the validators directory. Other
+/// validators (e.g., those of a remote signer) are only changed until the next restart.
+pub async fn patch_validator<T: SlotClock + 'static, E: EthSpec>(
+    req: Request<Body>,
+    voting_pubkey: PublicKey,
+    ctx: &Context<T, E>,
+) -> ApiResult {
+    let body: ValidatorPatchRequest = parse_json_body(req).await?;
+
+    let saved = match &ctx.validator_watcher {
+        Some(validator_watcher) => validator_watcher
+            .set_validator_enabled(&voting_pubkey, body.enabled)
+            .map_err(ApiError::ServerError)?,
+        None => false,
+    };
+
+    if !saved
+        && !ctx
+            .validator_store
+            .set_validator_enabled(&voting_pubkey, body.enabled)
+    {
+        return Err(unknown_validator(&voting_pubkey));
+    }
+
+    info!(
+        ctx.log,
+        "Validator status changed via HTTP API";
+        "enabled" => body.enabled,
+        "voting_pubkey" => format!("{:?}", voting_pubkey),
+    );
+
+    json_response(&ValidatorData {
+        voting_pubkey,
+        enabled: body.enabled,
+    })
+}
+
+/// Decrypt a keystore, save it in the validators directory and start validating with its keypair.
+///
+/// Requires a validators directory, so that the validator is loaded again after a restart.
+pub async fn post_keystore<T: SlotClock + 'static, E: EthSpec>(
+    req: Request<Body>,
+    ctx: &Context<T, E>,
+) -> ApiResult {
+    let KeystoreImportRequest {
+        keystore,
+        password,
+        enable,
+    } = parse_json_body(req).await?;
+
+    let validator_watcher = ctx.validator_watcher.clone().ok_or_else(|| {
+        ApiError::BadRequest("Importing keystores requires --validators-dir".to_string())
+    })?;
+
+    // Key derivation is deliberately slow, keep it away from the async runtime.
+    let voting_pubkey = ctx
+        .executor
+        .runtime_handle()
+        .spawn_blocking(move || {
+            let keypair = keystore.decrypt_keypair(password.as_bytes()).map_err(|e| {
+                ApiError::BadRequest(format!("Unable to decrypt keystore: {:?}", e))
+            })?;
+            validator_watcher
+                .import_keystore(keystore, password.as_bytes(), keypair, enable)
+                .map_err(ApiError::BadRequest)
+        })
+        .await
+        .map_err(|e| ApiError::ServerError(format!("Keystore import failed: {:?}", e)))??;
+
+    info!(
+        ctx.log,
+        "Imported validator keystore via HTTP API";
+        "enabled" => enable,
+        "voting_pubkey" => format!("{:?}", voting_pubkey),
+    );
+
+    json_response(&ValidatorData {
+        voting_pubkey,
+        enabled: enable,
+    })
+}
+
+/// Parse a public key from its `0x`-prefixed hex representation, as used in request paths.
+pub fn parse_pubkey(string: &str) -> Result<PublicKey, ApiError> {
+    const PREFIX: &str = "0x";
+    if string.starts_with(PREFIX) {
+        let pubkey_bytes = hex::decode(string.trim_start_matches(PREFIX))
+            .map_err(|e| ApiError::BadRequest(format!("Invalid hex string: {:?}", e)))?;
+        PublicKey::from_bytes(&pubkey_bytes)
+            .map_err(|e| ApiError::BadRequest(format!("Invalid public key: {:?}", e)))
+    } else {
+        Err(ApiError::BadRequest(
+            "Public key must have a 0x prefix".to_string(),
+        ))
+    }
+}
+
+fn unknown_validator(voting_pubkey: &PublicKey) -> ApiError {
+    ApiError::NotFound(format!("Unknown validator: {:?}", voting_pubkey))
+}
+
+async fn parse_json_body<T: DeserializeOwned>(req: Request<Body>) -> Result<T, ApiError> {
+    let bytes = hyper::body::to_bytes(req.into_body()).await?;
+    serde_json::from_slice(&bytes)
+        .map_err(|e| ApiError::BadRequest(format!("Unable to parse JSON body: {:?}", e)))
+}
+
+fn json_response<T: Serialize>(item: &T) -> ApiResult {
+    let body = serde_json::to_vec(item)
+        .map_err(|e| ApiError::ServerError(format!("Unable to serialize response: {:?}", e)))?;
+    Response::builder()
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .map_err(|e| ApiError::ServerError(format!("Failed to build response: {:?}", e)))
+}
@@ -0,0 +1,471 @@
+//! An HTTP API for inspecting and managing the validators of a running validator client.
+//!
+//! Every request must include an `Authorization: Bearer <token>` header, where the token is read
+//! from the `api-token.txt` file in the validator client datadir (it is generated on first use).
+//!
+//! ## Endpoints
+//!
+//! - `GET /lighthouse/validators`: list all validators and whether they are enabled.
+//! - `GET /lighthouse/validators/{pubkey}`: the duties of a validator in the current epoch and the
+//!   most recent messages it has signed.
+//! - `PATCH /lighthouse/validators/{pubkey}`: enable or disable a validator.
+//! - `POST /lighthouse/validators/keystore`: decrypt a keystore, save it in the validators
+//!   directory and add it to the validators.
+
+mod error;
+mod handlers;
+pub mod types;
+
+pub use api_token::{ApiToken, API_TOKEN_FILENAME};
+
+use crate::duties_service::DutiesService;
+use crate::validator_store::ValidatorStore;
+use crate::validator_watcher::ValidatorWatcher;
+use environment::TaskExecutor;
+use error::{ApiError, ApiResult};
+use futures::future::TryFutureExt;
+use hyper::header::AUTHORIZATION;
+use hyper::server::conn::AddrStream;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server};
+use serde_derive::{Deserialize, Serialize};
+use slog::{debug, info, warn, Logger};
+use slot_clock::SlotClock;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use types::EthSpec;
+
+/// HTTP API configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Config {
+    /// Enable the HTTP API server.
+    pub enabled: bool,
+    /// The IPv4 address the HTTP API server will listen on.
+    pub listen_address: Ipv4Addr,
+    /// The port the HTTP API server will listen on.
+    pub port: u16,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            enabled: false,
+            listen_address: Ipv4Addr::new(127, 0, 0, 1),
+            port: 5062,
+        }
+    }
+}
+
+/// The state shared by all requests to the HTTP API.
+pub struct Context<T, E: EthSpec> {
+    pub api_token: ApiToken,
+    pub validator_store: ValidatorStore<T, E>,
+    pub duties_service: DutiesService<T, E>,
+    /// `None` if the validator client is not running with a validators directory.
+    pub validator_watcher: Option<Arc<ValidatorWatcher<T, E>>>,
+    pub executor: TaskExecutor,
+    pub log: Logger,
+}
+
+/// Start the HTTP API server, returning the address it is listening on.
+pub fn start_server<T: SlotClock + 'static, E: EthSpec>(
+    executor: TaskExecutor,
+    config: &Config,
+    ctx: Arc<Context<T, E>>,
+) -> Result<SocketAddr, String> {
+    let log = executor.log().clone();
+
+    // Define the function that will build the request handler.
+    let make_service = make_service_fn(move |_socket: &AddrStream| {
+        let ctx = ctx.clone();
+
+        async move {
+            Ok::<_, hyper::Error>(service_fn(move |req: Request<Body>| {
+                route(req, ctx.clone())
+            }))
+        }
+    });
+
+    let bind_addr = (config.listen_address, config.port).into();
+    let server = Server::try_bind(&bind_addr)
+        .map_err(|e| format!("Unable to bind HTTP API to {}: {:?}", bind_addr, e))?
+        .serve(make_service);
+
+    // Determine the address the server is actually listening on.
+    //
+    // This may be different to `bind_addr` if bind port was 0 (this allows the OS to choose a free
+    // port).
+    let actual_listen_addr = server.local_addr();
+
+    // Configure the `hyper` server to gracefully shutdown when the shutdown channel is triggered.
+    let exit = executor.exit();
+    let inner_log = log.clone();
+    let server_exit = async move {
+        let _ = exit.await;
+        info!(inner_log, "HTTP API shutdown");
+    };
+
+    let inner_log = log.clone();
+    let server_future = server
+        .with_graceful_shutdown(server_exit)
+        .map_err(move |e| warn!(inner_log, "HTTP API server failed"; "error" => format!("{:?}", e)))
+        .unwrap_or_else(|_| ());
+
+    info!(
+        log,
+        "HTTP API started";
+        "address" => format!("{}", actual_listen_addr.ip()),
+        "port" => actual_listen_addr.port(),
+    );
+
+    executor.spawn_without_exit(server_future, "http_api");
+
+    Ok(actual_listen_addr)
+}
+
+async fn route<T: SlotClock + 'static, E: EthSpec>(
+    req: Request<Body>,
+    ctx: Arc<Context<T, E>>,
+) -> Result<Response<Body>, hyper::Error> {
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+
+    let response = match handle(req, &ctx).await {
+        Ok(response) => response,
+        Err(e) => {
+            debug!(
+                ctx.log,
+                "HTTP API request failed";
+                "error" => format!("{:?}", e),
+                "path" => &path,
+                "method" => format!("{}", method),
+            );
+            e.into()
+        }
+    };
+
+    Ok(response)
+}
+
+async fn handle<T: SlotClock + 'static, E: EthSpec>(
+    req: Request<Body>,
+    ctx: &Context<T, E>,
+) -> ApiResult {
+    if !ctx.api_token.authorizes(req.headers().get(AUTHORIZATION)) {
+        return Err(ApiError::Unauthorized(
+            "Missing or invalid API token".to_string(),
+        ));
+    }
+
+    let method = req.method().clone();
+    let path = req.uri().path().to_string();
+    let segments = path.trim_matches('/').split('/').collect::<Vec<_>>();
+
+    match (method, segments.as_slice()) {
+        (Method::GET, ["lighthouse", "validators"]) => handlers::get_validators(ctx),
+        (Method::POST, ["lighthouse", "validators", "keystore"]) => {
+            handlers::post_keystore(req, ctx).await
+        }
+        (Method::GET, ["lighthouse", "validators", pubkey]) => {
+            handlers::get_validator(handlers::parse_pubkey(pubkey)?, ctx)
+        }
+        (Method::PATCH, ["lighthouse", "validators", pubkey]) => {
+            handlers::patch_validator(req, handlers::parse_pubkey(pubkey)?, ctx).await
+        }
+        _ => Err(ApiError::NotFound(
+            "Request path and/or method not found.".to_owned(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::types::{KeystoreImportRequest, ValidatorData, ValidatorPatchRequest};
+    use super::*;
+    use crate::test_utils::{new_env, validator_store, E};
+    use environment::Environment;
+    use reqwest::{Client, Response, StatusCode};
+    use slot_clock::TestingSlotClock;
+    use std::fs;
+    use std::path::PathBuf;
+    use tempdir::TempDir;
+    use types::{test_utils::generate_deterministic_keypair, PublicKey};
+    use validator_dir::insecure_keys::generate_deterministic_keystore;
+    use validator_dir::{DISABLED_FILE, VOTING_KEYSTORE_FILE};
+
+    /// An HTTP API server for a validator client loading validators from a temporary directory.
+    struct ApiTester {
+        client: Client,
+        url: String,
+        token: String,
+        ctx: Arc<Context<TestingSlotClock, E>>,
+        validators_dir: TempDir,
+        secrets_dir: TempDir,
+        _data_dir: TempDir,
+    }
+
+    impl ApiTester {
+        fn new(env: &mut Environment<E>) -> Self {
+            let data_dir = TempDir::new("data").unwrap();
+            let validators_dir = TempDir::new("validators").unwrap();
+            let secrets_dir = TempDir::new("secrets").unwrap();
+
+            let context = env.core_context();
+            let (validator_store, duties_service) =
+                validator_store(&context, data_dir.path(), true);
+            let validator_watcher = Arc::new(ValidatorWatcher::new(
+                validators_dir.path().into(),
+                secrets_dir.path().into(),
+                validator_store.clone(),
+                context.clone(),
+            ));
+
+            let ctx = Arc::new(Context {
+                api_token: ApiToken::load_or_create(&data_dir.path().join(API_TOKEN_FILENAME))
+                    .unwrap(),
+                validator_store,
+                duties_service,
+                validator_watcher: Some(validator_watcher),
+                executor: context.executor.clone(),
+                log: context.log().clone(),
+            });
+
+            let config = Config {
+                enabled: true,
+                listen_address: Ipv4Addr::new(127, 0, 0, 1),
+                port: 0,
+            };
+            let listen_addr = env
+                .runtime()
+                .enter(|| start_server(context.executor.clone(), &config, ctx.clone()))
+                .unwrap();
+
+            let token = fs::read_to_string(data_dir.path().join(API_TOKEN_FILENAME)).unwrap();
+
+            Self {
+                client: Client::new(),
+                url: format!("http://{}", listen_addr),
+                token,
+                ctx,
+                validators_dir,
+                secrets_dir,
+                _data_dir: data_dir,
+            }
+        }
+
+        fn validator_dir(&self, voting_pubkey: &PublicKey) -> PathBuf {
+            self.validators_dir
+                .path()
+                .join(voting_pubkey.as_hex_string())
+        }
+
+        async fn import(&self, index: usize, enable: bool) -> Response {
+            let (keystore, password) = generate_deterministic_keystore(index).unwrap();
+            self.import_with_password(KeystoreImportRequest {
+                keystore,
+                password: String::from_utf8(password.as_bytes().to_vec()).unwrap(),
+                enable,
+            })
+            .await
+        }
+
+        async fn import_with_password(&self, request: KeystoreImportRequest) -> Response {
+            self.client
+                .post(&format!("{}/lighthouse/validators/keystore", self.url))
+                .bearer_auth(&self.token)
+                .json(&request)
+                .send()
+                .await
+                .unwrap()
+        }
+
+        async fn patch(&self, voting_pubkey: &PublicKey, enabled: bool) -> Response {
+            self.client
+                .patch(&format!(
+                    "{}/lighthouse/validators/{}",
+                    self.url,
+                    voting_pubkey.as_hex_string()
+                ))
+                .bearer_auth(&self.token)
+                .json(&ValidatorPatchRequest { enabled })
+                .send()
+                .await
+                .unwrap()
+        }
+
+        async fn validators(&self) -> Vec<ValidatorData> {
+            self.client
+                .get(&format!("{}/lighthouse/validators", self.url))
+                .bearer_auth(&self.token)
+                .send()
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap()
+        }
+    }
+
+    #[test]
+    fn import_saves_keystore() {
+        let mut env = new_env();
+        let tester = ApiTester::new(&mut env);
+
+        env.runtime().block_on(async {
+            let response = tester.import(0, true).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            let validator: ValidatorData = response.json().await.unwrap();
+            assert!(validator.enabled);
+
+            let dir = tester.validator_dir(&validator.voting_pubkey);
+            assert!(dir.join(VOTING_KEYSTORE_FILE).exists());
+            assert!(!dir.join(DISABLED_FILE).exists());
+            assert!(tester
+                .secrets_dir
+                .path()
+                .join(validator.voting_pubkey.as_hex_string())
+                .exists());
+
+            assert_eq!(tester.validators().await, vec![validator.clone()]);
+            assert_eq!(
+                tester
+                    .ctx
+                    .validator_store
+                    .is_enabled(&validator.voting_pubkey),
+                Some(true)
+            );
+
+            // The validator is not reloaded by a scan of the validators directory.
+            let validator_watcher = tester.ctx.validator_watcher.as_ref().unwrap();
+            validator_watcher.update().await.unwrap();
+            assert_eq!(validator_watcher.num_loaded(), 1);
+
+            // A validator cannot be imported twice.
+            assert_eq!(
+                tester.import(0, true).await.status(),
+                StatusCode::BAD_REQUEST
+            );
+        });
+    }
+
+    #[test]
+    fn import_disabled() {
+        let mut env = new_env();
+        let tester = ApiTester::new(&mut env);
+
+        env.runtime().block_on(async {
+            let validator: ValidatorData = tester.import(0, false).await.json().await.unwrap();
+            assert!(!validator.enabled);
+
+            let dir = tester.validator_dir(&validator.voting_pubkey);
+            assert!(dir.join(DISABLED_FILE).exists());
+            assert_eq!(
+                tester
+                    .ctx
+                    .validator_store
+                    .is_enabled(&validator.voting_pubkey),
+                Some(false)
+            );
+        });
+    }
+
+    #[test]
+    fn import_with_wrong_password() {
+        let mut env = new_env();
+        let tester = ApiTester::new(&mut env);
+
+        env.runtime().block_on(async {
+            let (keystore, _) = generate_deterministic_keystore(0).unwrap();
+            let response = tester
+                .import_with_password(KeystoreImportRequest {
+                    keystore,
+                    password: "wrong".to_string(),
+                    enable: true,
+                })
+                .await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+            assert!(tester.validators().await.is_empty());
+            assert_eq!(
+                fs::read_dir(tester.validators_dir.path()).unwrap().count(),
+                0
+            );
+        });
+    }
+
+    #[test]
+    fn disable_and_enable_are_saved() {
+        let mut env = new_env();
+        let tester = ApiTester::new(&mut env);
+
+        env.runtime().block_on(async {
+            let validator: ValidatorData = tester.import(0, true).await.json().await.unwrap();
+            let voting_pubkey = validator.voting_pubkey;
+            let disabled_file = tester.validator_dir(&voting_pubkey).join(DISABLED_FILE);
+
+            let response = tester.patch(&voting_pubkey, false).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert!(disabled_file.exists());
+            assert_eq!(
+                tester.validators().await,
+                vec![ValidatorData {
+                    voting_pubkey: voting_pubkey.clone(),
+                    enabled: false,
+                }]
+            );
+
+            let response = tester.patch(&voting_pubkey, true).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert!(!disabled_file.exists());
+            assert_eq!(
+                tester.ctx.validator_store.is_enabled(&voting_pubkey),
+                Some(true)
+            );
+
+            let unknown = generate_deterministic_keypair(1).pk;
+            assert_eq!(
+                tester.patch(&unknown, false).await.status(),
+                StatusCode::NOT_FOUND
+            );
+        });
+    }
+
+    #[test]
+    fn wrong_token_rejected() {
+        let mut env = new_env();
+        let tester = ApiTester::new(&mut env);
+
+        env.runtime().block_on(async {
+            let url = format!("{}/lighthouse/validators", tester.url);
+
+            let response = tester.client.get(&url).send().await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+            let response = tester
+                .client
+                .get(&url)
+                .bearer_auth(format!("{}0", tester.token))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+            // Requests which would change state are also rejected.
+            let (keystore, password) = generate_deterministic_keystore(0).unwrap();
+            let response = tester
+                .client
+                .post(&format!("{}/lighthouse/validators/keystore", tester.url))
+                .bearer_auth("wrong")
+                .json(&KeystoreImportRequest {
+                    keystore,
+                    password: String::from_utf8(password.as_bytes().to_vec()).unwrap(),
+                    enable: true,
+                })
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            assert!(tester.ctx.validator_store.validators().is_empty());
+        });
+    }
+}
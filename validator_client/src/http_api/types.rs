@@ -0,0 +1,40 @@
+use eth2_keystore::Keystore;
+use rest_types::ValidatorDuty;
+use serde_derive::{Deserialize, Serialize};
+use slashing_protection::SigningHistorySummary;
+use types::{Epoch, PublicKey};
+
+/// A validator known to the validator client.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorData {
+    pub voting_pubkey: PublicKey,
+    pub enabled: bool,
+}
+
+/// The state of a single validator, as returned by `GET /lighthouse/validators/{pubkey}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorStatus {
+    pub voting_pubkey: PublicKey,
+    pub enabled: bool,
+    /// The current epoch, to which `duties` apply.
+    pub epoch: Epoch,
+    /// `None` if duties for `epoch` have not yet been received from a beacon node.
+    pub duties: Option<ValidatorDuty>,
+    pub signing_history: SigningHistorySummary,
+}
+
+/// The body of `PATCH /lighthouse/validators/{pubkey}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidatorPatchRequest {
+    pub enabled: bool,
+}
+
+/// The body of `POST /lighthouse/validators/keystore`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeystoreImportRequest {
+    /// An EIP-2335 keystore.
+    pub keystore: Keystore,
+    pub password: String,
+    /// If `false`, the validator is imported but will not perform duties until it is enabled.
+    pub enable: bool,
+}
@@ -1,34 +1,48 @@
 mod attestation_service;
+mod beacon_node_fallback;
 mod block_service;
 mod cli;
 mod config;
+mod doppelganger_service;
 mod duties_service;
 mod fork_service;
+pub mod http_api;
 mod is_synced;
 mod notifier;
+mod signer;
+#[cfg(test)]
+mod test_utils;
 mod validator_store;
+mod validator_watcher;
 
 pub use cli::cli_app;
 pub use config::Config;
 
 use attestation_service::{AttestationService, AttestationServiceBuilder};
+use beacon_node_fallback::{BeaconNodeFallback, CandidateBeaconNode};
 use block_service::{BlockService, BlockServiceBuilder};
 use clap::ArgMatches;
 use config::SLASHING_PROTECTION_FILENAME;
+use doppelganger_service::{DoppelgangerService, DEFAULT_DOPPELGANGER_DETECTION_EPOCHS};
 use duties_service::{DutiesService, DutiesServiceBuilder};
 use environment::RuntimeContext;
 use fork_service::{ForkService, ForkServiceBuilder};
 use notifier::spawn_notifier;
 use remote_beacon_node::RemoteBeaconNode;
+use reqwest::{Client, Url};
+use signer::{LocalSigner, RemoteSigner, Signer};
 use slog::{error, info, warn, Logger};
 use slot_clock::SlotClock;
 use slot_clock::SystemTimeSlotClock;
+use std::fs;
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tokio::time::{delay_for, Duration};
-use types::{test_utils::generate_deterministic_keypair, EthSpec};
+use types::{test_utils::generate_deterministic_keypair, EthSpec, PublicKey};
 use validator_store::ValidatorStore;
+use validator_watcher::ValidatorWatcher;
 
-/// The interval between attempts to contact the beacon node during startup.
+/// The interval between attempts to contact the beacon nodes during startup.
 const RETRY_DELAY: Duration = Duration::from_secs(2);
 
 /// The global timeout for HTTP requests to the beacon node.
@@ -40,6 +54,9 @@ pub struct ProductionValidatorClient<T: EthSpec> {
     fork_service: ForkService<SystemTimeSlotClock, T>,
     block_service: BlockService<SystemTimeSlotClock, T>,
     attestation_service: AttestationService<SystemTimeSlotClock, T>,
+    beacon_nodes: Arc<BeaconNodeFallback<SystemTimeSlotClock, T>>,
+    validator_store: ValidatorStore<SystemTimeSlotClock, T>,
+    validator_watcher: Option<Arc<ValidatorWatcher<SystemTimeSlotClock, T>>>,
     config: Config,
 }
 
@@ -63,7 +80,7 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
         info!(
             log,
             "Starting validator client";
-            "beacon_node" => &config.http_server,
+            "beacon_nodes" => format!("{:?}", config.beacon_nodes),
             "datadir" => format!("{:?}", config.data_dir),
         );
 
@@ -75,22 +92,69 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             );
         }
 
-        let validators = (config.first_validator..=config.last_validator)
-            .map(generate_deterministic_keypair)
-            .collect::<Vec<_>>();
+        let validators = if let Some(url) = &config.remote_signer {
+            let validators = remote_signers(url).await?;
 
-        info!(
-            log,
-            "Decrypted validator keystores";
-            "count" => validators.len(),
-        );
+            info!(
+                log,
+                "Loaded validator keys from remote signer";
+                "count" => validators.len(),
+                "url" => url,
+            );
+
+            validators
+        } else if config.validators_dir.is_some() {
+            // Loaded by the `ValidatorWatcher` once the `ValidatorStore` exists.
+            vec![]
+        } else {
+            let validators = (config.first_validator..=config.last_validator)
+                .map(generate_deterministic_keypair)
+                .map(|keypair| {
+                    let signer: Arc<dyn Signer> = Arc::new(LocalSigner::new(keypair.clone()));
+                    (keypair.pk, signer)
+                })
+                .collect::<Vec<_>>();
 
-        let beacon_node =
-            RemoteBeaconNode::new_with_timeout(config.http_server.clone(), HTTP_TIMEOUT)
-                .map_err(|e| format!("Unable to init beacon node http client: {}", e))?;
+            info!(
+                log,
+                "Decrypted validator keystores";
+                "count" => validators.len(),
+            );
 
-        // TODO: check if all logs in wait_for_node are produed while awaiting
-        let beacon_node = wait_for_node(beacon_node, &log).await?;
+            validators
+        };
+
+        let beacon_node_api_token = config
+            .beacon_node_api_token_file
+            .as_ref()
+            .map(|path| {
+                fs::read_to_string(path)
+                    .map(|token| token.trim().to_string())
+                    .map_err(|e| format!("Unable to read API token at {:?}: {:?}", path, e))
+            })
+            .transpose()?;
+
+        let candidates = config
+            .beacon_nodes
+            .iter()
+            .map(|endpoint| {
+                RemoteBeaconNode::new_with_api_token(
+                    endpoint.clone(),
+                    HTTP_TIMEOUT,
+                    beacon_node_api_token.clone(),
+                )
+                .map(|beacon_node| CandidateBeaconNode::new(endpoint.clone(), beacon_node))
+                .map_err(|e| {
+                    format!(
+                        "Unable to init beacon node http client for {}: {}",
+                        endpoint, e
+                    )
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // The network parameters are read from the first beacon node to respond.
+        let beacon_node = wait_for_node(&candidates, &log).await?;
         let eth2_config = beacon_node
             .http
             .spec()
@@ -163,9 +227,18 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             Duration::from_millis(context.eth2_config.spec.milliseconds_per_slot),
         );
 
+        let beacon_nodes = Arc::new(BeaconNodeFallback::new(
+            candidates,
+            slot_clock.clone(),
+            config.allow_unsynced_beacon_node,
+            log.clone(),
+        ));
+        // Establish the status of each node before any service makes a request.
+        beacon_nodes.update_all_candidates().await;
+
         let fork_service = ForkServiceBuilder::new()
             .slot_clock(slot_clock.clone())
-            .beacon_node(beacon_node.clone())
+            .beacon_nodes(beacon_nodes.clone())
             .runtime_context(context.service_context("fork".into()))
             .build()?;
 
@@ -178,6 +251,27 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             log.clone(),
         )?;
 
+        let validator_watcher = if let Some(validators_dir) = &config.validators_dir {
+            let validator_watcher = Arc::new(ValidatorWatcher::new(
+                validators_dir.clone(),
+                config.secrets_dir.clone(),
+                validator_store.clone(),
+                context.service_context("validator_watcher".into()),
+            ));
+            validator_watcher.update().await?;
+
+            info!(
+                log,
+                "Loaded validators from directory";
+                "count" => validator_watcher.num_loaded(),
+                "validators_dir" => format!("{:?}", validators_dir),
+            );
+
+            Some(validator_watcher)
+        } else {
+            None
+        };
+
         info!(
             log,
             "Loaded validator keypair store";
@@ -190,27 +284,35 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             info!(log, "Validator auto-registration complete");
         }
 
+        if config.minimal_slashing_protection {
+            info!(
+                log,
+                "Pruning slashing protection database";
+                "history_epochs" => validator_store::SLASHING_PROTECTION_HISTORY_EPOCHS,
+            );
+            validator_store.prune_slashing_protection_db();
+        }
+
         let duties_service = DutiesServiceBuilder::new()
             .slot_clock(slot_clock.clone())
             .validator_store(validator_store.clone())
-            .beacon_node(beacon_node.clone())
+            .beacon_nodes(beacon_nodes.clone())
             .runtime_context(context.service_context("duties".into()))
-            .allow_unsynced_beacon_node(config.allow_unsynced_beacon_node)
             .build()?;
 
         let block_service = BlockServiceBuilder::new()
             .duties_service(duties_service.clone())
             .slot_clock(slot_clock.clone())
             .validator_store(validator_store.clone())
-            .beacon_node(beacon_node.clone())
+            .beacon_nodes(beacon_nodes.clone())
             .runtime_context(context.service_context("block".into()))
             .build()?;
 
         let attestation_service = AttestationServiceBuilder::new()
             .duties_service(duties_service.clone())
             .slot_clock(slot_clock)
-            .validator_store(validator_store)
-            .beacon_node(beacon_node)
+            .validator_store(validator_store.clone())
+            .beacon_nodes(beacon_nodes.clone())
             .runtime_context(context.service_context("attestation".into()))
             .build()?;
 
@@ -220,11 +322,19 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             fork_service,
             block_service,
             attestation_service,
+            beacon_nodes,
+            validator_store,
+            validator_watcher,
             config,
         })
     }
 
     pub fn start_service(&mut self) -> Result<(), String> {
+        self.beacon_nodes
+            .clone()
+            .start_update_service(&self.context)
+            .map_err(|e| format!("Unable to start beacon node fallback service: {}", e))?;
+
         self.duties_service
             .clone()
             .start_update_service(&self.context.eth2_config.spec)
@@ -235,15 +345,66 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
             .start_update_service(&self.context.eth2_config.spec)
             .map_err(|e| format!("Unable to start fork service: {}", e))?;
 
-        self.block_service
-            .clone()
-            .start_update_service(&self.context.eth2_config.spec)
-            .map_err(|e| format!("Unable to start block service: {}", e))?;
+        // The services which sign messages, which must not start until doppelganger detection
+        // (if enabled) has completed.
+        let block_service = self.block_service.clone();
+        let attestation_service = self.attestation_service.clone();
+        let spec = self.context.eth2_config.spec.clone();
+        let start_signing_services = move || {
+            block_service
+                .start_update_service(&spec)
+                .map_err(|e| format!("Unable to start block service: {}", e))?;
+
+            attestation_service
+                .start_update_service(&spec)
+                .map_err(|e| format!("Unable to start attestation service: {}", e))
+        };
+
+        if self.config.enable_doppelganger_detection {
+            DoppelgangerService::new(
+                self.duties_service.clone(),
+                self.validator_store.clone(),
+                self.beacon_nodes.clone(),
+                self.duties_service.slot_clock.clone(),
+                DEFAULT_DOPPELGANGER_DETECTION_EPOCHS,
+                self.context.service_context("doppelganger".into()),
+            )
+            .start(start_signing_services)
+            .map_err(|e| format!("Unable to start doppelganger service: {}", e))?;
+        } else {
+            start_signing_services()?;
+        }
 
-        self.attestation_service
-            .clone()
-            .start_update_service(&self.context.eth2_config.spec)
-            .map_err(|e| format!("Unable to start attestation service: {}", e))?;
+        if let Some(validator_watcher) = &self.validator_watcher {
+            validator_watcher
+                .clone()
+                .start_update_service()
+                .map_err(|e| format!("Unable to start validator watcher: {}", e))?;
+        }
+
+        if self.config.http_api.enabled {
+            let api_token = http_api::ApiToken::load_or_create(
+                &self.config.data_dir.join(http_api::API_TOKEN_FILENAME),
+            )?;
+            info!(
+                self.context.log(),
+                "Loaded HTTP API token";
+                "path" => format!("{:?}", api_token.path()),
+            );
+
+            let context = self.context.service_context("http_api".into());
+            let ctx = Arc::new(http_api::Context {
+                api_token,
+                validator_store: self.validator_store.clone(),
+                duties_service: self.duties_service.clone(),
+                validator_watcher: self.validator_watcher.clone(),
+                executor: context.executor.clone(),
+                log: context.log().clone(),
+            });
+
+            http_api::start_server(context.executor, &self.config.http_api, ctx)
+                .map_err(|e| format!("Unable to start HTTP API: {}", e))?;
+        }
 
         spawn_notifier(self).map_err(|e| format!("Failed to start notifier: {}", e))?;
 
@@ -251,41 +412,71 @@ impl<T: EthSpec> ProductionValidatorClient<T> {
     }
 }
 
-/// Request the version from the node, looping back and trying again on failure. Exit once the node
-/// has been contacted.
+/// Request the version from each node in turn, looping back and trying again once all nodes have
+/// failed. Exit once any node has been contacted, returning that node.
 async fn wait_for_node<E: EthSpec>(
-    beacon_node: RemoteBeaconNode<E>,
+    candidates: &[CandidateBeaconNode<E>],
     log: &Logger,
 ) -> Result<RemoteBeaconNode<E>, String> {
-    // Try to get the version string from the node, looping until success is returned.
+    // Try to get the version string from a node, looping until success is returned.
     loop {
-        let log = log.clone();
-        let result = beacon_node
-            .clone()
-            .http
-            .node()
-            .get_version()
-            .await
-            .map_err(|e| format!("{:?}", e));
-
-        match result {
-            Ok(version) => {
-                info!(
-                    log,
-                    "Connected to beacon node";
-                    "version" => version,
-                );
-
-                return Ok(beacon_node);
-            }
-            Err(e) => {
-                error!(
-                    log,
-                    "Unable to connect to beacon node";
-                    "error" => format!("{:?}", e),
-                );
-                delay_for(RETRY_DELAY).await;
+        for candidate in candidates {
+            let result = candidate
+                .beacon_node
+                .http
+                .node()
+                .get_version()
+                .await
+                .map_err(|e| format!("{:?}", e));
+
+            match result {
+                Ok(version) => {
+                    info!(
+                        log,
+                        "Connected to beacon node";
+                        "version" => version,
+                        "endpoint" => &candidate.endpoint,
+                    );
+
+                    return Ok(candidate.beacon_node.clone());
+                }
+                Err(e) => {
+                    error!(
+                        log,
+                        "Unable to connect to beacon node";
+                        "error" => format!("{:?}", e),
+                        "endpoint" => &candidate.endpoint,
+                    );
+                }
             }
         }
+
+        delay_for(RETRY_DELAY).await;
     }
 }
+
+/// Request the list of public keys held by the remote signer at `url`, returning a signer for
+/// each of them.
+async fn remote_signers(url: &str) -> Result<Vec<(PublicKey, Arc<dyn Signer>)>, String> {
+    let url = Url::parse(url).map_err(|e| format!("Invalid remote signer URL: {:?}", e))?;
+    let client = Client::builder()
+        .timeout(HTTP_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Unable to init remote signer http client: {:?}", e))?;
+
+    let public_keys = RemoteSigner::public_keys(&client, &url)
+        .await
+        .map_err(|e| format!("Unable to read public keys from remote signer: {:?}", e))?;
+
+    Ok(public_keys
+        .into_iter()
+        .map(|public_key| {
+            let signer: Arc<dyn Signer> = Arc::new(RemoteSigner::new(
+                client.clone(),
+                url.clone(),
+                public_key.clone(),
+            ));
+            (public_key, signer)
+        })
+        .collect())
+}
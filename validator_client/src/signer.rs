@@ -0,0 +1,282 @@
+//! Backends which produce signatures on behalf of the `ValidatorStore`.
+//!
+//! Slashing protection is enforced by the `ValidatorStore` before any `Signer` is called, so a
+//! `Signer` may sign any signing root it receives.
+
+use futures::future;
+use reqwest::{Client, Url};
+use serde_derive::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
+use types::{Epoch, Fork, Hash256, Keypair, PublicKey, Signature};
+
+/// The type of message being signed, provided so that remote signers may apply their own policy.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MessageType {
+    RandaoReveal,
+    BeaconBlock,
+    Attestation,
+    AggregateAndProof,
+    SelectionProof,
+}
+
+/// The information used to compute a signing root, excluding the message itself.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SigningContext {
+    pub message_type: MessageType,
+    /// The epoch used to select the fork version of the signing domain.
+    pub epoch: Epoch,
+    pub fork: Fork,
+    pub genesis_validators_root: Hash256,
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// The URL of the remote signer could not be used as a base for requests.
+    InvalidUrl(Url),
+    /// The request to the remote signer failed.
+    Http(reqwest::Error),
+    /// The remote signer returned a signature which is not valid for the validator.
+    InvalidSignature,
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Error::Http(e)
+    }
+}
+
+pub type SignatureFuture<'a> = Pin<Box<dyn Future<Output = Result<Signature, Error>> + Send + 'a>>;
+
+/// Produces signatures for a single validator.
+pub trait Signer: Send + Sync {
+    /// Sign `signing_root`, which was computed from the given `context`.
+    fn sign(&self, signing_root: Hash256, context: SigningContext) -> SignatureFuture<'_>;
+}
+
+/// Signs with a secret key held in memory.
+pub struct LocalSigner {
+    voting_keypair: Keypair,
+}
+
+impl LocalSigner {
+    pub fn new(voting_keypair: Keypair) -> Self {
+        Self { voting_keypair }
+    }
+}
+
+/// We derive our own `PartialEq` to avoid doing equality checks between secret keys.
+///
+/// It's nice to avoid secret key comparisons from a security perspective, but it's also a little
+/// risky when it comes to `HashMap` integrity (that's why we need `PartialEq`).
+///
+/// Currently, we obtain keypairs from keystores where we derive the `PublicKey` from a `SecretKey`
+/// via a hash function. In order to have two equal `PublicKey` with different `SecretKey` we would
+/// need to have either:
+///
+/// - A serious upstream integrity error.
+/// - A hash collision.
+///
+/// It seems reasonable to make these two assumptions in order to avoid the equality checks.
+impl PartialEq for LocalSigner {
+    fn eq(&self, other: &Self) -> bool {
+        self.voting_keypair.pk == other.voting_keypair.pk
+    }
+}
+
+impl Signer for LocalSigner {
+    fn sign(&self, signing_root: Hash256, _context: SigningContext) -> SignatureFuture<'_> {
+        let signature = Signature::new(signing_root.as_bytes(), &self.voting_keypair.sk);
+        Box::pin(future::ready(Ok(signature)))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct SignRequest {
+    signing_root: Hash256,
+    #[serde(flatten)]
+    context: SigningContext,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SignResponse {
+    signature: Signature,
+}
+
+/// Signs by sending the signing root to a remote signer over HTTP.
+///
+/// The remote signer is expected to provide the following endpoints:
+///
+/// - `GET <url>/keys`: returns a JSON list of the public keys it is able to sign for.
+/// - `POST <url>/sign/<public key>`: accepts a JSON `SignRequest` and returns a JSON
+///   `SignResponse`.
+pub struct RemoteSigner {
+    client: Client,
+    url: Url,
+    voting_pubkey: PublicKey,
+}
+
+impl RemoteSigner {
+    pub fn new(client: Client, url: Url, voting_pubkey: PublicKey) -> Self {
+        Self {
+            client,
+            url,
+            voting_pubkey,
+        }
+    }
+
+    /// Returns the public keys of all the validators for which the remote signer holds keys.
+    pub async fn public_keys(client: &Client, url: &Url) -> Result<Vec<PublicKey>, Error> {
+        let keys = client
+            .get(endpoint(url, &["keys"])?)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(keys)
+    }
+}
+
+impl Signer for RemoteSigner {
+    fn sign(&self, signing_root: Hash256, context: SigningContext) -> SignatureFuture<'_> {
+        Box::pin(async move {
+            let url = endpoint(&self.url, &["sign", &self.voting_pubkey.as_hex_string()])?;
+            let response: SignResponse = self
+                .client
+                .post(url)
+                .json(&SignRequest {
+                    signing_root,
+                    context,
+                })
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+
+            // Never pass on a signature that would be rejected by the network, it may hide a
+            // misconfigured signer.
+            if response
+                .signature
+                .verify(signing_root.as_bytes(), &self.voting_pubkey)
+            {
+                Ok(response.signature)
+            } else {
+                Err(Error::InvalidSignature)
+            }
+        })
+    }
+}
+
+/// Appends `segments` to the path of `base`.
+fn endpoint(base: &Url, segments: &[&str]) -> Result<Url, Error> {
+    let mut url = base.clone();
+    url.path_segments_mut()
+        .map_err(|()| Error::InvalidUrl(base.clone()))?
+        .pop_if_empty()
+        .extend(segments);
+    Ok(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyper::service::{make_service_fn, service_fn};
+    use hyper::{Body, Request, Response, Server};
+    use std::convert::Infallible;
+    use types::{test_utils::generate_deterministic_keypair, ChainSpec, Domain, SignedRoot};
+
+    /// A remote signer which holds a single keypair and signs every request.
+    async fn mock_signer(
+        request: Request<Body>,
+        keypair: Keypair,
+    ) -> Result<Response<Body>, Infallible> {
+        let path = request.uri().path().to_string();
+
+        let body = if path == "/signer/keys" {
+            serde_json::to_vec(&vec![keypair.pk.clone()]).unwrap()
+        } else if path.starts_with("/signer/sign/") {
+            let bytes = hyper::body::to_bytes(request.into_body()).await.unwrap();
+            let request: SignRequest = serde_json::from_slice(&bytes).unwrap();
+            serde_json::to_vec(&SignResponse {
+                signature: Signature::new(request.signing_root.as_bytes(), &keypair.sk),
+            })
+            .unwrap()
+        } else {
+            return Ok(Response::builder().status(404).body(Body::empty()).unwrap());
+        };
+
+        Ok(Response::new(Body::from(body)))
+    }
+
+    /// Start the mock signer on a random port, returning its URL.
+    fn spawn_mock_signer(keypair: Keypair) -> Url {
+        let make_service = make_service_fn(move |_| {
+            let keypair = keypair.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |request| {
+                    mock_signer(request, keypair.clone())
+                }))
+            }
+        });
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+        let url = Url::parse(&format!("http://{}/signer/", server.local_addr())).unwrap();
+        tokio::spawn(server);
+        url
+    }
+
+    fn context(epoch: Epoch) -> (Hash256, SigningContext) {
+        let spec = ChainSpec::minimal();
+        let fork = Fork {
+            previous_version: spec.genesis_fork_version,
+            current_version: spec.genesis_fork_version,
+            epoch: Epoch::new(0),
+        };
+        let genesis_validators_root = Hash256::repeat_byte(0x42);
+        let domain = spec.get_domain(epoch, Domain::Randao, &fork, genesis_validators_root);
+        let signing_context = SigningContext {
+            message_type: MessageType::RandaoReveal,
+            epoch,
+            fork,
+            genesis_validators_root,
+        };
+        (epoch.signing_root(domain), signing_context)
+    }
+
+    #[tokio::test]
+    async fn remote_signer_matches_local_signer() {
+        let keypair = generate_deterministic_keypair(0);
+        let url = spawn_mock_signer(keypair.clone());
+        let client = Client::new();
+
+        let public_keys = RemoteSigner::public_keys(&client, &url).await.unwrap();
+        assert_eq!(public_keys, vec![keypair.pk.clone()]);
+
+        let remote = RemoteSigner::new(client, url, keypair.pk.clone());
+        let local = LocalSigner::new(keypair);
+
+        let (signing_root, signing_context) = context(Epoch::new(1));
+        assert_eq!(
+            remote
+                .sign(signing_root, signing_context.clone())
+                .await
+                .unwrap(),
+            local.sign(signing_root, signing_context).await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn remote_signer_rejects_invalid_signature() {
+        // The signer holds a different key to the one it claims to sign for.
+        let url = spawn_mock_signer(generate_deterministic_keypair(1));
+        let remote = RemoteSigner::new(Client::new(), url, generate_deterministic_keypair(0).pk);
+
+        let (signing_root, signing_context) = context(Epoch::new(0));
+        assert!(matches!(
+            remote.sign(signing_root, signing_context).await,
+            Err(Error::InvalidSignature)
+        ));
+    }
+}
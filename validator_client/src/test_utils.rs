@@ -0,0 +1,79 @@
+//! Helpers for testing the services which depend upon a `ValidatorStore`.
+
+use crate::beacon_node_fallback::BeaconNodeFallback;
+use crate::config::Config;
+use crate::duties_service::{DutiesService, DutiesServiceBuilder};
+use crate::fork_service::ForkServiceBuilder;
+use crate::validator_store::ValidatorStore;
+use environment::{Environment, EnvironmentBuilder, RuntimeContext};
+use slot_clock::{SlotClock, TestingSlotClock};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use types::{EthSpec, Hash256, MinimalEthSpec, Slot};
+
+pub type E = MinimalEthSpec;
+
+pub fn new_env() -> Environment<E> {
+    EnvironmentBuilder::minimal()
+        .single_thread_tokio_runtime()
+        .expect("should start tokio runtime")
+        .null_logger()
+        .expect("should start null logger")
+        .build()
+        .expect("should build env")
+}
+
+/// Build a `ValidatorStore` without any validators, with its slashing protection database in
+/// `data_dir`, and a `DutiesService` for it. `auto_register` is as per `--auto-register`.
+///
+/// The services have no beacon nodes, so they never learn the fork or any duties.
+pub fn validator_store(
+    context: &RuntimeContext<E>,
+    data_dir: &Path,
+    auto_register: bool,
+) -> (
+    ValidatorStore<TestingSlotClock, E>,
+    DutiesService<TestingSlotClock, E>,
+) {
+    let slot_clock =
+        TestingSlotClock::new(Slot::new(0), Duration::from_secs(0), Duration::from_secs(1));
+    let beacon_nodes = Arc::new(BeaconNodeFallback::new(
+        vec![],
+        slot_clock.clone(),
+        false,
+        context.log().clone(),
+    ));
+
+    let fork_service = ForkServiceBuilder::new()
+        .slot_clock(slot_clock.clone())
+        .beacon_nodes(beacon_nodes.clone())
+        .runtime_context(context.service_context("fork".into()))
+        .build()
+        .expect("should build fork service");
+
+    let config = Config {
+        data_dir: data_dir.into(),
+        auto_register,
+        ..Config::default()
+    };
+    let validator_store = ValidatorStore::new(
+        vec![],
+        &config,
+        Hash256::zero(),
+        E::default_spec(),
+        fork_service,
+        context.log().clone(),
+    )
+    .expect("should build validator store");
+
+    let duties_service = DutiesServiceBuilder::new()
+        .slot_clock(slot_clock)
+        .validator_store(validator_store.clone())
+        .beacon_nodes(beacon_nodes)
+        .runtime_context(context.service_context("duties".into()))
+        .build()
+        .expect("should build duties service");
+
+    (validator_store, duties_service)
+}
@@ -1,46 +1,38 @@
 use crate::config::SLASHING_PROTECTION_FILENAME;
+use crate::signer::{MessageType, Signer, SigningContext};
 use crate::{config::Config, fork_service::ForkService};
 use parking_lot::RwLock;
-use slashing_protection::{NotSafe, Safe, SlashingDatabase};
-use slog::{crit, error, warn, Logger};
+use slashing_protection::{NotSafe, Safe, SigningHistorySummary, SlashingDatabase};
+use slog::{crit, debug, error, warn, Logger};
 use slot_clock::SlotClock;
 use std::collections::HashMap;
-use std::iter::FromIterator;
 use std::marker::PhantomData;
 use std::sync::Arc;
 use tempdir::TempDir;
 use types::{
-    Attestation, BeaconBlock, ChainSpec, Domain, Epoch, EthSpec, Fork, Hash256, Keypair, PublicKey,
-    SelectionProof, Signature, SignedAggregateAndProof, SignedBeaconBlock, SignedRoot, Slot,
+    AggregateAndProof, Attestation, BeaconBlock, ChainSpec, Domain, Epoch, EthSpec, Fork, Hash256,
+    PublicKey, SelectionProof, Signature, SignedAggregateAndProof, SignedBeaconBlock, SignedRoot,
+    Slot,
 };
 
-struct LocalValidator {
-    voting_keypair: Keypair,
-}
+/// The number of epochs of signing history retained for each validator when running with
+/// minimal slashing protection.
+pub const SLASHING_PROTECTION_HISTORY_EPOCHS: u64 = 512;
 
-/// We derive our own `PartialEq` to avoid doing equality checks between secret keys.
-///
-/// It's nice to avoid secret key comparisons from a security perspective, but it's also a little
-/// risky when it comes to `HashMap` integrity (that's why we need `PartialEq`).
-///
-/// Currently, we obtain keypairs from keystores where we derive the `PublicKey` from a `SecretKey`
-/// via a hash function. In order to have two equal `PublicKey` with different `SecretKey` we would
-/// need to have either:
-///
-/// - A serious upstream integrity error.
-/// - A hash collision.
-///
-/// It seems reasonable to make these two assumptions in order to avoid the equality checks.
-impl PartialEq for LocalValidator {
-    fn eq(&self, other: &Self) -> bool {
-        self.voting_keypair.pk == other.voting_keypair.pk
-    }
+/// A validator known to the `ValidatorStore`.
+struct Validator {
+    signer: Arc<dyn Signer>,
+    /// Disabled validators do not perform any duties, and will not sign any message.
+    enabled: bool,
 }
 
 #[derive(Clone)]
 pub struct ValidatorStore<T, E: EthSpec> {
-    validators: Arc<RwLock<HashMap<PublicKey, LocalValidator>>>,
+    validators: Arc<RwLock<HashMap<PublicKey, Validator>>>,
     slashing_protection: SlashingDatabase,
+    /// Register validators added at runtime with the slashing protection database.
+    auto_register: bool,
+    prune_slashing_protection: bool,
     genesis_validators_root: Hash256,
     spec: Arc<ChainSpec>,
     log: Logger,
@@ -51,7 +43,7 @@ pub struct ValidatorStore<T, E: EthSpec> {
 
 impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
     pub fn new(
-        validators: Vec<Keypair>,
+        validators: Vec<(PublicKey, Arc<dyn Signer>)>,
         config: &Config,
         genesis_validators_root: Hash256,
         spec: ChainSpec,
@@ -67,13 +59,22 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
                 )
             })?;
 
-        let validator_key_values = validators
-            .into_iter()
-            .map(|kp| (kp.pk.clone(), LocalValidator { voting_keypair: kp }));
-
         Ok(Self {
-            validators: Arc::new(RwLock::new(HashMap::from_iter(validator_key_values))),
+            validators: Arc::new(RwLock::new(
+                validators
+                    .into_iter()
+                    .map(|(pubkey, signer)| {
+                        let validator = Validator {
+                            signer,
+                            enabled: true,
+                        };
+                        (pubkey, validator)
+                    })
+                    .collect(),
+            )),
             slashing_protection,
+            auto_register: config.auto_register,
+            prune_slashing_protection: config.minimal_slashing_protection,
             genesis_validators_root,
             spec: Arc::new(spec),
             log,
@@ -87,22 +88,131 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
     ///
     /// Registration is required to protect against a lost or missing slashing database,
     /// such as when relocating validator keys to a new machine.
+    ///
+    /// Validators which are already registered are skipped.
     pub fn register_all_validators_for_slashing_protection(&self) -> Result<(), String> {
-        self.slashing_protection
-            .register_validators(self.validators.read().keys())
+        self.validators
+            .read()
+            .keys()
+            .try_for_each(|pubkey| {
+                self.slashing_protection
+                    .register_validator_if_absent(pubkey)
+            })
             .map_err(|e| format!("Error while registering validators: {:?}", e))
     }
 
+    /// Prune the slashing protection database if running with `--minimal-slashing-protection`,
+    /// otherwise do nothing.
+    pub fn prune_slashing_protection_db(&self) {
+        if !self.prune_slashing_protection {
+            return;
+        }
+
+        let block_window = SLASHING_PROTECTION_HISTORY_EPOCHS * E::slots_per_epoch();
+        match self
+            .slashing_protection
+            .prune(block_window, SLASHING_PROTECTION_HISTORY_EPOCHS)
+        {
+            Ok(()) => debug!(self.log, "Pruned slashing protection database"),
+            Err(e) => error!(
+                self.log,
+                "Error pruning slashing protection database";
+                "error" => format!("{:?}", e)
+            ),
+        }
+    }
+
+    /// Add a validator at runtime.
+    ///
+    /// If running with `--auto-register` the validator is registered with the slashing protection
+    /// database, unless it is already registered. Otherwise it must already be registered in order
+    /// to sign.
+    ///
+    /// Returns an error if the validator is already known.
+    pub fn add_validator(
+        &self,
+        voting_pubkey: PublicKey,
+        signer: Arc<dyn Signer>,
+        enabled: bool,
+    ) -> Result<(), String> {
+        let mut validators = self.validators.write();
+
+        if validators.contains_key(&voting_pubkey) {
+            return Err(format!("Validator {:?} is already known", voting_pubkey));
+        }
+
+        if self.auto_register {
+            self.slashing_protection
+                .register_validator_if_absent(&voting_pubkey)
+                .map_err(|e| format!("Error while registering validator: {:?}", e))?;
+        }
+
+        validators.insert(voting_pubkey, Validator { signer, enabled });
+
+        Ok(())
+    }
+
+    /// Stop signing for a validator, returning `false` if the validator is unknown.
+    ///
+    /// The validator's history is retained in the slashing protection database.
+    pub fn remove_validator(&self, voting_pubkey: &PublicKey) -> bool {
+        self.validators.write().remove(voting_pubkey).is_some()
+    }
+
+    /// Enable or disable a validator, returning `false` if the validator is unknown.
+    pub fn set_validator_enabled(&self, voting_pubkey: &PublicKey, enabled: bool) -> bool {
+        self.validators
+            .write()
+            .get_mut(voting_pubkey)
+            .map(|validator| validator.enabled = enabled)
+            .is_some()
+    }
+
+    /// Returns the public key of every known validator, and whether it is enabled.
+    pub fn validators(&self) -> Vec<(PublicKey, bool)> {
+        self.validators
+            .read()
+            .iter()
+            .map(|(pubkey, validator)| (pubkey.clone(), validator.enabled))
+            .collect()
+    }
+
+    /// Returns whether the validator is enabled, or `None` if it is unknown.
+    pub fn is_enabled(&self, voting_pubkey: &PublicKey) -> Option<bool> {
+        self.validators
+            .read()
+            .get(voting_pubkey)
+            .map(|validator| validator.enabled)
+    }
+
+    /// Summarise the most recent messages signed by a validator, from the slashing protection
+    /// database.
+    pub fn signing_history(
+        &self,
+        voting_pubkey: &PublicKey,
+    ) -> Result<SigningHistorySummary, String> {
+        self.slashing_protection
+            .signing_history_summary(voting_pubkey)
+            .map_err(|e| format!("Unable to read slashing protection database: {:?}", e))
+    }
+
+    /// Returns the public keys of the enabled validators.
     pub fn voting_pubkeys(&self) -> Vec<PublicKey> {
         self.validators
             .read()
             .iter()
-            .map(|(pubkey, _dir)| pubkey.clone())
+            .filter(|(_pubkey, validator)| validator.enabled)
+            .map(|(pubkey, _validator)| pubkey.clone())
             .collect()
     }
 
+    /// Returns the number of enabled validators.
     pub fn num_voting_validators(&self) -> usize {
-        self.validators.read().len()
+        self.validators
+            .read()
+            .values()
+            .filter(|validator| validator.enabled)
+            .count()
     }
 
     fn fork(&self) -> Option<Fork> {
@@ -115,26 +225,72 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         self.fork_service.fork()
     }
 
-    pub fn randao_reveal(&self, validator_pubkey: &PublicKey, epoch: Epoch) -> Option<Signature> {
-        // TODO: check this against the slot clock to make sure it's not an early reveal?
-        self.validators
-            .read()
-            .get(validator_pubkey)
-            .and_then(|local_validator| {
-                let voting_keypair = &local_validator.voting_keypair;
-                let domain = self.spec.get_domain(
-                    epoch,
-                    Domain::Randao,
-                    &self.fork()?,
-                    self.genesis_validators_root,
+    /// Returns the signer for `validator_pubkey`, if the validator is known and enabled.
+    ///
+    /// The signer is cloned out of the lock so that it may be used across an `await`.
+    fn signer(&self, validator_pubkey: &PublicKey) -> Option<Arc<dyn Signer>> {
+        match self.validators.read().get(validator_pubkey) {
+            Some(validator) if validator.enabled => Some(validator.signer.clone()),
+            Some(_) => {
+                warn!(
+                    self.log,
+                    "Not signing for disabled validator";
+                    "public_key" => format!("{:?}", validator_pubkey)
                 );
-                let message = epoch.signing_root(domain);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Sign `signing_root` with `signer`, logging any error.
+    async fn sign(
+        &self,
+        signer: &dyn Signer,
+        signing_root: Hash256,
+        message_type: MessageType,
+        epoch: Epoch,
+        fork: Fork,
+    ) -> Option<Signature> {
+        let context = SigningContext {
+            message_type,
+            epoch,
+            fork,
+            genesis_validators_root: self.genesis_validators_root,
+        };
 
-                Some(Signature::new(message.as_bytes(), &voting_keypair.sk))
+        signer
+            .sign(signing_root, context)
+            .await
+            .map_err(|e| {
+                error!(
+                    self.log,
+                    "Error whilst signing";
+                    "message_type" => format!("{:?}", message_type),
+                    "error" => format!("{:?}", e)
+                )
             })
+            .ok()
     }
 
-    pub fn sign_block(
+    pub async fn randao_reveal(
+        &self,
+        validator_pubkey: &PublicKey,
+        epoch: Epoch,
+    ) -> Option<Signature> {
+        // TODO: check this against the slot clock to make sure it's not an early reveal?
+        let signer = self.signer(validator_pubkey)?;
+        let fork = self.fork()?;
+        let domain =
+            self.spec
+                .get_domain(epoch, Domain::Randao, &fork, self.genesis_validators_root);
+        let message = epoch.signing_root(domain);
+
+        self.sign(&*signer, message, MessageType::RandaoReveal, epoch, fork)
+            .await
+    }
+
+    pub async fn sign_block(
         &self,
         validator_pubkey: &PublicKey,
         block: BeaconBlock<E>,
@@ -151,6 +307,8 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
             return None;
         }
 
+        let signer = self.signer(validator_pubkey)?;
+
         // Check for slashing conditions.
         let fork = self.fork()?;
         let domain = self.spec.get_domain(
@@ -169,16 +327,21 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         match slashing_status {
             // We can safely sign this block.
             Ok(Safe::Valid) => {
-                let validators = self.validators.read();
-                let validator = validators.get(validator_pubkey)?;
-                let voting_keypair = &validator.voting_keypair;
-
-                Some(block.sign(
-                    &voting_keypair.sk,
-                    &fork,
-                    self.genesis_validators_root,
-                    &self.spec,
-                ))
+                let message = block.signing_root(domain);
+                let signature = self
+                    .sign(
+                        &*signer,
+                        message,
+                        MessageType::BeaconBlock,
+                        block.epoch(),
+                        fork,
+                    )
+                    .await?;
+
+                Some(SignedBeaconBlock {
+                    message: block,
+                    signature,
+                })
             }
             Ok(Safe::SameData) => {
                 warn!(
@@ -207,7 +370,7 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         }
     }
 
-    pub fn sign_attestation(
+    pub async fn sign_attestation(
         &self,
         validator_pubkey: &PublicKey,
         validator_committee_position: usize,
@@ -219,6 +382,8 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
             return None;
         }
 
+        let signer = self.signer(validator_pubkey)?;
+
         // Checking for slashing conditions.
         let fork = self.fork()?;
 
@@ -237,26 +402,46 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
         match slashing_status {
             // We can safely sign this attestation.
             Ok(Safe::Valid) => {
-                let validators = self.validators.read();
-                let validator = validators.get(validator_pubkey)?;
-                let voting_keypair = &validator.voting_keypair;
-
-                attestation
-                    .sign(
-                        &voting_keypair.sk,
-                        validator_committee_position,
-                        &fork,
-                        self.genesis_validators_root,
-                        &self.spec,
-                    )
-                    .map_err(|e| {
+                match attestation
+                    .aggregation_bits
+                    .get(validator_committee_position)
+                {
+                    Ok(false) => (),
+                    Ok(true) => {
+                        error!(
+                            self.log,
+                            "Error whilst signing attestation";
+                            "error" => "already signed",
+                            "committee_position" => validator_committee_position,
+                        );
+                        return None;
+                    }
+                    Err(e) => {
                         error!(
                             self.log,
                             "Error whilst signing attestation";
                             "error" => format!("{:?}", e)
-                        )
-                    })
+                        );
+                        return None;
+                    }
+                }
+
+                let message = attestation.data.signing_root(domain);
+                let signature = self
+                    .sign(
+                        &*signer,
+                        message,
+                        MessageType::Attestation,
+                        attestation.data.target.epoch,
+                        fork,
+                    )
+                    .await?;
+
+                attestation
+                    .aggregation_bits
+                    .set(validator_committee_position, true)
                     .ok()?;
+                attestation.signature.add(&signature);
 
                 Some(())
             }
@@ -292,43 +477,65 @@ impl<T: SlotClock + 'static, E: EthSpec> ValidatorStore<T, E> {
     ///
     /// The resulting `SignedAggregateAndProof` is sent on the aggregation channel and cannot be
     /// modified by actors other than the signing validator.
-    pub fn produce_signed_aggregate_and_proof(
+    pub async fn produce_signed_aggregate_and_proof(
         &self,
         validator_pubkey: &PublicKey,
         validator_index: u64,
         aggregate: Attestation<E>,
         selection_proof: SelectionProof,
     ) -> Option<SignedAggregateAndProof<E>> {
-        let validators = self.validators.read();
-        let voting_keypair = &validators.get(validator_pubkey)?.voting_keypair;
+        let signer = self.signer(validator_pubkey)?;
+        let fork = self.fork()?;
 
-        Some(SignedAggregateAndProof::from_aggregate(
-            validator_index,
+        let message = AggregateAndProof {
+            aggregator_index: validator_index,
             aggregate,
-            Some(selection_proof),
-            &voting_keypair.sk,
-            &self.fork()?,
+            selection_proof: selection_proof.into(),
+        };
+
+        let target_epoch = message.aggregate.data.slot.epoch(E::slots_per_epoch());
+        let domain = self.spec.get_domain(
+            target_epoch,
+            Domain::AggregateAndProof,
+            &fork,
             self.genesis_validators_root,
-            &self.spec,
-        ))
+        );
+        let signing_root = message.signing_root(domain);
+
+        let signature = self
+            .sign(
+                &*signer,
+                signing_root,
+                MessageType::AggregateAndProof,
+                target_epoch,
+                fork,
+            )
+            .await?;
+
+        Some(SignedAggregateAndProof { message, signature })
     }
 
     /// Produces a `SelectionProof` for the `slot`, signed by with corresponding secret key to
     /// `validator_pubkey`.
-    pub fn produce_selection_proof(
+    pub async fn produce_selection_proof(
         &self,
         validator_pubkey: &PublicKey,
         slot: Slot,
     ) -> Option<SelectionProof> {
-        let validators = self.validators.read();
-        let voting_keypair = &validators.get(validator_pubkey)?.voting_keypair;
+        let signer = self.signer(validator_pubkey)?;
+        let fork = self.fork()?;
 
-        Some(SelectionProof::new::<E>(
-            slot,
-            &voting_keypair.sk,
-            &self.fork()?,
+        let epoch = slot.epoch(E::slots_per_epoch());
+        let domain = self.spec.get_domain(
+            epoch,
+            Domain::SelectionProof,
+            &fork,
             self.genesis_validators_root,
-            &self.spec,
-        ))
+        );
+        let message = slot.signing_root(domain);
+
+        self.sign(&*signer, message, MessageType::SelectionProof, epoch, fork)
+            .await
+            .map(SelectionProof::from)
     }
 }
@@ -0,0 +1,481 @@
+//! Keeps the validators in the `ValidatorStore` in sync with a directory of validator keystores,
+//! so that validators may be added or removed without restarting the validator client.
+//!
+//! The directory is re-scanned periodically and whenever the process receives `SIGHUP`.
+
+use crate::signer::LocalSigner;
+use crate::validator_store::ValidatorStore;
+use environment::RuntimeContext;
+use eth2_keystore::Keystore;
+use futures::stream::{self, Stream, StreamExt};
+use parking_lot::Mutex;
+use slog::{error, info, Logger};
+use slot_clock::SlotClock;
+use std::collections::{HashMap, HashSet};
+use std::fs::read_dir;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::time::{interval, Duration};
+use types::{EthSpec, Keypair, PublicKey};
+use validator_dir::{Builder, ValidatorDir};
+
+/// The interval between scans of the validators directory.
+const POLL_INTERVAL: Duration = Duration::from_secs(12);
+
+type RescanTriggers = Pin<Box<dyn Stream<Item = ()> + Send>>;
+
+/// Loads validators from a directory containing one `ValidatorDir` per validator, adding and
+/// removing them from the `ValidatorStore` as directories are added and removed.
+///
+/// When running with `--auto-register`, new validators are registered with the slashing protection
+/// database as they are added to the `ValidatorStore`. Their duties are requested at the next poll
+/// of the `DutiesService`.
+///
+/// Validators whose directory contains a `validator_dir::DISABLED_FILE` are loaded disabled.
+pub struct ValidatorWatcher<T, E: EthSpec> {
+    validators_dir: PathBuf,
+    secrets_dir: PathBuf,
+    validator_store: ValidatorStore<T, E>,
+    /// The validators loaded by `self`, keyed by their directory.
+    ///
+    /// Holding the `ValidatorDir` keeps its lockfile in place until the validator is removed.
+    loaded: Mutex<HashMap<PathBuf, (PublicKey, ValidatorDir)>>,
+    /// Directories which failed to load, used to avoid logging the same error at every scan.
+    failed: Mutex<HashSet<PathBuf>>,
+    context: RuntimeContext<E>,
+}
+
+impl<T: SlotClock + 'static, E: EthSpec> ValidatorWatcher<T, E> {
+    pub fn new(
+        validators_dir: PathBuf,
+        secrets_dir: PathBuf,
+        validator_store: ValidatorStore<T, E>,
+        context: RuntimeContext<E>,
+    ) -> Self {
+        Self {
+            validators_dir,
+            secrets_dir,
+            validator_store,
+            loaded: Mutex::new(HashMap::new()),
+            failed: Mutex::new(HashSet::new()),
+            context,
+        }
+    }
+
+    /// The number of validators currently loaded from the directory.
+    pub fn num_loaded(&self) -> usize {
+        self.loaded.lock().len()
+    }
+
+    /// Scan the validators directory, adding any new validators to the `ValidatorStore` and
+    /// removing any whose directory no longer exists.
+    pub async fn update(&self) -> Result<(), String> {
+        let log = self.context.log();
+
+        let (removed, new_paths) = {
+            // Read the directory whilst holding the lock, so that a validator being imported by
+            // `Self::import_keystore` is never seen on disk before it is loaded.
+            let mut loaded = self.loaded.lock();
+            let present = validator_paths(&self.validators_dir)?;
+
+            // Remove validators whose directories have disappeared.
+            let removed = loaded
+                .keys()
+                .filter(|path| !present.contains(*path))
+                .cloned()
+                .collect::<Vec<_>>();
+            let removed = removed
+                .into_iter()
+                .filter_map(|path| loaded.remove(&path).map(|validator| (path, validator)))
+                .collect::<Vec<_>>();
+
+            self.failed.lock().retain(|path| present.contains(path));
+
+            let new_paths = present
+                .into_iter()
+                .filter(|path| !loaded.contains_key(path))
+                .collect::<Vec<_>>();
+
+            (removed, new_paths)
+        };
+
+        for (path, (voting_pubkey, validator_dir)) in removed {
+            self.validator_store.remove_validator(&voting_pubkey);
+
+            // The lockfile was removed along with the directory, `ValidatorDir` will not try to
+            // remove it again.
+            drop(validator_dir);
+
+            info!(
+                log,
+                "Removed validator";
+                "dir" => format!("{:?}", path),
+                "voting_pubkey" => format!("{:?}", voting_pubkey),
+            );
+        }
+
+        // Add validators from new directories.
+        for path in new_paths {
+            match self.load_validator(path.clone()).await {
+                Ok((voting_pubkey, validator_dir)) => {
+                    self.failed.lock().remove(&path);
+                    info!(
+                        log,
+                        "Added validator";
+                        "dir" => format!("{:?}", path),
+                        "voting_pubkey" => format!("{:?}", voting_pubkey),
+                    );
+                    self.loaded
+                        .lock()
+                        .insert(path, (voting_pubkey, validator_dir));
+                }
+                Err(e) => {
+                    // The directory may still be being written, so it is retried at every scan.
+                    if self.failed.lock().insert(path.clone()) {
+                        error!(
+                            log,
+                            "Unable to load validator";
+                            "msg" => "will retry",
+                            "error" => e,
+                            "dir" => format!("{:?}", path),
+                        );
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Decrypt the keystore in `path` and add the validator to the `ValidatorStore`.
+    async fn load_validator(&self, path: PathBuf) -> Result<(PublicKey, ValidatorDir), String> {
+        let secrets_dir = self.secrets_dir.clone();
+
+        // Key derivation is deliberately slow, keep it away from the async runtime.
+        let (keypair, validator_dir) = self
+            .context
+            .executor
+            .runtime_handle()
+            .spawn_blocking(move || -> Result<(Keypair, ValidatorDir), String> {
+                let validator_dir = ValidatorDir::open(&path)
+                    .map_err(|e| format!("Unable to open validator dir: {:?}", e))?;
+                let keypair = validator_dir
+                    .voting_keypair(&secrets_dir)
+                    .map_err(|e| format!("Unable to decrypt voting keystore: {:?}", e))?;
+                Ok((keypair, validator_dir))
+            })
+            .await
+            .map_err(|e| format!("Keystore decryption failed: {:?}", e))??;
+
+        let voting_pubkey = keypair.pk.clone();
+        self.validator_store.add_validator(
+            voting_pubkey.clone(),
+            Arc::new(LocalSigner::new(keypair)),
+            validator_dir.is_enabled(),
+        )?;
+
+        Ok((voting_pubkey, validator_dir))
+    }
+
+    /// Save `keystore` and its `password` in the validators and secrets directories and add the
+    /// validator to the `ValidatorStore`, returning its public key.
+    ///
+    /// `keypair` must have been decrypted from `keystore`. The `enabled` state is persisted in the
+    /// new directory, so the validator is loaded in the same state after a restart.
+    pub fn import_keystore(
+        &self,
+        keystore: Keystore,
+        password: &[u8],
+        keypair: Keypair,
+        enabled: bool,
+    ) -> Result<PublicKey, String> {
+        let voting_pubkey = keypair.pk.clone();
+
+        if self.validator_store.is_enabled(&voting_pubkey).is_some() {
+            return Err(format!("Validator {:?} is already known", voting_pubkey));
+        }
+
+        // Hold the lock until the validator is loaded, see `Self::update`.
+        let mut loaded = self.loaded.lock();
+
+        let validator_dir = Builder::new(self.validators_dir.clone(), self.secrets_dir.clone())
+            .voting_keystore(keystore, password)
+            .store_withdrawal_keystore(false)
+            .build()
+            .map_err(|e| format!("Unable to create validator dir: {:?}", e))?;
+        validator_dir
+            .set_enabled(enabled)
+            .map_err(|e| format!("Unable to save validator status: {:?}", e))?;
+
+        self.validator_store.add_validator(
+            voting_pubkey.clone(),
+            Arc::new(LocalSigner::new(keypair)),
+            enabled,
+        )?;
+
+        info!(
+            self.context.log(),
+            "Added validator";
+            "dir" => format!("{:?}", validator_dir.dir()),
+            "voting_pubkey" => format!("{:?}", voting_pubkey),
+        );
+
+        loaded.insert(
+            validator_dir.dir().clone(),
+            (voting_pubkey.clone(), validator_dir),
+        );
+
+        Ok(voting_pubkey)
+    }
+
+    /// Enable or disable a validator which was loaded from the validators directory, saving the
+    /// change in its directory.
+    ///
+    /// Returns `Ok(false)` if the validator was not loaded from the validators directory.
+    pub fn set_validator_enabled(
+        &self,
+        voting_pubkey: &PublicKey,
+        enabled: bool,
+    ) -> Result<bool, String> {
+        let loaded = self.loaded.lock();
+
+        let validator_dir = match loaded.values().find(|(pubkey, _)| pubkey == voting_pubkey) {
+            Some((_, validator_dir)) => validator_dir,
+            None => return Ok(false),
+        };
+
+        validator_dir
+            .set_enabled(enabled)
+            .map_err(|e| format!("Unable to save validator status: {:?}", e))?;
+
+        Ok(self
+            .validator_store
+            .set_validator_enabled(voting_pubkey, enabled))
+    }
+
+    /// Starts the service which re-scans the validators directory every `POLL_INTERVAL` and
+    /// whenever `SIGHUP` is received.
+    pub fn start_update_service(self: Arc<Self>) -> Result<(), String> {
+        let log = self.context.log().clone();
+        let mut triggers =
+            stream::select(interval(POLL_INTERVAL).map(|_| ()), hangup_signals(&log)?);
+
+        let executor = self.context.executor.clone();
+
+        let update_fut = async move {
+            while triggers.next().await.is_some() {
+                if let Err(e) = self.update().await {
+                    error!(
+                        self.context.log(),
+                        "Unable to scan validators directory";
+                        "error" => e,
+                    );
+                }
+            }
+        };
+
+        executor.spawn(update_fut, "validator_watcher");
+
+        Ok(())
+    }
+}
+
+/// Returns the paths of all the directories in `validators_dir`.
+fn validator_paths(validators_dir: &Path) -> Result<HashSet<PathBuf>, String> {
+    read_dir(validators_dir)
+        .map_err(|e| format!("Unable to read {:?}: {:?}", validators_dir, e))?
+        .map(|entry| {
+            entry
+                .map(|entry| entry.path())
+                .map_err(|e| format!("Unable to read {:?}: {:?}", validators_dir, e))
+        })
+        .filter(|path| path.as_ref().map_or(true, |path| path.is_dir()))
+        .collect()
+}
+
+#[cfg(unix)]
+fn hangup_signals(log: &Logger) -> Result<RescanTriggers, String> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let log = log.clone();
+    let signals = signal(SignalKind::hangup())
+        .map_err(|e| format!("Unable to listen for SIGHUP: {:?}", e))?
+        .map(move |()| info!(log, "Received SIGHUP, scanning validators directory"));
+
+    Ok(Box::pin(signals))
+}
+
+#[cfg(not(unix))]
+fn hangup_signals(_log: &Logger) -> Result<RescanTriggers, String> {
+    Ok(Box::pin(stream::pending()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{new_env, validator_store, E};
+    use slot_clock::TestingSlotClock;
+    use std::fs;
+    use tempdir::TempDir;
+    use types::test_utils::generate_deterministic_keypair;
+
+    /// A `ValidatorWatcher` of a temporary validators directory.
+    struct WatcherTester {
+        watcher: ValidatorWatcher<TestingSlotClock, E>,
+        validator_store: ValidatorStore<TestingSlotClock, E>,
+        validators_dir: TempDir,
+        secrets_dir: TempDir,
+        _data_dir: TempDir,
+    }
+
+    impl WatcherTester {
+        fn new(context: &RuntimeContext<E>, auto_register: bool) -> Self {
+            let data_dir = TempDir::new("data").unwrap();
+            let validators_dir = TempDir::new("validators").unwrap();
+            let secrets_dir = TempDir::new("secrets").unwrap();
+
+            let (validator_store, _) = validator_store(context, data_dir.path(), auto_register);
+            let watcher = ValidatorWatcher::new(
+                validators_dir.path().into(),
+                secrets_dir.path().into(),
+                validator_store.clone(),
+                context.clone(),
+            );
+
+            Self {
+                watcher,
+                validator_store,
+                validators_dir,
+                secrets_dir,
+                _data_dir: data_dir,
+            }
+        }
+
+        /// Create the directory of a deterministic validator, as the account manager would.
+        fn create_validator(&self, index: usize) -> PathBuf {
+            let validator_dir = Builder::new(
+                self.validators_dir.path().into(),
+                self.secrets_dir.path().into(),
+            )
+            .insecure_voting_keypair(index)
+            .unwrap()
+            .store_withdrawal_keystore(false)
+            .build()
+            .unwrap();
+
+            validator_dir.dir().clone()
+        }
+
+        fn pubkeys(&self) -> Vec<PublicKey> {
+            let mut pubkeys = self
+                .validator_store
+                .validators()
+                .into_iter()
+                .map(|(pubkey, _)| pubkey)
+                .collect::<Vec<_>>();
+            pubkeys.sort_by_key(|pubkey| pubkey.as_hex_string());
+            pubkeys
+        }
+    }
+
+    fn sorted_pubkeys(indices: &[usize]) -> Vec<PublicKey> {
+        let mut pubkeys = indices
+            .iter()
+            .map(|&i| generate_deterministic_keypair(i).pk)
+            .collect::<Vec<_>>();
+        pubkeys.sort_by_key(|pubkey| pubkey.as_hex_string());
+        pubkeys
+    }
+
+    #[test]
+    fn validators_added_and_removed() {
+        let mut env = new_env();
+        let tester = WatcherTester::new(&env.core_context(), true);
+
+        env.runtime().block_on(async {
+            tester.watcher.update().await.unwrap();
+            assert_eq!(tester.watcher.num_loaded(), 0);
+
+            let dir_0 = tester.create_validator(0);
+            tester.create_validator(1);
+            tester.watcher.update().await.unwrap();
+            assert_eq!(tester.watcher.num_loaded(), 2);
+            assert_eq!(tester.pubkeys(), sorted_pubkeys(&[0, 1]));
+
+            // Loaded directories are locked.
+            assert!(ValidatorDir::open(&dir_0).is_err());
+
+            // Validators are registered with `--auto-register`.
+            let pubkey_0 = generate_deterministic_keypair(0).pk;
+            assert!(tester.validator_store.signing_history(&pubkey_0).is_ok());
+
+            fs::remove_dir_all(&dir_0).unwrap();
+            tester.watcher.update().await.unwrap();
+            assert_eq!(tester.watcher.num_loaded(), 1);
+            assert_eq!(tester.pubkeys(), sorted_pubkeys(&[1]));
+            assert!(!dir_0.exists());
+
+            // A validator may be added again after it has been removed.
+            tester.create_validator(0);
+            tester.watcher.update().await.unwrap();
+            assert_eq!(tester.pubkeys(), sorted_pubkeys(&[0, 1]));
+        });
+    }
+
+    #[test]
+    fn disabled_validators_loaded_disabled() {
+        let mut env = new_env();
+        let tester = WatcherTester::new(&env.core_context(), true);
+
+        env.runtime().block_on(async {
+            let dir = tester.create_validator(0);
+            ValidatorDir::open(&dir)
+                .unwrap()
+                .set_enabled(false)
+                .unwrap();
+
+            tester.watcher.update().await.unwrap();
+            let pubkey = generate_deterministic_keypair(0).pk;
+            assert_eq!(tester.validator_store.is_enabled(&pubkey), Some(false));
+
+            assert!(tester.watcher.set_validator_enabled(&pubkey, true).unwrap());
+            assert_eq!(tester.validator_store.is_enabled(&pubkey), Some(true));
+            assert!(!dir.join(validator_dir::DISABLED_FILE).exists());
+        });
+    }
+
+    #[test]
+    fn unloadable_validators_retried() {
+        let mut env = new_env();
+        let tester = WatcherTester::new(&env.core_context(), true);
+
+        env.runtime().block_on(async {
+            // A directory which is still being written.
+            let dir = tester.validators_dir.path().join("incomplete");
+            fs::create_dir(&dir).unwrap();
+            tester.watcher.update().await.unwrap();
+            assert_eq!(tester.watcher.num_loaded(), 0);
+
+            fs::remove_dir(&dir).unwrap();
+            tester.create_validator(0);
+            tester.watcher.update().await.unwrap();
+            assert_eq!(tester.pubkeys(), sorted_pubkeys(&[0]));
+        });
+    }
+
+    #[test]
+    fn validators_not_registered_without_auto_register() {
+        let mut env = new_env();
+        let tester = WatcherTester::new(&env.core_context(), false);
+
+        env.runtime().block_on(async {
+            tester.create_validator(0);
+            tester.watcher.update().await.unwrap();
+            assert_eq!(tester.watcher.num_loaded(), 1);
+
+            let pubkey = generate_deterministic_keypair(0).pk;
+            assert!(tester.validator_store.signing_history(&pubkey).is_err());
+        });
+    }
+}